@@ -0,0 +1,30 @@
+// Errors returned by Tauri commands, mirroring `error::Error` in the backend
+
+export type ErrorCode =
+  | "clipboard_busy"
+  | "clipboard"
+  | "permission_denied"
+  | "not_found"
+  | "io"
+  | "config"
+  | "shortcut"
+  | "platform"
+  | "input_blocked";
+
+export interface AppError {
+  code: ErrorCode;
+  message: string;
+}
+
+export function isAppError(error: unknown): error is AppError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    "code" in error &&
+    "message" in error
+  );
+}
+
+export function errorMessage(error: unknown): string {
+  return isAppError(error) ? error.message : String(error);
+}
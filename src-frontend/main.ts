@@ -1,9 +1,21 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
+import { errorMessage, isAppError } from "./errors";
+import { initTheme } from "./theme";
+
+// Draft shown when no other tab is selected (matches drafts::DEFAULT_DRAFT)
+const DEFAULT_DRAFT = "default";
+// How long a paste waits for the IME to finish composing before giving up
+const COMPOSITION_WAIT_MS = 3000;
 
 interface HistoryEntry {
   text: string;
   timestamp: string;
+  app?: string;
+  title?: string;
+  truncated?: boolean; // text is a preview ([history] preview_chars)
+  secret?: boolean; // text is a mask; reveal_entry has the text
 }
 
 interface Shortcuts {
@@ -25,6 +37,24 @@ interface Shortcuts {
   kill_word_back: string;
   delete_char: string;
   yank: string;
+  restore_draft: string;
+  external_editor: string;
+  capture: string;
+  pin: string;
+  toggle_mode: string;
+  rewrite: string;
+  summarize: string;
+  translate: string;
+  prompts: string;
+  symbols: string;
+  palette: string;
+  find_replace: string;
+  preview: string;
+  queue: string;
+  append: string;
+  always_on_top: string;
+  // Additional global hotkeys (hotkey -> action)
+  global?: Record<string, string>;
 }
 
 interface WindowConfig {
@@ -33,6 +63,9 @@ interface WindowConfig {
   history_lines: number;
   textarea_rows: number;
   textarea_cols: number;
+  effect?: string;
+  compact?: { textarea_cols: number; textarea_rows: number };
+  font_family?: string;
 }
 
 interface VoiceConfig {
@@ -40,10 +73,143 @@ interface VoiceConfig {
   delay_ms: number;
 }
 
+// Mirrors `dictation::VoiceStatus`
+interface VoiceStatus {
+  active: boolean;
+  level: number | null;
+  panel: "visible" | "closed";
+}
+
 interface Config {
   shortcuts: Shortcuts;
   window: WindowConfig;
   voice: VoiceConfig;
+  behavior?: { per_app_drafts?: boolean; evaluate_on_paste?: boolean; ime_safe_paste?: boolean };
+  ai?: { model: string; show_tokens: boolean; token_budget: number };
+  translate?: { on_paste: boolean };
+  spellcheck?: { enabled: boolean };
+}
+
+type TextAction = "rewrite" | "summarize" | "translate";
+
+interface TokenCount {
+  tokens: number;
+  encoding: string;
+  exact: boolean;
+}
+
+// Mirrors `spell::Misspelling`; offsets are UTF-16, like textarea indices
+interface Misspelling {
+  start: number;
+  end: number;
+  word: string;
+  suggestions: string[];
+}
+
+interface DraftVersion {
+  id: string;
+  name: string;
+  timestamp: string;
+  text: string;
+}
+
+interface PromptSummary {
+  name: string;
+  description: string;
+  variables: string[];
+  variant: string | null;
+  default: boolean;
+}
+
+// Mirrors `symbols::Symbol`
+interface SymbolEntry {
+  symbol: string;
+  name: string;
+  shortcode: string | null;
+  variants: string[];
+  recent: boolean;
+}
+
+// Mirrors `generate::GeneratorKind` and `generate::GenerateOptions`
+type GeneratorKind = "uuid_v4" | "uuid_v7" | "timestamp" | "password" | "lorem";
+
+interface GenerateOptions {
+  utc?: boolean;
+  length?: number;
+  lowercase?: boolean;
+  uppercase?: boolean;
+  digits?: boolean;
+  symbols?: boolean;
+  exclude_ambiguous?: boolean;
+  paragraphs?: number;
+}
+
+interface PaletteCommand {
+  label: string;
+  detail: string;
+  kind: GeneratorKind;
+  options?: GenerateOptions;
+}
+
+// A number after the command (">password 32", ">lorem 3") sets the length or paragraph count
+const PALETTE_COMMANDS: PaletteCommand[] = [
+  { label: "UUID v4", detail: "Random UUID", kind: "uuid_v4" },
+  { label: "UUID v7", detail: "Time-ordered UUID", kind: "uuid_v7" },
+  { label: "Timestamp", detail: "ISO 8601, local time", kind: "timestamp" },
+  { label: "Timestamp (UTC)", detail: "ISO 8601, UTC", kind: "timestamp", options: { utc: true } },
+  { label: "Password", detail: "20 letters, digits and symbols", kind: "password" },
+  {
+    label: "Password (alphanumeric)",
+    detail: "20 letters and digits, without look-alikes such as l/1 and O/0",
+    kind: "password",
+    options: { symbols: false, exclude_ambiguous: true },
+  },
+  {
+    label: "PIN",
+    detail: "6 digits",
+    kind: "password",
+    options: { length: 6, lowercase: false, uppercase: false, symbols: false },
+  },
+  { label: "Lorem ipsum", detail: "Placeholder paragraph", kind: "lorem" },
+];
+
+// Mirrors `highlight::Highlighted`
+interface Highlighted {
+  html: string;
+  language: string | null;
+}
+
+// Mirrors `links::Detected`
+interface ClipboardPreview {
+  kind: "text" | "image" | "files" | "other";
+  size: number; // characters, image bytes or files
+  preview: string;
+  truncated: boolean;
+}
+
+interface Detected {
+  kind: "url" | "email" | "path" | "color";
+  text: string;
+  start: number;
+  end: number;
+  target: string;
+}
+
+// Mirrors `find::Match` and `find::Replaced`
+interface RegexMatch {
+  start: number;
+  end: number;
+}
+
+interface RegexReplaced {
+  text: string;
+  count: number;
+}
+
+interface AppProfile {
+  process_name: string | null;
+  window: WindowConfig;
+  template: string | null;
 }
 
 // Parse shortcut string like "Ctrl+A" into { ctrl, alt, shift, key }
@@ -75,15 +241,67 @@ class PromptLineApp {
   private historySearch: HTMLInputElement;
   private searchBtn: HTMLButtonElement;
   private voiceToggle: HTMLInputElement;
+  private statusMessage: HTMLElement;
+  private tokenCount: HTMLElement;
+  private spellOverlay: HTMLElement;
+  private spellMenu: HTMLUListElement;
+  private findBar: HTMLElement;
+  private findPattern: HTMLInputElement;
+  private findReplacement: HTMLInputElement;
+  private findIgnoreCase: HTMLInputElement;
+  private findWholeWords: HTMLInputElement;
+  private findLiteral: HTMLInputElement;
+  private findStatus: HTMLElement;
+  private findMatches: RegexMatch[] = [];
+  // Markdown preview shown in place of the history
+  private markdownPreview: HTMLElement;
+  private previewing = false;
+  private previewedText: string | null = null;
   private historyEntries: HistoryEntry[] = [];
   private historyIndex = -1;
+  private checkedEntries: string[] = []; // Timestamps of entries to paste together, in pick order
   private searchMode = false;
   private searchQuery = "";
-  private draftSaveTimeout: number | null = null;
+  private draftTabs: HTMLElement;
+  private currentDraft = DEFAULT_DRAFT;
+  private draftNames: string[] = [];
+  private perAppDrafts = false;
+  private targetApp: string | null = null;
+  // Versions being stepped through by repeated restore_draft presses
+  private draftVersions: DraftVersion[] | null = null;
+  private draftVersionIndex = 0;
   private killRing: string = ""; // For Ctrl+Y (yank)
   private savedInput: string = ""; // For history navigation (readline behavior)
+  private revealedSecret = false; // The textarea holds a revealed secret entry, saved as secret
   private shortcuts!: Shortcuts;
   private voiceEnabled: boolean = false; // config.voice.enabled - controls if toggle is visible
+  private windowConfig: WindowConfig | null = null;
+  private compact: boolean = false; // Single-line bar without history (window mode)
+  private rewriting = false; // LLM reply is streaming into the textarea
+  private translateOnPaste = false; // [translate] on_paste
+  private evaluateOnPaste = false; // [behavior] evaluate_on_paste
+  private imeSafePaste = true; // [behavior] ime_safe_paste
+  private composing = false; // IME composition in progress in the textarea
+  // Prompt templates listed instead of history while the search starts with "/"
+  private prompts: PromptSummary[] | null = null;
+  private promptIndex = 0;
+  // Emojis and symbols listed instead of history while the search starts with ":"
+  private symbols: SymbolEntry[] | null = null;
+  private symbolIndex = 0;
+  private variantIndex = 0; // 0 = the symbol itself, then its skin tones
+  // Generator commands listed instead of history while the search starts with ">"
+  private palette: PaletteCommand[] | null = null;
+  private paletteIndex = 0;
+  private paletteCount: number | null = null; // Trailing number of the query
+  // Live token count ([ai] show_tokens / token_budget)
+  private showTokens = false;
+  private tokenBudget = 0;
+  private countedText: string | null = null;
+  // Misspelled words underlined by an overlay behind the text ([spellcheck])
+  private spellcheckEnabled = false;
+  private checkedText: string | null = null;
+  private misspellings: Misspelling[] = [];
+  private spellError: string | null = null;
 
   constructor() {
     this.textarea = document.getElementById("input-text") as HTMLTextAreaElement;
@@ -91,6 +309,19 @@ class PromptLineApp {
     this.historySearch = document.getElementById("history-search") as HTMLInputElement;
     this.searchBtn = document.getElementById("btn-search") as HTMLButtonElement;
     this.voiceToggle = document.getElementById("voice-toggle") as HTMLInputElement;
+    this.statusMessage = document.getElementById("status-message") as HTMLElement;
+    this.tokenCount = document.getElementById("token-count") as HTMLElement;
+    this.spellOverlay = document.getElementById("spell-overlay") as HTMLElement;
+    this.spellMenu = document.getElementById("spell-menu") as HTMLUListElement;
+    this.findBar = document.getElementById("find-bar") as HTMLElement;
+    this.findPattern = document.getElementById("find-pattern") as HTMLInputElement;
+    this.findReplacement = document.getElementById("find-replacement") as HTMLInputElement;
+    this.findIgnoreCase = document.getElementById("find-ignore-case") as HTMLInputElement;
+    this.findWholeWords = document.getElementById("find-whole-words") as HTMLInputElement;
+    this.findLiteral = document.getElementById("find-literal") as HTMLInputElement;
+    this.findStatus = document.getElementById("find-status") as HTMLElement;
+    this.markdownPreview = document.getElementById("markdown-preview") as HTMLElement;
+    this.draftTabs = document.getElementById("draft-tabs") as HTMLElement;
 
     this.init();
   }
@@ -98,9 +329,13 @@ class PromptLineApp {
   private async init(): Promise<void> {
     await this.loadConfig();
     await this.loadVoiceToggleState();
+    await this.loadWindowMode();
     this.setupEventListeners();
     this.loadHistory();
     this.loadDraft();
+    this.startTokenCounter();
+    this.startSpellChecker();
+    this.startMarkdownPreview();
     this.focusTextarea();
   }
 
@@ -121,6 +356,66 @@ class PromptLineApp {
     }
   }
 
+  private async loadIncognitoState(): Promise<void> {
+    try {
+      const incognito = await invoke<boolean>("get_incognito");
+      document.body.classList.toggle("incognito", incognito);
+      const screenPrivate = await invoke<boolean>("get_screen_share_private");
+      document.body.classList.toggle("screen-private", screenPrivate);
+    } catch (error) {
+      console.error("Failed to get incognito state:", error);
+    }
+  }
+
+  private async loadPinnedState(): Promise<void> {
+    try {
+      const pinned = await invoke<boolean>("get_pinned");
+      document.body.classList.toggle("pinned", pinned);
+    } catch (error) {
+      console.error("Failed to get pinned state:", error);
+    }
+  }
+
+  private async togglePinned(): Promise<void> {
+    try {
+      const pinned = !document.body.classList.contains("pinned");
+      await invoke("set_pinned", { pinned });
+    } catch (error) {
+      console.error("Failed to set pinned state:", error);
+    }
+  }
+
+  private async toggleAlwaysOnTop(): Promise<void> {
+    try {
+      const on = await invoke<boolean>("get_always_on_top");
+      await invoke("set_always_on_top", { on: !on });
+    } catch (error) {
+      this.showError(errorMessage(error));
+    }
+  }
+
+  private async loadWindowMode(): Promise<void> {
+    try {
+      this.setCompact((await invoke<string>("get_window_mode")) === "compact");
+    } catch (error) {
+      console.error("Failed to get window mode:", error);
+    }
+  }
+
+  private async toggleWindowMode(): Promise<void> {
+    try {
+      await invoke("set_window_mode", { mode: this.compact ? "expanded" : "compact" });
+    } catch (error) {
+      console.error("Failed to set window mode:", error);
+    }
+  }
+
+  private setCompact(compact: boolean): void {
+    this.compact = compact;
+    document.body.classList.toggle("compact", compact);
+    if (this.windowConfig) this.applyWindowConfig(this.windowConfig);
+  }
+
   private async updateVoiceToggleState(): Promise<void> {
     // Save toggle state to backend
     try {
@@ -135,6 +430,16 @@ class PromptLineApp {
       const config = await invoke<Config>("get_config");
       this.shortcuts = config.shortcuts;
       this.voiceEnabled = config.voice?.enabled ?? false;
+      this.perAppDrafts = config.behavior?.per_app_drafts ?? false;
+      this.translateOnPaste = config.translate?.on_paste ?? false;
+      this.evaluateOnPaste = config.behavior?.evaluate_on_paste ?? false;
+      this.imeSafePaste = config.behavior?.ime_safe_paste ?? true;
+      this.spellcheckEnabled = config.spellcheck?.enabled ?? false;
+      this.textarea.spellcheck = !this.spellcheckEnabled; // Not both squiggles
+      this.checkedText = null; // Dictionaries may have changed
+      this.tokenBudget = config.ai?.token_budget ?? 0;
+      this.showTokens = (config.ai?.show_tokens ?? false) || this.tokenBudget > 0;
+      this.countedText = null; // Redraw with the new budget
       this.applyWindowConfig(config.window);
     } catch (error) {
       console.error("Failed to load config:", error);
@@ -158,6 +463,22 @@ class PromptLineApp {
         kill_word_back: "Ctrl+w",
         delete_char: "Ctrl+d",
         yank: "Ctrl+y",
+        restore_draft: "Ctrl+Shift+z",
+        external_editor: "Ctrl+Shift+e",
+        capture: "Ctrl+Shift+Enter",
+        pin: "Ctrl+Shift+p",
+        toggle_mode: "Ctrl+Shift+m",
+        rewrite: "Ctrl+Shift+r",
+        summarize: "Ctrl+Shift+s",
+        translate: "Ctrl+Shift+l",
+        prompts: "Ctrl+Shift+t",
+        symbols: "Ctrl+Shift+u",
+        palette: "Ctrl+Shift+k",
+        find_replace: "Ctrl+h",
+        preview: "Ctrl+Shift+v",
+        queue: "Ctrl+Shift+q",
+        append: "Ctrl+Shift+a",
+        always_on_top: "Ctrl+Shift+o",
       };
       this.voiceEnabled = false;
       // Apply default window config
@@ -167,14 +488,38 @@ class PromptLineApp {
     this.updateShortcutDisplays();
   }
 
+  private async loadAppProfile(): Promise<void> {
+    try {
+      const profile = await invoke<AppProfile>("get_app_profile");
+      this.applyWindowConfig(profile.window);
+      await this.setDraftTarget(profile.process_name);
+      // Insert the app template only into an empty buffer
+      if (profile.template && !this.textarea.value) {
+        this.textarea.value = profile.template;
+        this.textarea.setSelectionRange(profile.template.length, profile.template.length);
+      }
+    } catch (error) {
+      console.error("Failed to load app profile:", error);
+    }
+  }
+
   private applyWindowConfig(window: WindowConfig): void {
+    this.windowConfig = window;
     const root = document.documentElement;
     root.style.setProperty("--font-size", `${window.font_size}px`);
     root.style.setProperty("--history-font-size", `${window.history_font_size}px`);
+    if (window.font_family) {
+      root.style.setProperty("--textarea-font-family", window.font_family);
+    } else {
+      root.style.removeProperty("--textarea-font-family");
+    }
+    // Let an acrylic/mica backdrop show through the page background
+    root.classList.toggle("window-effect", (window.effect ?? "none") !== "none");
 
     // Set textarea rows
+    const rows = this.compact ? (window.compact?.textarea_rows ?? 1) : window.textarea_rows;
     const lineHeight = window.font_size * 1.4;
-    const textareaHeight = window.textarea_rows * lineHeight + 20; // 20px padding
+    const textareaHeight = rows * lineHeight + 20; // 20px padding
     this.textarea.style.height = `${textareaHeight}px`;
   }
 
@@ -211,9 +556,80 @@ class PromptLineApp {
     document.getElementById("btn-paste")?.addEventListener("click", () => this.handlePaste());
     document.getElementById("btn-clear")?.addEventListener("click", () => this.handleClear());
     this.searchBtn.addEventListener("click", () => this.toggleSearchMode());
+    document.getElementById("btn-pin")?.addEventListener("click", () => this.togglePinned());
 
     // Voice input toggle
     this.voiceToggle.addEventListener("change", () => this.updateVoiceToggleState());
+    // Toggle flipped from a global shortcut
+    listen<boolean>("voice-toggle-changed", (event) => {
+      this.voiceToggle.checked = event.payload;
+    });
+    // Incognito mode toggled from the tray
+    listen<boolean>("incognito-changed", (event) => {
+      document.body.classList.toggle("incognito", event.payload);
+    });
+    // Screen sharing started or stopped (`behavior.screen_share_privacy`)
+    listen<boolean>("screen-share-changed", (event) => {
+      document.body.classList.toggle("screen-private", event.payload);
+    });
+
+    // Pinned windows are not hidden when focus moves elsewhere
+    listen<boolean>("pinned-changed", (event) => {
+      document.body.classList.toggle("pinned", event.payload);
+    });
+
+    listen<boolean>("always-on-top-changed", (event) => {
+      this.showError(event.payload ? "Always on top" : "Other windows can cover this one");
+    });
+
+    // Size, font or preset changed in settings or from the tray
+    listen<WindowConfig>("window-changed", (event) => {
+      this.applyWindowConfig(event.payload);
+    });
+
+    listen("history-changed", () => {
+      this.loadHistory();
+    });
+
+    // A draft was deleted or restored (possibly from another window)
+    listen<string>("draft-changed", () => {
+      this.refreshDraftTabs();
+    });
+
+    listen<string>("mode-changed", (event) => {
+      this.setCompact(event.payload === "compact");
+    });
+
+    // Launch shortcut released in hold-to-show mode
+    // Live state of Windows voice input
+    listen<VoiceStatus>("voice-status", (event) => {
+      this.applyVoiceStatus(event.payload);
+    });
+
+    // Local push-to-talk transcription, one segment at a time
+    listen<string>("speech-text", (event) => {
+      this.insertTranscript(event.payload);
+    });
+    listen<string>("speech-state", (event) => {
+      document.body.classList.toggle("speech-recording", event.payload === "recording");
+      document.body.classList.toggle("speech-transcribing", event.payload === "transcribing");
+    });
+
+    // LLM rewrite streaming in
+    listen<string>("rewrite-chunk", (event) => {
+      if (this.rewriting) {
+        this.textarea.value += event.payload;
+        this.textarea.scrollTop = this.textarea.scrollHeight;
+      }
+    });
+
+    listen<boolean>("launch-released", (event) => {
+      if (event.payload && this.textarea.value.trim()) {
+        this.handlePaste();
+      } else {
+        this.hideWindow();
+      }
+    });
 
     // Search input
     this.historySearch.addEventListener("input", () => {
@@ -228,6 +644,18 @@ class PromptLineApp {
         this.closeSearchMode();
         return;
       }
+      if (this.prompts !== null) {
+        this.handlePromptKey(e);
+        return;
+      }
+      if (this.symbols !== null) {
+        this.handleSymbolKey(e);
+        return;
+      }
+      if (this.palette !== null) {
+        this.handlePaletteKey(e);
+        return;
+      }
       // Navigate history while searching (readline: prev=older, next=newer)
       if (matchShortcut(e, this.shortcuts.history_prev)) {
         e.preventDefault();
@@ -259,6 +687,31 @@ class PromptLineApp {
         this.navigateHistory(-1); // -1 = newer entries
         return;
       }
+      // Tab: Pick the entry to paste together with others (like fzf)
+      if (e.key === "Tab") {
+        e.preventDefault();
+        this.toggleCheckedEntry(this.historyIndex);
+        this.navigateHistory(1);
+        return;
+      }
+      if (matchShortcut(e, this.shortcuts.paste) && this.checkedEntries.length > 0) {
+        e.preventDefault();
+        this.pasteCheckedEntries();
+        return;
+      }
+      // Queue the selected entry for the "paste_next" hotkey
+      if (matchShortcut(e, this.shortcuts.queue)) {
+        e.preventDefault();
+        const entry = this.historyEntries[this.historyIndex];
+        if (entry?.secret) {
+          invoke<string>("reveal_entry", { id: entry.timestamp })
+            .then((text) => this.queueForPaste([text]))
+            .catch((error) => this.showError(errorMessage(error)));
+        } else if (entry) {
+          this.entryText(entry).then((text) => this.queueForPaste([text]));
+        }
+        return;
+      }
       // Ctrl+R again: Navigate to next match (bash-like behavior)
       if (matchShortcut(e, this.shortcuts.search)) {
         e.preventDefault();
@@ -267,8 +720,51 @@ class PromptLineApp {
       }
     });
 
+    // Suggestions for a misspelled word on right-click
+    this.textarea.addEventListener("scroll", () => {
+      this.spellOverlay.scrollTop = this.textarea.scrollTop;
+    });
+    this.textarea.addEventListener("contextmenu", (e) => this.showSpellMenu(e));
+    document.addEventListener("mousedown", (e) => {
+      if (!this.spellMenu.contains(e.target as Node)) this.hideSpellMenu();
+    });
+    window.addEventListener("blur", () => this.hideSpellMenu());
+
+    // Regex find/replace bar: Enter finds the next match (Shift+Enter the
+    // previous one), Enter in the replacement replaces all
+    this.findPattern.addEventListener("input", () => this.updateFindMatches());
+    for (const option of [this.findIgnoreCase, this.findWholeWords, this.findLiteral]) {
+      option.addEventListener("change", () => this.updateFindMatches());
+    }
+    this.findPattern.addEventListener("keydown", (e) => {
+      if (e.key === "Enter") {
+        e.preventDefault();
+        this.findNext(e.shiftKey);
+      }
+    });
+    this.findReplacement.addEventListener("keydown", (e) => {
+      if (e.key === "Enter") {
+        e.preventDefault();
+        this.replaceAll();
+      }
+    });
+    document.getElementById("find-replace-all")?.addEventListener("click", () => this.replaceAll());
+
+    // Links in the preview must not navigate the app window away
+    this.markdownPreview.addEventListener("click", (e) => {
+      if ((e.target as HTMLElement).closest("a")) e.preventDefault();
+    });
+
+    this.textarea.addEventListener("compositionstart", () => {
+      this.composing = true;
+    });
+    this.textarea.addEventListener("compositionend", () => {
+      this.composing = false;
+    });
+
     // Keyboard shortcuts (readline bindings + app shortcuts)
     this.textarea.addEventListener("keydown", (e) => {
+      this.hideSpellMenu();
       // === App shortcuts ===
       // Paste and close
       if (matchShortcut(e, this.shortcuts.paste)) {
@@ -276,6 +772,52 @@ class PromptLineApp {
         this.handlePaste();
         return;
       }
+      // Rewrite, summarize or translate the text with the configured LLM
+      if (matchShortcut(e, this.shortcuts.rewrite)) {
+        e.preventDefault();
+        this.rewriteWithAi("rewrite");
+        return;
+      }
+      if (matchShortcut(e, this.shortcuts.summarize)) {
+        e.preventDefault();
+        this.rewriteWithAi("summarize");
+        return;
+      }
+      if (matchShortcut(e, this.shortcuts.translate)) {
+        e.preventDefault();
+        this.rewriteWithAi("translate");
+        return;
+      }
+      // Switch between the compact bar and the full editor
+      if (matchShortcut(e, this.shortcuts.toggle_mode)) {
+        e.preventDefault();
+        this.toggleWindowMode();
+        return;
+      }
+      // Keep the window up while referencing another app
+      if (matchShortcut(e, this.shortcuts.pin)) {
+        e.preventDefault();
+        this.togglePinned();
+        return;
+      }
+      // Append to the capture file and close
+      if (matchShortcut(e, this.shortcuts.capture)) {
+        e.preventDefault();
+        this.handleCapture();
+        return;
+      }
+      // Add to the end of the clipboard and close, to paste everything later
+      if (matchShortcut(e, this.shortcuts.append)) {
+        e.preventDefault();
+        this.handleAppend();
+        return;
+      }
+      // Stay above the window being read, or let it cover this one
+      if (matchShortcut(e, this.shortcuts.always_on_top)) {
+        e.preventDefault();
+        this.toggleAlwaysOnTop();
+        return;
+      }
 
       // Search history
       if (matchShortcut(e, this.shortcuts.search)) {
@@ -283,6 +825,33 @@ class PromptLineApp {
         this.toggleSearchMode();
         return;
       }
+      // Pick a prompt template
+      if (matchShortcut(e, this.shortcuts.prompts)) {
+        e.preventDefault();
+        this.openSearchMode();
+        this.historySearch.value = "/";
+        this.searchQuery = "/";
+        this.loadHistory();
+        return;
+      }
+      // Search emojis and symbols
+      if (matchShortcut(e, this.shortcuts.symbols)) {
+        e.preventDefault();
+        this.openSearchMode();
+        this.historySearch.value = ":";
+        this.searchQuery = ":";
+        this.loadHistory();
+        return;
+      }
+      // Generate a UUID, timestamp, password, ...
+      if (matchShortcut(e, this.shortcuts.palette)) {
+        e.preventDefault();
+        this.openSearchMode();
+        this.historySearch.value = ">";
+        this.searchQuery = ">";
+        this.loadHistory();
+        return;
+      }
 
       // === Readline: History ===
       // Previous history (go back to older entries)
@@ -373,6 +942,36 @@ class PromptLineApp {
         this.yank();
         return;
       }
+      // Edit the buffer in the external editor
+      if (matchShortcut(e, this.shortcuts.external_editor)) {
+        e.preventDefault();
+        this.editInExternalEditor();
+        return;
+      }
+      // Restore the previous saved version of the draft
+      if (matchShortcut(e, this.shortcuts.restore_draft)) {
+        e.preventDefault();
+        this.restorePreviousDraftVersion();
+        return;
+      }
+      // Show or hide the Markdown preview
+      if (matchShortcut(e, this.shortcuts.preview)) {
+        e.preventDefault();
+        this.togglePreview();
+        return;
+      }
+      // Find and replace with a regex
+      if (matchShortcut(e, this.shortcuts.find_replace)) {
+        e.preventDefault();
+        this.openFindBar();
+        return;
+      }
+      // Split the text into the paste queue
+      if (matchShortcut(e, this.shortcuts.queue)) {
+        e.preventDefault();
+        this.queueBuffer();
+        return;
+      }
       // Clear textarea
       if (matchShortcut(e, this.shortcuts.clear)) {
         e.preventDefault();
@@ -383,11 +982,13 @@ class PromptLineApp {
 
     // Global shortcuts (work even when textarea not focused)
     document.addEventListener("keydown", (e) => {
-      // Close: Close search or window
+      // Close: Close search, the find bar or the window
       if (matchShortcut(e, this.shortcuts.close)) {
         e.preventDefault();
         if (this.searchMode) {
           this.closeSearchMode();
+        } else if (!this.findBar.classList.contains("hidden")) {
+          this.closeFindBar();
         } else {
           this.hideWindow();
         }
@@ -396,86 +997,316 @@ class PromptLineApp {
 
     // Window focus listener
     window.addEventListener("focus", async () => {
-      await this.loadConfig(); // Reload config (may have changed in settings)
-      await this.loadVoiceToggleState(); // Update toggle visibility
-      this.loadHistory();
+      await this.refreshOnShow();
       this.focusTextarea();
     });
 
+    // Shown without focus ([behavior] no_activate); a click takes focus
+    listen("shown-inactive", () => {
+      this.refreshOnShow();
+    });
+    document.addEventListener("mousedown", (e) => {
+      // The paste button works without focus, so nothing has to be restored
+      if (document.hasFocus() || (e.target as HTMLElement).closest("#btn-paste")) return;
+      getCurrentWindow().setFocus();
+    });
+
     // Draft autosave on text change
     this.textarea.addEventListener("input", () => {
+      this.draftVersions = null;
       this.scheduleDraftSave();
+      if (!this.findBar.classList.contains("hidden")) {
+        this.updateFindMatches();
+      }
     });
   }
 
+  private async refreshOnShow(): Promise<void> {
+    await this.loadConfig(); // Reload config (may have changed in settings)
+    await this.loadAppProfile(); // Per-app window layout and template
+    await this.loadVoiceToggleState(); // Update toggle visibility
+    await this.loadIncognitoState();
+    await this.loadPinnedState();
+    // History is kept current by "history-changed"; just show the newest entries
+    this.scrollHistoryToBottom();
+  }
+
   private async handlePaste(): Promise<void> {
-    const text = this.textarea.value;
-    if (!text.trim()) return;
+    if (this.checkedEntries.length > 0) {
+      await this.pasteCheckedEntries();
+      return;
+    }
+    if (!(await this.compositionSettled())) return;
+    let text = this.textarea.value;
+    if (!text.trim() || this.rewriting) return;
 
+    this.hideError();
     try {
-      await invoke("paste_and_save", { text });
+      const expression = text.trimStart();
+      if (this.evaluateOnPaste && expression.startsWith("=")) {
+        // "=2^10" pastes "1024"; a result is never translated
+        text = await invoke<string>("evaluate_expression", { expr: expression.slice(1) });
+      } else if (this.translateOnPaste) {
+        this.showError("Translating...");
+        text = await invoke<string>("translate_text", { text });
+        this.hideError();
+      }
+      await invoke("paste_and_save", { text, secret: this.revealedSecret });
+      this.revealedSecret = false;
       await this.clearDraft();
       await this.hideWindow();
-      await invoke("simulate_paste");
+      await invoke("simulate_paste", { text });
       this.textarea.value = "";
       this.historyIndex = -1;
       this.savedInput = "";
     } catch (error) {
       console.error("Paste failed:", error);
+      // Bring the window back so the failure doesn't go unnoticed
+      await this.showWindow();
+      if (isAppError(error) && error.code === "clipboard_busy") {
+        this.showError("The clipboard is in use by another app. Press Ctrl+Enter to try again.");
+      } else {
+        this.showError(errorMessage(error));
+      }
     }
   }
 
-  private handleClear(): void {
-    this.textarea.value = "";
-    this.historyIndex = -1;
-    this.savedInput = "";
-    this.clearDraft();
-    this.focusTextarea();
+  // Wait for the IME to finish composing in the textarea, so the text taken
+  // for pasting is complete and hiding the window doesn't commit a half-typed word
+  private async compositionSettled(): Promise<boolean> {
+    if (!this.imeSafePaste || !this.composing) return true;
+    const settled = await new Promise<boolean>((resolve) => {
+      const timer = window.setTimeout(() => resolve(false), COMPOSITION_WAIT_MS);
+      this.textarea.addEventListener(
+        "compositionend",
+        () => {
+          window.clearTimeout(timer);
+          resolve(true);
+        },
+        { once: true },
+      );
+    });
+    if (!settled) {
+      this.showError("Finish or cancel the IME input before pasting");
+    }
+    return settled;
   }
 
-  private async hideWindow(): Promise<void> {
+  private async handleCapture(): Promise<void> {
+    const text = this.textarea.value;
+    if (!text.trim()) return;
+
+    this.hideError();
     try {
-      await getCurrentWindow().hide();
+      await invoke<string>("capture_to_file", { text });
+      await this.clearDraft();
+      this.textarea.value = "";
+      this.historyIndex = -1;
+      this.savedInput = "";
+      await this.hideWindow();
     } catch (error) {
-      console.error("Failed to hide window:", error);
-    }
-  }
-
-  private toggleSearchMode(): void {
-    if (this.searchMode) {
-      this.closeSearchMode();
-    } else {
-      this.openSearchMode();
+      console.error("Capture failed:", error);
+      this.showError(errorMessage(error));
     }
   }
 
-  private openSearchMode(): void {
-    this.searchMode = true;
-    this.historySearch.classList.remove("hidden");
-    this.historySearch.focus();
-  }
+  private async handleAppend(): Promise<void> {
+    if (!(await this.compositionSettled())) return;
+    const text = this.textarea.value;
+    if (!text.trim() || this.rewriting) return;
 
-  private closeSearchMode(): void {
-    this.searchMode = false;
-    this.searchQuery = "";
-    this.historySearch.value = "";
-    this.historySearch.classList.add("hidden");
-    this.loadHistory();
-    this.focusTextarea();
+    this.hideError();
+    try {
+      await invoke("paste_and_save", { text, append: true, secret: this.revealedSecret });
+      this.revealedSecret = false;
+      await this.clearDraft();
+      this.textarea.value = "";
+      this.historyIndex = -1;
+      this.savedInput = "";
+      await this.hideWindow();
+    } catch (error) {
+      console.error("Append failed:", error);
+      this.showError(errorMessage(error));
+    }
   }
 
-  private async loadHistory(): Promise<void> {
+  // Paste the picked history entries joined by history.join_separator
+  private async pasteCheckedEntries(): Promise<void> {
+    this.hideError();
     try {
-      this.historyEntries = await invoke<HistoryEntry[]>("get_history", { query: this.searchQuery });
-      this.renderHistory();
-      // Scroll to bottom (newest entry) on load
-      this.scrollHistoryToBottom();
+      await invoke("paste_entries", { ids: this.checkedEntries });
+      this.checkedEntries = [];
+      if (this.searchMode) this.closeSearchMode();
     } catch (error) {
-      console.error("Failed to load history:", error);
+      console.error("Paste failed:", error);
+      await this.showWindow();
+      this.showError(errorMessage(error));
     }
   }
 
-  private scrollHistoryToBottom(): void {
+  // Pick or unpick an entry for pasting together with others
+  private toggleCheckedEntry(index: number): void {
+    const entry = this.historyEntries[index];
+    if (!entry) return;
+    const position = this.checkedEntries.indexOf(entry.timestamp);
+    if (position >= 0) {
+      this.checkedEntries.splice(position, 1);
+    } else {
+      this.checkedEntries.push(entry.timestamp);
+    }
+    this.historyList.querySelector(`li[data-index="${index}"]`)?.classList.toggle("checked", position < 0);
+
+    const count = this.checkedEntries.length;
+    if (count === 0) {
+      this.hideError();
+    } else {
+      const entries = count === 1 ? "1 entry" : `${count} entries`;
+      this.showError(`${entries} picked. ${this.shortcuts.paste} pastes them joined.`);
+    }
+  }
+
+  // Split the text into paragraphs (or lines) for the "paste_next" hotkey
+  private async queueBuffer(): Promise<void> {
+    const text = this.textarea.value;
+    if (!text.trim()) return;
+
+    try {
+      const queued = await invoke<number>("queue_paste_text", { text });
+      await this.clearDraft();
+      this.textarea.value = "";
+      this.historyIndex = -1;
+      this.savedInput = "";
+      this.showQueued(queued);
+    } catch (error) {
+      this.showError(errorMessage(error));
+    }
+  }
+
+  private async queueForPaste(items: string[]): Promise<void> {
+    try {
+      this.showQueued(await invoke<number>("queue_paste_items", { items }));
+    } catch (error) {
+      this.showError(errorMessage(error));
+    }
+  }
+
+  private showQueued(queued: number): void {
+    const hotkey = Object.entries(this.shortcuts.global ?? {}).find(([, action]) => action === "paste_next")?.[0];
+    const items = queued === 1 ? "1 item" : `${queued} items`;
+    this.showError(
+      hotkey
+        ? `${items} queued. Press ${hotkey} in the target app to paste the next one.`
+        : `${items} queued. Bind "paste_next" in [shortcuts.global] to paste them.`,
+    );
+  }
+
+  private async showWindow(): Promise<void> {
+    try {
+      const current = getCurrentWindow();
+      await current.show();
+      await current.setFocus();
+    } catch (error) {
+      console.error("Failed to show window:", error);
+    }
+  }
+
+  private showError(message: string): void {
+    this.statusMessage.textContent = message;
+    this.statusMessage.classList.remove("hidden");
+  }
+
+  private hideError(): void {
+    this.statusMessage.classList.add("hidden");
+  }
+
+  private handleClear(): void {
+    this.hideError();
+    this.checkedEntries = [];
+    this.historyList.querySelectorAll("li.checked").forEach((item) => item.classList.remove("checked"));
+    this.textarea.value = "";
+    this.historyIndex = -1;
+    this.savedInput = "";
+    this.draftVersions = null;
+    this.clearDraft();
+    this.focusTextarea();
+  }
+
+  private async hideWindow(): Promise<void> {
+    try {
+      // Stop dictation while this window still has focus
+      await invoke("stop_voice_input");
+    } catch (error) {
+      console.error("Failed to stop voice input:", error);
+    }
+    try {
+      await getCurrentWindow().hide();
+    } catch (error) {
+      console.error("Failed to hide window:", error);
+    }
+  }
+
+  private toggleSearchMode(): void {
+    if (this.searchMode) {
+      this.closeSearchMode();
+    } else {
+      this.openSearchMode();
+    }
+  }
+
+  private openSearchMode(): void {
+    // Results are listed where the preview is shown
+    if (this.previewing) this.togglePreview();
+    this.searchMode = true;
+    this.historySearch.classList.remove("hidden");
+    this.historySearch.focus();
+  }
+
+  private closeSearchMode(): void {
+    this.searchMode = false;
+    this.prompts = null;
+    this.symbols = null;
+    this.palette = null;
+    this.searchQuery = "";
+    this.historySearch.value = "";
+    this.historySearch.classList.add("hidden");
+    this.loadHistory();
+    this.focusTextarea();
+  }
+
+  private async loadHistory(): Promise<void> {
+    if (this.searchMode && this.searchQuery.startsWith("/")) {
+      this.symbols = null;
+      this.palette = null;
+      await this.loadPrompts(this.searchQuery.slice(1));
+      return;
+    }
+    this.prompts = null;
+    if (this.searchMode && this.searchQuery.startsWith(":")) {
+      this.palette = null;
+      await this.loadSymbols(this.searchQuery.slice(1));
+      return;
+    }
+    this.symbols = null;
+    if (this.searchMode && this.searchQuery.startsWith(">")) {
+      this.loadPalette(this.searchQuery.slice(1));
+      return;
+    }
+    this.palette = null;
+    try {
+      this.historyEntries = await invoke<HistoryEntry[]>("get_history", { query: this.searchQuery });
+      // Nothing recent: look through the entries archived by max_entries
+      if (this.searchQuery.trim() && this.historyEntries.length === 0) {
+        this.historyEntries = await invoke<HistoryEntry[]>("search_archive", { query: this.searchQuery });
+      }
+      this.renderHistory();
+      // Scroll to bottom (newest entry) on load
+      this.scrollHistoryToBottom();
+    } catch (error) {
+      console.error("Failed to load history:", error);
+    }
+  }
+
+  private scrollHistoryToBottom(): void {
     const historySection = document.getElementById("history-section");
     if (historySection) {
       historySection.scrollTop = historySection.scrollHeight;
@@ -498,8 +1329,9 @@ class PromptLineApp {
     if (this.historyIndex === -1) {
       // Restore saved input when returning from history
       this.textarea.value = this.savedInput;
+      this.revealedSecret = false;
     } else {
-      this.textarea.value = this.historyEntries[this.historyIndex].text;
+      this.showEntryText(this.historyEntries[this.historyIndex]);
     }
 
     // Move cursor to end
@@ -511,12 +1343,38 @@ class PromptLineApp {
     if (index < 0 || index >= this.historyEntries.length) return;
 
     this.historyIndex = index;
-    this.textarea.value = this.historyEntries[index].text;
+    this.showEntryText(this.historyEntries[index]);
     this.textarea.setSelectionRange(this.textarea.value.length, this.textarea.value.length);
     this.updateHistorySelection();
     this.focusTextarea();
   }
 
+  // Put an entry into the textarea: the preview at once, the full text once
+  // fetched. A secret entry stays out until it is revealed.
+  private showEntryText(entry: HistoryEntry): void {
+    this.revealedSecret = false;
+    this.textarea.value = entry.secret ? "" : entry.text;
+    if (!entry.truncated) return;
+    this.entryText(entry).then((text) => {
+      // Skip if another entry was picked meanwhile
+      if (this.historyEntries[this.historyIndex] !== entry) return;
+      this.textarea.value = text;
+      this.textarea.setSelectionRange(text.length, text.length);
+    });
+  }
+
+  // Full text of an entry, fetched on first use if the list only has a preview
+  private async entryText(entry: HistoryEntry): Promise<string> {
+    if (!entry.truncated) return entry.text;
+    try {
+      entry.text = await invoke<string>("get_history_entry_text", { id: entry.timestamp });
+      entry.truncated = false;
+    } catch (error) {
+      console.error("Failed to get the entry text:", error);
+    }
+    return entry.text;
+  }
+
   private updateHistorySelection(): void {
     const items = this.historyList.querySelectorAll("li");
     items.forEach((item, i) => {
@@ -527,6 +1385,85 @@ class PromptLineApp {
         item.classList.remove("selected");
       }
     });
+    this.showEntryActions(this.historyIndex);
+  }
+
+  // Buttons for the links and paths of the selected entry, swatches for its
+  // colors, and a button to reveal it or mark it secret
+  private async showEntryActions(index: number): Promise<void> {
+    this.historyList.querySelectorAll(".entry-actions").forEach((actions) => actions.remove());
+    const entry = this.historyEntries[index];
+    if (!entry) return;
+    let found: Detected[] = [];
+    if (!entry.secret) {
+      try {
+        found = await invoke<Detected[]>("analyze_text", { text: await this.entryText(entry) });
+      } catch (error) {
+        console.error("Failed to analyze history entry:", error);
+        return;
+      }
+    }
+    const item = this.historyList.querySelector(`li[data-index="${index}"]`);
+    if (this.historyIndex !== index || !item) return;
+
+    const actions = document.createElement("div");
+    actions.className = "entry-actions";
+    const seen = new Set<string>();
+    for (const detected of found) {
+      if (seen.has(detected.target) || seen.size >= 6) continue;
+      seen.add(detected.target);
+      if (detected.kind === "color") {
+        const swatch = document.createElement("span");
+        swatch.className = "color-swatch";
+        swatch.style.backgroundColor = detected.target;
+        swatch.title = detected.text;
+        actions.append(swatch);
+        continue;
+      }
+      const button = document.createElement("button");
+      const reveal = detected.kind === "path";
+      const label = reveal ? detected.target.split(/[\\/]/).filter(Boolean).pop() ?? detected.target : detected.text;
+      button.textContent = `${reveal ? "Reveal" : "Open"} ${label.length > 40 ? label.substring(0, 40) + "..." : label}`;
+      button.title = detected.target;
+      button.addEventListener("click", async (e) => {
+        e.stopPropagation(); // Not a click on the entry
+        try {
+          if (reveal) {
+            await invoke("reveal_in_explorer", { path: detected.target });
+          } else {
+            await invoke("open_url", { url: detected.target });
+          }
+        } catch (error) {
+          this.showError(errorMessage(error));
+        }
+      });
+      actions.append(button);
+    }
+    actions.append(this.secretButton(entry));
+    item.append(actions);
+  }
+
+  // "Show Secret" puts a secret entry into the textarea; "Mark Secret" encrypts the entry
+  private secretButton(entry: HistoryEntry): HTMLButtonElement {
+    const button = document.createElement("button");
+    button.textContent = entry.secret ? "Show Secret" : "Mark Secret";
+    button.addEventListener("click", async (e) => {
+      e.stopPropagation(); // Not a click on the entry
+      try {
+        if (entry.secret) {
+          const text = await invoke<string>("reveal_entry", { id: entry.timestamp });
+          if (this.historyEntries[this.historyIndex] !== entry) return;
+          this.textarea.value = text;
+          this.revealedSecret = true;
+          this.focusTextarea();
+        } else {
+          await invoke("mark_entry_secret", { id: entry.timestamp });
+        }
+      } catch (error) {
+        this.showError(errorMessage(error));
+      }
+    });
+    return button;
   }
 
   private renderHistory(): void {
@@ -552,13 +1489,25 @@ class PromptLineApp {
           minute: "2-digit",
         });
         const escapedPreview = this.escapeHtml(preview).replace(/\n/g, " ");
-        const selectedClass = index === this.historyIndex ? "selected" : "";
+        const selectedClass = [
+          index === this.historyIndex ? "selected" : "",
+          this.checkedEntries.includes(entry.timestamp) ? "checked" : "",
+          entry.secret ? "secret" : "",
+        ].join(" ").trim();
         const highlightedPreview = this.searchQuery
           ? this.highlightSearch(escapedPreview, this.searchQuery)
           : escapedPreview;
 
+        // Where the text went, with the window title on hover
+        const source = entry.app
+          ? ` · ${this.escapeHtml(entry.app.replace(/\.exe$/i, ""))}`
+          : "";
+        const sourceTitle = entry.title
+          ? ` title="${this.escapeHtml(entry.title).replace(/"/g, "&quot;")}"`
+          : "";
+
         return `<li data-index="${index}" class="${selectedClass}">
-          <span class="timestamp">${timestamp}</span>
+          <span class="timestamp"${sourceTitle}>${timestamp}${source}</span>
           <span class="preview">${highlightedPreview}</span>
         </li>`;
       })
@@ -566,13 +1515,397 @@ class PromptLineApp {
 
     // Add click handlers
     this.historyList.querySelectorAll("li[data-index]").forEach((li) => {
-      li.addEventListener("click", () => {
+      li.addEventListener("click", (e) => {
         const index = parseInt((li as HTMLElement).dataset.index || "0", 10);
-        this.selectHistoryItem(index);
+        if ((e as MouseEvent).ctrlKey) {
+          this.toggleCheckedEntry(index);
+        } else {
+          this.selectHistoryItem(index);
+        }
+      });
+    });
+
+    if (!this.searchQuery) {
+      this.highlightHistoryPreviews(displayEntries);
+    }
+  }
+
+  // Color the previews of code entries: a fenced block, or several lines the
+  // backend recognizes as code. Search results keep their match highlights.
+  private async highlightHistoryPreviews(entries: HistoryEntry[]): Promise<void> {
+    const rendered = this.historyEntries;
+    for (const [index, entry] of entries.entries()) {
+      const fence = /^\s*```([\w+#.-]*)[^\n]*\n([\s\S]*?)(?:```|$)/.exec(entry.text);
+      if (!fence && !entry.text.includes("\n")) continue;
+      // Only the start is visible; the rest of the line is cut off by CSS
+      const code = (fence ? fence[2] : entry.text).substring(0, 300);
+      try {
+        const result = await invoke<Highlighted>("highlight_code", { text: code, langHint: fence?.[1] || null });
+        // History reloaded meanwhile
+        if (this.historyEntries !== rendered) return;
+        if (!result.language) continue;
+        const preview = this.historyList.querySelector(`li[data-index="${index}"] .preview`);
+        if (preview) preview.innerHTML = result.html.replace(/\n/g, " ");
+      } catch (error) {
+        console.error("Failed to highlight history entry:", error);
+        return;
+      }
+    }
+  }
+
+  private async loadPrompts(query: string): Promise<void> {
+    try {
+      this.prompts = await invoke<PromptSummary[]>("list_prompts", { query });
+    } catch (error) {
+      console.error("Failed to load prompts:", error);
+      this.prompts = [];
+      this.showError(errorMessage(error));
+    }
+    this.promptIndex = 0;
+    this.renderPrompts();
+  }
+
+  private renderPrompts(): void {
+    const prompts = this.prompts ?? [];
+    if (prompts.length === 0) {
+      this.historyList.innerHTML = `<li class="empty-message">No prompt templates (add them to prompts.toml)</li>`;
+      return;
+    }
+
+    this.historyList.innerHTML = prompts
+      .map((prompt, index) => {
+        const selectedClass = index === this.promptIndex ? "selected" : "";
+        const tags = [prompt.default ? "default" : "", prompt.variant ?? ""].filter(Boolean).join(", ");
+        const label = tags ? `${prompt.name} (${tags})` : prompt.name;
+        const variables = prompt.variables.map((name) => `{{${name}}}`).join(" ");
+        const detail = [prompt.description, variables].filter(Boolean).join(" ");
+        return `<li data-index="${index}" class="${selectedClass}">
+          <span class="timestamp">${this.escapeHtml(label)}</span>
+          <span class="preview">${this.escapeHtml(detail)}</span>
+        </li>`;
+      })
+      .join("");
+
+    this.historyList.querySelectorAll("li[data-index]").forEach((li) => {
+      li.addEventListener("click", () => {
+        const prompt = prompts[parseInt((li as HTMLElement).dataset.index || "0", 10)];
+        if (prompt) {
+          this.insertPrompt(prompt.name);
+        }
+      });
+    });
+  }
+
+  private handlePromptKey(e: KeyboardEvent): void {
+    const prompts = this.prompts ?? [];
+    // The list is drawn bottom-up, so "up" goes to higher indexes like history
+    let direction = 0;
+    if (e.key === "ArrowUp" || matchShortcut(e, this.shortcuts.history_prev)) {
+      direction = 1;
+    } else if (e.key === "ArrowDown" || matchShortcut(e, this.shortcuts.history_next)) {
+      direction = -1;
+    } else if (e.key === "Enter") {
+      e.preventDefault();
+      const prompt = prompts[this.promptIndex];
+      if (prompt) {
+        this.insertPrompt(prompt.name);
+      }
+      return;
+    }
+    if (direction !== 0) {
+      e.preventDefault();
+      this.promptIndex = Math.min(Math.max(this.promptIndex + direction, 0), Math.max(prompts.length - 1, 0));
+      this.renderPrompts();
+    }
+  }
+
+  // Insert at the cursor and select the first unfilled {{variable}} to type over
+  private async insertPrompt(name: string): Promise<void> {
+    try {
+      const text = await invoke<string>("insert_prompt", { name });
+      this.closeSearchMode();
+      const start = this.textarea.selectionStart;
+      this.textarea.setRangeText(text, start, this.textarea.selectionEnd, "end");
+      const placeholder = /\{\{[\w-]+\}\}/.exec(text);
+      if (placeholder) {
+        const from = start + placeholder.index;
+        this.textarea.setSelectionRange(from, from + placeholder[0].length);
+      }
+      this.draftVersions = null;
+      this.scheduleDraftSave();
+      if (text.includes("{{clipboard}}")) await this.warnClipboardNotText();
+    } catch (error) {
+      console.error("Failed to insert prompt:", error);
+      this.showError(errorMessage(error));
+    }
+  }
+
+  // {{clipboard}} was left in a template: say what the clipboard holds instead of text
+  private async warnClipboardNotText(): Promise<void> {
+    const clipboard = await invoke<ClipboardPreview>("get_clipboard_preview");
+    const holds = {
+      files: `${clipboard.size} ${clipboard.size === 1 ? "file" : "files"}`,
+      image: `an image (${clipboard.preview})`,
+      text: "text",
+      other: "no text",
+    }[clipboard.kind];
+    this.showError(`{{clipboard}} not filled: the clipboard holds ${holds}`);
+  }
+
+  private async loadSymbols(query: string): Promise<void> {
+    try {
+      this.symbols = await invoke<SymbolEntry[]>("search_symbols", { query });
+    } catch (error) {
+      console.error("Failed to search symbols:", error);
+      this.symbols = [];
+      this.showError(errorMessage(error));
+    }
+    this.symbolIndex = 0;
+    this.variantIndex = 0;
+    this.renderSymbols();
+  }
+
+  private renderSymbols(): void {
+    const symbols = this.symbols ?? [];
+    if (symbols.length === 0) {
+      const message = this.searchQuery.length > 1 ? "No matching symbols" : "Type to search emojis and symbols";
+      this.historyList.innerHTML = `<li class="empty-message">${message}</li>`;
+      return;
+    }
+
+    this.historyList.innerHTML = symbols
+      .map((symbol, index) => {
+        const selected = index === this.symbolIndex;
+        const choices = [symbol.symbol, ...symbol.variants]
+          .map((choice, variant) => {
+            const chosen = selected && variant === this.variantIndex ? " chosen" : "";
+            return `<span class="symbol-choice${chosen}" data-variant="${variant}">${this.escapeHtml(choice)}</span>`;
+          })
+          .join("");
+        const shortcode = symbol.shortcode ? ` :${symbol.shortcode}:` : "";
+        const recent = symbol.recent ? " (recent)" : "";
+        return `<li data-index="${index}" class="${selected ? "selected" : ""}">
+          <span class="symbol-choices">${choices}</span>
+          <span class="preview">${this.escapeHtml(symbol.name + shortcode + recent)}</span>
+        </li>`;
+      })
+      .join("");
+
+    this.historyList.querySelectorAll("li[data-index]").forEach((li) => {
+      li.addEventListener("click", (e) => {
+        const symbol = symbols[parseInt((li as HTMLElement).dataset.index || "0", 10)];
+        const choice = (e.target as HTMLElement).closest<HTMLElement>(".symbol-choice");
+        const variant = parseInt(choice?.dataset.variant || "0", 10);
+        if (symbol) {
+          this.insertSymbol([symbol.symbol, ...symbol.variants][variant] ?? symbol.symbol);
+        }
       });
     });
   }
 
+  // Up/down pick a symbol, left/right one of its skin tones
+  private handleSymbolKey(e: KeyboardEvent): void {
+    const symbols = this.symbols ?? [];
+    const symbol = symbols[this.symbolIndex];
+    let direction = 0;
+    if (e.key === "ArrowUp" || matchShortcut(e, this.shortcuts.history_prev)) {
+      direction = 1;
+    } else if (e.key === "ArrowDown" || matchShortcut(e, this.shortcuts.history_next)) {
+      direction = -1;
+    } else if ((e.key === "ArrowLeft" || e.key === "ArrowRight") && symbol && symbol.variants.length > 0) {
+      e.preventDefault();
+      const step = e.key === "ArrowRight" ? 1 : -1;
+      this.variantIndex = Math.min(Math.max(this.variantIndex + step, 0), symbol.variants.length);
+      this.renderSymbols();
+      return;
+    } else if (e.key === "Enter") {
+      e.preventDefault();
+      if (symbol) {
+        this.insertSymbol([symbol.symbol, ...symbol.variants][this.variantIndex] ?? symbol.symbol);
+      }
+      return;
+    }
+    if (direction !== 0) {
+      e.preventDefault();
+      this.symbolIndex = Math.min(Math.max(this.symbolIndex + direction, 0), Math.max(symbols.length - 1, 0));
+      this.variantIndex = 0;
+      this.renderSymbols();
+    }
+  }
+
+  private insertSymbol(symbol: string): void {
+    this.closeSearchMode();
+    this.textarea.setRangeText(symbol, this.textarea.selectionStart, this.textarea.selectionEnd, "end");
+    this.draftVersions = null;
+    this.scheduleDraftSave();
+    invoke("record_symbol", { symbol }).catch((error) => console.error("Failed to record symbol:", error));
+  }
+
+  private loadPalette(query: string): void {
+    const match = /^(.*?)\s*(\d+)$/.exec(query.trim());
+    const words = (match ? match[1] : query).trim().toLowerCase();
+    this.paletteCount = match ? parseInt(match[2], 10) : null;
+    this.palette = PALETTE_COMMANDS.filter((command) => command.label.toLowerCase().includes(words));
+    this.paletteIndex = 0;
+    this.renderPalette();
+  }
+
+  private renderPalette(): void {
+    const commands = this.palette ?? [];
+    if (commands.length === 0) {
+      this.historyList.innerHTML = `<li class="empty-message">No matching commands</li>`;
+      return;
+    }
+
+    this.historyList.innerHTML = commands
+      .map((command, index) => {
+        const selectedClass = index === this.paletteIndex ? "selected" : "";
+        return `<li data-index="${index}" class="${selectedClass}">
+          <span class="timestamp">${this.escapeHtml(command.label)}</span>
+          <span class="preview">${this.escapeHtml(command.detail)}</span>
+        </li>`;
+      })
+      .join("");
+
+    this.historyList.querySelectorAll("li[data-index]").forEach((li) => {
+      li.addEventListener("click", () => {
+        const command = commands[parseInt((li as HTMLElement).dataset.index || "0", 10)];
+        if (command) {
+          this.runPaletteCommand(command);
+        }
+      });
+    });
+  }
+
+  private handlePaletteKey(e: KeyboardEvent): void {
+    const commands = this.palette ?? [];
+    let direction = 0;
+    if (e.key === "ArrowUp" || matchShortcut(e, this.shortcuts.history_prev)) {
+      direction = 1;
+    } else if (e.key === "ArrowDown" || matchShortcut(e, this.shortcuts.history_next)) {
+      direction = -1;
+    } else if (e.key === "Enter") {
+      e.preventDefault();
+      const command = commands[this.paletteIndex];
+      if (command) {
+        this.runPaletteCommand(command);
+      }
+      return;
+    }
+    if (direction !== 0) {
+      e.preventDefault();
+      this.paletteIndex = Math.min(Math.max(this.paletteIndex + direction, 0), Math.max(commands.length - 1, 0));
+      this.renderPalette();
+    }
+  }
+
+  // Insert the generated text at the cursor
+  private async runPaletteCommand(command: PaletteCommand): Promise<void> {
+    const options: GenerateOptions = { ...command.options };
+    if (this.paletteCount !== null) {
+      if (command.kind === "password") options.length = this.paletteCount;
+      if (command.kind === "lorem") options.paragraphs = this.paletteCount;
+    }
+    try {
+      const text = await invoke<string>("generate_text", { kind: command.kind, options });
+      this.closeSearchMode();
+      this.textarea.setRangeText(text, this.textarea.selectionStart, this.textarea.selectionEnd, "end");
+      this.draftVersions = null;
+      this.scheduleDraftSave();
+    } catch (error) {
+      console.error("Failed to generate text:", error);
+      this.showError(errorMessage(error));
+    }
+  }
+
+  // Start from the selected text when it is on one line
+  private openFindBar(): void {
+    const selected = this.textarea.value.slice(this.textarea.selectionStart, this.textarea.selectionEnd);
+    if (selected && !selected.includes("\n")) {
+      this.findPattern.value = this.findLiteral.checked ? selected : this.escapeRegex(selected);
+    }
+    this.findBar.classList.remove("hidden");
+    this.findPattern.focus();
+    this.findPattern.select();
+    this.updateFindMatches();
+  }
+
+  private closeFindBar(): void {
+    this.findBar.classList.add("hidden");
+    this.findMatches = [];
+    this.focusTextarea();
+  }
+
+  // Flags understood by `find::compile`
+  private findFlags(): string {
+    return [
+      this.findIgnoreCase.checked ? "i" : "",
+      this.findWholeWords.checked ? "w" : "",
+      this.findLiteral.checked ? "F" : "",
+    ].join("");
+  }
+
+  private async updateFindMatches(): Promise<void> {
+    const pattern = this.findPattern.value;
+    this.findStatus.classList.remove("error");
+    if (!pattern) {
+      this.findMatches = [];
+      this.findStatus.textContent = "";
+      return;
+    }
+    try {
+      this.findMatches = await invoke<RegexMatch[]>("regex_find", {
+        text: this.textarea.value,
+        pattern,
+        flags: this.findFlags(),
+      });
+      const count = this.findMatches.length;
+      this.findStatus.textContent = count === 1 ? "1 match" : `${count} matches`;
+    } catch (error) {
+      this.findMatches = [];
+      this.findStatus.textContent = errorMessage(error);
+      this.findStatus.classList.add("error");
+    }
+  }
+
+  // Select the next match after the cursor (or the one before it), wrapping around
+  private findNext(backward: boolean): void {
+    const matches = this.findMatches;
+    if (matches.length === 0) return;
+    const match = backward
+      ? [...matches].reverse().find((m) => m.end < this.textarea.selectionEnd) ?? matches[matches.length - 1]
+      : matches.find((m) => m.start > this.textarea.selectionStart) ?? matches[0];
+    this.textarea.focus();
+    this.textarea.setSelectionRange(match.start, match.end);
+  }
+
+  private async replaceAll(): Promise<void> {
+    const pattern = this.findPattern.value;
+    if (!pattern || this.rewriting) return;
+    try {
+      const result = await invoke<RegexReplaced>("regex_replace", {
+        text: this.textarea.value,
+        pattern,
+        replacement: this.findReplacement.value,
+        flags: this.findFlags(),
+      });
+      if (result.count === 0) {
+        this.findStatus.textContent = "No matches";
+        return;
+      }
+      // The text before is kept as a draft version, so restore_draft undoes this
+      await this.saveDraft();
+      this.textarea.value = result.text;
+      this.draftVersions = null;
+      this.scheduleDraftSave();
+      await this.updateFindMatches();
+      this.findStatus.textContent = `Replaced ${result.count}`;
+    } catch (error) {
+      this.findStatus.textContent = errorMessage(error);
+      this.findStatus.classList.add("error");
+    }
+  }
+
   private highlightSearch(text: string, query: string): string {
     if (!query) return text;
     const regex = new RegExp(`(${this.escapeRegex(query)})`, "gi");
@@ -592,7 +1925,8 @@ class PromptLineApp {
   // Draft autosave methods
   private async loadDraft(): Promise<void> {
     try {
-      const draft = await invoke<string>("load_draft");
+      await this.refreshDraftTabs();
+      const draft = await invoke<string>("load_draft", { name: this.draftKey() });
       if (draft && !this.textarea.value) {
         this.textarea.value = draft;
         // Move cursor to end
@@ -604,20 +1938,167 @@ class PromptLineApp {
   }
 
   private scheduleDraftSave(): void {
-    // Debounce: save after 500ms of no typing
-    if (this.draftSaveTimeout !== null) {
-      clearTimeout(this.draftSaveTimeout);
-    }
-    this.draftSaveTimeout = window.setTimeout(() => {
-      this.saveDraft();
+    // The backend debounces writes, so send every change
+    this.saveDraft();
+  }
+
+  // The text changes from many places (typing, drafts, history, rewrite), so
+  // poll for changes instead of hooking each; counting runs in the backend
+  private startTokenCounter(): void {
+    window.setInterval(() => {
+      if (!this.showTokens) {
+        this.tokenCount.classList.add("hidden");
+        this.countedText = null;
+      } else if (this.textarea.value !== this.countedText) {
+        this.updateTokenCount(this.textarea.value);
+      }
+    }, 300);
+  }
+
+  private startSpellChecker(): void {
+    window.setInterval(() => {
+      if (!this.spellcheckEnabled) {
+        if (this.misspellings.length > 0) this.renderMisspellings([]);
+        this.checkedText = null;
+      } else if (this.textarea.value !== this.checkedText) {
+        this.updateSpelling(this.textarea.value);
+      }
     }, 500);
   }
 
+  private startMarkdownPreview(): void {
+    window.setInterval(() => {
+      if (this.previewing && this.textarea.value !== this.previewedText) {
+        this.updatePreview(this.textarea.value);
+      }
+    }, 300);
+  }
+
+  private togglePreview(): void {
+    this.previewing = !this.previewing;
+    this.previewedText = null;
+    document.body.classList.toggle("previewing", this.previewing);
+    this.markdownPreview.classList.toggle("hidden", !this.previewing);
+    if (this.previewing) {
+      this.updatePreview(this.textarea.value);
+    } else {
+      this.markdownPreview.innerHTML = "";
+      this.scrollHistoryToBottom();
+    }
+  }
+
+  private async updatePreview(text: string): Promise<void> {
+    this.previewedText = text;
+    try {
+      // Sanitized by the backend: raw HTML is escaped, script links are dropped
+      const html = await invoke<string>("render_markdown", { text });
+      if (this.previewing && this.textarea.value === text) this.markdownPreview.innerHTML = html;
+    } catch (error) {
+      console.error("Markdown preview failed:", error);
+      this.showError(errorMessage(error));
+    }
+  }
+
+  private async updateSpelling(text: string): Promise<void> {
+    this.checkedText = text;
+    try {
+      const misspellings = await invoke<Misspelling[]>("spellcheck", { text });
+      // Typing went on while checking; the next tick checks again
+      if (this.textarea.value === text) this.renderMisspellings(misspellings);
+      this.spellError = null;
+    } catch (error) {
+      console.error("Spell check failed:", error);
+      this.renderMisspellings([]);
+      // Once per problem, not on every keystroke
+      const message = errorMessage(error);
+      if (message !== this.spellError) this.showError(message);
+      this.spellError = message;
+    }
+  }
+
+  // Same text as the textarea, transparent, with the misspelled words underlined
+  private renderMisspellings(misspellings: Misspelling[]): void {
+    this.misspellings = misspellings;
+    const text = this.textarea.value;
+    const fragment = document.createDocumentFragment();
+    let position = 0;
+    for (const misspelling of misspellings) {
+      fragment.append(text.slice(position, misspelling.start));
+      const mark = document.createElement("mark");
+      mark.className = "misspelled";
+      mark.textContent = text.slice(misspelling.start, misspelling.end);
+      fragment.append(mark);
+      position = misspelling.end;
+    }
+    // A trailing newline needs something after it to take up a line
+    fragment.append(text.slice(position) + "\n");
+    this.spellOverlay.replaceChildren(fragment);
+    // Wrap at the same width as the text, which excludes the scrollbar
+    this.spellOverlay.style.width = `${this.textarea.clientWidth + 2}px`;
+    this.spellOverlay.scrollTop = this.textarea.scrollTop;
+  }
+
+  private showSpellMenu(e: MouseEvent): void {
+    const caret = this.textarea.selectionStart;
+    const misspelling = this.misspellings.find((m) => m.start <= caret && caret <= m.end);
+    if (!misspelling || this.textarea.value !== this.checkedText) return;
+    e.preventDefault();
+
+    this.spellMenu.replaceChildren();
+    const choices = misspelling.suggestions.length > 0 ? misspelling.suggestions : [null];
+    for (const suggestion of choices) {
+      const item = document.createElement("li");
+      item.textContent = suggestion ?? "No suggestions";
+      if (suggestion === null) {
+        item.classList.add("disabled");
+      } else {
+        item.addEventListener("click", () => this.replaceMisspelling(misspelling, suggestion));
+      }
+      this.spellMenu.append(item);
+    }
+    this.spellMenu.style.left = `${e.clientX}px`;
+    this.spellMenu.style.top = `${e.clientY}px`;
+    this.spellMenu.classList.remove("hidden");
+  }
+
+  private hideSpellMenu(): void {
+    this.spellMenu.classList.add("hidden");
+  }
+
+  private replaceMisspelling(misspelling: Misspelling, suggestion: string): void {
+    this.hideSpellMenu();
+    this.textarea.setRangeText(suggestion, misspelling.start, misspelling.end, "end");
+    // Saves the draft like typing would
+    this.textarea.dispatchEvent(new Event("input"));
+    this.focusTextarea();
+  }
+
+  private async updateTokenCount(text: string): Promise<void> {
+    this.countedText = text;
+    try {
+      const count = await invoke<TokenCount>("count_tokens", { text });
+      const tokens = `${count.exact ? "" : "~"}${count.tokens}`;
+      const over = this.tokenBudget > 0 && count.tokens > this.tokenBudget;
+      this.tokenCount.textContent = this.tokenBudget > 0 ? `${tokens} / ${this.tokenBudget} tokens` : `${tokens} tokens`;
+      this.tokenCount.title = over
+        ? `Over the token budget by ${count.tokens - this.tokenBudget} (${count.encoding})`
+        : `Counted with ${count.encoding}`;
+      this.tokenCount.classList.toggle("over-budget", over);
+      this.tokenCount.classList.remove("hidden");
+    } catch (error) {
+      console.error("Failed to count tokens:", error);
+      this.tokenCount.classList.add("hidden");
+    }
+  }
+
   private async saveDraft(): Promise<void> {
     try {
       const text = this.textarea.value;
       if (text) {
-        await invoke("save_draft", { text });
+        await invoke("save_draft", { name: this.draftKey(), text });
+        if (this.currentDraft !== DEFAULT_DRAFT && !this.draftNames.includes(this.currentDraft)) {
+          await this.refreshDraftTabs();
+        }
       } else {
         await this.clearDraft();
       }
@@ -628,12 +2109,215 @@ class PromptLineApp {
 
   private async clearDraft(): Promise<void> {
     try {
-      await invoke("clear_draft");
+      await invoke("delete_draft", { name: this.draftKey() });
+      await this.refreshDraftTabs();
     } catch (error) {
       console.error("Failed to clear draft:", error);
     }
   }
 
+  // Per-app drafts replace the default tab's content with one draft per target app
+  private draftKey(target: string | null = this.targetApp): string {
+    if (this.perAppDrafts && target && this.currentDraft === DEFAULT_DRAFT) {
+      return `@${target}`;
+    }
+    return this.currentDraft;
+  }
+
+  private async setDraftTarget(processName: string | null): Promise<void> {
+    const key = this.draftKey(processName);
+    if (key === this.draftKey()) {
+      this.targetApp = processName;
+      return;
+    }
+
+    // Flush the text typed for the previous app before swapping it out
+    await this.saveDraft();
+    this.targetApp = processName;
+    this.draftVersions = null;
+    try {
+      this.textarea.value = await invoke<string>("load_draft", { name: key });
+    } catch (error) {
+      console.error("Failed to load draft:", error);
+      this.textarea.value = "";
+    }
+    this.historyIndex = -1;
+    this.savedInput = "";
+    this.renderDraftTabs();
+  }
+
+  private async editInExternalEditor(): Promise<void> {
+    this.showError("Editing in external editor... close it to continue");
+    try {
+      const text = await invoke<string>("edit_in_external_editor", { text: this.textarea.value });
+      this.hideError();
+      this.textarea.value = text;
+      this.textarea.setSelectionRange(text.length, text.length);
+      this.draftVersions = null;
+      this.scheduleDraftSave();
+    } catch (error) {
+      console.error("External editor failed:", error);
+      this.showError(errorMessage(error));
+    }
+    await this.showWindow();
+    this.focusTextarea();
+  }
+
+  // The original stays in the draft versions, so restore_draft brings it back
+  private async rewriteWithAi(action: TextAction): Promise<void> {
+    const original = this.textarea.value;
+    if (this.rewriting || !original.trim()) return;
+    await this.saveDraft();
+    this.rewriting = true;
+    this.textarea.readOnly = true;
+    this.textarea.value = "";
+    const progress: Record<TextAction, string> = {
+      rewrite: "Rewriting...",
+      summarize: "Summarizing...",
+      translate: "Translating...",
+    };
+    this.showError(progress[action]);
+    try {
+      // Translation has its own backend ([translate]), which may be the LLM
+      const text =
+        action === "translate"
+          ? await invoke<string>("translate_text", { text: original })
+          : await invoke<string>("rewrite_text", { text: original, action });
+      this.hideError();
+      this.textarea.value = text;
+      this.draftVersions = null;
+      this.scheduleDraftSave();
+    } catch (error) {
+      console.error("Rewrite failed:", error);
+      this.textarea.value = original;
+      this.showError(errorMessage(error));
+    } finally {
+      this.rewriting = false;
+      this.textarea.readOnly = false;
+      const end = this.textarea.value.length;
+      this.textarea.setSelectionRange(end, end);
+    }
+  }
+
+  // Each press goes one version further back; typing starts over from the newest
+  private async restorePreviousDraftVersion(): Promise<void> {
+    try {
+      if (this.draftVersions === null) {
+        await this.saveDraft();
+        const key = this.draftKey();
+        const current = this.textarea.value;
+        this.draftVersions = (await invoke<DraftVersion[]>("list_draft_versions")).filter(
+          (version) => version.name === key && version.text !== current
+        );
+        this.draftVersionIndex = 0;
+      }
+
+      const version = this.draftVersions[this.draftVersionIndex];
+      if (!version) {
+        this.showError("No earlier version of this draft");
+        return;
+      }
+      this.draftVersionIndex++;
+
+      await invoke("restore_draft_version", { id: version.id });
+      this.hideError();
+      this.textarea.value = version.text;
+      this.textarea.setSelectionRange(version.text.length, version.text.length);
+      this.historyIndex = -1;
+      this.savedInput = "";
+    } catch (error) {
+      console.error("Failed to restore draft version:", error);
+      this.showError(errorMessage(error));
+    }
+  }
+
+  // Draft tabs: one per saved draft, plus the one being edited
+  private async refreshDraftTabs(): Promise<void> {
+    try {
+      this.draftNames = await invoke<string[]>("list_drafts");
+    } catch (error) {
+      console.error("Failed to list drafts:", error);
+    }
+    this.renderDraftTabs();
+  }
+
+  private renderDraftTabs(): void {
+    const names = [DEFAULT_DRAFT, ...this.draftNames.filter((name) => name !== DEFAULT_DRAFT)];
+    if (!names.includes(this.currentDraft)) {
+      names.push(this.currentDraft);
+    }
+
+    this.draftTabs.innerHTML = "";
+    if (names.length > 1) {
+      for (const name of names) {
+        const tab = document.createElement("button");
+        tab.className = "draft-tab" + (name === this.currentDraft ? " active" : "");
+        // With per-app drafts the default tab holds the target app's draft
+        tab.textContent = name === DEFAULT_DRAFT && this.perAppDrafts && this.targetApp ? this.targetApp : name;
+        tab.title = name === DEFAULT_DRAFT ? name : `${name} (middle-click to delete)`;
+        tab.addEventListener("click", () => this.switchDraft(name));
+        tab.addEventListener("auxclick", (e) => {
+          if (e.button === 1 && name !== DEFAULT_DRAFT) {
+            this.deleteDraft(name);
+          }
+        });
+        this.draftTabs.appendChild(tab);
+      }
+    }
+
+    const add = document.createElement("button");
+    add.className = "draft-tab draft-add";
+    add.textContent = "+";
+    add.title = "New draft";
+    add.addEventListener("click", () => this.newDraft());
+    this.draftTabs.appendChild(add);
+  }
+
+  private async switchDraft(name: string): Promise<void> {
+    if (name === this.currentDraft) {
+      this.focusTextarea();
+      return;
+    }
+    await this.saveDraft();
+    this.currentDraft = name;
+    this.draftVersions = null;
+    try {
+      const draft = await invoke<string>("load_draft", { name: this.draftKey() });
+      this.textarea.value = draft;
+      this.textarea.setSelectionRange(draft.length, draft.length);
+    } catch (error) {
+      console.error("Failed to load draft:", error);
+      this.textarea.value = "";
+    }
+    this.historyIndex = -1;
+    this.savedInput = "";
+    this.renderDraftTabs();
+    this.focusTextarea();
+  }
+
+  private async newDraft(): Promise<void> {
+    let n = 2;
+    while (this.draftNames.includes(`draft ${n}`) || this.currentDraft === `draft ${n}`) {
+      n++;
+    }
+    await this.switchDraft(`draft ${n}`);
+  }
+
+  private async deleteDraft(name: string): Promise<void> {
+    try {
+      await invoke("delete_draft", { name });
+    } catch (error) {
+      console.error("Failed to delete draft:", error);
+    }
+    if (name === this.currentDraft) {
+      this.textarea.value = "";
+      this.currentDraft = DEFAULT_DRAFT;
+      await this.loadDraft();
+    } else {
+      await this.refreshDraftTabs();
+    }
+  }
+
   // === Readline: Cursor Movement ===
   private moveCursorToLineStart(): void {
     const pos = this.textarea.selectionStart;
@@ -689,6 +2373,30 @@ class PromptLineApp {
     this.textarea.setSelectionRange(newPos, newPos);
   }
 
+  private applyVoiceStatus(status: VoiceStatus): void {
+    const toggle = this.voiceToggle.closest(".voice-toggle") as HTMLElement;
+    const listening = status.active && status.panel === "visible";
+    toggle.classList.toggle("listening", listening);
+    toggle.style.setProperty("--mic-level", String(listening ? (status.level ?? 0) : 0));
+  }
+
+  // Insert transcribed speech at the cursor, separated from adjacent text by a space
+  private insertTranscript(text: string): void {
+    const start = this.textarea.selectionStart;
+    const end = this.textarea.selectionEnd;
+    const before = this.textarea.value.substring(0, start);
+    const after = this.textarea.value.substring(end);
+    const prefix = before && !/\s$/.test(before) ? " " : "";
+    const suffix = after && !/^\s/.test(after) ? " " : "";
+    const inserted = prefix + text + suffix;
+
+    this.textarea.value = before + inserted + after;
+    const pos = start + prefix.length + text.length;
+    this.textarea.setSelectionRange(pos, pos);
+    this.draftVersions = null;
+    this.scheduleDraftSave();
+  }
+
   // === Readline: Kill (Delete) ===
   private killToEnd(): void {
     const pos = this.textarea.selectionStart;
@@ -768,5 +2476,6 @@ class PromptLineApp {
 
 // Initialize app when DOM is ready
 document.addEventListener("DOMContentLoaded", () => {
+  initTheme();
   new PromptLineApp();
 });
@@ -0,0 +1,182 @@
+// History picker: search the history and paste an entry straight into the app
+// the picker was summoned over (the `show_history_picker` global hotkey)
+
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { errorMessage } from "./errors";
+import { initTheme } from "./theme";
+
+interface HistoryEntry {
+  text: string;
+  timestamp: string;
+  app?: string; // process the text went to
+  title?: string; // that window's title
+}
+
+// Same as the main window's history list
+const MAX_SHOWN = 50;
+
+class HistoryPicker {
+  private searchInput: HTMLInputElement;
+  private historyList: HTMLUListElement;
+  private statusMessage: HTMLElement;
+  private entries: HistoryEntry[] = [];
+  private selected = 0;
+  private pasting = false;
+
+  constructor() {
+    this.searchInput = document.getElementById("picker-search") as HTMLInputElement;
+    this.historyList = document.getElementById("history-list") as HTMLUListElement;
+    this.statusMessage = document.getElementById("status-message") as HTMLElement;
+
+    this.searchInput.addEventListener("input", () => this.loadHistory());
+    this.searchInput.addEventListener("keydown", (e) => this.handleKeydown(e));
+
+    // Shown again by the hotkey: start over with an empty search
+    listen("picker-shown", () => this.reset());
+    listen("history-changed", () => this.loadHistory());
+    // Entries are blurred while the screen is shared
+    listen<boolean>("screen-share-changed", (event) => {
+      document.body.classList.toggle("screen-private", event.payload);
+    });
+    invoke<boolean>("get_screen_share_private")
+      .then((screenPrivate) => document.body.classList.toggle("screen-private", screenPrivate))
+      .catch((error) => console.error("Failed to get screen sharing state:", error));
+
+    this.reset();
+  }
+
+  private async reset(): Promise<void> {
+    this.searchInput.value = "";
+    this.hideError();
+    await this.loadHistory();
+    this.searchInput.focus();
+  }
+
+  private async loadHistory(): Promise<void> {
+    const query = this.searchInput.value;
+    try {
+      let entries = await invoke<HistoryEntry[]>("get_history", { query });
+      // Nothing recent: look through the entries archived by max_entries
+      if (query.trim() && entries.length === 0) {
+        entries = await invoke<HistoryEntry[]>("search_archive", { query });
+      }
+      this.entries = entries.slice(0, MAX_SHOWN);
+      this.selected = 0;
+      this.render();
+    } catch (error) {
+      console.error("Failed to load history:", error);
+      this.showError(errorMessage(error));
+    }
+  }
+
+  private handleKeydown(e: KeyboardEvent): void {
+    // The list is newest-last on screen, so "up" is an older entry
+    if (e.key === "ArrowUp" || (e.ctrlKey && e.key === "k")) {
+      e.preventDefault();
+      this.select(this.selected + 1);
+    } else if (e.key === "ArrowDown" || (e.ctrlKey && e.key === "j")) {
+      e.preventDefault();
+      this.select(this.selected - 1);
+    } else if (e.key === "Enter" && !e.isComposing) {
+      e.preventDefault();
+      this.paste(this.selected);
+    } else if (e.key === "Escape") {
+      e.preventDefault();
+      getCurrentWindow().hide();
+    }
+  }
+
+  private select(index: number): void {
+    if (index < 0 || index >= this.entries.length) return;
+    this.selected = index;
+    this.historyList.querySelectorAll("li[data-index]").forEach((item, i) => {
+      item.classList.toggle("selected", i === index);
+      if (i === index) item.scrollIntoView({ block: "nearest" });
+    });
+  }
+
+  private async paste(index: number): Promise<void> {
+    const entry = this.entries[index];
+    if (!entry || this.pasting) return;
+
+    this.pasting = true;
+    this.hideError();
+    try {
+      await invoke("paste_history_entry", { id: entry.timestamp });
+    } catch (error) {
+      console.error("Paste failed:", error);
+      // The picker hides before pasting; bring it back to show why
+      await getCurrentWindow().show();
+      await getCurrentWindow().setFocus();
+      this.showError(errorMessage(error));
+    } finally {
+      this.pasting = false;
+    }
+  }
+
+  private render(): void {
+    if (this.entries.length === 0) {
+      this.historyList.innerHTML = `<li class="empty-message">${
+        this.searchInput.value ? "No matching history" : "No history yet"
+      }</li>`;
+      return;
+    }
+
+    const query = this.searchInput.value;
+    this.historyList.innerHTML = this.entries
+      .map((entry, index) => {
+        const preview = entry.text.length > 80 ? entry.text.substring(0, 80) + "..." : entry.text;
+        const timestamp = new Date(entry.timestamp).toLocaleString("ja-JP", {
+          month: "short",
+          day: "numeric",
+          hour: "2-digit",
+          minute: "2-digit",
+        });
+        const escapedPreview = escapeHtml(preview).replace(/\n/g, " ");
+        const highlightedPreview = query ? highlightSearch(escapedPreview, query) : escapedPreview;
+        const source = entry.app ? ` · ${escapeHtml(entry.app.replace(/\.exe$/i, ""))}` : "";
+        const sourceTitle = entry.title ? ` title="${escapeHtml(entry.title).replace(/"/g, "&quot;")}"` : "";
+
+        return `<li data-index="${index}" class="${index === this.selected ? "selected" : ""}">
+          <span class="timestamp"${sourceTitle}>${timestamp}${source}</span>
+          <span class="preview">${highlightedPreview}</span>
+        </li>`;
+      })
+      .join("");
+
+    this.historyList.querySelectorAll("li[data-index]").forEach((li) => {
+      li.addEventListener("click", () => {
+        this.paste(parseInt((li as HTMLElement).dataset.index || "0", 10));
+      });
+    });
+    this.historyList.querySelector("li.selected")?.scrollIntoView({ block: "nearest" });
+  }
+
+  private showError(message: string): void {
+    this.statusMessage.textContent = message;
+    this.statusMessage.classList.remove("hidden");
+  }
+
+  private hideError(): void {
+    this.statusMessage.classList.add("hidden");
+  }
+}
+
+function escapeHtml(text: string): string {
+  const div = document.createElement("div");
+  div.textContent = text;
+  return div.innerHTML;
+}
+
+function highlightSearch(text: string, query: string): string {
+  const escaped = query.replace(/[.*+?^${}()|[\]\\]/g, "\\$&");
+  return text.replace(new RegExp(`(${escaped})`, "gi"), '<span class="search-highlight">$1</span>');
+}
+
+// Initialize picker when DOM is ready
+document.addEventListener("DOMContentLoaded", () => {
+  initTheme();
+  new HistoryPicker();
+});
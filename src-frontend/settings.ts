@@ -1,5 +1,8 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
+import { errorMessage } from "./errors";
+import { initTheme } from "./theme";
 
 interface Shortcuts {
   launch: string;
@@ -22,10 +25,33 @@ interface Shortcuts {
   kill_word_back: string;
   delete_char: string;
   yank: string;
+  restore_draft: string;
+  external_editor: string;
+  capture: string;
+  pin: string;
+  toggle_mode: string;
+  rewrite: string;
+  summarize: string;
+  translate: string;
+  prompts: string;
+  symbols: string;
+  palette: string;
+  find_replace: string;
+  preview: string;
+  queue: string;
+  append: string;
+  always_on_top?: string;
+  // Additional global hotkeys (hotkey -> action)
+  global?: Record<string, string>;
 }
 
 interface HistoryConfig {
   max_entries: number;
+  sync_dir: string;
+  join_separator?: string;
+  trash_days?: number;
+  retention_days?: number;
+  retention_action?: string;
 }
 
 interface WindowConfig {
@@ -34,33 +60,197 @@ interface WindowConfig {
   history_lines: number;
   textarea_rows: number;
   textarea_cols: number;
+  position?: string | { x: number; y: number };
+  opacity?: number;
+  effect?: string;
+  scale?: number;
+  preset?: string;
+  presets?: Record<string, unknown>;
+  always_on_top?: boolean;
 }
 
+interface WindowOverride {
+  font_size?: number;
+  history_font_size?: number;
+  history_lines?: number;
+  textarea_rows?: number;
+  textarea_cols?: number;
+}
+
+type PasteMethod = "keys" | "wm_paste" | "uia";
+
 interface AppPasteOverride {
   process_name: string;
+  title?: string;
   shortcut: string;
+  window?: WindowOverride;
+  template?: string;
+  voice?: boolean;
+  method?: PasteMethod;
+  undo_shortcut?: string;
 }
 
 interface BehaviorConfig {
   simulate_paste_shortcut: string;
+  paste_method: PasteMethod;
   app_overrides: AppPasteOverride[];
+  detect_terminals: boolean;
+  auto_pause_fullscreen: boolean;
+  screen_share_privacy?: boolean;
+  screen_share_processes?: string[];
+  hold_to_show: boolean;
+  paste_on_release: boolean;
+  tray_left_click: string;
+  jump_list_entries?: number;
+  autostart: boolean;
+  start_hidden: boolean;
+  notify_on_paste: boolean;
+  per_app_drafts: boolean;
+  evaluate_on_paste: boolean;
+  ime_safe_paste?: boolean;
+  external_editor: string;
+  hide_on_blur: boolean;
+  no_activate?: boolean;
+  clipboard_retries: number;
+  clipboard_retry_delay_ms: number;
+  clear_clipboard_first: boolean;
+  append_separator?: string;
 }
 
 interface VoiceConfig {
   enabled: boolean;
   delay_ms: number;
+  engine: "dictation" | "voice_access";
+  shortcut: string;
+  stop_shortcut: string;
+  whisper: WhisperConfig;
+}
+
+interface WhisperConfig {
+  model: string;
+  language: string;
+}
+
+interface LoggingConfig {
+  level: string;
+}
+
+interface ThemeConfig {
+  mode: string;
+  accent: string;
+  custom_css: string;
+}
+
+interface EffectiveShortcut {
+  shortcut: string | null;
+  configured: string;
+  is_fallback: boolean;
+}
+
+interface LegacyImportSummary {
+  source: string;
+  history_found: number;
+  history_imported: number;
+  settings_imported: boolean;
+}
+
+interface SyncConfig {
+  enabled: boolean;
+  provider: "webdav" | "s3";
+  url: string;
+  bucket: string;
+  region: string;
+  username: string;
+  interval_minutes: number;
+  file_name: string;
+  include_secret_entries: boolean;
+}
+
+interface LocalModelConfig {
+  model: string;
+  context_size: number;
+  server: string;
+}
+
+interface AiConfig {
+  enabled: boolean;
+  provider: "api" | "local";
+  endpoint: string;
+  model: string;
+  instruction: string;
+  local: LocalModelConfig;
+  show_tokens: boolean;
+  token_budget: number;
+}
+
+interface TranslateConfig {
+  backend: "ai" | "deepl" | "google";
+  target_lang: string;
+  on_paste: boolean;
+}
+
+interface SpellcheckConfig {
+  enabled: boolean;
+  dictionaries: string[];
+  words: string[];
+}
+
+type SecretName = "sync-server" | "sync-passphrase" | "ai-api-key" | "translate-api-key";
+
+interface CompactReport {
+  duplicates: number;
+  short: number;
+  old: number;
+  backup: string;
+}
+
+interface TrashedEntry {
+  text: string;
+  timestamp: string;
+  truncated: boolean;
+  deleted_at: string;
+}
+
+interface RestoreSummary {
+  created: string;
+  history_imported: number;
+  config: Config;
+}
+
+interface RunningApp {
+  process_name: string;
+  exe_path: string | null;
+  title: string;
+  icon: string | null; // PNG data URL
 }
 
 interface Config {
+  locale?: string;
   shortcuts: Shortcuts;
   history: HistoryConfig;
   window: WindowConfig;
   behavior: BehaviorConfig;
   voice: VoiceConfig;
+  logging: LoggingConfig;
+  theme?: ThemeConfig;
+  sync?: SyncConfig;
+  ai?: AiConfig;
+  translate?: TranslateConfig;
+  spellcheck?: SpellcheckConfig;
+  symbols?: { skin_tone: string };
+}
+
+// "a, b,,c" -> ["a", "b", "c"]
+function splitList(value: string): string[] {
+  return value
+    .split(",")
+    .map((item) => item.trim())
+    .filter((item) => item.length > 0);
 }
 
 class SettingsApp {
   private config: Config | null = null;
+  private runningAppsLoaded = false;
 
   // Form elements
   private fontSize: HTMLInputElement;
@@ -68,8 +258,28 @@ class SettingsApp {
   private historyLines: HTMLInputElement;
   private textareaRows: HTMLInputElement;
   private textareaCols: HTMLInputElement;
+  private windowPosition: HTMLSelectElement;
+  private windowOpacity: HTMLInputElement;
+  private windowEffect: HTMLSelectElement;
+  private windowAlwaysOnTop: HTMLInputElement;
+  private windowScale: HTMLInputElement;
+  private windowPreset: HTMLSelectElement;
+  private themeMode: HTMLSelectElement;
+  private locale: HTMLSelectElement;
+  private themeAccent: HTMLInputElement;
+  private themeCustomCss: HTMLInputElement;
   private maxEntries: HTMLInputElement;
+  private historySyncDir: HTMLInputElement;
+  private historyJoinSeparator: HTMLSelectElement;
+  private compactDuplicates: HTMLInputElement;
+  private compactMinChars: HTMLInputElement;
+  private compactOlderThan: HTMLInputElement;
+  private trashEntries: HTMLSelectElement;
+  private trashDays: HTMLInputElement;
+  private retentionDays: HTMLInputElement;
+  private retentionAction: HTMLSelectElement;
   private statusMessage: HTMLElement;
+  private effectiveShortcut: HTMLElement;
 
   // Shortcut elements
   private shortcutLaunch: HTMLInputElement;
@@ -92,9 +302,44 @@ class SettingsApp {
   private shortcutKillWordBack: HTMLInputElement;
   private shortcutDeleteChar: HTMLInputElement;
   private shortcutYank: HTMLInputElement;
+  private shortcutRestoreDraft: HTMLInputElement;
+  private shortcutExternalEditor: HTMLInputElement;
+  private shortcutCapture: HTMLInputElement;
+  private shortcutPin: HTMLInputElement;
+  private shortcutToggleMode: HTMLInputElement;
+  private shortcutRewrite: HTMLInputElement;
+  private shortcutSummarize: HTMLInputElement;
+  private shortcutTranslate: HTMLInputElement;
+  private shortcutPrompts: HTMLInputElement;
+  private shortcutSymbols: HTMLInputElement;
+  private shortcutPalette: HTMLInputElement;
+  private shortcutFindReplace: HTMLInputElement;
+  private shortcutPreview: HTMLInputElement;
+  private shortcutQueue: HTMLInputElement;
+  private shortcutAppend: HTMLInputElement;
+  private shortcutAlwaysOnTop: HTMLInputElement;
+  private symbolsSkinTone: HTMLSelectElement;
 
   // Behavior
   private simulatePasteShortcut: HTMLInputElement;
+  private pasteMethod: HTMLSelectElement;
+  private detectTerminals: HTMLInputElement;
+  private autoPauseFullscreen: HTMLInputElement;
+  private screenSharePrivacy: HTMLInputElement;
+  private screenShareProcesses: HTMLInputElement;
+  private holdToShow: HTMLInputElement;
+  private pasteOnRelease: HTMLInputElement;
+  private trayLeftClick: HTMLSelectElement;
+  private jumpListEntries: HTMLInputElement;
+  private autostart: HTMLInputElement;
+  private startHidden: HTMLInputElement;
+  private notifyOnPaste: HTMLInputElement;
+  private perAppDrafts: HTMLInputElement;
+  private evaluateOnPaste: HTMLInputElement;
+  private imeSafePaste: HTMLInputElement;
+  private externalEditor: HTMLInputElement;
+  private hideOnBlur: HTMLInputElement;
+  private noActivate: HTMLInputElement;
   private appOverride1Process: HTMLInputElement;
   private appOverride1Shortcut: HTMLInputElement;
   private appOverride2Process: HTMLInputElement;
@@ -105,6 +350,48 @@ class SettingsApp {
   // Voice input
   private voiceEnabled: HTMLInputElement;
   private voiceDelay: HTMLInputElement;
+  private voiceEngine: HTMLSelectElement;
+  private voiceShortcut: HTMLInputElement;
+  private voiceStopShortcut: HTMLInputElement;
+  private whisperModel: HTMLInputElement;
+  private whisperLanguage: HTMLInputElement;
+
+  // Diagnostics
+  private backupPathInput: HTMLInputElement;
+  private syncEnabled: HTMLInputElement;
+  private syncProvider: HTMLSelectElement;
+  private syncUrl: HTMLInputElement;
+  private syncBucket: HTMLInputElement;
+  private syncRegion: HTMLInputElement;
+  private syncUsername: HTMLInputElement;
+  private syncInterval: HTMLInputElement;
+  private syncServerSecret: HTMLInputElement;
+  private syncPassphrase: HTMLInputElement;
+  private syncSecretsStatus: HTMLElement;
+  private aiEnabled: HTMLInputElement;
+  private aiProvider: HTMLSelectElement;
+  private aiEndpoint: HTMLInputElement;
+  private aiLocalModel: HTMLInputElement;
+  private aiLocalContext: HTMLInputElement;
+  private aiLocalServer: HTMLInputElement;
+  private aiModel: HTMLInputElement;
+  private aiInstruction: HTMLInputElement;
+  private aiShowTokens: HTMLInputElement;
+  private aiTokenBudget: HTMLInputElement;
+  private aiApiKey: HTMLInputElement;
+  private aiKeyStatus: HTMLElement;
+  private translateBackend: HTMLSelectElement;
+  private translateTarget: HTMLInputElement;
+  private translateOnPaste: HTMLInputElement;
+  private translateApiKey: HTMLInputElement;
+  private translateKeyStatus: HTMLElement;
+  private spellcheckEnabled: HTMLInputElement;
+  private spellcheckDictionaries: HTMLInputElement;
+  private spellcheckWords: HTMLInputElement;
+  private logLevel: HTMLSelectElement;
+  private recentLogs: HTMLPreElement;
+  private diagnosticsReport: HTMLPreElement;
+  private copyDiagnosticsBtn: HTMLButtonElement;
 
   constructor() {
     this.fontSize = document.getElementById("font-size") as HTMLInputElement;
@@ -112,8 +399,30 @@ class SettingsApp {
     this.historyLines = document.getElementById("history-lines") as HTMLInputElement;
     this.textareaRows = document.getElementById("textarea-rows") as HTMLInputElement;
     this.textareaCols = document.getElementById("textarea-cols") as HTMLInputElement;
+    this.windowPosition = document.getElementById("window-position") as HTMLSelectElement;
+    this.windowOpacity = document.getElementById("window-opacity") as HTMLInputElement;
+    this.windowEffect = document.getElementById("window-effect") as HTMLSelectElement;
+    this.windowAlwaysOnTop = document.getElementById("window-always-on-top") as HTMLInputElement;
+    this.windowScale = document.getElementById("window-scale") as HTMLInputElement;
+    this.windowPreset = document.getElementById("window-preset") as HTMLSelectElement;
+    this.themeMode = document.getElementById("theme-mode") as HTMLSelectElement;
+    this.locale = document.getElementById("locale") as HTMLSelectElement;
+    // Preview the chosen language; the tray and notifications follow on save
+    this.locale.addEventListener("change", () => this.applyStrings(this.locale.value));
+    this.themeAccent = document.getElementById("theme-accent") as HTMLInputElement;
+    this.themeCustomCss = document.getElementById("theme-custom-css") as HTMLInputElement;
     this.maxEntries = document.getElementById("max-entries") as HTMLInputElement;
+    this.historySyncDir = document.getElementById("history-sync-dir") as HTMLInputElement;
+    this.historyJoinSeparator = document.getElementById("history-join-separator") as HTMLSelectElement;
+    this.compactDuplicates = document.getElementById("compact-duplicates") as HTMLInputElement;
+    this.compactMinChars = document.getElementById("compact-min-chars") as HTMLInputElement;
+    this.compactOlderThan = document.getElementById("compact-older-than") as HTMLInputElement;
+    this.trashEntries = document.getElementById("trash-entries") as HTMLSelectElement;
+    this.trashDays = document.getElementById("trash-days") as HTMLInputElement;
+    this.retentionDays = document.getElementById("retention-days") as HTMLInputElement;
+    this.retentionAction = document.getElementById("retention-action") as HTMLSelectElement;
     this.statusMessage = document.getElementById("status-message") as HTMLElement;
+    this.effectiveShortcut = document.getElementById("effective-shortcut") as HTMLElement;
 
     // Shortcut inputs
     this.shortcutLaunch = document.getElementById("shortcut-launch") as HTMLInputElement;
@@ -136,28 +445,151 @@ class SettingsApp {
     this.shortcutKillWordBack = document.getElementById("shortcut-kill-word-back") as HTMLInputElement;
     this.shortcutDeleteChar = document.getElementById("shortcut-delete-char") as HTMLInputElement;
     this.shortcutYank = document.getElementById("shortcut-yank") as HTMLInputElement;
+    this.shortcutRestoreDraft = document.getElementById("shortcut-restore-draft") as HTMLInputElement;
+    this.shortcutExternalEditor = document.getElementById("shortcut-external-editor") as HTMLInputElement;
+    this.shortcutCapture = document.getElementById("shortcut-capture") as HTMLInputElement;
+    this.shortcutPin = document.getElementById("shortcut-pin") as HTMLInputElement;
+    this.shortcutToggleMode = document.getElementById("shortcut-toggle-mode") as HTMLInputElement;
+    this.shortcutRewrite = document.getElementById("shortcut-rewrite") as HTMLInputElement;
+    this.shortcutSummarize = document.getElementById("shortcut-summarize") as HTMLInputElement;
+    this.shortcutTranslate = document.getElementById("shortcut-translate") as HTMLInputElement;
+    this.shortcutPrompts = document.getElementById("shortcut-prompts") as HTMLInputElement;
+    this.shortcutSymbols = document.getElementById("shortcut-symbols") as HTMLInputElement;
+    this.shortcutPalette = document.getElementById("shortcut-palette") as HTMLInputElement;
+    this.shortcutFindReplace = document.getElementById("shortcut-find-replace") as HTMLInputElement;
+    this.shortcutPreview = document.getElementById("shortcut-preview") as HTMLInputElement;
+    this.shortcutQueue = document.getElementById("shortcut-queue") as HTMLInputElement;
+    this.shortcutAppend = document.getElementById("shortcut-append") as HTMLInputElement;
+    this.shortcutAlwaysOnTop = document.getElementById("shortcut-always-on-top") as HTMLInputElement;
+    this.symbolsSkinTone = document.getElementById("symbols-skin-tone") as HTMLSelectElement;
 
     // Behavior
     this.simulatePasteShortcut = document.getElementById("simulate-paste-shortcut") as HTMLInputElement;
+    this.pasteMethod = document.getElementById("paste-method") as HTMLSelectElement;
+    this.detectTerminals = document.getElementById("detect-terminals") as HTMLInputElement;
+    this.autoPauseFullscreen = document.getElementById("auto-pause-fullscreen") as HTMLInputElement;
+    this.screenSharePrivacy = document.getElementById("screen-share-privacy") as HTMLInputElement;
+    this.screenShareProcesses = document.getElementById("screen-share-processes") as HTMLInputElement;
+    this.holdToShow = document.getElementById("hold-to-show") as HTMLInputElement;
+    this.pasteOnRelease = document.getElementById("paste-on-release") as HTMLInputElement;
+    this.trayLeftClick = document.getElementById("tray-left-click") as HTMLSelectElement;
+    this.jumpListEntries = document.getElementById("jump-list-entries") as HTMLInputElement;
+    this.autostart = document.getElementById("autostart") as HTMLInputElement;
+    this.startHidden = document.getElementById("start-hidden") as HTMLInputElement;
+    this.notifyOnPaste = document.getElementById("notify-on-paste") as HTMLInputElement;
+    this.perAppDrafts = document.getElementById("per-app-drafts") as HTMLInputElement;
+    this.evaluateOnPaste = document.getElementById("evaluate-on-paste") as HTMLInputElement;
+    this.imeSafePaste = document.getElementById("ime-safe-paste") as HTMLInputElement;
+    this.externalEditor = document.getElementById("external-editor") as HTMLInputElement;
+    this.hideOnBlur = document.getElementById("hide-on-blur") as HTMLInputElement;
+    this.noActivate = document.getElementById("no-activate") as HTMLInputElement;
     this.appOverride1Process = document.getElementById("app-override-1-process") as HTMLInputElement;
     this.appOverride1Shortcut = document.getElementById("app-override-1-shortcut") as HTMLInputElement;
     this.appOverride2Process = document.getElementById("app-override-2-process") as HTMLInputElement;
     this.appOverride2Shortcut = document.getElementById("app-override-2-shortcut") as HTMLInputElement;
     this.appOverride3Process = document.getElementById("app-override-3-process") as HTMLInputElement;
     this.appOverride3Shortcut = document.getElementById("app-override-3-shortcut") as HTMLInputElement;
+    const pickWindow = document.getElementById("btn-pick-window") as HTMLButtonElement;
+    pickWindow.addEventListener("click", () => this.handlePickWindow(pickWindow));
+    // Listed when a process field is first used, as extracting icons takes a moment
+    for (const input of [this.appOverride1Process, this.appOverride2Process, this.appOverride3Process]) {
+      input.addEventListener("focus", () => this.loadRunningApps(), { once: true });
+    }
 
     // Voice input
     this.voiceEnabled = document.getElementById("voice-enabled") as HTMLInputElement;
     this.voiceDelay = document.getElementById("voice-delay") as HTMLInputElement;
+    this.voiceEngine = document.getElementById("voice-engine") as HTMLSelectElement;
+    this.voiceShortcut = document.getElementById("voice-shortcut") as HTMLInputElement;
+    this.voiceStopShortcut = document.getElementById("voice-stop-shortcut") as HTMLInputElement;
+    this.whisperModel = document.getElementById("whisper-model") as HTMLInputElement;
+    this.whisperLanguage = document.getElementById("whisper-language") as HTMLInputElement;
+
+    // Backup
+    this.backupPathInput = document.getElementById("backup-path") as HTMLInputElement;
+    this.syncEnabled = document.getElementById("sync-enabled") as HTMLInputElement;
+    this.syncProvider = document.getElementById("sync-provider") as HTMLSelectElement;
+    this.syncUrl = document.getElementById("sync-url") as HTMLInputElement;
+    this.syncBucket = document.getElementById("sync-bucket") as HTMLInputElement;
+    this.syncRegion = document.getElementById("sync-region") as HTMLInputElement;
+    this.syncUsername = document.getElementById("sync-username") as HTMLInputElement;
+    this.syncInterval = document.getElementById("sync-interval") as HTMLInputElement;
+    this.syncServerSecret = document.getElementById("sync-server-secret") as HTMLInputElement;
+    this.syncPassphrase = document.getElementById("sync-passphrase") as HTMLInputElement;
+    this.syncSecretsStatus = document.getElementById("sync-secrets-status") as HTMLElement;
+    this.aiEnabled = document.getElementById("ai-enabled") as HTMLInputElement;
+    this.aiProvider = document.getElementById("ai-provider") as HTMLSelectElement;
+    this.aiEndpoint = document.getElementById("ai-endpoint") as HTMLInputElement;
+    this.aiLocalModel = document.getElementById("ai-local-model") as HTMLInputElement;
+    this.aiLocalContext = document.getElementById("ai-local-context") as HTMLInputElement;
+    this.aiLocalServer = document.getElementById("ai-local-server") as HTMLInputElement;
+    this.aiModel = document.getElementById("ai-model") as HTMLInputElement;
+    this.aiInstruction = document.getElementById("ai-instruction") as HTMLInputElement;
+    this.aiShowTokens = document.getElementById("ai-show-tokens") as HTMLInputElement;
+    this.aiTokenBudget = document.getElementById("ai-token-budget") as HTMLInputElement;
+    this.aiApiKey = document.getElementById("ai-api-key") as HTMLInputElement;
+    this.aiKeyStatus = document.getElementById("ai-key-status") as HTMLElement;
+    this.translateBackend = document.getElementById("translate-backend") as HTMLSelectElement;
+    this.translateTarget = document.getElementById("translate-target") as HTMLInputElement;
+    this.translateOnPaste = document.getElementById("translate-on-paste") as HTMLInputElement;
+    this.translateApiKey = document.getElementById("translate-api-key") as HTMLInputElement;
+    this.translateKeyStatus = document.getElementById("translate-key-status") as HTMLElement;
+    this.spellcheckEnabled = document.getElementById("spellcheck-enabled") as HTMLInputElement;
+    this.spellcheckDictionaries = document.getElementById("spellcheck-dictionaries") as HTMLInputElement;
+    this.spellcheckWords = document.getElementById("spellcheck-words") as HTMLInputElement;
+
+    // Diagnostics
+    this.logLevel = document.getElementById("log-level") as HTMLSelectElement;
+    this.recentLogs = document.getElementById("recent-logs") as HTMLPreElement;
+    this.diagnosticsReport = document.getElementById("diagnostics-report") as HTMLPreElement;
+    this.copyDiagnosticsBtn = document.getElementById("btn-copy-diagnostics") as HTMLButtonElement;
 
     this.setupEventListeners();
     this.loadConfig();
+    this.loadEffectiveShortcut();
+    this.loadStoredSecrets();
+    this.loadTrash();
   }
 
   private setupEventListeners(): void {
     document.getElementById("btn-save")?.addEventListener("click", () => this.handleSave());
     document.getElementById("btn-cancel")?.addEventListener("click", () => this.handleCancel());
+    document.getElementById("btn-compact-history")?.addEventListener("click", () => this.handleCompactHistory());
     document.getElementById("btn-clear-history")?.addEventListener("click", () => this.handleClearHistory());
+    document.getElementById("btn-restore-trash")?.addEventListener("click", () => this.handleRestoreTrash(false));
+    document.getElementById("btn-restore-all-trash")?.addEventListener("click", () => this.handleRestoreTrash(true));
+    document.getElementById("btn-empty-trash")?.addEventListener("click", () => this.handleEmptyTrash());
+    document.getElementById("btn-export-config")?.addEventListener("click", () => this.handleExportConfig());
+    document.getElementById("btn-import-config")?.addEventListener("click", () => this.handleImportConfig());
+    document.getElementById("btn-reset-config")?.addEventListener("click", () => this.handleResetConfig());
+    document.getElementById("btn-reset-window")?.addEventListener("click", () => this.handleResetWindow());
+    document.getElementById("btn-import-legacy")?.addEventListener("click", () => this.handleImportLegacy());
+    document.getElementById("btn-save-ai-key")?.addEventListener("click", () => this.handleSaveAiKey());
+    document
+      .getElementById("btn-save-translate-key")
+      ?.addEventListener("click", () => this.handleSaveTranslateKey());
+    document.getElementById("btn-save-sync-secrets")?.addEventListener("click", () => this.handleSaveSyncSecrets());
+    document.getElementById("btn-backup-now")?.addEventListener("click", () => this.handleBackupNow());
+    document.getElementById("btn-restore-backup")?.addEventListener("click", () => this.handleRestoreBackup());
+    document.getElementById("btn-show-logs")?.addEventListener("click", () => this.handleShowLogs());
+    document.getElementById("btn-run-diagnostics")?.addEventListener("click", () => this.handleRunDiagnostics());
+    this.copyDiagnosticsBtn.addEventListener("click", () => this.handleCopyDiagnostics());
+
+    // Normalize the global hotkey as typed (e.g. "ctrl+shift+f5" -> "Ctrl+Shift+F5")
+    this.shortcutLaunch.addEventListener("blur", () => this.normalizeGlobalShortcut(this.shortcutLaunch));
+    const recordLaunch = document.getElementById("btn-record-launch") as HTMLButtonElement;
+    recordLaunch.addEventListener("click", () => this.recordShortcut(this.shortcutLaunch, recordLaunch));
+
+    // Hotkey registration result (fallbacks, re-registration on save)
+    listen<EffectiveShortcut>("effective-shortcut-changed", (event) => {
+      this.showEffectiveShortcut(event.payload);
+    });
+
+    // Profile switched from the tray: show the new profile's settings
+    listen<string>("profile-changed", async (event) => {
+      await this.loadConfig();
+      this.showStatus(`Switched to profile "${event.payload}"`, "success");
+    });
 
     // Escape to close
     document.addEventListener("keydown", (e) => {
@@ -172,12 +604,45 @@ class SettingsApp {
     try {
       this.config = await invoke<Config>("get_config");
       this.populateForm();
+      await this.applyStrings(this.locale.value);
     } catch (error) {
       console.error("Failed to load config:", error);
       this.showStatus("Failed to load configuration", "error");
     }
   }
 
+  // Translate the elements marked with data-i18n ("" follows the OS language)
+  private async applyStrings(locale: string): Promise<void> {
+    try {
+      const strings = await invoke<Record<string, string>>("get_strings", { locale });
+      document.querySelectorAll<HTMLElement>("[data-i18n]").forEach((element) => {
+        const text = strings[element.dataset.i18n ?? ""];
+        if (text) element.textContent = text;
+      });
+    } catch (error) {
+      console.error("Failed to load strings:", error);
+    }
+  }
+
+  private async loadEffectiveShortcut(): Promise<void> {
+    try {
+      this.showEffectiveShortcut(await invoke<EffectiveShortcut>("get_effective_shortcut"));
+    } catch (error) {
+      console.error("Failed to get effective shortcut:", error);
+    }
+  }
+
+  private showEffectiveShortcut(effective: EffectiveShortcut): void {
+    if (!effective.shortcut) {
+      this.effectiveShortcut.textContent = "No global hotkey could be registered.";
+    } else if (effective.is_fallback) {
+      this.effectiveShortcut.textContent =
+        `${effective.configured} is unavailable; using ${effective.shortcut} instead.`;
+    } else {
+      this.effectiveShortcut.textContent = `Active: ${effective.shortcut}`;
+    }
+  }
+
   private populateForm(): void {
     if (!this.config) return;
 
@@ -187,9 +652,32 @@ class SettingsApp {
     this.historyLines.value = String(this.config.window.history_lines);
     this.textareaRows.value = String(this.config.window.textarea_rows);
     this.textareaCols.value = String(this.config.window.textarea_cols);
+    const position = this.config.window.position ?? "remember";
+    this.windowPosition.value = typeof position === "string" ? position : "fixed";
+    this.windowOpacity.value = String(this.config.window.opacity ?? 1);
+    this.windowEffect.value = this.config.window.effect ?? "none";
+    this.windowAlwaysOnTop.checked = this.config.window.always_on_top ?? true;
+    this.windowScale.value = this.config.window.scale ? String(this.config.window.scale) : "";
+    // Presets are defined in config.toml; only the active one is picked here
+    this.windowPreset.replaceChildren(new Option("Default", ""));
+    for (const name of Object.keys(this.config.window.presets ?? {})) {
+      this.windowPreset.add(new Option(name, name));
+    }
+    this.windowPreset.value = this.config.window.preset ?? "";
+
+    // Theme settings
+    this.themeMode.value = this.config.theme?.mode ?? "dark";
+    this.locale.value = this.config.locale ?? "";
+    this.themeAccent.value = this.config.theme?.accent ?? "";
+    this.themeCustomCss.value = this.config.theme?.custom_css ?? "";
 
     // History settings
     this.maxEntries.value = String(this.config.history.max_entries);
+    this.historySyncDir.value = this.config.history.sync_dir ?? "";
+    this.historyJoinSeparator.value = this.config.history.join_separator ?? "blank_line";
+    this.trashDays.value = String(this.config.history.trash_days ?? 30);
+    this.retentionDays.value = String(this.config.history.retention_days ?? 0);
+    this.retentionAction.value = this.config.history.retention_action ?? "archive";
 
     // Shortcuts
     this.shortcutLaunch.value = this.config.shortcuts.launch;
@@ -212,9 +700,44 @@ class SettingsApp {
     this.shortcutKillWordBack.value = this.config.shortcuts.kill_word_back;
     this.shortcutDeleteChar.value = this.config.shortcuts.delete_char;
     this.shortcutYank.value = this.config.shortcuts.yank;
+    this.shortcutRestoreDraft.value = this.config.shortcuts.restore_draft ?? "Ctrl+Shift+z";
+    this.shortcutExternalEditor.value = this.config.shortcuts.external_editor ?? "Ctrl+Shift+e";
+    this.shortcutCapture.value = this.config.shortcuts.capture ?? "Ctrl+Shift+Enter";
+    this.shortcutPin.value = this.config.shortcuts.pin ?? "Ctrl+Shift+p";
+    this.shortcutToggleMode.value = this.config.shortcuts.toggle_mode ?? "Ctrl+Shift+m";
+    this.shortcutRewrite.value = this.config.shortcuts.rewrite ?? "Ctrl+Shift+r";
+    this.shortcutSummarize.value = this.config.shortcuts.summarize ?? "Ctrl+Shift+s";
+    this.shortcutTranslate.value = this.config.shortcuts.translate ?? "Ctrl+Shift+l";
+    this.shortcutPrompts.value = this.config.shortcuts.prompts ?? "Ctrl+Shift+t";
+    this.shortcutSymbols.value = this.config.shortcuts.symbols ?? "Ctrl+Shift+u";
+    this.shortcutPalette.value = this.config.shortcuts.palette ?? "Ctrl+Shift+k";
+    this.shortcutFindReplace.value = this.config.shortcuts.find_replace ?? "Ctrl+h";
+    this.shortcutPreview.value = this.config.shortcuts.preview ?? "Ctrl+Shift+v";
+    this.shortcutQueue.value = this.config.shortcuts.queue ?? "Ctrl+Shift+q";
+    this.shortcutAppend.value = this.config.shortcuts.append ?? "Ctrl+Shift+a";
+    this.shortcutAlwaysOnTop.value = this.config.shortcuts.always_on_top ?? "Ctrl+Shift+o";
+    this.symbolsSkinTone.value = this.config.symbols?.skin_tone ?? "default";
 
     // Behavior
     this.simulatePasteShortcut.value = this.config.behavior.simulate_paste_shortcut;
+    this.pasteMethod.value = this.config.behavior.paste_method ?? "keys";
+    this.detectTerminals.checked = this.config.behavior.detect_terminals ?? true;
+    this.autoPauseFullscreen.checked = this.config.behavior.auto_pause_fullscreen ?? false;
+    this.screenSharePrivacy.checked = this.config.behavior.screen_share_privacy ?? false;
+    this.screenShareProcesses.value = (this.config.behavior.screen_share_processes ?? []).join(", ");
+    this.holdToShow.checked = this.config.behavior.hold_to_show ?? false;
+    this.pasteOnRelease.checked = this.config.behavior.paste_on_release ?? true;
+    this.trayLeftClick.value = this.config.behavior.tray_left_click || "toggle";
+    this.jumpListEntries.value = String(this.config.behavior.jump_list_entries ?? 0);
+    this.autostart.checked = this.config.behavior.autostart ?? false;
+    this.startHidden.checked = this.config.behavior.start_hidden ?? true;
+    this.notifyOnPaste.checked = this.config.behavior.notify_on_paste ?? false;
+    this.perAppDrafts.checked = this.config.behavior.per_app_drafts ?? false;
+    this.evaluateOnPaste.checked = this.config.behavior.evaluate_on_paste ?? false;
+    this.imeSafePaste.checked = this.config.behavior.ime_safe_paste ?? true;
+    this.externalEditor.value = this.config.behavior.external_editor ?? "";
+    this.hideOnBlur.checked = this.config.behavior.hide_on_blur ?? false;
+    this.noActivate.checked = this.config.behavior.no_activate ?? false;
 
     // App overrides
     const overrides = this.config.behavior.app_overrides || [];
@@ -234,14 +757,57 @@ class SettingsApp {
     // Voice settings
     this.voiceEnabled.checked = this.config.voice?.enabled ?? false;
     this.voiceDelay.value = String(this.config.voice?.delay_ms ?? 500);
+    this.voiceEngine.value = this.config.voice?.engine ?? "dictation";
+    this.voiceShortcut.value = this.config.voice?.shortcut ?? "";
+    this.voiceStopShortcut.value = this.config.voice?.stop_shortcut ?? "";
+    this.whisperModel.value = this.config.voice?.whisper?.model ?? "";
+    this.whisperLanguage.value = this.config.voice?.whisper?.language ?? "auto";
+
+    // AI rewrite; empty fields fall back to the backend defaults on save
+    this.aiEnabled.checked = this.config.ai?.enabled ?? false;
+    this.aiProvider.value = this.config.ai?.provider ?? "api";
+    this.aiEndpoint.value = this.config.ai?.endpoint ?? "";
+    this.aiLocalModel.value = this.config.ai?.local?.model ?? "";
+    this.aiLocalContext.value = String(this.config.ai?.local?.context_size ?? 4096);
+    this.aiLocalServer.value = this.config.ai?.local?.server ?? "llama-server";
+    this.aiModel.value = this.config.ai?.model ?? "";
+    this.aiInstruction.value = this.config.ai?.instruction ?? "";
+    this.aiShowTokens.checked = this.config.ai?.show_tokens ?? false;
+    this.aiTokenBudget.value = String(this.config.ai?.token_budget ?? 0);
+
+    // Translation
+    this.translateBackend.value = this.config.translate?.backend ?? "ai";
+    this.translateTarget.value = this.config.translate?.target_lang ?? "en";
+    this.translateOnPaste.checked = this.config.translate?.on_paste ?? false;
+
+    // Spell check
+    this.spellcheckEnabled.checked = this.config.spellcheck?.enabled ?? false;
+    this.spellcheckDictionaries.value = (this.config.spellcheck?.dictionaries ?? ["en_US"]).join(", ");
+    this.spellcheckWords.value = (this.config.spellcheck?.words ?? []).join(", ");
+
+    // Remote backup
+    this.syncEnabled.checked = this.config.sync?.enabled ?? false;
+    this.syncProvider.value = this.config.sync?.provider ?? "webdav";
+    this.syncUrl.value = this.config.sync?.url ?? "";
+    this.syncBucket.value = this.config.sync?.bucket ?? "";
+    this.syncRegion.value = this.config.sync?.region ?? "us-east-1";
+    this.syncUsername.value = this.config.sync?.username ?? "";
+    this.syncInterval.value = String(this.config.sync?.interval_minutes ?? 60);
+
+    // Diagnostics
+    this.logLevel.value = this.config.logging?.level || "info";
   }
 
   private async handleSave(): Promise<void> {
     if (!this.config) return;
 
-    // Update config from form
+    // Update config from form (keep fields not shown in the form, e.g. per-app window/template)
+    const overrides = this.config.behavior.app_overrides || [];
     const newConfig: Config = {
+      ...this.config,
+      locale: this.locale.value,
       shortcuts: {
+        ...this.config.shortcuts,
         launch: this.shortcutLaunch.value || "Ctrl+Shift+Space",
         paste: this.shortcutPaste.value || "Ctrl+Enter",
         close: this.shortcutClose.value || "Escape",
@@ -262,37 +828,146 @@ class SettingsApp {
         kill_word_back: this.shortcutKillWordBack.value || "Ctrl+w",
         delete_char: this.shortcutDeleteChar.value || "Ctrl+d",
         yank: this.shortcutYank.value || "Ctrl+y",
+        restore_draft: this.shortcutRestoreDraft.value || "Ctrl+Shift+z",
+        external_editor: this.shortcutExternalEditor.value || "Ctrl+Shift+e",
+        capture: this.shortcutCapture.value || "Ctrl+Shift+Enter",
+        pin: this.shortcutPin.value || "Ctrl+Shift+p",
+        toggle_mode: this.shortcutToggleMode.value || "Ctrl+Shift+m",
+        rewrite: this.shortcutRewrite.value || "Ctrl+Shift+r",
+        summarize: this.shortcutSummarize.value || "Ctrl+Shift+s",
+        translate: this.shortcutTranslate.value || "Ctrl+Shift+l",
+        prompts: this.shortcutPrompts.value || "Ctrl+Shift+t",
+        symbols: this.shortcutSymbols.value || "Ctrl+Shift+u",
+        palette: this.shortcutPalette.value || "Ctrl+Shift+k",
+        find_replace: this.shortcutFindReplace.value || "Ctrl+h",
+        preview: this.shortcutPreview.value || "Ctrl+Shift+v",
+        queue: this.shortcutQueue.value || "Ctrl+Shift+q",
+        append: this.shortcutAppend.value || "Ctrl+Shift+a",
+        always_on_top: this.shortcutAlwaysOnTop.value || "Ctrl+Shift+o",
       },
       history: {
+        ...this.config.history,
         max_entries: parseInt(this.maxEntries.value, 10) || 1000,
+        sync_dir: this.historySyncDir.value.trim(),
+        join_separator: this.historyJoinSeparator.value || "blank_line",
+        trash_days: parseInt(this.trashDays.value, 10) || 30,
+        retention_days: Math.max(0, parseInt(this.retentionDays.value, 10) || 0),
+        retention_action: this.retentionAction.value || "archive",
       },
       window: {
+        ...this.config.window,
         font_size: parseFloat(this.fontSize.value) || 14,
         history_font_size: parseFloat(this.historyFontSize.value) || 12,
         history_lines: parseInt(this.historyLines.value, 10) || 3,
         textarea_rows: parseInt(this.textareaRows.value, 10) || 3,
         textarea_cols: parseInt(this.textareaCols.value, 10) || 60,
+        // A fixed {x, y} position can only be set in config.toml, so keep it as is
+        position: this.windowPosition.value === "fixed" ? this.config.window.position : this.windowPosition.value,
+        opacity: parseFloat(this.windowOpacity.value) || 1,
+        effect: this.windowEffect.value,
+        always_on_top: this.windowAlwaysOnTop.checked,
+        // Empty follows the monitor's scale factor
+        scale: parseFloat(this.windowScale.value) || undefined,
+        preset: this.windowPreset.value,
       },
       behavior: {
+        ...this.config.behavior,
         simulate_paste_shortcut: this.simulatePasteShortcut.value || "Ctrl+V",
+        paste_method: this.pasteMethod.value as PasteMethod,
+        detect_terminals: this.detectTerminals.checked,
+        auto_pause_fullscreen: this.autoPauseFullscreen.checked,
+        screen_share_privacy: this.screenSharePrivacy.checked,
+        screen_share_processes: splitList(this.screenShareProcesses.value),
+        hold_to_show: this.holdToShow.checked,
+        paste_on_release: this.pasteOnRelease.checked,
+        tray_left_click: this.trayLeftClick.value,
+        jump_list_entries: Math.max(0, parseInt(this.jumpListEntries.value, 10) || 0),
+        autostart: this.autostart.checked,
+        start_hidden: this.startHidden.checked,
+        notify_on_paste: this.notifyOnPaste.checked,
+        per_app_drafts: this.perAppDrafts.checked,
+        evaluate_on_paste: this.evaluateOnPaste.checked,
+        ime_safe_paste: this.imeSafePaste.checked,
+        external_editor: this.externalEditor.value.trim(),
+        hide_on_blur: this.hideOnBlur.checked,
+        no_activate: this.noActivate.checked,
         app_overrides: [
           {
+            ...overrides[0],
             process_name: this.appOverride1Process.value,
             shortcut: this.appOverride1Shortcut.value,
           },
           {
+            ...overrides[1],
             process_name: this.appOverride2Process.value,
             shortcut: this.appOverride2Shortcut.value,
           },
           {
+            ...overrides[2],
             process_name: this.appOverride3Process.value,
             shortcut: this.appOverride3Shortcut.value,
           },
+          // Overrides beyond the three editable rows are kept as-is
+          ...overrides.slice(3),
         ],
       },
       voice: {
+        ...this.config.voice,
         enabled: this.voiceEnabled.checked,
         delay_ms: parseInt(this.voiceDelay.value, 10) || 500,
+        engine: this.voiceEngine.value as VoiceConfig["engine"],
+        shortcut: this.voiceShortcut.value.trim(),
+        stop_shortcut: this.voiceStopShortcut.value.trim(),
+        whisper: {
+          model: this.whisperModel.value.trim(),
+          language: this.whisperLanguage.value.trim() || "auto",
+        },
+      },
+      logging: {
+        ...this.config.logging,
+        level: this.logLevel.value,
+      },
+      theme: {
+        ...this.config.theme,
+        mode: this.themeMode.value,
+        accent: this.themeAccent.value.trim(),
+        custom_css: this.themeCustomCss.value.trim(),
+      },
+      ai: {
+        enabled: this.aiEnabled.checked,
+        provider: this.aiProvider.value as AiConfig["provider"],
+        endpoint: this.aiEndpoint.value.trim() || "https://api.openai.com/v1",
+        model: this.aiModel.value.trim() || "gpt-4o-mini",
+        instruction: this.aiInstruction.value.trim() || (this.config.ai?.instruction ?? ""),
+        local: {
+          model: this.aiLocalModel.value.trim(),
+          context_size: parseInt(this.aiLocalContext.value, 10) || 4096,
+          server: this.aiLocalServer.value.trim() || "llama-server",
+        },
+        show_tokens: this.aiShowTokens.checked,
+        token_budget: Math.max(parseInt(this.aiTokenBudget.value, 10) || 0, 0),
+      },
+      translate: {
+        backend: this.translateBackend.value as TranslateConfig["backend"],
+        target_lang: this.translateTarget.value.trim() || "en",
+        on_paste: this.translateOnPaste.checked,
+      },
+      symbols: { skin_tone: this.symbolsSkinTone.value },
+      spellcheck: {
+        enabled: this.spellcheckEnabled.checked,
+        dictionaries: splitList(this.spellcheckDictionaries.value),
+        words: splitList(this.spellcheckWords.value),
+      },
+      sync: {
+        file_name: "prompt-line-rs.backup",
+        ...this.config.sync,
+        enabled: this.syncEnabled.checked,
+        provider: this.syncProvider.value as SyncConfig["provider"],
+        url: this.syncUrl.value.trim(),
+        bucket: this.syncBucket.value.trim(),
+        region: this.syncRegion.value.trim() || "us-east-1",
+        username: this.syncUsername.value.trim(),
+        interval_minutes: parseInt(this.syncInterval.value, 10) || 60,
       },
     };
 
@@ -303,7 +978,94 @@ class SettingsApp {
       await getCurrentWindow().close();
     } catch (error) {
       console.error("Failed to save config:", error);
-      this.showStatus(`Failed to save: ${error}`, "error");
+      this.showStatus(`Failed to save: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async normalizeGlobalShortcut(input: HTMLInputElement): Promise<void> {
+    if (!input.value.trim()) return;
+    try {
+      input.value = await invoke<string>("normalize_shortcut", { shortcut: input.value });
+      input.classList.remove("invalid");
+
+      // Warn before saving a hotkey another app already owns
+      const available = await invoke<boolean>("test_shortcut", { shortcut: input.value });
+      if (!available) {
+        input.classList.add("invalid");
+        this.showStatus(`${input.value} is already used by another application`, "error");
+      }
+    } catch (error) {
+      input.classList.add("invalid");
+      const keys = await invoke<string[]>("get_shortcut_keys").catch(() => []);
+      this.showStatus(`${errorMessage(error)}. Keys: ${keys.join(" ")}`, "error");
+    }
+  }
+
+  private async loadRunningApps(): Promise<void> {
+    if (this.runningAppsLoaded) return;
+    this.runningAppsLoaded = true;
+    try {
+      const apps = await invoke<RunningApp[]>("list_running_apps");
+      const list = document.getElementById("running-apps") as HTMLDataListElement;
+      list.replaceChildren(
+        ...apps.map((app) => {
+          const option = document.createElement("option");
+          option.value = app.process_name;
+          option.label = app.title;
+          return option;
+        }),
+      );
+    } catch (error) {
+      console.error("Failed to list running apps:", error);
+    }
+  }
+
+  private async handlePickWindow(button: HTMLButtonElement): Promise<void> {
+    const processInputs = [this.appOverride1Process, this.appOverride2Process, this.appOverride3Process];
+    const input = processInputs.find((input) => !input.value.trim());
+    if (!input) {
+      this.showStatus("All override rows are in use; clear one first", "error");
+      return;
+    }
+
+    const label = button.textContent;
+    button.textContent = "Click a window...";
+    button.disabled = true;
+    try {
+      const picked = await invoke<{ process_name: string | null; title: string | null } | null>("pick_window");
+      if (!picked?.process_name) {
+        this.showStatus("No window picked", "error");
+        return;
+      }
+      input.value = picked.process_name;
+      this.showStatus(`Picked ${picked.process_name}${picked.title ? ` (${picked.title})` : ""}`, "success");
+      await getCurrentWindow().setFocus();
+    } catch (error) {
+      console.error("Failed to pick window:", error);
+      this.showStatus(`Failed to pick window: ${errorMessage(error)}`, "error");
+    } finally {
+      button.textContent = label;
+      button.disabled = false;
+    }
+  }
+
+  // Set a hotkey by pressing it; keys already taken by a hotkey are caught too
+  private async recordShortcut(input: HTMLInputElement, button: HTMLButtonElement): Promise<void> {
+    const label = button.textContent;
+    button.textContent = "Press keys...";
+    button.disabled = true;
+    try {
+      const shortcut = await invoke<string | null>("capture_next_shortcut");
+      if (shortcut) {
+        input.value = shortcut;
+        await this.normalizeGlobalShortcut(input);
+      }
+    } catch (error) {
+      console.error("Failed to record shortcut:", error);
+      this.showStatus(`Failed to record shortcut: ${errorMessage(error)}`, "error");
+    } finally {
+      button.textContent = label;
+      button.disabled = false;
     }
   }
 
@@ -315,17 +1077,316 @@ class SettingsApp {
     }
   }
 
+  private async handleCompactHistory(): Promise<void> {
+    const positive = (input: HTMLInputElement): number | null => {
+      const value = parseInt(input.value, 10);
+      return value > 0 ? value : null;
+    };
+    const options = {
+      duplicates: this.compactDuplicates.checked,
+      min_chars: positive(this.compactMinChars),
+      older_than_days: positive(this.compactOlderThan),
+    };
+    if (!options.duplicates && options.min_chars === null && options.older_than_days === null) {
+      this.showStatus("Choose what to remove first", "error");
+      return;
+    }
+
+    try {
+      const report = await invoke<CompactReport>("compact_history", { options });
+      await this.loadTrash();
+      const removed = report.duplicates + report.short + report.old;
+      if (removed === 0) {
+        this.showStatus("Nothing to clean up", "success");
+        return;
+      }
+      this.showStatus(
+        `Removed ${removed} entries (${report.duplicates} duplicates, ${report.short} short, ${report.old} old); ` +
+          `backup: ${report.backup}`,
+        "success",
+      );
+    } catch (error) {
+      console.error("Failed to clean up history:", error);
+      this.showStatus(`Failed to clean up history: ${errorMessage(error)}`, "error");
+    }
+  }
+
   private async handleClearHistory(): Promise<void> {
-    if (!confirm("Are you sure you want to clear all history? This action cannot be undone.")) {
+    if (!confirm("Are you sure you want to clear all history? The entries can be restored from the trash.")) {
       return;
     }
 
     try {
       await invoke("clear_history");
+      await this.loadTrash();
       this.showStatus("History cleared successfully", "success");
     } catch (error) {
       console.error("Failed to clear history:", error);
-      this.showStatus(`Failed to clear history: ${error}`, "error");
+      this.showStatus(`Failed to clear history: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async loadTrash(): Promise<void> {
+    try {
+      const trashed = await invoke<TrashedEntry[]>("get_trash");
+      this.trashEntries.replaceChildren();
+      for (const entry of trashed) {
+        const firstLine = entry.text.split("\n", 1)[0];
+        const text = firstLine.length > 60 ? `${firstLine.slice(0, 60)}…` : firstLine;
+        const deleted = new Date(entry.deleted_at).toLocaleDateString();
+        this.trashEntries.add(new Option(`${text} (deleted ${deleted})`, entry.timestamp));
+      }
+      this.trashEntries.disabled = trashed.length === 0;
+    } catch (error) {
+      console.error("Failed to load trash:", error);
+    }
+  }
+
+  private async handleRestoreTrash(all: boolean): Promise<void> {
+    const id = all ? null : this.trashEntries.value;
+    if (!all && !id) {
+      this.showStatus("The trash is empty", "error");
+      return;
+    }
+
+    try {
+      const restored = await invoke<number>("restore_from_trash", { id });
+      await this.loadTrash();
+      this.showStatus(`Restored ${restored} entries`, "success");
+    } catch (error) {
+      console.error("Failed to restore from trash:", error);
+      this.showStatus(`Failed to restore from trash: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async handleEmptyTrash(): Promise<void> {
+    if (!confirm("Delete the entries in the trash for good? This action cannot be undone.")) {
+      return;
+    }
+
+    try {
+      await invoke("empty_trash");
+      await this.loadTrash();
+      this.showStatus("Trash emptied", "success");
+    } catch (error) {
+      console.error("Failed to empty trash:", error);
+      this.showStatus(`Failed to empty trash: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private backupPath(): string | null {
+    const path = this.backupPathInput.value.trim();
+    if (!path) {
+      this.showStatus("Enter a file path first", "error");
+      return null;
+    }
+    return path;
+  }
+
+  private async handleExportConfig(): Promise<void> {
+    const path = this.backupPath();
+    if (!path) return;
+
+    try {
+      await invoke("export_config", { path });
+      this.showStatus("Settings exported", "success");
+    } catch (error) {
+      console.error("Failed to export config:", error);
+      this.showStatus(`Failed to export settings: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async handleImportConfig(): Promise<void> {
+    const path = this.backupPath();
+    if (!path) return;
+
+    await this.replaceConfig("import_config", { path }, "Settings imported");
+  }
+
+  private async handleResetConfig(): Promise<void> {
+    if (!confirm("Reset all settings to their defaults? The current settings are kept as config.toml.bak.")) {
+      return;
+    }
+
+    await this.replaceConfig("reset_config", {}, "Settings reset to defaults");
+  }
+
+  private async handleResetWindow(): Promise<void> {
+    try {
+      await invoke("reset_window_geometry");
+      this.showStatus("Window size and position reset", "success");
+    } catch (error) {
+      console.error("Failed to reset window geometry:", error);
+      this.showStatus(`Failed to reset window: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async handleImportLegacy(): Promise<void> {
+    try {
+      const summary = await invoke<LegacyImportSummary>("import_legacy_data");
+      if (summary.settings_imported) {
+        await this.loadConfig();
+      }
+      const settings = summary.settings_imported ? " and settings" : "";
+      this.showStatus(`Imported ${summary.history_imported} history entries${settings}`, "success");
+    } catch (error) {
+      console.error("Failed to import prompt-line data:", error);
+      this.showStatus(`Failed to import prompt-line data: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async loadStoredSecrets(): Promise<void> {
+    try {
+      const names: SecretName[] = [
+        "sync-server",
+        "sync-passphrase",
+        "ai-api-key",
+        "translate-api-key",
+      ];
+      const [server, passphrase, aiKey, translateKey] = await Promise.all(
+        names.map((name) => invoke<boolean>("get_secret", { name })),
+      );
+      const state = (set: boolean) => (set ? "stored" : "not set");
+      this.syncSecretsStatus.textContent =
+        `Password ${state(server)}, passphrase ${state(passphrase)} ` +
+        "(kept in the OS credential store; the passphrase is needed to restore on another machine)";
+      this.aiKeyStatus.textContent =
+        `Key ${state(aiKey)} (kept in the OS credential store; local servers need none, ` +
+        "so storing an empty field removes it)";
+      this.translateKeyStatus.textContent =
+        `Key ${state(translateKey)} (DeepL or Google; not needed for the AI Rewrite model)`;
+    } catch (error) {
+      console.error("Failed to read stored secrets:", error);
+      this.syncSecretsStatus.textContent = `Credential store unavailable: ${errorMessage(error)}`;
+      this.aiKeyStatus.textContent = this.syncSecretsStatus.textContent;
+      this.translateKeyStatus.textContent = this.syncSecretsStatus.textContent;
+    }
+  }
+
+  private async handleSaveAiKey(): Promise<void> {
+    try {
+      await invoke("set_secret", { name: "ai-api-key", value: this.aiApiKey.value });
+      await this.loadStoredSecrets();
+      this.showStatus(this.aiApiKey.value ? "API key stored" : "API key removed", "success");
+      this.aiApiKey.value = "";
+    } catch (error) {
+      console.error("Failed to store API key:", error);
+      this.showStatus(`Failed to store API key: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async handleSaveTranslateKey(): Promise<void> {
+    try {
+      await invoke("set_secret", { name: "translate-api-key", value: this.translateApiKey.value });
+      await this.loadStoredSecrets();
+      this.showStatus(
+        this.translateApiKey.value ? "Translation key stored" : "Translation key removed",
+        "success",
+      );
+      this.translateApiKey.value = "";
+    } catch (error) {
+      console.error("Failed to store translation key:", error);
+      this.showStatus(`Failed to store translation key: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async handleSaveSyncSecrets(): Promise<void> {
+    // Empty fields keep what is stored
+    const updates: [SecretName, string][] = [];
+    if (this.syncServerSecret.value) {
+      updates.push(["sync-server", this.syncServerSecret.value]);
+    }
+    if (this.syncPassphrase.value) {
+      updates.push(["sync-passphrase", this.syncPassphrase.value]);
+    }
+    if (updates.length === 0) {
+      this.showStatus("Enter a password or passphrase first", "error");
+      return;
+    }
+    try {
+      for (const [name, value] of updates) {
+        await invoke("set_secret", { name, value });
+      }
+      await this.loadStoredSecrets();
+      this.syncServerSecret.value = "";
+      this.syncPassphrase.value = "";
+      this.showStatus("Secrets stored", "success");
+    } catch (error) {
+      console.error("Failed to store secrets:", error);
+      this.showStatus(`Failed to store secrets: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async handleBackupNow(): Promise<void> {
+    this.showStatus("Uploading backup...", "success");
+    try {
+      const uploaded = await invoke<boolean>("backup_now");
+      this.showStatus(uploaded ? "Backup uploaded" : "Nothing changed since the last backup", "success");
+    } catch (error) {
+      console.error("Backup failed:", error);
+      this.showStatus(errorMessage(error), "error");
+    }
+  }
+
+  private async handleRestoreBackup(): Promise<void> {
+    if (!confirm("Replace the current settings with the backup and merge its history?")) {
+      return;
+    }
+    try {
+      const summary = await invoke<RestoreSummary>("restore_backup");
+      this.config = summary.config;
+      this.populateForm();
+      const created = new Date(summary.created).toLocaleString();
+      this.showStatus(`Restored backup from ${created} (${summary.history_imported} new history entries)`, "success");
+    } catch (error) {
+      console.error("Restore failed:", error);
+      this.showStatus(`Restore failed: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async replaceConfig(command: string, args: Record<string, unknown>, message: string): Promise<void> {
+    try {
+      this.config = await invoke<Config>(command, args);
+      this.populateForm();
+      this.showStatus(message, "success");
+    } catch (error) {
+      console.error(`Failed to ${command}:`, error);
+      // The new settings may have been applied with warnings, so show what is active now
+      await this.loadConfig();
+      this.showStatus(errorMessage(error), "error");
+    }
+  }
+
+  private async handleShowLogs(): Promise<void> {
+    try {
+      const lines = await invoke<string[]>("get_recent_logs", { lines: 200 });
+      this.recentLogs.textContent = lines.length ? lines.join("\n") : "(no log entries)";
+      this.recentLogs.classList.remove("hidden");
+      this.recentLogs.scrollTop = this.recentLogs.scrollHeight;
+    } catch (error) {
+      console.error("Failed to load logs:", error);
+      this.showStatus(`Failed to load logs: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async handleRunDiagnostics(): Promise<void> {
+    try {
+      const report = await invoke<Record<string, unknown>>("run_diagnostics");
+      this.diagnosticsReport.textContent = JSON.stringify(report, null, 2);
+      this.diagnosticsReport.classList.remove("hidden");
+      this.copyDiagnosticsBtn.classList.remove("hidden");
+    } catch (error) {
+      console.error("Failed to run diagnostics:", error);
+      this.showStatus(`Failed to run diagnostics: ${errorMessage(error)}`, "error");
+    }
+  }
+
+  private async handleCopyDiagnostics(): Promise<void> {
+    try {
+      await navigator.clipboard.writeText(this.diagnosticsReport.textContent || "");
+      this.showStatus("Report copied to clipboard", "success");
+    } catch (error) {
+      this.showStatus(`Failed to copy report: ${errorMessage(error)}`, "error");
     }
   }
 
@@ -344,5 +1405,6 @@ class SettingsApp {
 
 // Initialize app when DOM is ready
 document.addEventListener("DOMContentLoaded", () => {
+  initTheme();
   new SettingsApp();
 });
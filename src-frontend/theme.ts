@@ -0,0 +1,43 @@
+// Theme shared by the main, settings and picker windows, mirroring `theme::Theme` in the backend
+
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+export interface Theme {
+  mode: "dark" | "light" | "system";
+  dark: boolean;
+  accent: string | null;
+  custom_css: string | null;
+}
+
+export function applyTheme(theme: Theme): void {
+  const root = document.documentElement;
+  root.dataset.theme = theme.dark ? "dark" : "light";
+
+  if (theme.accent) {
+    root.style.setProperty("--accent-color", theme.accent);
+    root.style.setProperty("--accent-hover", `color-mix(in srgb, ${theme.accent} 85%, black)`);
+  } else {
+    root.style.removeProperty("--accent-color");
+    root.style.removeProperty("--accent-hover");
+  }
+
+  // Custom CSS goes last in <head> so it overrides the built-in styles
+  let style = document.getElementById("custom-css") as HTMLStyleElement | null;
+  if (!style) {
+    style = document.createElement("style");
+    style.id = "custom-css";
+    document.head.appendChild(style);
+  }
+  style.textContent = theme.custom_css ?? "";
+}
+
+// Apply the current theme and follow config and OS theme changes
+export async function initTheme(): Promise<void> {
+  listen<Theme>("theme-changed", (event) => applyTheme(event.payload));
+  try {
+    applyTheme(await invoke<Theme>("get_theme"));
+  } catch (error) {
+    console.error("Failed to get theme:", error);
+  }
+}
@@ -0,0 +1,190 @@
+//! Rewrite, summarize or translate the buffer with an LLM
+//!
+//! With `[ai] enabled`, `rewrite` sends the text and an instruction to an
+//! OpenAI-compatible chat completions endpoint (OpenAI, Ollama, LM Studio,
+//! OpenRouter, ...) and streams the reply back: each piece is emitted as a
+//! `rewrite-chunk` event as soon as it arrives, and the full text is returned
+//! at the end. The API key is kept in `secrets`; local servers need none.
+//! With `provider = "local"` the endpoint is a `llama` server on this machine.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{AiConfig, AiProvider};
+use crate::error::{Error, Result};
+use crate::secrets::{self, Secret};
+use crate::AppState;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// Longest pause between two pieces of the reply
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+const SYSTEM_PROMPT: &str = "You transform the user's text as instructed. Reply with only the \
+     resulting text, without explanations, headings or surrounding quotes.";
+
+/// Whether a rewrite is running; only one at a time
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// What to do with the text when no instruction is given
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextAction {
+    /// `[ai] instruction`
+    #[default]
+    Rewrite,
+    Summarize,
+}
+
+impl TextAction {
+    pub fn instruction(self, config: &AiConfig) -> String {
+        match self {
+            TextAction::Rewrite => config.instruction.clone(),
+            TextAction::Summarize => {
+                "Summarize this text concisely, keeping its key points and its language."
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// Rewrite `text` following `instruction`, emitting the reply as it streams in.
+/// Blocks until the reply is complete (and, for a local model, until it loads).
+pub fn rewrite(
+    app: &AppHandle,
+    config: &AiConfig,
+    text: &str,
+    instruction: &str,
+) -> Result<String> {
+    if !config.enabled {
+        return Err(Error::Config(
+            "AI rewriting is disabled; enable it in Settings ([ai])".to_string(),
+        ));
+    }
+    if text.trim().is_empty() {
+        return Err(Error::Config("Nothing to rewrite".to_string()));
+    }
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(Error::Config("A rewrite is already running".to_string()));
+    }
+    let result = request(app, config, text, instruction);
+    RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+fn request(app: &AppHandle, config: &AiConfig, text: &str, instruction: &str) -> Result<String> {
+    let (endpoint, model, api_key) = match config.provider {
+        AiProvider::Api => {
+            let endpoint = config.endpoint.trim().trim_end_matches('/');
+            if endpoint.is_empty() {
+                return Err(Error::Config(
+                    "No AI endpoint configured (ai.endpoint)".to_string(),
+                ));
+            }
+            (
+                endpoint.to_string(),
+                config.model.clone(),
+                secrets::get(Secret::AiApiKey)?,
+            )
+        }
+        // llama-server answers with whatever model it loaded
+        AiProvider::Local => (
+            app.state::<AppState>().llama.endpoint(&config.local)?,
+            "local".to_string(),
+            None,
+        ),
+    };
+    let body = json!({
+        "model": model,
+        "stream": true,
+        "messages": [
+            { "role": "system", "content": SYSTEM_PROMPT },
+            { "role": "user", "content": format!("{}\n\n---\n\n{}", instruction.trim(), text) },
+        ],
+    });
+
+    let mut request = agent()?
+        .post(&format!("{}/chat/completions", endpoint))
+        .set("Accept", "text/event-stream");
+    if let Some(key) = api_key {
+        request = request.set("Authorization", &format!("Bearer {}", key));
+    }
+    tracing::info!(
+        "Rewriting {} characters with {}",
+        text.chars().count(),
+        model
+    );
+    let response = request.send_json(body).map_err(network_error)?;
+
+    // Some servers ignore `stream` and answer with a single JSON object
+    if response.content_type() == "application/json" {
+        let reply: Value = response
+            .into_json()
+            .map_err(|e| Error::Network(format!("Invalid AI response: {}", e)))?;
+        let content = reply["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or_else(|| Error::Network("The AI response has no text".to_string()))?
+            .trim()
+            .to_string();
+        let _ = app.emit("rewrite-chunk", &content);
+        return Ok(content);
+    }
+
+    let mut content = String::new();
+    for line in BufReader::new(response.into_reader()).lines() {
+        let line = line.map_err(|e| Error::Network(format!("AI response interrupted: {}", e)))?;
+        let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+            continue;
+        };
+        if data == "[DONE]" {
+            break;
+        }
+        let event: Value = serde_json::from_str(data)
+            .map_err(|e| Error::Network(format!("Invalid AI response: {}", e)))?;
+        if let Some(message) = event["error"]["message"].as_str() {
+            return Err(Error::Network(format!("AI request failed: {}", message)));
+        }
+        if let Some(delta) = event["choices"][0]["delta"]["content"].as_str() {
+            content.push_str(delta);
+            let _ = app.emit("rewrite-chunk", delta);
+        }
+    }
+    if content.trim().is_empty() {
+        return Err(Error::Network("The AI response has no text".to_string()));
+    }
+    Ok(content.trim().to_string())
+}
+
+/// HTTPS client for LLM and translation APIs
+pub fn agent() -> Result<ureq::Agent> {
+    let tls = native_tls::TlsConnector::new()
+        .map_err(|e| Error::Network(format!("TLS unavailable: {}", e)))?;
+    Ok(ureq::AgentBuilder::new()
+        .tls_connector(Arc::new(tls))
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(READ_TIMEOUT)
+        .build())
+}
+
+fn network_error(error: ureq::Error) -> Error {
+    match error {
+        ureq::Error::Status(401 | 403, _) => {
+            Error::Network("AI request refused: check the API key".to_string())
+        }
+        ureq::Error::Status(code, response) => {
+            let status = response.status_text().to_string();
+            // OpenAI-style servers explain the failure in the body
+            let detail = response
+                .into_json::<Value>()
+                .ok()
+                .and_then(|body| body["error"]["message"].as_str().map(str::to_string))
+                .unwrap_or(status);
+            Error::Network(format!("AI request failed: HTTP {} {}", code, detail))
+        }
+        ureq::Error::Transport(e) => Error::Network(format!("AI request failed: {}", e)),
+    }
+}
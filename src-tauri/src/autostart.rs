@@ -0,0 +1,38 @@
+//! Start the app at login
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_autostart::ManagerExt;
+
+use crate::error::{Error, Result};
+use crate::lock::RwLockExt;
+use crate::AppState;
+
+/// Register or remove the login item to match `enabled`
+pub fn apply(app: &AppHandle, enabled: bool) -> Result<()> {
+    let autolaunch = app.autolaunch();
+    let current = autolaunch
+        .is_enabled()
+        .map_err(|e| Error::Platform(format!("Failed to read autostart state: {}", e)))?;
+
+    if current != enabled {
+        let result = if enabled {
+            autolaunch.enable()
+        } else {
+            autolaunch.disable()
+        };
+        result.map_err(|e| Error::Platform(format!("Failed to update autostart: {}", e)))?;
+    }
+
+    crate::tray::set_autostart_checked(app, enabled);
+    Ok(())
+}
+
+/// Turn autostart on or off and persist it in the config
+pub fn set(app: &AppHandle, enabled: bool) -> Result<()> {
+    apply(app, enabled)?;
+
+    let state = app.state::<AppState>();
+    let mut config = state.config.write_locked();
+    config.behavior.autostart = enabled;
+    config.save()
+}
@@ -0,0 +1,555 @@
+//! Inline calculator
+//!
+//! Evaluates arithmetic such as `1200 * 1.08 + 350` or `sqrt(2) ^ 3` with
+//! the usual precedence (`^` binds tightest and is right-associative), a few
+//! functions and constants, and unit conversions: `5 km in mi`,
+//! `2 h + 30 min to min`, `72 F in C`, `1.5 GiB in MB`. Quantities of the
+//! same kind can be added and subtracted; multiplying or dividing by a plain
+//! number scales them.
+
+use crate::error::{Error, Result};
+
+/// Evaluate `expr` and format the result, with its unit if it has one
+pub fn evaluate(expr: &str) -> Result<String> {
+    let mut tokens = tokenize(expr)?;
+    if tokens.is_empty() {
+        return Err(invalid("nothing to calculate"));
+    }
+
+    // A trailing "in <unit>" / "to <unit>" converts the result
+    let target = match tokens.as_slice() {
+        [.., Token::Word(keyword), Token::Word(unit)]
+            if tokens.len() > 2 && matches!(keyword.as_str(), "in" | "to" | "as") =>
+        {
+            let unit = find_unit(unit).ok_or_else(|| invalid(&format!("unknown unit {}", unit)))?;
+            tokens.truncate(tokens.len() - 2);
+            Some(unit)
+        }
+        _ => None,
+    };
+
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.expression()?;
+    if let Some(token) = parser.tokens.get(parser.pos) {
+        return Err(invalid(&format!("unexpected {}", token)));
+    }
+    if !value.number.is_finite() {
+        return Err(invalid("the result is not a finite number"));
+    }
+
+    match (target, value.unit) {
+        (Some(target), Some(unit)) if target.kind == unit.kind => Ok(format!(
+            "{} {}",
+            format_number(target.in_unit(value.number)),
+            target.name
+        )),
+        (Some(target), Some(unit)) => Err(invalid(&format!(
+            "can't convert {} to {}",
+            unit.kind.name(),
+            target.kind.name()
+        ))),
+        (Some(target), None) => Err(invalid(&format!(
+            "the result has no unit to convert to {}",
+            target.name
+        ))),
+        (None, Some(unit)) => Ok(format!(
+            "{} {}",
+            format_number(unit.in_unit(value.number)),
+            unit.name
+        )),
+        (None, None) => Ok(format_number(value.number)),
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Config(format!("Invalid expression: {}", message))
+}
+
+/// Up to 12 significant digits, without trailing zeros
+fn format_number(number: f64) -> String {
+    if number == 0.0 {
+        return "0".to_string();
+    }
+    if number.abs() >= 1e15 || number.abs() < 1e-9 {
+        return format!("{:e}", number);
+    }
+    let digits = 11 - number.abs().log10().floor() as i32;
+    let text = format!("{:.*}", digits.clamp(0, 15) as usize, number);
+    let text = if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &text
+    };
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Length,
+    Mass,
+    Time,
+    Data,
+    Temperature,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Length => "a length",
+            Kind::Mass => "a mass",
+            Kind::Time => "a time",
+            Kind::Data => "a data size",
+            Kind::Temperature => "a temperature",
+        }
+    }
+}
+
+/// `base = value * factor + offset`, in meters, grams, seconds, bytes or kelvin
+#[derive(Debug, Clone, Copy)]
+struct Unit {
+    name: &'static str,
+    kind: Kind,
+    factor: f64,
+    offset: f64,
+}
+
+impl Unit {
+    fn to_base(self, value: f64) -> f64 {
+        value * self.factor + self.offset
+    }
+
+    fn in_unit(self, value: f64) -> f64 {
+        (value - self.offset) / self.factor
+    }
+}
+
+const fn unit(name: &'static str, kind: Kind, factor: f64) -> Unit {
+    Unit {
+        name,
+        kind,
+        factor,
+        offset: 0.0,
+    }
+}
+
+const UNITS: &[(&[&str], Unit)] = &[
+    (&["mm"], unit("mm", Kind::Length, 0.001)),
+    (&["cm"], unit("cm", Kind::Length, 0.01)),
+    (&["m", "meter", "meters"], unit("m", Kind::Length, 1.0)),
+    (&["km"], unit("km", Kind::Length, 1000.0)),
+    (&["in", "inch", "inches"], unit("in", Kind::Length, 0.0254)),
+    (&["ft", "foot", "feet"], unit("ft", Kind::Length, 0.3048)),
+    (&["yd", "yard", "yards"], unit("yd", Kind::Length, 0.9144)),
+    (&["mi", "mile", "miles"], unit("mi", Kind::Length, 1609.344)),
+    (&["mg"], unit("mg", Kind::Mass, 0.001)),
+    (&["g", "gram", "grams"], unit("g", Kind::Mass, 1.0)),
+    (&["kg"], unit("kg", Kind::Mass, 1000.0)),
+    (
+        &["t", "tonne", "tonnes"],
+        unit("t", Kind::Mass, 1_000_000.0),
+    ),
+    (
+        &["oz", "ounce", "ounces"],
+        unit("oz", Kind::Mass, 28.349523125),
+    ),
+    (
+        &["lb", "lbs", "pound", "pounds"],
+        unit("lb", Kind::Mass, 453.59237),
+    ),
+    (&["ms"], unit("ms", Kind::Time, 0.001)),
+    (
+        &["s", "sec", "second", "seconds"],
+        unit("s", Kind::Time, 1.0),
+    ),
+    (&["min", "minute", "minutes"], unit("min", Kind::Time, 60.0)),
+    (&["h", "hr", "hour", "hours"], unit("h", Kind::Time, 3600.0)),
+    (&["d", "day", "days"], unit("d", Kind::Time, 86400.0)),
+    (&["wk", "week", "weeks"], unit("wk", Kind::Time, 604_800.0)),
+    (&["B", "byte", "bytes"], unit("B", Kind::Data, 1.0)),
+    (&["KB", "kB"], unit("KB", Kind::Data, 1e3)),
+    (&["MB"], unit("MB", Kind::Data, 1e6)),
+    (&["GB"], unit("GB", Kind::Data, 1e9)),
+    (&["TB"], unit("TB", Kind::Data, 1e12)),
+    (&["KiB"], unit("KiB", Kind::Data, 1024.0)),
+    (&["MiB"], unit("MiB", Kind::Data, 1_048_576.0)),
+    (&["GiB"], unit("GiB", Kind::Data, 1_073_741_824.0)),
+    (&["TiB"], unit("TiB", Kind::Data, 1_099_511_627_776.0)),
+    (&["K", "kelvin"], unit("K", Kind::Temperature, 1.0)),
+    (
+        &["C", "°C", "celsius"],
+        Unit {
+            name: "°C",
+            kind: Kind::Temperature,
+            factor: 1.0,
+            offset: 273.15,
+        },
+    ),
+    (
+        &["F", "°F", "fahrenheit"],
+        Unit {
+            name: "°F",
+            kind: Kind::Temperature,
+            factor: 5.0 / 9.0,
+            offset: 273.15 - 32.0 * 5.0 / 9.0,
+        },
+    ),
+];
+
+/// Units are case-sensitive ("MB" vs "mb" would be ambiguous), spelled-out
+/// names are not
+fn find_unit(word: &str) -> Option<Unit> {
+    let lower = word.to_lowercase();
+    UNITS
+        .iter()
+        .find(|(names, _)| {
+            names
+                .iter()
+                .any(|name| *name == word || (name.len() > 3 && *name == lower))
+        })
+        .map(|(_, unit)| *unit)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Word(String),
+    Op(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Word(w) => write!(f, "\"{}\"", w),
+            Token::Op(c) => write!(f, "\"{}\"", c),
+        }
+    }
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek() {
+                // Thousands separators
+                if c == ',' || c == '_' {
+                    chars.next();
+                } else if c.is_ascii_digit() || c == '.' {
+                    number.push(c);
+                    chars.next();
+                } else if (c == 'e' || c == 'E') && !number.contains(['e', 'E']) {
+                    // Exponent, unless it's the start of a word
+                    let mut ahead = chars.clone();
+                    ahead.next();
+                    let next = ahead.next();
+                    let after = ahead.next();
+                    let is_exponent = match next {
+                        Some(d) if d.is_ascii_digit() => true,
+                        Some('+' | '-') => after.is_some_and(|d| d.is_ascii_digit()),
+                        _ => false,
+                    };
+                    if !is_exponent {
+                        break;
+                    }
+                    number.push(c);
+                    chars.next();
+                    if let Some(sign) = chars.next_if(|&c| c == '+' || c == '-') {
+                        number.push(sign);
+                    }
+                } else {
+                    break;
+                }
+            }
+            let value = number
+                .parse()
+                .map_err(|_| invalid(&format!("bad number {}", number)))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '°' {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_alphanumeric() || c == '°' {
+                    word.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Word(word));
+        } else if "+-*/^%()×÷".contains(c) {
+            let op = match c {
+                '×' => '*',
+                '÷' => '/',
+                c => c,
+            };
+            tokens.push(Token::Op(op));
+            chars.next();
+        } else {
+            return Err(invalid(&format!("unexpected \"{}\"", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+/// A number, in the base unit of its kind if it has one
+#[derive(Debug, Clone, Copy)]
+struct Value {
+    number: f64,
+    /// Unit it was written in, used to show the result
+    unit: Option<Unit>,
+}
+
+impl Value {
+    fn plain(number: f64) -> Self {
+        Value { number, unit: None }
+    }
+}
+
+/// Deepest nesting of parentheses, signs and powers; deeper input would
+/// overflow the stack
+const MAX_DEPTH: usize = 256;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Nesting of the rule being parsed
+    depth: usize,
+}
+
+impl Parser {
+    /// Parse one nested rule with `parse`, refusing input nested too deeply
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Value>) -> Result<Value> {
+        if self.depth >= MAX_DEPTH {
+            return Err(invalid("expression nested too deeply"));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat_op(&mut self, ops: &str) -> Option<char> {
+        match self.peek() {
+            Some(Token::Op(c)) if ops.contains(*c) => {
+                let c = *c;
+                self.pos += 1;
+                Some(c)
+            }
+            _ => None,
+        }
+    }
+
+    /// term (("+" | "-") term)*
+    fn expression(&mut self) -> Result<Value> {
+        let mut value = self.term()?;
+        while let Some(op) = self.eat_op("+-") {
+            let rhs = self.term()?;
+            let unit = match (value.unit, rhs.unit) {
+                (Some(a), Some(b)) if a.kind != b.kind => {
+                    return Err(invalid(&format!(
+                        "can't add {} and {}",
+                        a.kind.name(),
+                        b.kind.name()
+                    )))
+                }
+                (Some(_), None) | (None, Some(_)) => {
+                    return Err(invalid("can't add a number and a quantity with a unit"))
+                }
+                (Some(a), _) if a.kind == Kind::Temperature => {
+                    return Err(invalid("temperatures can only be converted"))
+                }
+                (a, _) => a,
+            };
+            let number = if op == '+' {
+                value.number + rhs.number
+            } else {
+                value.number - rhs.number
+            };
+            value = Value { number, unit };
+        }
+        Ok(value)
+    }
+
+    /// power (("*" | "/" | "%") power)*, or implicit multiplication: "2(3+4)", "2pi"
+    fn term(&mut self) -> Result<Value> {
+        let mut value = self.power()?;
+        loop {
+            let op = match self.eat_op("*/%") {
+                Some(op) => op,
+                None if matches!(self.peek(), Some(Token::Op('(') | Token::Word(_)))
+                    && !self.at_conversion() =>
+                {
+                    '*'
+                }
+                None => break,
+            };
+            let rhs = self.power()?;
+            let temperature = |v: &Value| v.unit.is_some_and(|u| u.kind == Kind::Temperature);
+            if temperature(&value) || temperature(&rhs) {
+                return Err(invalid("temperatures can only be converted"));
+            }
+            value = match (op, value.unit, rhs.unit) {
+                ('*', Some(_), Some(_)) => {
+                    return Err(invalid("can't multiply two quantities with units"))
+                }
+                ('*', unit, None) | ('*', None, unit) => Value {
+                    number: value.number * rhs.number,
+                    unit,
+                },
+                ('/', Some(a), Some(b)) if a.kind == b.kind => {
+                    Value::plain(value.number / rhs.number)
+                }
+                ('/', _, Some(_)) => return Err(invalid("can't divide by a quantity with a unit")),
+                (_, _, Some(_)) => return Err(invalid("can't take the remainder by a unit")),
+                ('/', unit, None) => Value {
+                    number: value.number / rhs.number,
+                    unit,
+                },
+                (_, unit, None) => Value {
+                    number: value.number % rhs.number,
+                    unit,
+                },
+            };
+        }
+        Ok(value)
+    }
+
+    fn at_conversion(&self) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if find_unit(w).is_some())
+    }
+
+    /// unary ("^" power)?
+    fn power(&mut self) -> Result<Value> {
+        let base = self.unary()?;
+        if self.eat_op("^").is_some() {
+            let exponent = self.nested(Self::power)?;
+            if base.unit.is_some() || exponent.unit.is_some() {
+                return Err(invalid("can't raise a quantity with a unit to a power"));
+            }
+            return Ok(Value::plain(base.number.powf(exponent.number)));
+        }
+        Ok(base)
+    }
+
+    /// ("-" | "+") unary | primary unit? "%"?
+    fn unary(&mut self) -> Result<Value> {
+        if let Some(op) = self.eat_op("+-") {
+            let value = self.nested(Self::unary)?;
+            if op == '+' {
+                return Ok(value);
+            }
+            // Negate as written: -40 °C is not -(313.15 K)
+            let number = match value.unit {
+                Some(unit) => unit.to_base(-unit.in_unit(value.number)),
+                None => -value.number,
+            };
+            return Ok(Value { number, ..value });
+        }
+        let value = self.primary()?;
+        // "5 km": the number is converted to meters
+        if value.unit.is_none() {
+            if let Some(Token::Word(word)) = self.peek() {
+                if let Some(unit) = find_unit(word) {
+                    self.pos += 1;
+                    return Ok(Value {
+                        number: unit.to_base(value.number),
+                        unit: Some(unit),
+                    });
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    /// number | constant | function "(" expression ")" | "(" expression ")"
+    fn primary(&mut self) -> Result<Value> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| invalid("unexpected end"))?;
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Value::plain(n)),
+            Token::Op('(') => {
+                let value = self.nested(Self::expression)?;
+                if self.eat_op(")").is_none() {
+                    return Err(invalid("missing \")\""));
+                }
+                Ok(value)
+            }
+            Token::Word(word) => match word.to_lowercase().as_str() {
+                "pi" | "π" => Ok(Value::plain(std::f64::consts::PI)),
+                "e" => Ok(Value::plain(std::f64::consts::E)),
+                name => {
+                    let function = function(name)
+                        .ok_or_else(|| invalid(&format!("unknown name \"{}\"", word)))?;
+                    if !matches!(self.peek(), Some(Token::Op('('))) {
+                        return Err(invalid(&format!("{} needs parentheses", word)));
+                    }
+                    let argument = self.primary()?;
+                    if argument.unit.is_some() {
+                        return Err(invalid(&format!("{} takes a plain number", word)));
+                    }
+                    Ok(Value::plain(function(argument.number)))
+                }
+            },
+            token => Err(invalid(&format!("unexpected {}", token))),
+        }
+    }
+}
+
+fn function(name: &str) -> Option<fn(f64) -> f64> {
+    Some(match name {
+        "sqrt" => f64::sqrt,
+        "cbrt" => f64::cbrt,
+        "abs" => f64::abs,
+        "round" => f64::round,
+        "floor" => f64::floor,
+        "ceil" => f64::ceil,
+        "exp" => f64::exp,
+        "ln" => f64::ln,
+        "log" => f64::log10,
+        "log2" => f64::log2,
+        "sin" => f64::sin,
+        "cos" => f64::cos,
+        "tan" => f64::tan,
+        "asin" => f64::asin,
+        "acos" => f64::acos,
+        "atan" => f64::atan,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deep_nesting_is_an_error_not_a_crash() {
+        for expr in [
+            format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000)),
+            format!("{}1", "-".repeat(10_000)),
+            vec!["1"; 10_000].join("^"),
+        ] {
+            let error = evaluate(&expr).expect_err("too deep").to_string();
+            assert!(error.contains("nested too deeply"), "{}", error);
+        }
+        assert_eq!(evaluate("-(-(2 ^ 2))").expect("shallow nesting"), "4");
+    }
+}
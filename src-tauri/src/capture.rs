@@ -0,0 +1,49 @@
+//! Quick capture: append the buffer to a notes file instead of pasting
+
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use crate::config::CaptureTarget;
+use crate::error::{Error, Result};
+
+/// Append `text` to the target's file, creating it (and its folder) if needed.
+/// Returns the path written to.
+pub fn append(target: &CaptureTarget, text: &str) -> Result<PathBuf> {
+    let now = chrono::Local::now();
+    let path = crate::paths::expand_home(&now.format(&target.path).to_string())?;
+    let entry = target
+        .template
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H:%M").to_string())
+        .replace("{text}", text);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(Error::io("Failed to create capture folder"))?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(&path)
+        .map_err(Error::io("Failed to open capture file"))?;
+
+    // Start on a new line if the file doesn't end with one
+    let mut last = [0u8; 1];
+    let needs_newline = file.seek(SeekFrom::End(-1)).is_ok()
+        && file.read_exact(&mut last).is_ok()
+        && last[0] != b'\n';
+    let mut contents = String::new();
+    if needs_newline {
+        contents.push('\n');
+    }
+    contents.push_str(&entry);
+    if !entry.ends_with('\n') {
+        contents.push('\n');
+    }
+    file.write_all(contents.as_bytes())
+        .map_err(Error::io("Failed to append to capture file"))?;
+
+    tracing::info!("Captured {} bytes to {}", text.len(), path.display());
+    Ok(path)
+}
@@ -1,59 +1,201 @@
 //! Clipboard operations module
 
 use arboard::Clipboard;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::config::BehaviorConfig;
+use crate::error::{Error, Result};
+
+/// Keep copies out of clipboard history, cloud clipboard and clipboard managers
+static PRIVATE: AtomicBool = AtomicBool::new(false);
+
+/// Characters of clipboard text, or of file names, in a `ClipboardPreview`
+const PREVIEW_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardKind {
+    Text,
+    Image,
+    Files,
+    /// Empty, or only formats other than these
+    Other,
+}
+
+/// What the clipboard holds, without all of it
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardPreview {
+    pub kind: ClipboardKind,
+    /// Characters of text, bytes of image data or number of files
+    pub size: usize,
+    /// Start of the text, image dimensions ("1920x1080") or file names, one per line
+    pub preview: String,
+    /// `preview` is only the start
+    pub truncated: bool,
+}
+
+impl ClipboardPreview {
+    fn new(kind: ClipboardKind, size: usize, preview: &str) -> Self {
+        let cut = preview.char_indices().nth(PREVIEW_CHARS).map(|(i, _)| i);
+        Self {
+            kind,
+            size,
+            preview: preview[..cut.unwrap_or(preview.len())].to_string(),
+            truncated: cut.is_some(),
+        }
+    }
+
+    /// Nothing to show (`--dry-run`)
+    pub fn other() -> Self {
+        Self::new(ClipboardKind::Other, 0, "")
+    }
+}
+
+/// Mark later copies private (incognito, screen sharing) or not
+pub fn set_private(private: bool) {
+    PRIVATE.store(private, Ordering::Relaxed);
+}
 
 /// Copy text to clipboard and return Result
-pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
-    let mut clipboard =
-        Clipboard::new().map_err(|e| format!("Failed to access clipboard: {}", e))?;
+///
+/// Other apps (clipboard managers, RDP, Office) often hold the clipboard open
+/// for a moment, so busy errors are retried with exponential backoff as set by
+/// `behavior.clipboard_retries` and `behavior.clipboard_retry_delay_ms`.
+pub fn copy_to_clipboard(text: &str, behavior: &BehaviorConfig) -> Result<()> {
+    let mut delay = Duration::from_millis(behavior.clipboard_retry_delay_ms as u64);
+    let mut retries_left = behavior.clipboard_retries;
+    loop {
+        match try_copy(text, behavior.clear_clipboard_first) {
+            Err(e) if retries_left > 0 && is_transient(&e) => {
+                tracing::debug!("{}; retrying in {:?}", e, delay);
+                std::thread::sleep(delay);
+                delay *= 2;
+                retries_left -= 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn try_copy(text: &str, clear_first: bool) -> Result<()> {
+    let mut clipboard = Clipboard::new().map_err(Error::clipboard("Failed to access clipboard"))?;
 
-    // Clear clipboard first to remove any existing content (including images)
-    clipboard
-        .clear()
-        .map_err(|e| format!("Failed to clear clipboard: {}", e))?;
+    // Setting text already replaces every format on the clipboard in one step;
+    // a separate clear shows clipboard listeners an empty clipboard in between
+    if clear_first {
+        clipboard
+            .clear()
+            .map_err(Error::clipboard("Failed to clear clipboard"))?;
+    }
 
-    clipboard
-        .set_text(text.to_string())
-        .map_err(|e| format!("Failed to set clipboard text: {}", e))?;
+    let set = clipboard.set();
+    #[cfg(windows)]
+    let set = if PRIVATE.load(Ordering::Relaxed) {
+        arboard::SetExtWindows::exclude_from_monitoring(set)
+    } else {
+        set
+    };
+    set.text(text.to_string())
+        .map_err(Error::clipboard("Failed to set clipboard text"))?;
 
     Ok(())
 }
 
-/// Parse shortcut string and simulate keypress
-/// Supports: Ctrl, Shift, Alt modifiers with a single key (e.g., "Ctrl+V", "Ctrl+Shift+V")
-#[cfg(windows)]
-pub fn simulate_paste(shortcut: &str) -> Result<(), String> {
-    use windows::Win32::UI::Input::KeyboardAndMouse::{
-        SendInput, INPUT, VIRTUAL_KEY, VK_CONTROL, VK_LWIN, VK_MENU, VK_SHIFT,
-    };
+/// Errors that go away once the app holding the clipboard lets go of it
+fn is_transient(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::Clipboard {
+            source: arboard::Error::ClipboardOccupied | arboard::Error::Unknown { .. },
+            ..
+        }
+    )
+}
 
-    // Parse shortcut string
-    let parts: Vec<&str> = shortcut.split('+').map(|s| s.trim()).collect();
-    if parts.is_empty() {
-        return Err("Empty shortcut".to_string());
-    }
-
-    let mut modifiers: Vec<VIRTUAL_KEY> = Vec::new();
-    let mut main_key: Option<VIRTUAL_KEY> = None;
-
-    for part in parts {
-        let upper = part.to_uppercase();
-        match upper.as_str() {
-            "CTRL" | "CONTROL" => modifiers.push(VK_CONTROL),
-            "SHIFT" => modifiers.push(VK_SHIFT),
-            "ALT" => modifiers.push(VK_MENU),
-            "WIN" | "SUPER" | "META" => modifiers.push(VK_LWIN),
-            _ => {
-                // Assume it's the main key
-                main_key = Some(parse_key(&upper)?);
-            }
+/// Read the clipboard text, `None` if there is none or it can't be read
+pub fn read_text() -> Option<String> {
+    let result = Clipboard::new().and_then(|mut clipboard| clipboard.get_text());
+    match result {
+        Ok(text) => Some(text),
+        Err(arboard::Error::ContentNotAvailable) => None,
+        Err(e) => {
+            tracing::debug!("Failed to read clipboard: {}", e);
+            None
         }
     }
+}
 
-    let main_key = main_key.ok_or_else(|| "No main key specified in shortcut".to_string())?;
+/// Describe the clipboard contents: files, text or an image, in that order
+/// when it holds several (Office puts both text and a picture of copied cells)
+pub fn preview() -> Result<ClipboardPreview> {
+    let mut clipboard = Clipboard::new().map_err(Error::clipboard("Failed to access clipboard"))?;
+
+    if let Some(files) = available(clipboard.get().file_list())? {
+        let names: Vec<String> = files
+            .iter()
+            .map(|path| {
+                path.file_name()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        return Ok(ClipboardPreview::new(
+            ClipboardKind::Files,
+            files.len(),
+            &names.join("\n"),
+        ));
+    }
+    if let Some(text) = available(clipboard.get_text())? {
+        return Ok(ClipboardPreview::new(
+            ClipboardKind::Text,
+            text.chars().count(),
+            &text,
+        ));
+    }
+    if let Some(image) = available(clipboard.get_image())? {
+        return Ok(ClipboardPreview::new(
+            ClipboardKind::Image,
+            image.bytes.len(),
+            &format!("{}x{}", image.width, image.height),
+        ));
+    }
+    Ok(ClipboardPreview::other())
+}
+
+/// The clipboard contents in one format, `None` if it isn't there
+fn available<T>(result: std::result::Result<T, arboard::Error>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(arboard::Error::ContentNotAvailable | arboard::Error::ClipboardNotSupported) => {
+            Ok(None)
+        }
+        Err(e) => Err(Error::clipboard("Failed to read clipboard")(e)),
+    }
+}
+
+/// Check that the clipboard can be opened and read
+pub fn check_access() -> Result<()> {
+    let mut clipboard = Clipboard::new().map_err(Error::clipboard("Failed to access clipboard"))?;
+    match clipboard.get_text() {
+        Ok(_) | Err(arboard::Error::ContentNotAvailable) => Ok(()),
+        Err(e) => Err(Error::clipboard("Failed to read clipboard")(e)),
+    }
+}
+
+/// Parse shortcut string and simulate keypress
+/// Supports: Ctrl, Shift, Alt, Win modifiers with a single key (e.g., "Ctrl+V", "Ctrl+Shift+V")
+#[cfg(windows)]
+pub fn simulate_paste(shortcut: &str) -> Result<()> {
+    let (modifiers, main_key) = parse_shortcut(shortcut)?;
+
+    // Ctrl/Shift still held from the launch hotkey would turn Ctrl+V into Ctrl+Shift+V
+    release_held_modifiers()?;
 
     // Build input sequence: modifiers down, key down, key up, modifiers up (reverse order)
-    let mut inputs: Vec<INPUT> = Vec::new();
+    let mut inputs = Vec::new();
 
     // Modifiers down
     for &modifier in &modifiers {
@@ -71,69 +213,147 @@ pub fn simulate_paste(shortcut: &str) -> Result<(), String> {
         inputs.push(create_key_input(modifier, true));
     }
 
-    unsafe {
-        let result = SendInput(&inputs, std::mem::size_of::<INPUT>() as i32);
+    send_inputs(&inputs)
+}
+
+/// Trigger Windows voice input by sending its shortcut (e.g. "Win+H")
+/// Spawns a thread with delay for better system shortcut handling
+#[cfg(windows)]
+pub fn trigger_voice_input(shortcut: &str, delay_ms: u32) -> Result<()> {
+    let (modifiers, main_key) = parse_shortcut(shortcut)?;
+
+    std::thread::spawn(move || {
+        // Wait for window to fully settle
+        std::thread::sleep(std::time::Duration::from_millis(delay_ms as u64));
 
-        if result == 0 {
-            return Err("Failed to send input events".to_string());
+        if let Err(e) = send_system_shortcut(&modifiers, main_key) {
+            tracing::warn!("Voice input shortcut failed: {}", e);
         }
-    }
+    });
 
     Ok(())
 }
 
-/// Trigger Windows voice input (Win+H)
-/// Spawns a thread with delay for better system shortcut handling
+/// Stop Windows voice input by sending its stop shortcut
+/// Blocks until the keys are sent, so the caller can hide the window afterwards
 #[cfg(windows)]
-pub fn trigger_voice_input(delay_ms: u32) -> Result<(), String> {
-    std::thread::spawn(move || {
-        use std::thread::sleep;
-        use std::time::Duration;
-        use windows::Win32::UI::Input::KeyboardAndMouse::{
-            keybd_event, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP,
-        };
-
-        // Virtual key codes
-        const VK_LWIN: u8 = 0x5B;
-        const VK_H: u8 = 0x48;
-        const VK_CONTROL: u8 = 0x11;
-        const VK_SHIFT: u8 = 0x10;
-        const VK_MENU: u8 = 0x12; // Alt key
+pub fn stop_voice_input(shortcut: &str) -> Result<()> {
+    let (modifiers, main_key) = parse_shortcut(shortcut)?;
+    send_system_shortcut(&modifiers, main_key)
+}
 
-        // Wait for window to fully settle
-        sleep(Duration::from_millis(delay_ms as u64));
+/// Press a shortcut one key at a time after releasing held modifiers;
+/// system shortcuts such as Win+H are missed when the whole chord arrives at once
+#[cfg(windows)]
+fn send_system_shortcut(
+    modifiers: &[windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY],
+    main_key: windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY,
+) -> Result<()> {
+    use std::thread::sleep;
+    use std::time::Duration;
 
-        unsafe {
-            // Release any modifier keys that might be held from the hotkey
-            keybd_event(VK_CONTROL, 0, KEYEVENTF_KEYUP, 0);
-            keybd_event(VK_SHIFT, 0, KEYEVENTF_KEYUP, 0);
-            keybd_event(VK_MENU, 0, KEYEVENTF_KEYUP, 0);
-            sleep(Duration::from_millis(50));
+    let step = Duration::from_millis(50);
 
-            // Win key down
-            keybd_event(VK_LWIN, 0, KEYEVENTF_EXTENDEDKEY, 0);
-            sleep(Duration::from_millis(50));
+    if release_held_modifiers()? {
+        sleep(step);
+    }
 
-            // H key down
-            keybd_event(VK_H, 0, Default::default(), 0);
-            sleep(Duration::from_millis(50));
+    let mut presses: Vec<_> = modifiers.iter().map(|&m| (m, false)).collect();
+    presses.push((main_key, false));
+    presses.push((main_key, true));
+    presses.extend(modifiers.iter().rev().map(|&m| (m, true)));
+    for (key, key_up) in presses {
+        send_inputs(&[create_key_input(key, key_up)])?;
+        sleep(step);
+    }
 
-            // H key up
-            keybd_event(VK_H, 0, KEYEVENTF_KEYUP, 0);
-            sleep(Duration::from_millis(50));
+    Ok(())
+}
 
-            // Win key up
-            keybd_event(VK_LWIN, 0, KEYEVENTF_EXTENDEDKEY | KEYEVENTF_KEYUP, 0);
-        }
-    });
+/// Send key-up for Ctrl, Shift and Alt if they are physically held (e.g. still
+/// down from the hotkey that showed the window); returns whether any were.
+/// They are not pressed again afterwards.
+#[cfg(windows)]
+fn release_held_modifiers() -> Result<bool> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        GetAsyncKeyState, VK_CONTROL, VK_MENU, VK_SHIFT,
+    };
+
+    let held: Vec<_> = [VK_CONTROL, VK_SHIFT, VK_MENU]
+        .into_iter()
+        // High bit set: the key is down right now
+        .filter(|&key| unsafe { GetAsyncKeyState(key.0 as i32) } < 0)
+        .map(|key| create_key_input(key, true))
+        .collect();
+    if held.is_empty() {
+        return Ok(false);
+    }
+
+    tracing::debug!("Releasing {} held modifier key(s)", held.len());
+    send_inputs(&held)?;
+    Ok(true)
+}
+
+/// Split a shortcut string such as "Ctrl+Shift+V" or "Win+H" into its
+/// modifier keys and main key
+///
+/// Accepts the same key names as global shortcuts (`hotkey::check_shortcut`),
+/// so any shortcut that passes config validation can be sent.
+#[cfg(windows)]
+fn parse_shortcut(
+    shortcut: &str,
+) -> Result<(
+    Vec<windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY>,
+    windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY,
+)> {
+    use tauri_plugin_global_shortcut::Modifiers;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        VIRTUAL_KEY, VK_CONTROL, VK_LWIN, VK_MENU, VK_SHIFT,
+    };
+
+    let (held, code) = crate::hotkey::check_shortcut(shortcut)?;
+    let held = held.unwrap_or_else(Modifiers::empty);
+    let modifiers = [
+        (Modifiers::SUPER, VK_LWIN),
+        (Modifiers::CONTROL, VK_CONTROL),
+        (Modifiers::ALT, VK_MENU),
+        (Modifiers::SHIFT, VK_SHIFT),
+    ]
+    .into_iter()
+    .filter(|(modifier, _)| held.contains(*modifier))
+    .map(|(_, key)| key)
+    .collect();
+
+    let main_key = crate::hotkey::virtual_key(code)
+        .ok_or_else(|| Error::Shortcut(format!("Unknown key in shortcut '{}'", shortcut)))?;
+    Ok((modifiers, VIRTUAL_KEY(main_key)))
+}
 
+#[cfg(windows)]
+fn send_inputs(inputs: &[windows::Win32::UI::Input::KeyboardAndMouse::INPUT]) -> Result<()> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{SendInput, INPUT};
+
+    let sent = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent == 0 {
+        return Err(Error::Platform("Failed to send input events".to_string()));
+    }
     Ok(())
 }
 
-/// Trigger Windows voice input (Win+H) - non-Windows stub
+/// Stop Windows voice input - non-Windows stub
+#[cfg(not(windows))]
+pub fn stop_voice_input(_shortcut: &str) -> Result<()> {
+    Err(Error::Platform(
+        "Voice input is only supported on Windows".to_string(),
+    ))
+}
+
+/// Trigger Windows voice input - non-Windows stub
 #[cfg(not(windows))]
-pub fn trigger_voice_input(_delay_ms: u32) -> Result<(), String> {
-    Err("Voice input is only supported on Windows".to_string())
+pub fn trigger_voice_input(_shortcut: &str, _delay_ms: u32) -> Result<()> {
+    Err(Error::Platform(
+        "Voice input is only supported on Windows".to_string(),
+    ))
 }
 
 #[cfg(windows)]
@@ -171,58 +391,9 @@ fn create_key_input(
     }
 }
 
-#[cfg(windows)]
-fn parse_key(
-    key: &str,
-) -> Result<windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY, String> {
-    use windows::Win32::UI::Input::KeyboardAndMouse::*;
-
-    match key {
-        // Letters
-        "A" => Ok(VIRTUAL_KEY(0x41)),
-        "B" => Ok(VIRTUAL_KEY(0x42)),
-        "C" => Ok(VIRTUAL_KEY(0x43)),
-        "D" => Ok(VIRTUAL_KEY(0x44)),
-        "E" => Ok(VIRTUAL_KEY(0x45)),
-        "F" => Ok(VIRTUAL_KEY(0x46)),
-        "G" => Ok(VIRTUAL_KEY(0x47)),
-        "H" => Ok(VIRTUAL_KEY(0x48)),
-        "I" => Ok(VIRTUAL_KEY(0x49)),
-        "J" => Ok(VIRTUAL_KEY(0x4A)),
-        "K" => Ok(VIRTUAL_KEY(0x4B)),
-        "L" => Ok(VIRTUAL_KEY(0x4C)),
-        "M" => Ok(VIRTUAL_KEY(0x4D)),
-        "N" => Ok(VIRTUAL_KEY(0x4E)),
-        "O" => Ok(VIRTUAL_KEY(0x4F)),
-        "P" => Ok(VIRTUAL_KEY(0x50)),
-        "Q" => Ok(VIRTUAL_KEY(0x51)),
-        "R" => Ok(VIRTUAL_KEY(0x52)),
-        "S" => Ok(VIRTUAL_KEY(0x53)),
-        "T" => Ok(VIRTUAL_KEY(0x54)),
-        "U" => Ok(VIRTUAL_KEY(0x55)),
-        "V" => Ok(VIRTUAL_KEY(0x56)),
-        "W" => Ok(VIRTUAL_KEY(0x57)),
-        "X" => Ok(VIRTUAL_KEY(0x58)),
-        "Y" => Ok(VIRTUAL_KEY(0x59)),
-        "Z" => Ok(VIRTUAL_KEY(0x5A)),
-        // Numbers
-        "0" => Ok(VIRTUAL_KEY(0x30)),
-        "1" => Ok(VIRTUAL_KEY(0x31)),
-        "2" => Ok(VIRTUAL_KEY(0x32)),
-        "3" => Ok(VIRTUAL_KEY(0x33)),
-        "4" => Ok(VIRTUAL_KEY(0x34)),
-        "5" => Ok(VIRTUAL_KEY(0x35)),
-        "6" => Ok(VIRTUAL_KEY(0x36)),
-        "7" => Ok(VIRTUAL_KEY(0x37)),
-        "8" => Ok(VIRTUAL_KEY(0x38)),
-        "9" => Ok(VIRTUAL_KEY(0x39)),
-        // Special keys
-        "INSERT" => Ok(VK_INSERT),
-        _ => Err(format!("Unknown key: {}", key)),
-    }
-}
-
 #[cfg(not(windows))]
-pub fn simulate_paste(_shortcut: &str) -> Result<(), String> {
-    Err("Keyboard simulation is only supported on Windows".to_string())
+pub fn simulate_paste(_shortcut: &str) -> Result<()> {
+    Err(Error::Platform(
+        "Keyboard simulation is only supported on Windows".to_string(),
+    ))
 }
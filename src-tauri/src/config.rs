@@ -1,11 +1,20 @@
 //! Configuration management module
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::foreground::WindowInfo;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Language of the tray menu, notifications and settings window ("en",
+    /// "ja"); empty follows the OS
+    #[serde(default)]
+    pub locale: String,
+
     #[serde(default = "default_shortcuts")]
     pub shortcuts: Shortcuts,
 
@@ -20,6 +29,30 @@ pub struct Config {
 
     #[serde(default = "default_voice")]
     pub voice: VoiceConfig,
+
+    #[serde(default = "default_logging")]
+    pub logging: LoggingConfig,
+
+    #[serde(default)]
+    pub capture: CaptureConfig,
+
+    #[serde(default)]
+    pub theme: ThemeConfig,
+
+    #[serde(default)]
+    pub sync: SyncConfig,
+
+    #[serde(default)]
+    pub ai: AiConfig,
+
+    #[serde(default)]
+    pub translate: TranslateConfig,
+
+    #[serde(default)]
+    pub spellcheck: SpellcheckConfig,
+
+    #[serde(default)]
+    pub symbols: SymbolsConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,12 +130,159 @@ pub struct Shortcuts {
     /// Yank (paste from kill ring) (readline: Ctrl+Y)
     #[serde(default = "default_yank")]
     pub yank: String,
+
+    /// Restore the previous saved version of the draft (repeat to go further back)
+    #[serde(default = "default_restore_draft")]
+    pub restore_draft: String,
+
+    /// Open the buffer in the external editor
+    #[serde(default = "default_external_editor")]
+    pub external_editor: String,
+
+    /// Append the buffer to the capture target instead of pasting
+    #[serde(default = "default_capture")]
+    pub capture: String,
+
+    /// Pin the window so it stays up when focus moves elsewhere
+    #[serde(default = "default_pin")]
+    pub pin: String,
+
+    /// Switch between the compact bar and the expanded editor
+    #[serde(default = "default_toggle_mode")]
+    pub toggle_mode: String,
+
+    /// Rewrite the buffer with the configured LLM (`[ai]`)
+    #[serde(default = "default_rewrite")]
+    pub rewrite: String,
+
+    /// Summarize the buffer with the configured LLM (`[ai]`)
+    #[serde(default = "default_summarize")]
+    pub summarize: String,
+
+    /// Translate the buffer into `translate.target_lang`
+    #[serde(default = "default_translate")]
+    pub translate: String,
+
+    /// Pick a template from `prompts.toml` to insert
+    #[serde(default = "default_prompts")]
+    pub prompts: String,
+
+    /// Search emojis and symbols to insert
+    #[serde(default = "default_symbols")]
+    pub symbols: String,
+
+    /// Command palette: UUIDs, timestamps, passwords, lorem ipsum
+    #[serde(default = "default_palette")]
+    pub palette: String,
+
+    /// Regex find and replace over the buffer
+    #[serde(default = "default_find_replace")]
+    pub find_replace: String,
+
+    /// Show the text rendered as Markdown in place of the history
+    #[serde(default = "default_preview")]
+    pub preview: String,
+
+    /// Add the buffer, or the selected entry while searching, to the paste queue
+    #[serde(default = "default_queue")]
+    pub queue: String,
+
+    /// Add the text to the end of the clipboard instead of pasting
+    #[serde(default = "default_append")]
+    pub append: String,
+
+    /// Keep the window above other windows, or let them cover it
+    #[serde(default = "default_always_on_top_shortcut")]
+    pub always_on_top: String,
+
+    // === Additional global hotkeys ===
+    /// Global hotkey -> action ("toggle", "paste_last", "paste_next", "undo_paste",
+    /// "show_settings", "toggle_voice", "push_to_talk", "show_history_picker",
+    /// "snippet:<template name>")
+    #[serde(default)]
+    pub global: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryConfig {
     #[serde(default = "default_max_entries")]
     pub max_entries: usize,
+
+    /// Earlier draft versions kept for restoring (0 disables snapshots)
+    #[serde(default = "default_draft_versions")]
+    pub draft_versions: usize,
+
+    /// Shared folder (Dropbox, Syncthing...) for syncing history between
+    /// machines; empty disables sync
+    #[serde(default)]
+    pub sync_dir: String,
+
+    /// Put between entries pasted together with `paste_entries`
+    #[serde(default)]
+    pub join_separator: JoinSeparator,
+
+    /// Characters of each entry sent to the history list; longer entries are
+    /// fetched in full when opened
+    #[serde(default = "default_preview_chars")]
+    pub preview_chars: usize,
+
+    /// Days cleared or cleaned-up entries stay in the trash (applies after restart)
+    #[serde(default = "default_trash_days")]
+    pub trash_days: u32,
+
+    /// Entries older than this many days are taken out of the history by
+    /// `retention_action`; 0 keeps them (applies after restart)
+    #[serde(default)]
+    pub retention_days: u32,
+
+    #[serde(default)]
+    pub retention_action: RetentionAction,
+}
+
+impl HistoryConfig {
+    /// Sync folder with `~` expanded; relative paths are under the config directory
+    pub fn sync_dir(&self) -> Result<Option<PathBuf>> {
+        let dir = self.sync_dir.trim();
+        if dir.is_empty() {
+            return Ok(None);
+        }
+        let path = crate::paths::expand_home(dir)?;
+        if path.is_relative() {
+            return Ok(Some(crate::paths::config_dir()?.join(path)));
+        }
+        Ok(Some(path))
+    }
+}
+
+/// What happens to history entries older than `retention_days`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// Move them to the archive, where they can still be searched
+    #[default]
+    Archive,
+    /// Delete them from the history, the archive, the trash and this
+    /// machine's sync file
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinSeparator {
+    Newline,
+    #[default]
+    BlankLine,
+    Comma,
+}
+
+impl JoinSeparator {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Newline => "\n",
+            Self::BlankLine => "\n\n",
+            Self::Comma => ", ",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,6 +301,111 @@ pub struct WindowConfig {
 
     #[serde(default = "default_textarea_cols")]
     pub textarea_cols: u32,
+
+    /// Where the window appears when shown
+    #[serde(default)]
+    pub position: WindowPosition,
+
+    /// Window opacity, 0.2 (mostly see-through) to 1.0 (opaque)
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+
+    /// Background effect behind the window (Windows only)
+    #[serde(default)]
+    pub effect: WindowEffect,
+
+    /// Dimensions used in compact mode
+    #[serde(default)]
+    pub compact: CompactWindow,
+
+    /// Scale factor used instead of the monitor's (e.g. 1.5 for 150%); unset follows the OS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+
+    /// Textarea font (CSS font-family); empty uses the built-in monospace stack
+    #[serde(default)]
+    pub font_family: String,
+
+    /// Name of the active preset in `presets`; empty uses the values above as is
+    #[serde(default)]
+    pub preset: String,
+
+    /// Named layouts (e.g. "commit", "chat") applied on top of the values above
+    #[serde(default)]
+    pub presets: BTreeMap<String, WindowOverride>,
+
+    /// Keep the window above other windows when the app starts
+    #[serde(default = "default_always_on_top")]
+    pub always_on_top: bool,
+}
+
+/// Window layout: the full editor with history, or a single-line bar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowMode {
+    #[default]
+    Expanded,
+    Compact,
+}
+
+/// Compact mode size; the font sizes are shared with expanded mode
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CompactWindow {
+    #[serde(default = "default_textarea_cols")]
+    pub textarea_cols: u32,
+
+    #[serde(default = "default_compact_rows")]
+    pub textarea_rows: u32,
+}
+
+impl Default for CompactWindow {
+    fn default() -> Self {
+        Self {
+            textarea_cols: default_textarea_cols(),
+            textarea_rows: default_compact_rows(),
+        }
+    }
+}
+
+/// Background material: `"none"`, `"acrylic"` (Windows 10+) or `"mica"` (Windows 11)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowEffect {
+    #[default]
+    None,
+    Acrylic,
+    Mica,
+}
+
+/// Window placement: `"center"`, `"cursor"`, `"focused"`, `"remember"` or `{ x, y }`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WindowPosition {
+    Mode(PositionMode),
+    /// Fixed screen position in physical pixels
+    Fixed {
+        x: i32,
+        y: i32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PositionMode {
+    /// Center of the primary monitor
+    Center,
+    /// Center of the monitor under the mouse cursor
+    Cursor,
+    /// Center of the monitor showing the focused window (falls back to the cursor's)
+    Focused,
+    /// Where the window was last left (saved across restarts)
+    Remember,
+}
+
+impl Default for WindowPosition {
+    fn default() -> Self {
+        WindowPosition::Mode(PositionMode::Remember)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -129,28 +414,565 @@ pub struct BehaviorConfig {
     #[serde(default = "default_simulate_paste_shortcut")]
     pub simulate_paste_shortcut: String,
 
+    /// How text is put into the target app
+    #[serde(default)]
+    pub paste_method: PasteMethod,
+
     /// Per-app paste shortcut overrides
     #[serde(default = "default_app_overrides")]
     pub app_overrides: Vec<AppPasteOverride>,
+
+    /// Use the built-in terminal list when no override matches
+    #[serde(default = "default_detect_terminals")]
+    pub detect_terminals: bool,
+
+    /// Pause global hotkeys while a fullscreen app (e.g. a game) is in front
+    #[serde(default)]
+    pub auto_pause_fullscreen: bool,
+
+    /// Don't save or show history while the screen is shared or recorded
+    #[serde(default)]
+    pub screen_share_privacy: bool,
+
+    /// Processes whose window on screen means the screen is shared, for apps
+    /// the built-in detection misses (e.g. "CptHost.exe")
+    #[serde(default)]
+    pub screen_share_processes: Vec<String>,
+
+    /// Show the window only while the launch shortcut is held
+    #[serde(default)]
+    pub hold_to_show: bool,
+
+    /// In hold-to-show mode, paste non-empty text when the shortcut is released
+    #[serde(default = "default_paste_on_release")]
+    pub paste_on_release: bool,
+
+    /// Tray icon left-click action: "toggle", "paste_last", "settings" or "none"
+    #[serde(default = "default_tray_left_click")]
+    pub tray_left_click: String,
+
+    /// Recent history entries listed in the taskbar jump list; 0 (the
+    /// default) keeps history text out of it
+    #[serde(default = "default_jump_list_entries")]
+    pub jump_list_entries: usize,
+
+    /// Start the app when the user logs in
+    #[serde(default)]
+    pub autostart: bool,
+
+    /// Launch with only the tray icon; show the window on the launch hotkey
+    #[serde(default = "default_start_hidden")]
+    pub start_hidden: bool,
+
+    /// Show a notification after each successful paste
+    #[serde(default)]
+    pub notify_on_paste: bool,
+
+    /// Keep a separate draft for each target app (keyed by process name)
+    #[serde(default)]
+    pub per_app_drafts: bool,
+
+    /// Paste the result instead when the buffer starts with '=' (e.g. "=2^10")
+    #[serde(default)]
+    pub evaluate_on_paste: bool,
+
+    /// Command for editing the buffer externally (e.g. "code --wait");
+    /// empty uses $VISUAL / $EDITOR, then Notepad
+    #[serde(default)]
+    pub external_editor: String,
+
+    /// Hide the main window when it loses focus (unless pinned)
+    #[serde(default)]
+    pub hide_on_blur: bool,
+
+    /// Extra attempts when another app holds the clipboard open
+    #[serde(default = "default_clipboard_retries")]
+    pub clipboard_retries: u32,
+
+    /// Wait before the first retry; doubled for each further retry
+    #[serde(default = "default_clipboard_retry_delay_ms")]
+    pub clipboard_retry_delay_ms: u32,
+
+    /// Empty the clipboard in a separate step before setting the text
+    #[serde(default)]
+    pub clear_clipboard_first: bool,
+
+    /// Put between the clipboard text and the text appended to it
+    #[serde(default = "default_append_separator")]
+    pub append_separator: JoinSeparator,
+
+    /// Keep the paste out of unfinished IME compositions, in this window and
+    /// in the target app
+    #[serde(default = "default_ime_safe_paste")]
+    pub ime_safe_paste: bool,
+
+    /// Show the window without taking focus from the target app; it takes
+    /// focus when clicked
+    #[serde(default)]
+    pub no_activate: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppPasteOverride {
-    /// Process name (e.g., "alacritty.exe")
+    /// Process name (e.g., "alacritty.exe") or full executable path
     pub process_name: String,
-    /// Shortcut to use for this app (e.g., "Ctrl+Shift+V")
+    /// Window title substring (case-insensitive) the override is limited to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Shortcut to use for this app (e.g., "Ctrl+Shift+V"), empty = default
+    #[serde(default)]
     pub shortcut: String,
+    /// Window size/font overrides applied when shown over this app
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowOverride>,
+    /// Text inserted into the empty buffer when the window opens for this app
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Start voice input (true) or not (false) when shown over this app,
+    /// regardless of the voice toggle; unset follows the toggle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice: Option<bool>,
+    /// Paste method for this app, unset = `behavior.paste_method`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<PasteMethod>,
+    /// Keys `undo_last_paste` sends to this app (e.g. "Ctrl+Z"); unset only
+    /// restores the clipboard
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undo_shortcut: Option<String>,
+}
+
+impl AppPasteOverride {
+    /// Whether the override applies to a window: the process name (or the
+    /// full path, if one is given) matches, and the title contains `title`
+    fn matches(&self, target: &WindowInfo) -> bool {
+        let wanted = self.process_name.trim();
+        if wanted.is_empty() {
+            return false;
+        }
+        let process_matches = if wanted.contains(['\\', '/']) {
+            target
+                .exe_path
+                .as_deref()
+                .is_some_and(|path| same_path(path, wanted))
+        } else {
+            target.is_process(wanted)
+        };
+        let title_matches = match self.title.as_deref().map(str::trim) {
+            Some(title) if !title.is_empty() => target
+                .title
+                .as_deref()
+                .is_some_and(|actual| actual.to_lowercase().contains(&title.to_lowercase())),
+            _ => true,
+        };
+        process_matches && title_matches
+    }
+}
+
+/// Compare Windows paths case-insensitively, either slash counting as a separator
+fn same_path(a: &str, b: &str) -> bool {
+    let normalize = |path: &str| path.replace('/', "\\").to_lowercase();
+    normalize(a) == normalize(b)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteMethod {
+    /// Press the paste shortcut
+    #[default]
+    Keys,
+    /// Send WM_PASTE to the focused control; for apps with remapped keys
+    WmPaste,
+    /// Append the text to the focused field through UI Automation
+    Uia,
+}
+
+/// Partial `WindowConfig` for app overrides and presets; unset fields fall back
+/// to the global `[window]` values
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_font_size: Option<f32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_lines: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub textarea_rows: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub textarea_cols: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceConfig {
-    /// Enable automatic voice input (Win+H) when window is shown
+    /// Enable automatic voice input when window is shown
     #[serde(default = "default_voice_enabled")]
     pub enabled: bool,
 
     /// Delay in milliseconds before triggering voice input
     #[serde(default = "default_voice_delay_ms")]
     pub delay_ms: u32,
+
+    /// Windows speech feature the shortcut is sent to
+    #[serde(default)]
+    pub engine: VoiceEngine,
+
+    /// Shortcut that starts voice input; empty uses the engine's default
+    #[serde(default)]
+    pub shortcut: String,
+
+    /// Shortcut sent to stop voice input when the window is hidden
+    /// (e.g. "Esc"); empty sends the start shortcut again, which toggles it off
+    #[serde(default)]
+    pub stop_shortcut: String,
+
+    /// Local transcription for the `push_to_talk` hotkey
+    #[serde(default)]
+    pub whisper: WhisperConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhisperConfig {
+    /// ggml model file (e.g. "ggml-base.bin"); relative paths are resolved
+    /// against the config directory and a leading "~" is expanded
+    #[serde(default)]
+    pub model: String,
+
+    /// Spoken language as a code such as "en" or "ja"; "auto" detects it
+    #[serde(default = "default_whisper_language")]
+    pub language: String,
+}
+
+impl Default for WhisperConfig {
+    fn default() -> Self {
+        Self {
+            model: String::new(),
+            language: default_whisper_language(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceEngine {
+    /// Voice typing (Win+H)
+    #[default]
+    Dictation,
+    /// Voice Access on Windows 11 (Alt+Shift+B toggles its microphone)
+    VoiceAccess,
+}
+
+impl VoiceConfig {
+    /// Shortcut sent to start voice input
+    pub fn trigger_shortcut(&self) -> &str {
+        if !self.shortcut.trim().is_empty() {
+            return &self.shortcut;
+        }
+        match self.engine {
+            VoiceEngine::Dictation => "Win+H",
+            VoiceEngine::VoiceAccess => "Alt+Shift+B",
+        }
+    }
+
+    /// Shortcut sent to stop voice input
+    pub fn stop_shortcut(&self) -> &str {
+        if self.stop_shortcut.trim().is_empty() {
+            self.trigger_shortcut()
+        } else {
+            &self.stop_shortcut
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// Minimum level written to the log: "error", "warn", "info", "debug" or "trace"
+    #[serde(default = "default_log_level")]
+    pub level: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CaptureConfig {
+    /// Files the capture shortcut can append to; the first is the default
+    #[serde(default)]
+    pub targets: Vec<CaptureTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureTarget {
+    /// Name used to pick the target (e.g. "daily")
+    pub name: String,
+    /// File to append to; strftime patterns (e.g. "%Y-%m-%d") and a leading "~" are expanded
+    pub path: String,
+    /// Text written per capture; `{text}`, `{date}` and `{time}` are replaced
+    #[serde(default = "default_capture_template")]
+    pub template: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    /// Color scheme; `system` follows the OS light/dark setting
+    #[serde(default)]
+    pub mode: ThemeMode,
+
+    /// Accent color as any CSS color (e.g. "#4a9eff"); empty keeps the built-in one
+    #[serde(default)]
+    pub accent: String,
+
+    /// Stylesheet loaded after the built-in styles; relative paths are resolved
+    /// against the config directory and a leading "~" is expanded
+    #[serde(default)]
+    pub custom_css: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    #[default]
+    Dark,
+    Light,
+    System,
+}
+
+/// Encrypted remote backup of history and config; the server password and the
+/// encryption passphrase are kept in the OS keyring, not here
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Upload a backup every `interval_minutes`
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub provider: SyncProvider,
+
+    /// WebDAV folder URL, or S3 endpoint (e.g. "https://s3.eu-west-1.amazonaws.com")
+    #[serde(default)]
+    pub url: String,
+
+    /// S3 bucket
+    #[serde(default)]
+    pub bucket: String,
+
+    /// S3 region
+    #[serde(default = "default_sync_region")]
+    pub region: String,
+
+    /// WebDAV user name or S3 access key ID
+    #[serde(default)]
+    pub username: String,
+
+    #[serde(default = "default_sync_interval_minutes")]
+    pub interval_minutes: u32,
+
+    /// Name of the backup file on the server
+    #[serde(default = "default_sync_file_name")]
+    pub file_name: String,
+
+    /// Back up secret history entries too; they stay encrypted, so only this
+    /// Windows user can read them after a restore
+    #[serde(default)]
+    pub include_secret_entries: bool,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: SyncProvider::default(),
+            url: String::new(),
+            bucket: String::new(),
+            region: default_sync_region(),
+            username: String::new(),
+            interval_minutes: default_sync_interval_minutes(),
+            file_name: default_sync_file_name(),
+            include_secret_entries: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncProvider {
+    #[default]
+    WebDav,
+    S3,
+}
+
+/// LLM text actions through an OpenAI-compatible chat completions API or a
+/// local llama.cpp server; the API key is kept in the OS keyring, not here
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiConfig {
+    /// Enable the rewrite, summarize and translate shortcuts
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Where text is sent: `api` (`endpoint`) or `local` (never leaves the machine)
+    #[serde(default)]
+    pub provider: AiProvider,
+
+    /// Base URL of the API, without `/chat/completions`
+    #[serde(default = "default_ai_endpoint")]
+    pub endpoint: String,
+
+    #[serde(default = "default_ai_model")]
+    pub model: String,
+
+    /// Instruction used when the rewrite shortcut is pressed
+    #[serde(default = "default_ai_instruction")]
+    pub instruction: String,
+
+    #[serde(default)]
+    pub local: LocalModelConfig,
+
+    /// Show a live token count of the buffer (counted for `model`)
+    #[serde(default)]
+    pub show_tokens: bool,
+
+    /// Warn when the buffer exceeds this many tokens; 0 = no budget.
+    /// A budget shows the count even without `show_tokens`.
+    #[serde(default)]
+    pub token_budget: usize,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: AiProvider::default(),
+            endpoint: default_ai_endpoint(),
+            model: default_ai_model(),
+            instruction: default_ai_instruction(),
+            local: LocalModelConfig::default(),
+            show_tokens: false,
+            token_budget: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AiProvider {
+    #[default]
+    Api,
+    Local,
+}
+
+/// GGUF model run by llama.cpp's `llama-server` for `provider = "local"`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalModelConfig {
+    /// GGUF model file; relative paths are resolved against the config
+    /// directory and a leading "~" is expanded
+    #[serde(default)]
+    pub model: String,
+
+    /// Context size in tokens (prompt and reply together)
+    #[serde(default = "default_local_context_size")]
+    pub context_size: u32,
+
+    /// The `llama-server` executable, found on PATH unless a path is given
+    #[serde(default = "default_local_server")]
+    pub server: String,
+}
+
+impl Default for LocalModelConfig {
+    fn default() -> Self {
+        Self {
+            model: String::new(),
+            context_size: default_local_context_size(),
+            server: default_local_server(),
+        }
+    }
+}
+
+/// Translation of the buffer; the DeepL or Google API key is kept in the OS
+/// keyring, not here
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslateConfig {
+    #[serde(default)]
+    pub backend: TranslateBackend,
+
+    /// Language to translate into, e.g. "en", "ja" or "en-GB"
+    #[serde(default = "default_translate_target_lang")]
+    pub target_lang: String,
+
+    /// Translate the text before it is pasted (history keeps the translation)
+    #[serde(default)]
+    pub on_paste: bool,
+}
+
+impl Default for TranslateConfig {
+    fn default() -> Self {
+        Self {
+            backend: TranslateBackend::default(),
+            target_lang: default_translate_target_lang(),
+            on_paste: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslateBackend {
+    /// The `[ai]` model, API or local
+    #[default]
+    Ai,
+    Deepl,
+    Google,
+}
+
+/// Spell checking of the buffer with Hunspell dictionaries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpellcheckConfig {
+    /// Underline misspelled words as you type
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Dictionary names such as "en_US" (`<name>.aff` and `<name>.dic` in the
+    /// `dictionaries` folder next to config.toml, or the system's hunspell
+    /// folders) or paths without the extension
+    #[serde(default = "default_spellcheck_dictionaries")]
+    pub dictionaries: Vec<String>,
+
+    /// Words accepted in addition to the dictionaries
+    #[serde(default)]
+    pub words: Vec<String>,
+}
+
+impl Default for SpellcheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dictionaries: default_spellcheck_dictionaries(),
+            words: Vec::new(),
+        }
+    }
+}
+
+/// Emoji and symbol picker
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymbolsConfig {
+    /// Skin tone emojis are inserted in; the others are offered as variants
+    #[serde(default)]
+    pub skin_tone: SkinTone,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkinTone {
+    /// The yellow one
+    #[default]
+    Default,
+    Light,
+    MediumLight,
+    Medium,
+    MediumDark,
+    Dark,
 }
 
 // Default values (matching prompt-line + readline)
@@ -176,11 +998,37 @@ fn default_shortcuts() -> Shortcuts {
         kill_word_back: "Ctrl+w".to_string(),
         delete_char: "Ctrl+d".to_string(),
         yank: "Ctrl+y".to_string(),
+        restore_draft: default_restore_draft(),
+        external_editor: default_external_editor(),
+        capture: default_capture(),
+        pin: default_pin(),
+        toggle_mode: default_toggle_mode(),
+        rewrite: default_rewrite(),
+        summarize: default_summarize(),
+        translate: default_translate(),
+        prompts: default_prompts(),
+        symbols: default_symbols(),
+        palette: default_palette(),
+        find_replace: default_find_replace(),
+        preview: default_preview(),
+        queue: default_queue(),
+        append: default_append(),
+        always_on_top: default_always_on_top_shortcut(),
+        global: BTreeMap::new(),
     }
 }
 
 fn default_history() -> HistoryConfig {
-    HistoryConfig { max_entries: 1000 }
+    HistoryConfig {
+        max_entries: 1000,
+        draft_versions: default_draft_versions(),
+        sync_dir: String::new(),
+        join_separator: JoinSeparator::default(),
+        preview_chars: default_preview_chars(),
+        trash_days: default_trash_days(),
+        retention_days: 0,
+        retention_action: RetentionAction::default(),
+    }
 }
 
 fn default_window() -> WindowConfig {
@@ -190,23 +1038,66 @@ fn default_window() -> WindowConfig {
         history_lines: default_history_lines(),
         textarea_rows: default_textarea_rows(),
         textarea_cols: default_textarea_cols(),
+        position: WindowPosition::default(),
+        opacity: default_opacity(),
+        effect: WindowEffect::default(),
+        compact: CompactWindow::default(),
+        scale: None,
+        font_family: String::new(),
+        preset: String::new(),
+        presets: BTreeMap::new(),
+        always_on_top: default_always_on_top(),
     }
 }
 
 fn default_behavior() -> BehaviorConfig {
     BehaviorConfig {
         simulate_paste_shortcut: default_simulate_paste_shortcut(),
+        paste_method: PasteMethod::default(),
         app_overrides: default_app_overrides(),
+        detect_terminals: default_detect_terminals(),
+        auto_pause_fullscreen: false,
+        screen_share_privacy: false,
+        screen_share_processes: Vec::new(),
+        hold_to_show: false,
+        paste_on_release: default_paste_on_release(),
+        tray_left_click: default_tray_left_click(),
+        jump_list_entries: default_jump_list_entries(),
+        autostart: false,
+        start_hidden: default_start_hidden(),
+        notify_on_paste: false,
+        per_app_drafts: false,
+        evaluate_on_paste: false,
+        external_editor: String::new(),
+        hide_on_blur: false,
+        clipboard_retries: default_clipboard_retries(),
+        clipboard_retry_delay_ms: default_clipboard_retry_delay_ms(),
+        clear_clipboard_first: false,
+        append_separator: default_append_separator(),
+        ime_safe_paste: default_ime_safe_paste(),
+        no_activate: false,
     }
 }
 
+fn default_append_separator() -> JoinSeparator {
+    JoinSeparator::Newline
+}
+
 fn default_voice() -> VoiceConfig {
     VoiceConfig {
         enabled: default_voice_enabled(),
         delay_ms: default_voice_delay_ms(),
+        engine: VoiceEngine::default(),
+        shortcut: String::new(),
+        stop_shortcut: String::new(),
+        whisper: WhisperConfig::default(),
     }
 }
 
+fn default_whisper_language() -> String {
+    "auto".to_string()
+}
+
 fn default_voice_enabled() -> bool {
     false
 }
@@ -215,6 +1106,20 @@ fn default_voice_delay_ms() -> u32 {
     500
 }
 
+fn default_logging() -> LoggingConfig {
+    LoggingConfig {
+        level: default_log_level(),
+    }
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_capture_template() -> String {
+    "{text}".to_string()
+}
+
 fn default_simulate_paste_shortcut() -> String {
     "Ctrl+V".to_string()
 }
@@ -223,19 +1128,93 @@ fn default_app_overrides() -> Vec<AppPasteOverride> {
     vec![
         AppPasteOverride {
             process_name: "alacritty.exe".to_string(),
+            title: None,
             shortcut: "Ctrl+Shift+V".to_string(),
+            window: None,
+            template: None,
+            voice: None,
+            method: None,
+            undo_shortcut: None,
         },
         AppPasteOverride {
             process_name: "wezterm-gui.exe".to_string(),
+            title: None,
             shortcut: "Ctrl+Shift+V".to_string(),
+            window: None,
+            template: None,
+            voice: None,
+            method: None,
+            undo_shortcut: None,
         },
         AppPasteOverride {
             process_name: String::new(),
+            title: None,
             shortcut: String::new(),
+            window: None,
+            template: None,
+            voice: None,
+            method: None,
+            undo_shortcut: None,
         },
     ]
 }
 
+fn default_clipboard_retries() -> u32 {
+    5
+}
+
+fn default_clipboard_retry_delay_ms() -> u32 {
+    20
+}
+
+fn default_detect_terminals() -> bool {
+    true
+}
+
+fn default_ime_safe_paste() -> bool {
+    true
+}
+
+fn default_paste_on_release() -> bool {
+    true
+}
+
+fn default_tray_left_click() -> String {
+    "toggle".to_string()
+}
+
+fn default_jump_list_entries() -> usize {
+    0
+}
+
+fn default_start_hidden() -> bool {
+    true
+}
+
+/// Known terminal emulators and the paste shortcut they accept
+const KNOWN_TERMINALS: &[(&str, &str)] = &[
+    ("windowsterminal.exe", "Ctrl+Shift+V"),
+    ("alacritty.exe", "Ctrl+Shift+V"),
+    ("wezterm-gui.exe", "Ctrl+Shift+V"),
+    ("kitty.exe", "Ctrl+Shift+V"),
+    ("conemu.exe", "Ctrl+Shift+V"),
+    ("conemu64.exe", "Ctrl+Shift+V"),
+    ("tabby.exe", "Ctrl+Shift+V"),
+    ("hyper.exe", "Ctrl+Shift+V"),
+    ("rio.exe", "Ctrl+Shift+V"),
+    // mintty only binds Ctrl+Shift+V when CtrlShiftShortcuts is on
+    ("mintty.exe", "Shift+Insert"),
+];
+
+/// Paste shortcut for a known terminal emulator process
+fn known_terminal_shortcut(process_name: &str) -> Option<&'static str> {
+    let process_lower = process_name.to_lowercase();
+    KNOWN_TERMINALS
+        .iter()
+        .find(|(name, _)| *name == process_lower)
+        .map(|(_, shortcut)| *shortcut)
+}
+
 fn default_launch() -> String {
     "Ctrl+Shift+Space".to_string()
 }
@@ -310,10 +1289,128 @@ fn default_yank() -> String {
     "Ctrl+y".to_string()
 }
 
+fn default_restore_draft() -> String {
+    "Ctrl+Shift+z".to_string()
+}
+
+fn default_external_editor() -> String {
+    "Ctrl+Shift+e".to_string()
+}
+
+fn default_capture() -> String {
+    "Ctrl+Shift+Enter".to_string()
+}
+
+fn default_pin() -> String {
+    "Ctrl+Shift+p".to_string()
+}
+
+fn default_toggle_mode() -> String {
+    "Ctrl+Shift+m".to_string()
+}
+
+fn default_rewrite() -> String {
+    "Ctrl+Shift+r".to_string()
+}
+
+fn default_summarize() -> String {
+    "Ctrl+Shift+s".to_string()
+}
+
+fn default_translate() -> String {
+    "Ctrl+Shift+l".to_string()
+}
+
+fn default_prompts() -> String {
+    "Ctrl+Shift+t".to_string()
+}
+
+fn default_symbols() -> String {
+    "Ctrl+Shift+u".to_string()
+}
+
+fn default_palette() -> String {
+    "Ctrl+Shift+k".to_string()
+}
+
+fn default_find_replace() -> String {
+    "Ctrl+h".to_string()
+}
+
+fn default_preview() -> String {
+    "Ctrl+Shift+v".to_string()
+}
+
+fn default_queue() -> String {
+    "Ctrl+Shift+q".to_string()
+}
+
+fn default_append() -> String {
+    "Ctrl+Shift+a".to_string()
+}
+
+fn default_always_on_top_shortcut() -> String {
+    "Ctrl+Shift+o".to_string()
+}
+
 fn default_max_entries() -> usize {
     1000
 }
 
+fn default_sync_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_sync_interval_minutes() -> u32 {
+    60
+}
+
+fn default_sync_file_name() -> String {
+    "prompt-line-rs.backup".to_string()
+}
+
+fn default_ai_endpoint() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_ai_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+fn default_translate_target_lang() -> String {
+    "en".to_string()
+}
+
+fn default_spellcheck_dictionaries() -> Vec<String> {
+    vec!["en_US".to_string()]
+}
+
+fn default_local_context_size() -> u32 {
+    4096
+}
+
+fn default_local_server() -> String {
+    "llama-server".to_string()
+}
+
+fn default_ai_instruction() -> String {
+    "Improve this prompt for an LLM: make it clear, specific and well structured. \
+     Keep its language and intent."
+        .to_string()
+}
+
+fn default_draft_versions() -> usize {
+    50
+}
+
+fn default_preview_chars() -> usize {
+    1000
+}
+
+fn default_trash_days() -> u32 {
+    30
+}
+
 fn default_font_size() -> f32 {
     14.0
 }
@@ -334,8 +1431,68 @@ fn default_textarea_cols() -> u32 {
     60
 }
 
+fn default_compact_rows() -> u32 {
+    1
+}
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+fn default_always_on_top() -> bool {
+    true
+}
+
 impl WindowConfig {
-    /// Calculate window width in pixels
+    /// Return a copy with the fields set in `overrides` replaced
+    pub fn with_override(&self, overrides: &WindowOverride) -> WindowConfig {
+        WindowConfig {
+            font_size: overrides.font_size.unwrap_or(self.font_size),
+            history_font_size: overrides
+                .history_font_size
+                .unwrap_or(self.history_font_size),
+            history_lines: overrides.history_lines.unwrap_or(self.history_lines),
+            textarea_rows: overrides.textarea_rows.unwrap_or(self.textarea_rows),
+            textarea_cols: overrides.textarea_cols.unwrap_or(self.textarea_cols),
+            position: self.position,
+            opacity: self.opacity,
+            effect: self.effect,
+            compact: self.compact,
+            scale: self.scale,
+            font_family: overrides
+                .font_family
+                .clone()
+                .unwrap_or_else(|| self.font_family.clone()),
+            preset: self.preset.clone(),
+            presets: self.presets.clone(),
+            always_on_top: self.always_on_top,
+        }
+    }
+
+    /// Return a copy with the active preset applied
+    pub fn with_active_preset(&self) -> WindowConfig {
+        match self.presets.get(&self.preset) {
+            Some(preset) => self.with_override(preset),
+            None => self.clone(),
+        }
+    }
+
+    /// Window size in logical pixels for a layout mode
+    pub fn size_pixels(&self, mode: WindowMode) -> (f64, f64) {
+        match mode {
+            WindowMode::Expanded => (self.width_pixels(), self.height_pixels()),
+            WindowMode::Compact => {
+                // Textarea and main padding only; header, history and button bar are hidden
+                let char_width = self.font_size as f64 * 0.6;
+                let width = (self.compact.textarea_cols as f64 * char_width) + 24.0;
+                let line_height = self.font_size as f64 * 1.4;
+                let height = (self.compact.textarea_rows as f64 * line_height) + 20.0 + 24.0;
+                (width, height)
+            }
+        }
+    }
+
+    /// Calculate window width in logical pixels
     pub fn width_pixels(&self) -> f64 {
         // Monospace char width ≈ font_size * 0.6
         let char_width = self.font_size as f64 * 0.6;
@@ -343,7 +1500,7 @@ impl WindowConfig {
         (self.textarea_cols as f64 * char_width) + padding
     }
 
-    /// Calculate window height in pixels
+    /// Calculate window height in logical pixels
     pub fn height_pixels(&self) -> f64 {
         let font_size = self.font_size as f64;
         let history_font_size = self.history_font_size as f64;
@@ -378,56 +1535,345 @@ impl WindowConfig {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            locale: String::new(),
             shortcuts: default_shortcuts(),
             history: default_history(),
             window: default_window(),
             behavior: default_behavior(),
             voice: default_voice(),
+            logging: default_logging(),
+            capture: CaptureConfig::default(),
+            theme: ThemeConfig::default(),
+            sync: SyncConfig::default(),
+            ai: AiConfig::default(),
+            translate: TranslateConfig::default(),
+            spellcheck: SpellcheckConfig::default(),
+            symbols: SymbolsConfig::default(),
         }
     }
 }
 
 impl Config {
-    /// Load config from file, or create default if not exists
-    pub fn load() -> Result<Self, String> {
-        let path = Self::default_path()?;
+    /// Find the first app override matching the target window
+    pub fn app_override(&self, target: &WindowInfo) -> Option<&AppPasteOverride> {
+        self.behavior
+            .app_overrides
+            .iter()
+            .find(|o| o.matches(target))
+    }
 
+    /// Paste shortcut to simulate for the given target window
+    ///
+    /// Explicit app overrides win, then the built-in terminal list, then the default.
+    pub fn paste_shortcut_for(&self, target: &WindowInfo) -> String {
+        if let Some(shortcut) = self
+            .app_override(target)
+            .map(|o| o.shortcut.clone())
+            .filter(|s| !s.trim().is_empty())
+        {
+            return shortcut;
+        }
+
+        if self.behavior.detect_terminals {
+            if let Some(shortcut) = target
+                .process_name
+                .as_deref()
+                .and_then(known_terminal_shortcut)
+            {
+                return shortcut.to_string();
+            }
+        }
+
+        self.behavior.simulate_paste_shortcut.clone()
+    }
+
+    /// Paste method for the given target window
+    pub fn paste_method_for(&self, target: &WindowInfo) -> PasteMethod {
+        self.app_override(target)
+            .and_then(|o| o.method)
+            .unwrap_or(self.behavior.paste_method)
+    }
+
+    /// Keys that undo a paste in the given target window, if configured
+    pub fn undo_shortcut_for(&self, target: &WindowInfo) -> Option<String> {
+        self.app_override(target)
+            .and_then(|o| o.undo_shortcut.clone())
+            .filter(|s| !s.trim().is_empty())
+    }
+
+    /// Whether to start voice input when shown over the given target window
+    ///
+    /// Needs `voice.enabled`; an app override's `voice` wins over the toggle.
+    pub fn voice_for(&self, target: &WindowInfo, toggle_on: bool) -> bool {
+        self.voice.enabled
+            && self
+                .app_override(target)
+                .and_then(|o| o.voice)
+                .unwrap_or(toggle_on)
+    }
+
+    /// Window layout for the given target window: the active preset, then
+    /// app overrides on top
+    pub fn window_for(&self, target: &WindowInfo) -> WindowConfig {
+        let window = self.window.with_active_preset();
+        match self.app_override(target).and_then(|o| o.window.as_ref()) {
+            Some(overrides) => window.with_override(overrides),
+            None => window,
+        }
+    }
+
+    /// One top-level table of the config (e.g. "window"), as the settings UI edits it
+    pub fn section(&self, name: &str) -> Result<serde_json::Value> {
+        let mut config = serde_json::to_value(self)
+            .map_err(|e| Error::Config(format!("Failed to serialize config: {}", e)))?;
+        config
+            .get_mut(name)
+            .map(serde_json::Value::take)
+            .ok_or_else(|| unknown_section(name))
+    }
+
+    /// Copy of the config with one top-level table replaced
+    ///
+    /// The new table is rejected if it doesn't parse or holds values the app
+    /// can't use, so a bad section never reaches the running app or the file.
+    pub fn with_section(&self, name: &str, value: serde_json::Value) -> Result<Self> {
+        let mut config = serde_json::to_value(self)
+            .map_err(|e| Error::Config(format!("Failed to serialize config: {}", e)))?;
+        *config.get_mut(name).ok_or_else(|| unknown_section(name))? = value;
+        let config: Self = serde_json::from_value(config)
+            .map_err(|e| Error::Config(format!("Invalid [{}] settings: {}", name, e)))?;
+        config.validate(name)?;
+        Ok(config)
+    }
+
+    /// Check every table for values that parse but can't be used
+    pub fn validate_all(&self) -> Result<()> {
+        for section in VALIDATED_SECTIONS {
+            self.validate(section)?;
+        }
+        Ok(())
+    }
+
+    /// Check one table for values that parse but can't be used
+    fn validate(&self, section: &str) -> Result<()> {
+        let invalid = |message: String| Err(Error::Config(format!("[{}] {}", section, message)));
+        let check_shortcut = |shortcut: &str| {
+            crate::hotkey::check_shortcut(shortcut)
+                .map(|_| ())
+                .map_err(|e| Error::Config(format!("[{}] {}", section, e)))
+        };
+
+        match section {
+            "shortcuts" => {
+                check_shortcut(&self.shortcuts.launch)?;
+                for (shortcut, action) in &self.shortcuts.global {
+                    check_shortcut(shortcut)?;
+                    if crate::hotkey::ShortcutAction::parse(action).is_none() {
+                        return invalid(format!("Unknown action '{}' for {}", action, shortcut));
+                    }
+                }
+            }
+            "history" => {
+                if self.history.max_entries == 0 {
+                    return invalid("max_entries must be at least 1".to_string());
+                }
+                if self.history.preview_chars == 0 {
+                    return invalid("preview_chars must be at least 1".to_string());
+                }
+            }
+            "window" => {
+                let window = &self.window;
+                if window.font_size <= 0.0 || window.history_font_size <= 0.0 {
+                    return invalid("Font sizes must be greater than 0".to_string());
+                }
+                if window.textarea_rows == 0 || window.textarea_cols == 0 {
+                    return invalid(
+                        "textarea_rows and textarea_cols must be at least 1".to_string(),
+                    );
+                }
+                if !(crate::window::MIN_OPACITY..=1.0).contains(&window.opacity) {
+                    return invalid(format!(
+                        "opacity must be between {} and 1.0",
+                        crate::window::MIN_OPACITY
+                    ));
+                }
+                if window.scale.is_some_and(|scale| scale <= 0.0) {
+                    return invalid("scale must be greater than 0".to_string());
+                }
+                if !window.preset.is_empty() && !window.presets.contains_key(&window.preset) {
+                    return invalid(format!("Unknown window preset: {}", window.preset));
+                }
+            }
+            "behavior" => {
+                let behavior = &self.behavior;
+                check_shortcut(&behavior.simulate_paste_shortcut)?;
+                if behavior.tray_left_click != "none"
+                    && crate::hotkey::ShortcutAction::parse(&behavior.tray_left_click).is_none()
+                {
+                    return invalid(format!(
+                        "Unknown tray_left_click action: {}",
+                        behavior.tray_left_click
+                    ));
+                }
+                // Rows without a process name never match (the settings
+                // window always sends its empty rows), so they aren't checked
+                for app in behavior
+                    .app_overrides
+                    .iter()
+                    .filter(|app| !app.process_name.trim().is_empty())
+                {
+                    let undo = app.undo_shortcut.as_deref().unwrap_or_default();
+                    for shortcut in [app.shortcut.as_str(), undo] {
+                        if !shortcut.trim().is_empty() {
+                            check_shortcut(shortcut)?;
+                        }
+                    }
+                }
+            }
+            "voice" => {
+                for shortcut in [&self.voice.shortcut, &self.voice.stop_shortcut] {
+                    if !shortcut.trim().is_empty() {
+                        check_shortcut(shortcut)?;
+                    }
+                }
+            }
+            "sync" if self.sync.interval_minutes == 0 => {
+                return invalid("interval_minutes must be at least 1".to_string());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Load the active profile's config, or create default if not exists
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load config from a file, or create default if not exists
+    pub fn load_from(path: &Path) -> Result<Self> {
         if !path.exists() {
             // Create default config
             let config = Config::default();
-            config.save()?;
+            config.save_to(path)?;
             return Ok(config);
         }
 
-        let contents =
-            fs::read_to_string(&path).map_err(|e| format!("Failed to read config file: {}", e))?;
+        Self::read(path)
+    }
+
+    /// Read and parse an existing config file
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(Error::io("Failed to read config file"))?;
 
-        toml::from_str(&contents).map_err(|e| format!("Failed to parse config file: {}", e))
+        toml::from_str(&contents)
+            .map_err(|e| Error::Config(format!("Failed to parse config file: {}", e)))
     }
 
-    /// Save config to file
-    pub fn save(&self) -> Result<(), String> {
-        let path = Self::default_path()?;
+    /// Save config to the active profile's file
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
 
+    /// Save config to a file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+            fs::create_dir_all(parent).map_err(Error::io("Failed to create config directory"))?;
         }
 
         let toml = toml::to_string_pretty(self)
-            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+            .map_err(|e| Error::Config(format!("Failed to serialize config: {}", e)))?;
 
-        fs::write(&path, toml).map_err(|e| format!("Failed to write config file: {}", e))?;
+        fs::write(path, toml).map_err(Error::io("Failed to write config file"))?;
 
         Ok(())
     }
 
-    /// Get default config file path
-    pub fn default_path() -> Result<PathBuf, String> {
-        let config_dir = directories::ProjectDirs::from("com", "prompt-line", "prompt-line-rs")
-            .ok_or_else(|| "Failed to get config directory".to_string())?;
+    /// Copy the active profile's config file to `<file>.bak` before it is replaced
+    pub fn backup() -> Result<()> {
+        let path = Self::default_path()?;
+        if path.exists() {
+            let mut backup = path.clone().into_os_string();
+            backup.push(".bak");
+            fs::copy(&path, backup).map_err(Error::io("Failed to back up config file"))?;
+        }
+        Ok(())
+    }
+
+    /// Get the active profile's config file path
+    pub fn default_path() -> Result<PathBuf> {
+        profile_path(&active_profile())
+    }
+}
+
+/// Tables `validate` has checks for
+const VALIDATED_SECTIONS: &[&str] = &[
+    "shortcuts",
+    "history",
+    "window",
+    "behavior",
+    "voice",
+    "sync",
+];
+
+fn unknown_section(name: &str) -> Error {
+    Error::Config(format!("Unknown config section: {}", name))
+}
 
-        Ok(config_dir.config_dir().join("config.toml"))
+/// Name of the profile stored in `config.toml`
+pub const DEFAULT_PROFILE: &str = "default";
+/// File in the config directory remembering the active profile
+const ACTIVE_PROFILE_FILE: &str = "active-profile";
+
+/// Config file of a profile: `config.toml` for the default, `config.<name>.toml` otherwise
+pub fn profile_path(name: &str) -> Result<PathBuf> {
+    let file_name = if name == DEFAULT_PROFILE {
+        "config.toml".to_string()
+    } else {
+        format!("config.{}.toml", name)
+    };
+    Ok(crate::paths::config_dir()?.join(file_name))
+}
+
+/// Profiles found in the config directory, default first
+pub fn list_profiles() -> Result<Vec<String>> {
+    let dir = crate::paths::config_dir()?;
+    let mut profiles: Vec<String> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let profile = name.strip_prefix("config.")?.strip_suffix(".toml")?;
+                (!profile.is_empty() && !profile.contains('.')).then(|| profile.to_string())
+            })
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(Error::io("Failed to read config directory")(e)),
+    };
+    profiles.sort();
+    profiles.insert(0, DEFAULT_PROFILE.to_string());
+    Ok(profiles)
+}
+
+/// Currently selected profile (falls back to the default if its file is gone)
+pub fn active_profile() -> String {
+    let name = crate::paths::config_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(ACTIVE_PROFILE_FILE)).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    match name {
+        Some(name) if profile_path(&name).is_ok_and(|path| path.exists()) => name,
+        _ => DEFAULT_PROFILE.to_string(),
     }
 }
+
+/// Remember the selected profile for the next launch
+pub fn set_active_profile(name: &str) -> Result<()> {
+    let dir = crate::paths::config_dir()?;
+    fs::create_dir_all(&dir).map_err(Error::io("Failed to create config directory"))?;
+    fs::write(dir.join(ACTIVE_PROFILE_FILE), name)
+        .map_err(Error::io("Failed to save active profile"))
+}
@@ -0,0 +1,116 @@
+//! Named counters for numbering pasted text
+//!
+//! Each counter counts up from 1 and survives a restart; they are kept in
+//! `counters.json` in the data directory. Prompt templates take the next
+//! number with `{{counter:name}}` (e.g. for ticket, screenshot or test case
+//! numbers); a counter used twice in one template gives the same number both
+//! times.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::error::{Error, Result};
+use crate::lock::MutexExt;
+
+const COUNTERS_FILE: &str = "counters.json";
+const PLACEHOLDER_PREFIX: &str = "{{counter:";
+
+/// Held while a counter is read and written back
+static FILE: Mutex<()> = Mutex::new(());
+
+/// Advance the counter `name` and return its new value (1 the first time)
+pub fn next(name: &str) -> Result<u64> {
+    check_name(name)?;
+    let _file = FILE.locked();
+    let mut counters = load()?;
+    let value = counters.entry(name.to_string()).or_insert(0);
+    *value += 1;
+    let value = *value;
+    save(&counters)?;
+    Ok(value)
+}
+
+/// Start the counter `name` over at 1
+pub fn reset(name: &str) -> Result<()> {
+    check_name(name)?;
+    let _file = FILE.locked();
+    let mut counters = load()?;
+    if counters.remove(name).is_some() {
+        save(&counters)?;
+    }
+    Ok(())
+}
+
+/// Replace each `{{counter:name}}` in `text` with the next value of that counter
+pub fn fill(text: &str) -> Result<String> {
+    let mut values: HashMap<&str, u64> = HashMap::new();
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(PLACEHOLDER_PREFIX) {
+        let after = &rest[start + PLACEHOLDER_PREFIX.len()..];
+        let Some(end) = after.find("}}").filter(|end| is_valid_name(&after[..*end])) else {
+            // Not a counter; keep the text as is
+            filled.push_str(&rest[..start + PLACEHOLDER_PREFIX.len()]);
+            rest = after;
+            continue;
+        };
+        let name = &after[..end];
+        let value = match values.get(name) {
+            Some(value) => *value,
+            None => {
+                let value = next(name)?;
+                values.insert(name, value);
+                value
+            }
+        };
+        filled.push_str(&rest[..start]);
+        filled.push_str(&value.to_string());
+        rest = &after[end + 2..];
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if is_valid_name(name) {
+        Ok(())
+    } else {
+        Err(Error::Config(format!(
+            "Invalid counter name \"{}\": use letters, digits, '_' and '-'",
+            name
+        )))
+    }
+}
+
+fn load() -> Result<BTreeMap<String, u64>> {
+    let path = counters_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let contents = fs::read_to_string(&path).map_err(Error::io("Failed to read counters"))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| Error::Config(format!("Failed to parse {}: {}", COUNTERS_FILE, e)))
+}
+
+fn save(counters: &BTreeMap<String, u64>) -> Result<()> {
+    let path = counters_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(Error::io("Failed to create directory"))?;
+    }
+    let json = serde_json::to_string_pretty(counters)
+        .map_err(|e| Error::Config(format!("Failed to serialize counters: {}", e)))?;
+    fs::write(path, json).map_err(Error::io("Failed to save counters"))
+}
+
+fn counters_path() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join(COUNTERS_FILE))
+}
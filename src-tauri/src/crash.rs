@@ -0,0 +1,103 @@
+//! Panic hook that saves the draft and writes a crash report
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+/// Latest draft (name, text) received from the main window
+static DRAFT: Mutex<Option<(String, String)>> = Mutex::new(None);
+/// Recent commands and actions, oldest first
+static BREADCRUMBS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+const MAX_BREADCRUMBS: usize = 20;
+/// Marker holding the path of a report not yet shown to the user
+const PENDING_FILE: &str = "crash.pending";
+
+/// Remember the draft so it can be flushed if the app panics
+pub fn set_draft(name: &str, text: &str) {
+    if let Ok(mut draft) = DRAFT.lock() {
+        *draft = (!text.is_empty()).then(|| (name.to_string(), text.to_string()));
+    }
+}
+
+/// Record a command or action for the crash report
+pub fn breadcrumb(name: &str) {
+    if let Ok(mut crumbs) = BREADCRUMBS.lock() {
+        if crumbs.len() == MAX_BREADCRUMBS {
+            crumbs.pop_front();
+        }
+        crumbs.push_back(format!(
+            "{} {}",
+            chrono::Local::now().format("%H:%M:%S"),
+            name
+        ));
+    }
+}
+
+/// Install the panic hook; the default hook still runs afterwards
+pub fn install() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        write_crash(&info.to_string());
+        default_hook(info);
+    }));
+}
+
+/// Tell the user about a crash from the previous run
+pub fn report_previous_crash(app: &AppHandle) {
+    let Some(marker) = crash_dir().map(|dir| dir.join(PENDING_FILE)) else {
+        return;
+    };
+    let Ok(report_path) = std::fs::read_to_string(&marker) else {
+        return;
+    };
+    let _ = std::fs::remove_file(&marker);
+
+    crate::notify::error(
+        app,
+        &crate::i18n::tr("notify.crashed", &[("path", report_path.trim())]),
+    );
+}
+
+fn crash_dir() -> Option<PathBuf> {
+    crate::paths::data_dir().ok().map(|dir| dir.join("crash"))
+}
+
+/// Flush the draft and write the report; must not panic itself
+fn write_crash(message: &str) {
+    // try_lock: the panic may have happened while one of these was held
+    if let Ok(Some((name, text))) = DRAFT.try_lock().map(|d| d.clone()) {
+        let _ = crate::drafts::save(&name, &text);
+    }
+
+    let Some(dir) = crash_dir() else {
+        return;
+    };
+    if std::fs::create_dir_all(&dir).is_err() {
+        return;
+    }
+
+    let now = chrono::Local::now();
+    let report_path = dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+    let breadcrumbs = BREADCRUMBS
+        .try_lock()
+        .map(|crumbs| crumbs.iter().cloned().collect::<Vec<_>>().join("\n"))
+        .unwrap_or_default();
+
+    let report = format!(
+        "prompt-line-rs {}\nTime: {}\nOS: {}\n\n{}\n\nRecent commands:\n{}\n\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        now.to_rfc3339(),
+        std::env::consts::OS,
+        message,
+        breadcrumbs,
+        std::backtrace::Backtrace::force_capture(),
+    );
+
+    if let Ok(mut file) = std::fs::File::create(&report_path) {
+        let _ = file.write_all(report.as_bytes());
+        let _ = std::fs::write(dir.join(PENDING_FILE), report_path.display().to_string());
+    }
+}
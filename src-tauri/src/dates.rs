@@ -0,0 +1,232 @@
+//! Dates in prompt templates
+//!
+//! `{{date}}` is today's date. Offsets shift it (`{{date+3d}}`,
+//! `{{date-1w}}`, `{{date+1m-1d}}`; units d, w, m, y), and after a colon
+//! comes a chrono format (`{{date+3d:%Y-%m-%d}}`), a phrase
+//! (`{{date:monday next week}}`) or both (`{{date:end of month|%d %b}}`).
+//! Offsets apply after the phrase. Dates are local, and formats may use the
+//! current time of day.
+//!
+//! Phrases are a small English vocabulary: today, tomorrow, yesterday,
+//! weekday names with this/next/last (or "this/next/last week"), "in 3 days",
+//! "2 weeks ago", "next month" and "start of"/"end of" a week, month or year.
+//! Weeks start on Monday.
+
+use chrono::{Datelike, Days, Local, Months, NaiveDate, Weekday};
+use std::fmt::Write;
+
+use crate::error::{Error, Result};
+
+const PLACEHOLDER_PREFIX: &str = "{{date";
+const DEFAULT_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, Copy)]
+enum Unit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// Replace each `{{date...}}` in `text` with the date it describes
+pub fn fill(text: &str) -> Result<String> {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(PLACEHOLDER_PREFIX) {
+        let after = &rest[start + PLACEHOLDER_PREFIX.len()..];
+        // `{{date}}`, `{{date+...}}`, `{{date:...}}`, not `{{dates}}`
+        let is_date = after.starts_with(['}', '+', '-', ':']);
+        let Some(end) = after.find("}}").filter(|_| is_date) else {
+            filled.push_str(&rest[..start + PLACEHOLDER_PREFIX.len()]);
+            rest = after;
+            continue;
+        };
+        filled.push_str(&rest[..start]);
+        filled.push_str(&render(&after[..end])?);
+        rest = &after[end + 2..];
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+/// The date of a placeholder without its `{{date` and `}}`
+fn render(spec: &str) -> Result<String> {
+    let (offsets, argument) = spec.split_once(':').unwrap_or((spec, ""));
+    let (phrase, format) = if argument.contains('%') && !argument.contains('|') {
+        ("", argument)
+    } else {
+        argument
+            .split_once('|')
+            .unwrap_or((argument, DEFAULT_FORMAT))
+    };
+
+    let now = Local::now().naive_local();
+    let mut date = match phrase.trim() {
+        "" => now.date(),
+        phrase => parse_phrase(phrase, now.date())
+            .ok_or_else(|| Error::Config(format!("Unknown date \"{}\"", phrase)))?,
+    };
+    for (amount, unit) in parse_offsets(offsets)? {
+        date = shift(date, amount, unit)
+            .ok_or_else(|| Error::Config(format!("Date out of range: {{{{date{}}}}}", spec)))?;
+    }
+
+    let mut text = String::new();
+    write!(text, "{}", date.and_time(now.time()).format(format.trim()))
+        .map_err(|_| Error::Config(format!("Invalid date format \"{}\"", format.trim())))?;
+    Ok(text)
+}
+
+/// `+3d-1w` -> [(3, Day), (-1, Week)]
+fn parse_offsets(offsets: &str) -> Result<Vec<(i64, Unit)>> {
+    let invalid = || Error::Config(format!("Invalid date offset \"{}\"", offsets));
+    let mut parsed = Vec::new();
+    let mut rest = offsets.trim();
+    while !rest.is_empty() {
+        let sign = match rest.chars().next() {
+            Some('+') => 1,
+            Some('-') => -1,
+            _ => return Err(invalid()),
+        };
+        rest = &rest[1..];
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: i64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit = rest
+            .chars()
+            .next()
+            .and_then(unit_letter)
+            .ok_or_else(invalid)?;
+        rest = &rest[1..];
+        parsed.push((sign * amount, unit));
+    }
+    Ok(parsed)
+}
+
+fn unit_letter(letter: char) -> Option<Unit> {
+    match letter {
+        'd' => Some(Unit::Day),
+        'w' => Some(Unit::Week),
+        'm' => Some(Unit::Month),
+        'y' => Some(Unit::Year),
+        _ => None,
+    }
+}
+
+fn unit_word(word: &str) -> Option<Unit> {
+    match word {
+        "day" | "days" => Some(Unit::Day),
+        "week" | "weeks" => Some(Unit::Week),
+        "month" | "months" => Some(Unit::Month),
+        "year" | "years" => Some(Unit::Year),
+        _ => None,
+    }
+}
+
+/// The date a phrase like "monday next week" or "in 3 days" means, seen from `today`
+fn parse_phrase(phrase: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let phrase = phrase.to_lowercase();
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    let weekday = |word: &str| word.parse::<Weekday>().ok();
+    let relative = |word: &str| match word {
+        "this" => Some(0),
+        "next" => Some(1),
+        "last" => Some(-1),
+        _ => None,
+    };
+
+    match words.as_slice() {
+        ["today"] => Some(today),
+        ["tomorrow"] => shift(today, 1, Unit::Day),
+        ["yesterday"] => shift(today, -1, Unit::Day),
+        ["in", amount, unit] => shift(today, amount.parse().ok()?, unit_word(unit)?),
+        [amount, unit, "ago"] => shift(today, -amount.parse::<i64>().ok()?, unit_word(unit)?),
+        // "friday": today or the coming one
+        [day] => {
+            let ahead = days_until(today.weekday(), weekday(day)?);
+            shift(today, ahead, Unit::Day)
+        }
+        // "next friday": the first one after today, "last friday": the latest before
+        ["next", day] if weekday(day).is_some() => {
+            let ahead = days_until(today.weekday(), weekday(day)?);
+            shift(today, if ahead == 0 { 7 } else { ahead }, Unit::Day)
+        }
+        ["last", day] if weekday(day).is_some() => {
+            let behind = days_until(weekday(day)?, today.weekday());
+            shift(today, -(if behind == 0 { 7 } else { behind }), Unit::Day)
+        }
+        ["this", day] => in_week(today, 0, weekday(day)?),
+        [day, which, "week"] | [which, "week", day] if weekday(day).is_some() => {
+            in_week(today, relative(which)?, weekday(day)?)
+        }
+        [which, unit] => shift(today, relative(which)?, unit_word(unit)?),
+        [edge @ ("start" | "beginning" | "end"), "of", period @ ..] => {
+            let (amount, unit) = match period {
+                [unit] => (0, unit_word(unit)?),
+                [which, unit] => (relative(which)?, unit_word(unit)?),
+                _ => return None,
+            };
+            let start = period_start(shift(today, amount, unit)?, unit)?;
+            if *edge == "end" {
+                shift(shift(start, 1, unit)?, -1, Unit::Day)
+            } else {
+                Some(start)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Days from `from` forward to the next `to` (0 if the same day)
+fn days_until(from: Weekday, to: Weekday) -> i64 {
+    (i64::from(to.num_days_from_monday()) - i64::from(from.num_days_from_monday())).rem_euclid(7)
+}
+
+/// `day` of the week `weeks` away from the one `today` is in
+fn in_week(today: NaiveDate, weeks: i64, day: Weekday) -> Option<NaiveDate> {
+    let monday = period_start(today, Unit::Week)?;
+    shift(
+        monday,
+        weeks * 7 + i64::from(day.num_days_from_monday()),
+        Unit::Day,
+    )
+}
+
+/// First day of the week, month or year `date` is in
+fn period_start(date: NaiveDate, unit: Unit) -> Option<NaiveDate> {
+    match unit {
+        Unit::Day => Some(date),
+        Unit::Week => shift(
+            date,
+            -i64::from(date.weekday().num_days_from_monday()),
+            Unit::Day,
+        ),
+        Unit::Month => date.with_day(1),
+        Unit::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1),
+    }
+}
+
+/// `date` moved by `amount` units; months and years keep the day of the
+/// month where they can, or end on the last day of a shorter month
+fn shift(date: NaiveDate, amount: i64, unit: Unit) -> Option<NaiveDate> {
+    let (amount, unit) = match unit {
+        Unit::Week => (amount.checked_mul(7)?, Unit::Day),
+        Unit::Year => (amount.checked_mul(12)?, Unit::Month),
+        unit => (amount, unit),
+    };
+    let magnitude = amount.unsigned_abs();
+    match unit {
+        Unit::Day if amount >= 0 => date.checked_add_days(Days::new(magnitude)),
+        Unit::Day => date.checked_sub_days(Days::new(magnitude)),
+        _ => {
+            let months = Months::new(u32::try_from(magnitude).ok()?);
+            if amount >= 0 {
+                date.checked_add_months(months)
+            } else {
+                date.checked_sub_months(months)
+            }
+        }
+    }
+}
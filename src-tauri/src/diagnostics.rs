@@ -0,0 +1,101 @@
+//! Health-check report for support requests
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::lock::MutexExt;
+use crate::{config, foreground, logging, paths, AppState};
+
+/// Result of a single check
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn from_result<T>(
+        result: crate::error::Result<T>,
+        ok_detail: impl FnOnce(T) -> String,
+    ) -> Self {
+        match result {
+            Ok(value) => Self {
+                ok: true,
+                detail: ok_detail(value),
+            },
+            Err(e) => Self {
+                ok: false,
+                detail: e.to_string(),
+            },
+        }
+    }
+}
+
+/// Everything support usually needs to know
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsReport {
+    pub app_version: String,
+    pub os_version: String,
+    pub portable: bool,
+    pub config_path: Option<String>,
+    pub data_dir: Option<String>,
+    pub log_dir: Option<String>,
+    pub history_entries: usize,
+    pub history_file_size: Option<u64>,
+    pub launch_shortcut: Option<String>,
+    pub launch_shortcut_is_fallback: bool,
+    pub global_shortcuts: usize,
+    pub hotkeys_paused: bool,
+    pub clipboard: Check,
+    pub foreground_window: Check,
+}
+
+/// Collect the diagnostics report
+pub fn run(app: &AppHandle) -> DiagnosticsReport {
+    let state = app.state::<AppState>();
+
+    let (history_entries, history_path) = {
+        let history = state.history.locked();
+        (history.len(), history.file_path().to_path_buf())
+    };
+    let effective = state.effective_shortcut.locked().clone();
+    let global_shortcuts = state.shortcut_actions.locked().len();
+    let hotkeys_paused = state.hotkey_pause.locked().is_paused();
+
+    let foreground_window = if cfg!(windows) {
+        match foreground::get_foreground_process_name() {
+            Some(name) => Check {
+                ok: true,
+                detail: format!("Foreground process: {}", name),
+            },
+            None => Check {
+                ok: false,
+                detail: "Could not read the foreground process".to_string(),
+            },
+        }
+    } else {
+        Check {
+            ok: false,
+            detail: "Only supported on Windows".to_string(),
+        }
+    };
+
+    DiagnosticsReport {
+        app_version: app.package_info().version.to_string(),
+        os_version: os_info::get().to_string(),
+        portable: paths::is_portable(),
+        config_path: config::Config::default_path()
+            .ok()
+            .map(|p| p.display().to_string()),
+        data_dir: paths::data_dir().ok().map(|p| p.display().to_string()),
+        log_dir: logging::log_dir().ok().map(|p| p.display().to_string()),
+        history_entries,
+        history_file_size: std::fs::metadata(&history_path).ok().map(|m| m.len()),
+        launch_shortcut: effective.shortcut,
+        launch_shortcut_is_fallback: effective.is_fallback,
+        global_shortcuts,
+        hotkeys_paused,
+        clipboard: Check::from_result(state.clipboard.check_access(), |_| "Readable".to_string()),
+        foreground_window,
+    }
+}
@@ -0,0 +1,166 @@
+//! Live state of Windows voice input while it runs
+//!
+//! Once voice input is triggered, a monitor thread sends a `voice-status`
+//! event every 100 ms with the microphone peak level and whether the voice
+//! input panel is on screen, until dictation is stopped. The level comes from
+//! the capture endpoint's meter, so the app never opens the microphone itself.
+//!
+//! If the panel was seen and then disappears, the user closed it; dictation is
+//! then marked inactive so hiding the window doesn't send the stop shortcut
+//! and reopen it.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::VoiceEngine;
+use crate::lock::{MutexExt, RwLockExt};
+use crate::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Whether a monitor thread is running
+static MONITOR_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Payload of `voice-status`
+#[derive(Debug, Clone, Serialize)]
+pub struct VoiceStatus {
+    /// Voice input is running; the last event after it stops has `false`
+    pub active: bool,
+    /// Microphone peak level from 0.0 to 1.0, `None` if it can't be read
+    pub level: Option<f32>,
+    /// Whether the voice input panel is on screen
+    pub panel: PanelState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PanelState {
+    Visible,
+    Closed,
+}
+
+/// Mark voice input as running and start the monitor if needed
+pub fn started(app: &AppHandle) {
+    *app.state::<AppState>().dictation_active.locked() = true;
+    if MONITOR_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let handle = app.clone();
+    let spawned = std::thread::Builder::new()
+        .name("dictation-monitor".to_string())
+        .spawn(move || loop {
+            monitor(&handle);
+            MONITOR_RUNNING.store(false, Ordering::SeqCst);
+            // Restarted between the monitor's last check and the store above
+            if !is_active(&handle) || MONITOR_RUNNING.swap(true, Ordering::SeqCst) {
+                break;
+            }
+        });
+    if let Err(e) = spawned {
+        MONITOR_RUNNING.store(false, Ordering::SeqCst);
+        tracing::warn!("Failed to start dictation monitor: {}", e);
+    }
+}
+
+fn is_active(app: &AppHandle) -> bool {
+    *app.state::<AppState>().dictation_active.locked()
+}
+
+/// Processes hosting the voice input panel of each engine
+fn panel_processes(engine: VoiceEngine) -> &'static [&'static str] {
+    match engine {
+        VoiceEngine::Dictation => &["TextInputHost.exe"],
+        VoiceEngine::VoiceAccess => &["VoiceAccess.exe"],
+    }
+}
+
+#[cfg(windows)]
+fn monitor(app: &AppHandle) {
+    use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED};
+
+    let initialized = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }.is_ok();
+    let meter = MicMeter::open();
+    poll(app, || meter.as_ref().map(MicMeter::level));
+    drop(meter);
+    if initialized {
+        unsafe { CoUninitialize() };
+    }
+}
+
+#[cfg(not(windows))]
+fn monitor(app: &AppHandle) {
+    poll(app, || None);
+}
+
+fn poll(app: &AppHandle, mut level: impl FnMut() -> Option<f32>) {
+    let engine = app.state::<AppState>().config.read_locked().voice.engine;
+    let processes = panel_processes(engine);
+    let mut panel_seen = false;
+
+    while is_active(app) {
+        let panel = if crate::foreground::is_process_window_shown(processes) {
+            panel_seen = true;
+            PanelState::Visible
+        } else {
+            PanelState::Closed
+        };
+        if panel_seen && panel == PanelState::Closed {
+            tracing::debug!("Voice input panel closed by the user");
+            *app.state::<AppState>().dictation_active.locked() = false;
+            break;
+        }
+
+        let _ = app.emit(
+            "voice-status",
+            VoiceStatus {
+                active: true,
+                level: level(),
+                panel,
+            },
+        );
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    let _ = app.emit(
+        "voice-status",
+        VoiceStatus {
+            active: false,
+            level: None,
+            panel: PanelState::Closed,
+        },
+    );
+}
+
+/// Peak meter of the default capture device
+#[cfg(windows)]
+struct MicMeter(windows::Win32::Media::Audio::Endpoints::IAudioMeterInformation);
+
+#[cfg(windows)]
+impl MicMeter {
+    fn open() -> Option<Self> {
+        use windows::Win32::Media::Audio::{
+            eCapture, eConsole, IMMDeviceEnumerator, MMDeviceEnumerator,
+        };
+        use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_ALL};
+
+        let result = unsafe {
+            CoCreateInstance::<_, IMMDeviceEnumerator>(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                .and_then(|enumerator| enumerator.GetDefaultAudioEndpoint(eCapture, eConsole))
+                .and_then(|device| device.Activate(CLSCTX_ALL, None))
+        };
+        match result {
+            Ok(meter) => Some(Self(meter)),
+            Err(e) => {
+                tracing::debug!("Microphone level unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    fn level(&self) -> f32 {
+        unsafe { self.0.GetPeakValue() }.unwrap_or(0.0)
+    }
+}
@@ -0,0 +1,318 @@
+//! Named drafts (scratchpads)
+//!
+//! Each draft is a text file in `<data>/drafts/<name>.txt`. An empty draft
+//! has no file, so `list` only returns drafts that hold text. Per-app drafts
+//! are named `@<process name>` and are not listed. Earlier versions are
+//! kept as snapshots in `<data>/drafts/versions/`.
+//!
+//! The main window sends its text on every change; `DraftManager` batches
+//! those calls and writes each file atomically (temp file + rename).
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+
+/// Draft used when no other is selected (the former single `draft.txt`)
+pub const DEFAULT_DRAFT: &str = "default";
+
+const MAX_NAME_LEN: usize = 64;
+
+fn drafts_dir() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join("drafts"))
+}
+
+/// File holding a draft; rejects names that aren't usable as file names
+pub fn draft_path(name: &str) -> Result<PathBuf> {
+    let invalid = name.trim().is_empty()
+        || name.len() > MAX_NAME_LEN
+        || name.starts_with('.')
+        || name
+            .chars()
+            .any(|c| c.is_control() || r#"/\:*?"<>|"#.contains(c));
+    if invalid {
+        return Err(Error::Config(format!("Invalid draft name: {:?}", name)));
+    }
+    Ok(drafts_dir()?.join(format!("{}.txt", name)))
+}
+
+/// Quiet period after the last change before a draft is written
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+enum Message {
+    Save {
+        name: String,
+        text: String,
+        max_versions: usize,
+    },
+    Flush(mpsc::Sender<()>),
+}
+
+/// Debounces draft saves on a background writer thread
+pub struct DraftManager {
+    sender: mpsc::Sender<Message>,
+}
+
+impl DraftManager {
+    /// Start the writer thread
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("draft-writer".to_string())
+            .spawn(move || run_writer(receiver))
+            .expect("Failed to start draft writer");
+        Self { sender }
+    }
+
+    /// Queue a draft to be written once typing pauses (empty text deletes it)
+    pub fn save(&self, name: &str, text: &str, max_versions: usize) -> Result<()> {
+        draft_path(name)?;
+        let _ = self.sender.send(Message::Save {
+            name: name.to_string(),
+            text: text.to_string(),
+            max_versions,
+        });
+        Ok(())
+    }
+
+    /// Write all queued drafts now and wait until they are on disk
+    pub fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        if self.sender.send(Message::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+}
+
+fn run_writer(receiver: mpsc::Receiver<Message>) {
+    let mut pending: HashMap<String, (String, usize)> = HashMap::new();
+    let mut deadline: Option<Instant> = None;
+
+    loop {
+        let message = match deadline {
+            Some(deadline) => {
+                match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(message) => Some(message),
+                    Err(mpsc::RecvTimeoutError::Timeout) => None,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            None => match receiver.recv() {
+                Ok(message) => Some(message),
+                Err(_) => break,
+            },
+        };
+
+        match message {
+            Some(Message::Save {
+                name,
+                text,
+                max_versions,
+            }) => {
+                pending.insert(name, (text, max_versions));
+                deadline = Some(Instant::now() + DEBOUNCE);
+            }
+            Some(Message::Flush(done)) => {
+                write_pending(&mut pending);
+                deadline = None;
+                let _ = done.send(());
+            }
+            None => {
+                write_pending(&mut pending);
+                deadline = None;
+            }
+        }
+    }
+    write_pending(&mut pending);
+}
+
+fn write_pending(pending: &mut HashMap<String, (String, usize)>) {
+    for (name, (text, max_versions)) in pending.drain() {
+        let result =
+            snapshot(&name, max_versions, text.is_empty()).and_then(|_| save(&name, &text));
+        if let Err(e) = result {
+            tracing::error!("Failed to save draft {}: {}", name, e);
+        }
+    }
+}
+
+/// Save a draft immediately; saving empty text deletes it
+pub fn save(name: &str, text: &str) -> Result<()> {
+    if text.is_empty() {
+        return delete(name);
+    }
+    write_atomic(&draft_path(name)?, text)
+}
+
+/// Write to a temp file next to `path` and rename it over, so a crash
+/// mid-write never leaves a truncated file
+fn write_atomic(path: &Path, text: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(Error::io("Failed to create directory"))?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    fs::write(&temp, text).map_err(Error::io("Failed to save draft"))?;
+    fs::rename(&temp, path).map_err(Error::io("Failed to save draft"))
+}
+
+/// Load a draft (empty if it doesn't exist)
+pub fn load(name: &str) -> Result<String> {
+    let path = draft_path(name)?;
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    fs::read_to_string(&path).map_err(Error::io("Failed to load draft"))
+}
+
+/// Delete a draft
+pub fn delete(name: &str) -> Result<()> {
+    let path = draft_path(name)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(Error::io("Failed to delete draft"))?;
+    }
+    Ok(())
+}
+
+/// Names of the saved drafts, default first
+pub fn list() -> Result<Vec<String>> {
+    let entries = match fs::read_dir(drafts_dir()?) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::io("Failed to read drafts directory")(e)),
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let name = file_name.strip_suffix(".txt")?;
+            (!name.starts_with('@')).then(|| name.to_string())
+        })
+        .collect();
+    names.sort_by_key(|name| (name != DEFAULT_DRAFT, name.to_lowercase()));
+    Ok(names)
+}
+
+/// Move the single `draft.txt` of earlier versions into the default draft
+pub fn migrate_legacy_draft() {
+    let Ok(old) = crate::paths::data_dir().map(|dir| dir.join("draft.txt")) else {
+        return;
+    };
+    let Ok(new) = draft_path(DEFAULT_DRAFT) else {
+        return;
+    };
+    if !old.exists() || new.exists() {
+        return;
+    }
+    let result = new
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::rename(&old, &new));
+    if let Err(e) = result {
+        tracing::warn!("Failed to migrate draft.txt: {}", e);
+    }
+}
+
+/// A saved earlier version of a draft
+#[derive(Debug, Clone, Serialize)]
+pub struct DraftVersion {
+    /// File stem: `<timestamp>_<draft name>`
+    pub id: String,
+    pub name: String,
+    pub timestamp: String,
+    pub text: String,
+}
+
+/// Minimum time between automatic snapshots of the same draft
+const SNAPSHOT_INTERVAL_SECS: i64 = 60;
+const VERSION_TIME_FORMAT: &str = "%Y%m%d-%H%M%S%3f";
+
+fn versions_dir() -> Result<PathBuf> {
+    Ok(drafts_dir()?.join("versions"))
+}
+
+/// Snapshot a draft before it is overwritten or deleted, keeping at most
+/// `max_versions` snapshots overall. Unless `force` is set, a draft is
+/// snapshotted at most once a minute while it is being typed.
+pub fn snapshot(name: &str, max_versions: usize, force: bool) -> Result<()> {
+    if max_versions == 0 {
+        return Ok(());
+    }
+    let text = load(name)?;
+    if text.is_empty() {
+        return Ok(());
+    }
+
+    let versions = list_versions()?;
+    if let Some(latest) = versions.iter().find(|v| v.name == name) {
+        let recent = parse_version_time(&latest.timestamp).is_some_and(|time| {
+            (chrono::Local::now().naive_local() - time).num_seconds() < SNAPSHOT_INTERVAL_SECS
+        });
+        if latest.text == text || (recent && !force) {
+            return Ok(());
+        }
+    }
+
+    let dir = versions_dir()?;
+    fs::create_dir_all(&dir).map_err(Error::io("Failed to create directory"))?;
+    let id = format!(
+        "{}_{}",
+        chrono::Local::now().format(VERSION_TIME_FORMAT),
+        name
+    );
+    write_atomic(&dir.join(format!("{}.txt", id)), &text)?;
+
+    // Drop the oldest snapshots beyond the limit (the new one is not in `versions`)
+    for old in versions.iter().skip(max_versions.saturating_sub(1)) {
+        let _ = fs::remove_file(dir.join(format!("{}.txt", old.id)));
+    }
+    Ok(())
+}
+
+/// All draft snapshots, newest first
+pub fn list_versions() -> Result<Vec<DraftVersion>> {
+    let dir = versions_dir()?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::io("Failed to read draft versions")(e)),
+    };
+
+    let mut versions: Vec<DraftVersion> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let id = file_name.strip_suffix(".txt")?;
+            let (timestamp, name) = id.split_once('_')?;
+            let text = fs::read_to_string(entry.path()).ok()?;
+            Some(DraftVersion {
+                id: id.to_string(),
+                name: name.to_string(),
+                timestamp: timestamp.to_string(),
+                text,
+            })
+        })
+        .collect();
+    versions.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(versions)
+}
+
+/// Put a snapshot back into its draft, snapshotting the current text first
+pub fn restore_version(id: &str, max_versions: usize) -> Result<DraftVersion> {
+    let version = list_versions()?
+        .into_iter()
+        .find(|v| v.id == id)
+        .ok_or_else(|| Error::NotFound(format!("Unknown draft version: {}", id)))?;
+    snapshot(&version.name, max_versions, true)?;
+    save(&version.name, &version.text)?;
+    Ok(version)
+}
+
+fn parse_version_time(timestamp: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::NaiveDateTime::parse_from_str(timestamp, VERSION_TIME_FORMAT).ok()
+}
@@ -0,0 +1,100 @@
+//! Edit the buffer in an external editor
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::{Error, Result};
+
+/// Editor used when neither the config nor `$VISUAL`/`$EDITOR` names one
+#[cfg(windows)]
+const FALLBACK_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const FALLBACK_EDITOR: &str = "vi";
+
+/// Write `text` to a temp file, open it in the editor, wait for the editor
+/// to exit and return the edited text. Blocks for as long as the editor is open.
+pub fn edit(text: &str, configured: &str) -> Result<String> {
+    let command = editor_command(configured);
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| Error::Config("No external editor configured".to_string()))?;
+
+    let path = std::env::temp_dir().join(format!(
+        "prompt-line-{}-{}.md",
+        std::process::id(),
+        chrono::Local::now().format("%Y%m%d-%H%M%S%3f")
+    ));
+    fs::write(&path, text).map_err(Error::io("Failed to write editor file"))?;
+
+    let result = run_editor(program, args, &path);
+    let edited = result
+        .and_then(|_| fs::read_to_string(&path).map_err(Error::io("Failed to read editor file")));
+    let _ = fs::remove_file(&path);
+    let mut edited = edited?;
+
+    // Most editors add a final newline; drop it unless the buffer had one
+    if !text.ends_with('\n') && edited.ends_with('\n') {
+        edited.pop();
+        if edited.ends_with('\r') {
+            edited.pop();
+        }
+    }
+    Ok(edited)
+}
+
+fn run_editor(program: &str, args: &[String], path: &Path) -> Result<()> {
+    tracing::info!("Opening external editor: {}", program);
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .map_err(Error::io("Failed to start external editor"))?;
+    if !status.success() {
+        return Err(Error::Platform(format!(
+            "External editor exited with {}",
+            status
+        )));
+    }
+    Ok(())
+}
+
+/// Program and arguments: the configured command, else `$VISUAL`/`$EDITOR`
+fn editor_command(configured: &str) -> Vec<String> {
+    let command = Some(configured.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .or_else(|| {
+            std::env::var("VISUAL")
+                .ok()
+                .filter(|c| !c.trim().is_empty())
+        })
+        .or_else(|| {
+            std::env::var("EDITOR")
+                .ok()
+                .filter(|c| !c.trim().is_empty())
+        })
+        .unwrap_or_else(|| FALLBACK_EDITOR.to_string());
+    split_command(&command)
+}
+
+/// Split on whitespace, keeping double-quoted parts (paths with spaces) together
+fn split_command(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in command.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
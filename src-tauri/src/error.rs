@@ -0,0 +1,104 @@
+//! Crate-wide error type
+//!
+//! Commands return `Result<T, Error>`; the frontend receives `{ code, message }`
+//! so it can react to specific failures (e.g. `clipboard_busy`).
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::io::ErrorKind;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Clipboard could not be opened or written
+    #[error("{context}: {source}")]
+    Clipboard {
+        context: &'static str,
+        #[source]
+        source: arboard::Error,
+    },
+
+    /// File system error
+    #[error("{context}: {source}")]
+    Io {
+        context: &'static str,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Config or data file could not be parsed or serialized
+    #[error("{0}")]
+    Config(String),
+
+    /// Invalid, unknown or unavailable shortcut
+    #[error("{0}")]
+    Shortcut(String),
+
+    /// OS API failure or feature unsupported on this platform
+    #[error("{0}")]
+    Platform(String),
+
+    /// Remote server unreachable or refused the request
+    #[error("{0}")]
+    Network(String),
+
+    /// A history entry (or other item) the request names doesn't exist
+    #[error("{0}")]
+    NotFound(String),
+
+    /// A secret history entry was read without revealing it, or is damaged
+    #[error("{0}")]
+    Secret(String),
+
+    /// The target app runs elevated, so Windows drops simulated input
+    #[error(
+        "The target app is running as administrator, so Windows blocks simulated paste. \
+         The text is on the clipboard; paste it manually or run prompt-line-rs as administrator."
+    )]
+    InputBlocked,
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    /// Build a `map_err` adapter for IO errors
+    pub fn io(context: &'static str) -> impl FnOnce(std::io::Error) -> Self {
+        move |source| Self::Io { context, source }
+    }
+
+    /// Build a `map_err` adapter for clipboard errors
+    pub fn clipboard(context: &'static str) -> impl FnOnce(arboard::Error) -> Self {
+        move |source| Self::Clipboard { context, source }
+    }
+
+    /// Stable code the frontend can match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Clipboard {
+                source: arboard::Error::ClipboardOccupied,
+                ..
+            } => "clipboard_busy",
+            Self::Clipboard { .. } => "clipboard",
+            Self::Io { source, .. } => match source.kind() {
+                ErrorKind::PermissionDenied => "permission_denied",
+                ErrorKind::NotFound => "not_found",
+                _ => "io",
+            },
+            Self::Config(_) => "config",
+            Self::Shortcut(_) => "shortcut",
+            Self::Platform(_) => "platform",
+            Self::Network(_) => "network",
+            Self::NotFound(_) => "not_found",
+            Self::Secret(_) => "secret",
+            Self::InputBlocked => "input_blocked",
+        }
+    }
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Error", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
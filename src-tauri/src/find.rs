@@ -0,0 +1,118 @@
+//! Regex find and replace over the buffer
+//!
+//! Patterns use the `regex` crate's syntax, the same as ripgrep: `\d`, `\b`,
+//! `\p{Greek}`, named groups, no look-around or backreferences. Replacements
+//! refer to groups as `$1` or `${name}`; `$$` is a literal `$`.
+//!
+//! Flags are a string of letters: `i` (ignore case), `m` (`^`/`$` match at
+//! line breaks), `s` (`.` matches line breaks), `x` (ignore whitespace and
+//! `#` comments), `U` (lazy by default), and as in ripgrep `w` (whole words)
+//! and `F` (fixed string: the pattern is taken literally).
+
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+
+/// Compiled programs above this are rejected instead of eating memory
+const SIZE_LIMIT: usize = 10 * (1 << 20);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Match {
+    /// UTF-16 offset of the first character, like `selectionStart`
+    pub start: usize,
+    /// UTF-16 offset after the last character
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Replaced {
+    pub text: String,
+    /// Number of matches replaced
+    pub count: usize,
+}
+
+/// All non-overlapping matches of `pattern` in `text`
+pub fn find(text: &str, pattern: &str, flags: &str) -> Result<Vec<Match>> {
+    let regex = compile(pattern, flags)?;
+    let mut matches = Vec::new();
+    // Byte offsets to UTF-16, counting from the previous match
+    let (mut byte, mut utf16) = (0, 0);
+    for found in regex.find_iter(text) {
+        utf16 += text[byte..found.start()].encode_utf16().count();
+        let start = utf16;
+        utf16 += found.as_str().encode_utf16().count();
+        byte = found.end();
+        matches.push(Match { start, end: utf16 });
+    }
+    Ok(matches)
+}
+
+/// Replace every match of `pattern` in `text`
+pub fn replace(text: &str, pattern: &str, replacement: &str, flags: &str) -> Result<Replaced> {
+    let regex = compile(pattern, flags)?;
+    let count = regex.find_iter(text).count();
+    Ok(Replaced {
+        text: regex.replace_all(text, replacement).into_owned(),
+        count,
+    })
+}
+
+fn compile(pattern: &str, flags: &str) -> Result<Regex> {
+    if pattern.is_empty() {
+        return Err(Error::Config("Nothing to find".to_string()));
+    }
+    let mut source = pattern.to_string();
+    let mut case_insensitive = false;
+    let mut multi_line = false;
+    let mut dot_matches_new_line = false;
+    let mut ignore_whitespace = false;
+    let mut swap_greed = false;
+    let mut whole_words = false;
+    for flag in flags.chars() {
+        match flag {
+            'i' => case_insensitive = true,
+            'm' => multi_line = true,
+            's' => dot_matches_new_line = true,
+            'x' => ignore_whitespace = true,
+            'U' => swap_greed = true,
+            'w' => whole_words = true,
+            'F' => source = regex::escape(pattern),
+            other => {
+                return Err(Error::Config(format!(
+                    "Unknown regex flag '{}' (use i, m, s, x, U, w or F)",
+                    other
+                )))
+            }
+        }
+    }
+    if whole_words {
+        // A `#` comment in verbose mode would swallow the closing group
+        let end = if ignore_whitespace { "\n" } else { "" };
+        source = format!(r"\b(?:{}{})\b", source, end);
+    }
+
+    RegexBuilder::new(&source)
+        .case_insensitive(case_insensitive)
+        .multi_line(multi_line)
+        .dot_matches_new_line(dot_matches_new_line)
+        .ignore_whitespace(ignore_whitespace)
+        .swap_greed(swap_greed)
+        .size_limit(SIZE_LIMIT)
+        .build()
+        .map_err(pattern_error)
+}
+
+/// The one-line reason; syntax errors otherwise repeat the pattern with a caret
+fn pattern_error(error: regex::Error) -> Error {
+    let message = match error {
+        regex::Error::Syntax(detail) => detail
+            .lines()
+            .find_map(|line| line.strip_prefix("error: "))
+            .unwrap_or(detail.trim())
+            .to_string(),
+        regex::Error::CompiledTooBig(_) => "the pattern is too large".to_string(),
+        other => other.to_string(),
+    };
+    Error::Config(format!("Invalid pattern: {}", message))
+}
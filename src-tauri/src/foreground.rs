@@ -0,0 +1,666 @@
+//! Foreground window inspection (target app detection)
+
+use serde::Serialize;
+
+/// The window prompt-line-rs was summoned over (the paste target)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WindowInfo {
+    /// Executable file name, e.g. "Code.exe"
+    pub process_name: Option<String>,
+    /// Full path of the executable
+    pub exe_path: Option<String>,
+    /// Window title, e.g. the browser tab's page title
+    pub title: Option<String>,
+}
+
+impl WindowInfo {
+    /// Whether this is the window of `process_name` (compared case-insensitively)
+    pub fn is_process(&self, process_name: &str) -> bool {
+        self.process_name
+            .as_deref()
+            .is_some_and(|name| name.eq_ignore_ascii_case(process_name))
+    }
+}
+
+/// Process name, executable path and title of the foreground window
+#[cfg(windows)]
+pub fn foreground_window() -> WindowInfo {
+    use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return WindowInfo::default();
+        }
+        WindowInfo {
+            process_name: window_process_name(hwnd),
+            exe_path: window_exe_path(hwnd),
+            title: window_title(hwnd),
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn foreground_window() -> WindowInfo {
+    WindowInfo::default()
+}
+
+/// Get the process name of the foreground window
+#[cfg(windows)]
+pub fn get_foreground_process_name() -> Option<String> {
+    use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return None;
+        }
+        window_process_name(hwnd)
+    }
+}
+
+/// Get the process name of the process owning a window
+#[cfg(windows)]
+unsafe fn window_process_name(hwnd: windows::Win32::Foundation::HWND) -> Option<String> {
+    use windows::Win32::Foundation::{CloseHandle, MAX_PATH};
+    use windows::Win32::System::ProcessStatus::K32GetModuleBaseNameW;
+    use windows::Win32::System::Threading::{
+        OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+    let mut process_id: u32 = 0;
+    GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+    if process_id == 0 {
+        return None;
+    }
+
+    let handle = OpenProcess(
+        PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
+        false,
+        process_id,
+    )
+    .ok()?;
+    if handle.is_invalid() {
+        return None;
+    }
+
+    let mut buffer = [0u16; MAX_PATH as usize];
+    let len = K32GetModuleBaseNameW(handle, None, &mut buffer);
+    let _ = CloseHandle(handle);
+
+    if len == 0 {
+        return None;
+    }
+
+    Some(String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+#[cfg(not(windows))]
+pub fn get_foreground_process_name() -> Option<String> {
+    None
+}
+
+/// Get the full executable path of the process owning a window
+///
+/// Limited query access also works for elevated processes, unlike the module
+/// functions used for the base name.
+#[cfg(windows)]
+unsafe fn window_exe_path(hwnd: windows::Win32::Foundation::HWND) -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+    let mut process_id: u32 = 0;
+    GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+    if process_id == 0 {
+        return None;
+    }
+
+    let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id).ok()?;
+    // Long paths need more than MAX_PATH
+    let mut buffer = [0u16; 1024];
+    let mut len = buffer.len() as u32;
+    let result = QueryFullProcessImageNameW(
+        handle,
+        PROCESS_NAME_WIN32,
+        PWSTR(buffer.as_mut_ptr()),
+        &mut len,
+    );
+    let _ = CloseHandle(handle);
+
+    result.ok()?;
+    Some(String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+/// Get the title of a window
+#[cfg(windows)]
+unsafe fn window_title(hwnd: windows::Win32::Foundation::HWND) -> Option<String> {
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowTextW;
+
+    let mut buffer = [0u16; 512];
+    let len = GetWindowTextW(hwnd, &mut buffer);
+    (len > 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+/// Check whether any of the given processes shows a window on screen
+///
+/// Windows that are hidden or cloaked (suspended UWP views such as the
+/// resident input flyouts) don't count.
+#[cfg(windows)]
+pub fn is_process_window_shown(process_names: &[&str]) -> bool {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::EnumWindows;
+
+    struct Search<'a> {
+        process_names: &'a [&'a str],
+        found: bool,
+    }
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut Search);
+        if !is_window_shown(hwnd) {
+            return true.into();
+        }
+        let matches = window_process_name(hwnd).is_some_and(|name| {
+            search
+                .process_names
+                .iter()
+                .any(|wanted| name.eq_ignore_ascii_case(wanted))
+        });
+        if matches {
+            search.found = true;
+            // Stop enumerating
+            return false.into();
+        }
+        true.into()
+    }
+
+    let mut search = Search {
+        process_names,
+        found: false,
+    };
+    unsafe {
+        // Fails when the callback stops early, which is how a match is reported
+        let _ = EnumWindows(Some(visit), LPARAM(&mut search as *mut Search as isize));
+    }
+    search.found
+}
+
+#[cfg(not(windows))]
+pub fn is_process_window_shown(_process_names: &[&str]) -> bool {
+    false
+}
+
+/// Whether a window is visible and not cloaked
+#[cfg(windows)]
+unsafe fn is_window_shown(hwnd: windows::Win32::Foundation::HWND) -> bool {
+    use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED};
+    use windows::Win32::UI::WindowsAndMessaging::IsWindowVisible;
+
+    if !IsWindowVisible(hwnd).as_bool() {
+        return false;
+    }
+    let mut cloaked: u32 = 0;
+    let cloaked_ok = DwmGetWindowAttribute(
+        hwnd,
+        DWMWA_CLOAKED,
+        &mut cloaked as *mut u32 as *mut core::ffi::c_void,
+        std::mem::size_of::<u32>() as u32,
+    )
+    .is_ok();
+    !(cloaked_ok && cloaked != 0)
+}
+
+/// How long `pick_window` waits for a window to be picked
+#[cfg(windows)]
+const PICK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+#[cfg(windows)]
+thread_local! {
+    /// Window clicked while `pick_window` waits
+    static PICKED: std::cell::Cell<Option<windows::Win32::Foundation::HWND>> =
+        const { std::cell::Cell::new(None) };
+}
+
+/// Wait for the user to pick a window, for creating an app override: the next
+/// window clicked, or the next one brought to the front some other way
+/// (Alt+Tab, the taskbar). This app's own windows are passed over, and the
+/// picking click is swallowed. `None` if nothing is picked within 15 seconds.
+#[cfg(windows)]
+pub fn pick_window() -> crate::error::Result<Option<WindowInfo>> {
+    use crate::error::Error;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetMessageW, KillTimer, SetTimer, SetWindowsHookExW,
+        UnhookWindowsHookEx, MSG, WH_MOUSE_LL, WM_TIMER,
+    };
+
+    PICKED.set(None);
+    let hook = unsafe { SetWindowsHookExW(WH_MOUSE_LL, Some(pick_mouse_proc), None, 0) }
+        .map_err(|e| Error::Platform(format!("Failed to install mouse hook: {}", e)))?;
+    // Foreground changes don't reach this thread; poll for them
+    let timer = unsafe { SetTimer(HWND::default(), 0, 100, None) };
+    let deadline = std::time::Instant::now() + PICK_TIMEOUT;
+
+    // The mouse hook is called from this message loop and posts WM_QUIT on a pick
+    let mut msg = MSG::default();
+    while unsafe { GetMessageW(&mut msg, HWND::default(), 0, 0) }.as_bool() {
+        if msg.message != WM_TIMER {
+            continue;
+        }
+        let hwnd = unsafe { GetForegroundWindow() };
+        if !hwnd.0.is_null() && !is_own_window(hwnd) {
+            PICKED.set(Some(hwnd));
+            break;
+        }
+        if std::time::Instant::now() >= deadline {
+            tracing::debug!("Window picking timed out");
+            break;
+        }
+    }
+    unsafe {
+        let _ = KillTimer(HWND::default(), timer);
+        let _ = UnhookWindowsHookEx(hook);
+    }
+
+    Ok(PICKED.take().map(|hwnd| unsafe {
+        WindowInfo {
+            process_name: window_process_name(hwnd),
+            exe_path: window_exe_path(hwnd),
+            title: window_title(hwnd),
+        }
+    }))
+}
+
+#[cfg(not(windows))]
+pub fn pick_window() -> crate::error::Result<Option<WindowInfo>> {
+    Err(crate::error::Error::Platform(
+        "Picking a window is only supported on Windows".to_string(),
+    ))
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn pick_mouse_proc(
+    code: i32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::Foundation::LRESULT;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, GetAncestor, PostQuitMessage, WindowFromPoint, GA_ROOT, HC_ACTION, HHOOK,
+        MSLLHOOKSTRUCT, WM_LBUTTONDOWN,
+    };
+
+    if code == HC_ACTION as i32 && wparam.0 as u32 == WM_LBUTTONDOWN {
+        let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+        let hwnd = GetAncestor(WindowFromPoint(info.pt), GA_ROOT);
+        if !hwnd.0.is_null() && !is_own_window(hwnd) {
+            PICKED.set(Some(hwnd));
+            PostQuitMessage(0);
+            // Swallow the click so it doesn't act on the picked window
+            return LRESULT(1);
+        }
+    }
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+/// Whether a window belongs to this process
+#[cfg(windows)]
+fn is_own_window(hwnd: windows::Win32::Foundation::HWND) -> bool {
+    use windows::Win32::System::Threading::GetCurrentProcessId;
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+    let mut process_id: u32 = 0;
+    unsafe {
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+        process_id == GetCurrentProcessId()
+    }
+}
+
+/// Whether a window is another app's main window on screen
+#[cfg(windows)]
+unsafe fn is_app_window(hwnd: windows::Win32::Foundation::HWND) -> bool {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindow, GetWindowLongW, GWL_EXSTYLE, GW_OWNER, WS_EX_TOOLWINDOW,
+    };
+
+    // Dialogs and palettes belong to a main window listed on its own
+    let owned = GetWindow(hwnd, GW_OWNER).is_ok_and(|owner| !owner.0.is_null());
+    let tool = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32 & WS_EX_TOOLWINDOW.0 != 0;
+    !owned && !tool && !is_own_window(hwnd) && is_window_shown(hwnd)
+}
+
+/// Bring the topmost app window to the front if the taskbar has the focus, as
+/// it does after a jump list item is clicked, so a paste goes to that app
+#[cfg(windows)]
+pub fn leave_taskbar() {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetClassNameW, GetForegroundWindow, SetForegroundWindow,
+    };
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        // Windows are enumerated top to bottom; stop at the first one
+        if is_app_window(hwnd) {
+            *(lparam.0 as *mut HWND) = hwnd;
+            return false.into();
+        }
+        true.into()
+    }
+
+    unsafe {
+        let mut class = [0u16; 64];
+        let len = GetClassNameW(GetForegroundWindow(), &mut class) as usize;
+        let class = String::from_utf16_lossy(&class[..len]);
+        if class != "Shell_TrayWnd" && class != "Shell_SecondaryTrayWnd" {
+            return;
+        }
+        let mut top = HWND::default();
+        let _ = EnumWindows(Some(visit), LPARAM(&mut top as *mut HWND as isize));
+        if !top.0.is_null() && SetForegroundWindow(top).as_bool() {
+            // Let the app take the focus before keys are sent to it
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn leave_taskbar() {}
+
+/// An app window on screen, offered when adding an app override
+#[derive(Debug, Clone, Serialize)]
+pub struct RunningApp {
+    pub process_name: String,
+    pub exe_path: Option<String>,
+    pub title: String,
+    /// The executable's icon as a PNG data URL
+    pub icon: Option<String>,
+}
+
+/// Titled top-level windows on screen, except tool windows and this app's own,
+/// sorted by process name
+#[cfg(windows)]
+pub fn running_apps() -> Vec<RunningApp> {
+    use std::collections::HashMap;
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::EnumWindows;
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let windows = &mut *(lparam.0 as *mut Vec<HWND>);
+        if is_app_window(hwnd) {
+            windows.push(hwnd);
+        }
+        true.into()
+    }
+
+    let mut windows: Vec<HWND> = Vec::new();
+    unsafe {
+        let _ = EnumWindows(Some(visit), LPARAM(&mut windows as *mut Vec<HWND> as isize));
+    }
+
+    // Apps often have several windows; extract each executable's icon once
+    let mut icons: HashMap<String, Option<String>> = HashMap::new();
+    let mut apps: Vec<RunningApp> = windows
+        .into_iter()
+        .filter_map(|hwnd| unsafe {
+            let title = window_title(hwnd)?;
+            let process_name = window_process_name(hwnd)?;
+            let exe_path = window_exe_path(hwnd);
+            let icon = exe_path.as_ref().and_then(|path| {
+                icons
+                    .entry(path.clone())
+                    .or_insert_with(|| exe_icon(path))
+                    .clone()
+            });
+            Some(RunningApp {
+                process_name,
+                exe_path,
+                title,
+                icon,
+            })
+        })
+        .collect();
+    apps.sort_by_key(|app| app.process_name.to_lowercase());
+    apps
+}
+
+#[cfg(not(windows))]
+pub fn running_apps() -> Vec<RunningApp> {
+    Vec::new()
+}
+
+/// First icon of an executable as a PNG data URL
+#[cfg(windows)]
+fn exe_icon(path: &str) -> Option<String> {
+    use base64::Engine;
+    use windows::core::HSTRING;
+    use windows::Win32::Graphics::Gdi::{
+        DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO,
+        BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows::Win32::UI::Shell::ExtractIconExW;
+    use windows::Win32::UI::WindowsAndMessaging::{DestroyIcon, GetIconInfo, HICON, ICONINFO};
+
+    unsafe {
+        let mut icon = HICON::default();
+        if ExtractIconExW(&HSTRING::from(path), 0, Some(&mut icon), None, 1) == 0
+            || icon.is_invalid()
+        {
+            return None;
+        }
+        let mut info = ICONINFO::default();
+        let info_ok = GetIconInfo(icon, &mut info).is_ok();
+        let _ = DestroyIcon(icon);
+        if !info_ok {
+            return None;
+        }
+
+        let mut bitmap = BITMAP::default();
+        let mut bgra = Vec::new();
+        let (mut width, mut height) = (0, 0);
+        if GetObjectW(
+            info.hbmColor,
+            std::mem::size_of::<BITMAP>() as i32,
+            Some(&mut bitmap as *mut BITMAP as *mut core::ffi::c_void),
+        ) != 0
+        {
+            width = bitmap.bmWidth;
+            height = bitmap.bmHeight;
+            let mut header = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: width,
+                    // Negative: rows top to bottom
+                    biHeight: -height,
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            bgra = vec![0u8; (width * height * 4) as usize];
+            let dc = GetDC(None);
+            let lines = GetDIBits(
+                dc,
+                info.hbmColor,
+                0,
+                height as u32,
+                Some(bgra.as_mut_ptr() as *mut core::ffi::c_void),
+                &mut header,
+                DIB_RGB_COLORS,
+            );
+            ReleaseDC(None, dc);
+            if lines == 0 {
+                bgra.clear();
+            }
+        }
+        let _ = DeleteObject(info.hbmColor);
+        let _ = DeleteObject(info.hbmMask);
+        if bgra.is_empty() {
+            return None;
+        }
+
+        // Icons from before alpha channels leave it all zero
+        let opaque = bgra.chunks_exact(4).all(|pixel| pixel[3] == 0);
+        let rgba: Vec<u8> = bgra
+            .chunks_exact(4)
+            .flat_map(|p| [p[2], p[1], p[0], if opaque { 255 } else { p[3] }])
+            .collect();
+
+        let mut png_data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png_data, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&rgba))
+            .ok()?;
+        Some(format!(
+            "data:image/png;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(png_data)
+        ))
+    }
+}
+
+/// Check whether Windows will block simulated input to the foreground window
+///
+/// UIPI silently drops `SendInput` events aimed at an elevated (admin) process
+/// unless this process is elevated as well. Only a process whose token says
+/// it is elevated counts; one that can't be queried doesn't.
+#[cfg(windows)]
+pub fn is_foreground_input_blocked() -> bool {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        GetCurrentProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    unsafe {
+        if is_process_elevated(GetCurrentProcess()) {
+            return false;
+        }
+
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return false;
+        }
+
+        let mut process_id: u32 = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+        if process_id == 0 {
+            return false;
+        }
+
+        // Limited access is enough for the token query and is granted for
+        // elevated processes; when it isn't, elevation is unknown and the
+        // paste goes ahead
+        let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) else {
+            return false;
+        };
+        let elevated = is_process_elevated(handle);
+        let _ = CloseHandle(handle);
+        elevated
+    }
+}
+
+/// Check whether the process behind `process` runs with an elevated token
+#[cfg(windows)]
+unsafe fn is_process_elevated(process: windows::Win32::Foundation::HANDLE) -> bool {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{
+        GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+    };
+    use windows::Win32::System::Threading::OpenProcessToken;
+
+    let mut token = HANDLE::default();
+    if OpenProcessToken(process, TOKEN_QUERY, &mut token).is_err() {
+        return false;
+    }
+
+    let mut elevation = TOKEN_ELEVATION::default();
+    let mut return_length: u32 = 0;
+    let result = GetTokenInformation(
+        token,
+        TokenElevation,
+        Some(&mut elevation as *mut TOKEN_ELEVATION as *mut core::ffi::c_void),
+        std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+        &mut return_length,
+    );
+    let _ = CloseHandle(token);
+
+    result.is_ok() && elevation.TokenIsElevated != 0
+}
+
+#[cfg(not(windows))]
+pub fn is_foreground_input_blocked() -> bool {
+    false
+}
+
+/// Check whether a fullscreen app (Direct3D exclusive, presentation, or fullscreen window) is in front
+#[cfg(windows)]
+pub fn is_fullscreen_app_active() -> bool {
+    use windows::Win32::UI::Shell::{
+        SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE,
+        QUNS_RUNNING_D3D_FULL_SCREEN,
+    };
+
+    match unsafe { SHQueryUserNotificationState() } {
+        Ok(state) => {
+            state == QUNS_BUSY
+                || state == QUNS_RUNNING_D3D_FULL_SCREEN
+                || state == QUNS_PRESENTATION_MODE
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(windows))]
+pub fn is_fullscreen_app_active() -> bool {
+    false
+}
+
+/// Center of the monitor showing the foreground window (physical pixels)
+#[cfg(windows)]
+pub fn foreground_monitor_center() -> Option<(f64, f64)> {
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONULL,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return None;
+        }
+
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL);
+        if monitor.is_invalid() {
+            return None;
+        }
+
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+            return None;
+        }
+
+        let rect = info.rcMonitor;
+        Some((
+            (rect.left + rect.right) as f64 / 2.0,
+            (rect.top + rect.bottom) as f64 / 2.0,
+        ))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn foreground_monitor_center() -> Option<(f64, f64)> {
+    None
+}
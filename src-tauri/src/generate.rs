@@ -0,0 +1,156 @@
+//! Text generators for the command palette
+//!
+//! UUIDs (random v4 or time-ordered v7), ISO 8601 timestamps, passwords and
+//! lorem ipsum placeholder text. Passwords are drawn from the OS random
+//! number generator and contain at least one character of each selected class.
+
+use chrono::{Local, SecondsFormat, Utc};
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!#$%&()*+,-./:;<=>?@[]^_{|}~";
+/// Easily confused when read aloud or typed from paper
+const AMBIGUOUS: &str = "Il1O0o|";
+
+const MAX_PASSWORD_LENGTH: usize = 256;
+const MAX_PARAGRAPHS: usize = 50;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeneratorKind {
+    UuidV4,
+    UuidV7,
+    Timestamp,
+    Password,
+    Lorem,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GenerateOptions {
+    /// Timestamp in UTC ("...Z") instead of local time with its offset
+    pub utc: bool,
+    /// Password length in characters
+    pub length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    /// Leave out characters such as l, 1, O and 0
+    pub exclude_ambiguous: bool,
+    /// Number of lorem ipsum paragraphs
+    pub paragraphs: usize,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            utc: false,
+            length: 20,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+            exclude_ambiguous: false,
+            paragraphs: 1,
+        }
+    }
+}
+
+pub fn generate(kind: GeneratorKind, options: &GenerateOptions) -> Result<String> {
+    match kind {
+        GeneratorKind::UuidV4 => Ok(Uuid::new_v4().to_string()),
+        GeneratorKind::UuidV7 => Ok(Uuid::now_v7().to_string()),
+        GeneratorKind::Timestamp if options.utc => {
+            Ok(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+        }
+        GeneratorKind::Timestamp => Ok(Local::now().to_rfc3339_opts(SecondsFormat::Secs, false)),
+        GeneratorKind::Password => password(options),
+        GeneratorKind::Lorem => lorem(options.paragraphs),
+    }
+}
+
+fn password(options: &GenerateOptions) -> Result<String> {
+    if options.length == 0 || options.length > MAX_PASSWORD_LENGTH {
+        return Err(Error::Config(format!(
+            "Password length must be between 1 and {}",
+            MAX_PASSWORD_LENGTH
+        )));
+    }
+    let classes: Vec<Vec<char>> = [
+        (options.lowercase, LOWERCASE),
+        (options.uppercase, UPPERCASE),
+        (options.digits, DIGITS),
+        (options.symbols, SYMBOLS),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, chars)| {
+        chars
+            .chars()
+            .filter(|c| !options.exclude_ambiguous || !AMBIGUOUS.contains(*c))
+            .collect()
+    })
+    .collect();
+    if classes.is_empty() {
+        return Err(Error::Config(
+            "Select at least one kind of character for the password".to_string(),
+        ));
+    }
+    if options.length < classes.len() {
+        return Err(Error::Config(format!(
+            "A password with {} kinds of characters needs at least {} characters",
+            classes.len(),
+            classes.len()
+        )));
+    }
+
+    let all: Vec<char> = classes.concat();
+    let mut rng = OsRng;
+    // One of each class, the rest from all of them, then shuffled
+    let mut chars: Vec<char> = classes
+        .iter()
+        .filter_map(|class| class.choose(&mut rng).copied())
+        .collect();
+    while chars.len() < options.length {
+        chars.push(all[rng.gen_range(0..all.len())]);
+    }
+    chars.shuffle(&mut rng);
+    Ok(chars.into_iter().collect())
+}
+
+/// Paragraphs of 4 to 8 sentences; the first one starts with "Lorem ipsum"
+fn lorem(paragraphs: usize) -> Result<String> {
+    if paragraphs == 0 || paragraphs > MAX_PARAGRAPHS {
+        return Err(Error::Config(format!(
+            "Paragraph count must be between 1 and {}",
+            MAX_PARAGRAPHS
+        )));
+    }
+    let mut rng = thread_rng();
+    let text: Vec<String> = (0..paragraphs)
+        .map(|paragraph| {
+            let sentences = rng.gen_range(4..=8);
+            (0..sentences)
+                .map(|sentence| {
+                    if paragraph == 0 && sentence == 0 {
+                        lipsum::lipsum(8)
+                    } else {
+                        let words = rng.gen_range(6..=16);
+                        lipsum::lipsum_words_with_rng(&mut rng, words)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+    Ok(text.join("\n\n"))
+}
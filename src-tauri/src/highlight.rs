@@ -0,0 +1,178 @@
+//! Syntax highlighting of code snippets
+//!
+//! Uses syntect's bundled Sublime Text grammars. The output is HTML spans with
+//! `hl-` prefixed scope classes (`hl-comment`, `hl-string`, `hl-keyword`, ...)
+//! that the stylesheet colors per theme. Without a language hint the language
+//! is guessed from the first line (shebangs, `<?xml`), then from JSON syntax
+//! and a few telltale keywords; text that looks like none is left plain.
+
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+/// Longer text is shown plain; highlighting it would stall the preview
+const MAX_HIGHLIGHT_BYTES: usize = 256 * 1024;
+
+/// Substrings that give a language away; it takes two to count as code
+const MARKERS: &[(&str, &[&str])] = &[
+    (
+        "Rust",
+        &[
+            "fn ", "let mut ", "impl ", "pub ", "::", "println!", "use std", "&mut ",
+        ],
+    ),
+    (
+        "Python",
+        &[
+            "def ", "import ", "self.", "elif ", "print(", "__init__", "None",
+        ],
+    ),
+    ("Go", &["package ", "func ", ":= ", "fmt.", "err != nil"]),
+    (
+        "Java",
+        &[
+            "public class",
+            "public static",
+            "System.out",
+            "private ",
+            "@Override",
+        ],
+    ),
+    (
+        "C++",
+        &["#include", "std::", "int main", "nullptr", "cout <<"],
+    ),
+    (
+        "JavaScript",
+        &[
+            "function ",
+            "const ",
+            "=> ",
+            "console.log",
+            "require(",
+            "===",
+            "document.",
+        ],
+    ),
+    (
+        "SQL",
+        &[
+            "SELECT ",
+            "FROM ",
+            "WHERE ",
+            "INSERT INTO",
+            "CREATE TABLE",
+            "JOIN ",
+        ],
+    ),
+    ("HTML", &["<div", "<html", "<body", "<span", "<p>", "href="]),
+    (
+        "Bourne Again Shell (bash)",
+        &[
+            "#!/bin/", "echo ", "fi\n", "then\n", "$(", "export ", "sudo ",
+        ],
+    ),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Highlighted {
+    /// Escaped text with `<span class="hl-...">` around tokens
+    pub html: String,
+    /// Grammar used, e.g. "Rust"; none when the text was left plain
+    pub language: Option<String>,
+}
+
+/// Highlight `text` as `lang_hint` (a name or file extension such as "rust",
+/// "py" or "sh"), or as the detected language without a hint
+pub fn highlight(text: &str, lang_hint: Option<&str>) -> Highlighted {
+    let syntaxes = syntaxes();
+    let syntax = lang_hint
+        .map(str::trim)
+        .filter(|hint| !hint.is_empty())
+        .and_then(|hint| syntaxes.find_syntax_by_token(hint))
+        .or_else(|| detect(text));
+    match syntax {
+        Some(syntax) if text.len() <= MAX_HIGHLIGHT_BYTES => to_html(text, syntax),
+        _ => plain(text),
+    }
+}
+
+/// Escaped text without highlighting
+pub fn plain(text: &str) -> Highlighted {
+    Highlighted {
+        html: escape_html(text),
+        language: None,
+    }
+}
+
+fn to_html(text: &str, syntax: &SyntaxReference) -> Highlighted {
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(
+        syntax,
+        syntaxes(),
+        ClassStyle::SpacedPrefixed { prefix: "hl-" },
+    );
+    for line in LinesWithEndings::from(text) {
+        if let Err(e) = generator.parse_html_for_line_which_includes_newline(line) {
+            tracing::warn!("Failed to highlight {}: {}", syntax.name, e);
+            return plain(text);
+        }
+    }
+    Highlighted {
+        html: generator.finalize(),
+        language: Some(syntax.name.clone()),
+    }
+}
+
+fn detect(text: &str) -> Option<&'static SyntaxReference> {
+    let syntaxes = syntaxes();
+    let first_line = text.trim_start().lines().next()?;
+    if let Some(syntax) = syntaxes.find_syntax_by_first_line(first_line) {
+        return Some(syntax);
+    }
+    let trimmed = text.trim();
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+    {
+        return syntaxes.find_syntax_by_name("JSON");
+    }
+
+    let (name, score) = MARKERS
+        .iter()
+        .map(|(name, markers)| {
+            let score = markers
+                .iter()
+                .filter(|marker| text.contains(*marker))
+                .count();
+            (*name, score)
+        })
+        .max_by_key(|(_, score)| *score)?;
+    if score >= 2 {
+        syntaxes.find_syntax_by_name(name)
+    } else {
+        None
+    }
+}
+
+/// The bundled grammars, loaded on first use
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,24 +1,159 @@
 //! History management module
 //!
 //! Stores input history in JSONL format (one JSON object per line)
+//!
+//! Entries pushed out by `max_entries` are never deleted: they are appended to
+//! `archive/history-YYYY-MM.jsonl` next to the history file, by the month they
+//! were written, where `search_archive` can still find them.
+//!
+//! With `history.sync_dir` set, each machine also appends its entries to its
+//! own `history-<machine>.jsonl` in that shared folder and merges the other
+//! machines' files on load. Every file has a single writer, so a file sync
+//! tool never sees conflicting edits.
+//!
+//! Cleared and cleaned-up entries go to the `Trash`, from where they can be
+//! restored for a while.
+//!
+//! With `history.retention_days` set, `expire` takes entries past that age
+//! out of the history regardless of `max_entries`: into the archive, or out
+//! of every file this machine writes with `retention_action = "delete"`.
+//!
+//! The history file may be shared by several running instances. Reads and
+//! writes hold an advisory lock on `history.jsonl.lock`, and `save` merges
+//! what other instances changed in the file since this one last read or
+//! wrote it, so no instance's entries are lost.
+//!
+//! Secret entries hold their text encrypted for the current Windows user
+//! (`secrets::protect`). They are listed masked, left out of searches and the
+//! sync folder, and read only through `plain_text`.
+//!
+//! Searches go through a `SearchIndex` kept up to date as entries come and
+//! go. The archive gets one too, built when it is first searched and extended
+//! as entries are archived.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Local, Utc};
 use serde::{Deserialize, Serialize};
-use std::fs::{File, OpenOptions};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::config::{HistoryConfig, RetentionAction};
+use crate::error::{Error, Result};
+use crate::foreground::WindowInfo;
+use crate::index::SearchIndex;
+use crate::trash::{Trash, Trashed};
+
+const ARCHIVE_DIR: &str = "archive";
+const TRASH_FILE: &str = "trash.jsonl";
+/// Shown in place of the text of a secret entry
+const SECRET_MASK: &str = "••••••••";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
     pub text: String,
     pub timestamp: DateTime<Utc>,
+    /// Process name of the app the text went to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    /// Title of that app's window at the time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// `text` is encrypted; see `plain_text`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub secret: bool,
+}
+
+/// An entry as listed in the UI, with long text cut short
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryPreview {
+    pub text: String,
+    pub timestamp: DateTime<Utc>,
+    pub app: Option<String>,
+    pub title: Option<String>,
+    /// `text` is only the start; `get_history_entry_text` has the rest
+    pub truncated: bool,
+    /// `text` is a mask; `reveal_entry` has the text
+    pub secret: bool,
+}
+
+/// What `History::compact` removes; every rule is off by default
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CompactOptions {
+    /// Earlier copies of a text that was entered again later
+    pub duplicates: bool,
+    /// Entries with fewer characters than this, ignoring surrounding whitespace
+    pub min_chars: Option<usize>,
+    /// Entries written more than this many days ago
+    pub older_than_days: Option<u32>,
+}
+
+/// How many entries each rule of `History::compact` removed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompactReport {
+    pub duplicates: usize,
+    pub short: usize,
+    pub old: usize,
+    /// Copy of the history file from before the compaction
+    pub backup: PathBuf,
+}
+
+impl CompactReport {
+    pub fn removed(&self) -> usize {
+        self.duplicates + self.short + self.old
+    }
 }
 
 impl HistoryEntry {
-    pub fn new(text: String) -> Self {
+    /// The entry with its text cut to `max_chars` characters
+    pub fn preview(&self, max_chars: usize) -> EntryPreview {
+        if self.secret {
+            return EntryPreview {
+                text: SECRET_MASK.to_string(),
+                timestamp: self.timestamp,
+                app: self.app.clone(),
+                title: self.title.clone(),
+                truncated: false,
+                secret: true,
+            };
+        }
+        let cut = self.text.char_indices().nth(max_chars).map(|(i, _)| i);
+        EntryPreview {
+            text: self.text[..cut.unwrap_or(self.text.len())].to_string(),
+            timestamp: self.timestamp,
+            app: self.app.clone(),
+            title: self.title.clone(),
+            truncated: cut.is_some(),
+            secret: false,
+        }
+    }
+
+    /// The text, decrypted if the entry is secret
+    pub fn plain_text(&self) -> Result<String> {
+        if self.secret {
+            crate::secrets::unprotect(&self.text)
+        } else {
+            Ok(self.text.clone())
+        }
+    }
+
+    /// Text searches look in; none for secret entries
+    fn indexed_text(&self) -> &str {
+        if self.secret {
+            ""
+        } else {
+            &self.text
+        }
+    }
+
+    pub fn new(text: String, target: &WindowInfo) -> Self {
         Self {
             text,
             timestamp: Utc::now(),
+            app: target.process_name.clone(),
+            title: target.title.clone(),
+            secret: false,
         }
     }
 }
@@ -27,94 +162,443 @@ pub struct History {
     file_path: PathBuf,
     entries: Vec<HistoryEntry>,
     max_entries: usize,
+    /// Days entries are kept; 0 keeps them
+    retention_days: u32,
+    retention_action: RetentionAction,
+    /// This machine's file in the sync folder
+    sync_file: Option<PathBuf>,
+    /// Index of `entries`; the first entry has id `index.first()`
+    index: SearchIndex,
+    /// Archived entries, loaded on the first archive search
+    archive: Option<Archive>,
+    trash: Trash,
+    /// Timestamps of the entries in the history file as of the last load or
+    /// save, to tell other instances' changes from this one's
+    on_disk: HashSet<DateTime<Utc>>,
+}
+
+/// Archived entries held in memory for searching
+struct Archive {
+    entries: Vec<HistoryEntry>,
+    index: SearchIndex,
+}
+
+impl Archive {
+    fn load(dir: &Path) -> Result<Self> {
+        let mut archive = Self {
+            entries: Vec::new(),
+            index: SearchIndex::default(),
+        };
+        if dir.exists() {
+            let mut entries = Vec::new();
+            for path in history_files(dir)? {
+                entries.extend(read_entries(&path)?);
+            }
+            entries.sort_by_key(|e| e.timestamp);
+            archive.push(entries);
+        }
+        Ok(archive)
+    }
+
+    fn push(&mut self, entries: impl IntoIterator<Item = HistoryEntry>) {
+        for entry in entries {
+            self.index.push(entry.indexed_text());
+            self.entries.push(entry);
+        }
+    }
+
+    /// Entries containing `query_lower`, most recent first
+    fn search(&self, query_lower: &str) -> Vec<HistoryEntry> {
+        let mut results: Vec<HistoryEntry> = self
+            .index
+            .search(query_lower)
+            .into_iter()
+            .map(|id| self.entries[id as usize].clone())
+            .collect();
+        results.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+        results
+    }
 }
 
 impl History {
-    /// Create a new History instance with the given file path
-    pub fn new(file_path: PathBuf, max_entries: usize) -> Result<Self, String> {
+    /// Create a new History instance with the given file path and settings,
+    /// expiring entries past `retention_days` right away
+    pub fn new(file_path: PathBuf, config: &HistoryConfig) -> Result<Self> {
         // Ensure parent directory exists
         if let Some(parent) = file_path.parent() {
             std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create history directory: {}", e))?;
+                .map_err(Error::io("Failed to create history directory"))?;
         }
+        let sync_dir = config.sync_dir().unwrap_or_else(|e| {
+            tracing::warn!("History sync disabled: {}", e);
+            None
+        });
 
         let mut history = Self {
+            trash: Trash::new(file_path.with_file_name(TRASH_FILE), config.trash_days),
             file_path,
             entries: Vec::new(),
-            max_entries,
+            max_entries: config.max_entries,
+            retention_days: config.retention_days,
+            retention_action: config.retention_action,
+            sync_file: sync_dir.map(|dir| dir.join(format!("history-{}.jsonl", machine_name()))),
+            index: SearchIndex::default(),
+            archive: None,
+            on_disk: HashSet::new(),
         };
 
         history.load()?;
+        if let Err(e) = history.trash.purge() {
+            tracing::warn!("Failed to purge trash: {}", e);
+        }
+        if let Err(e) = history.expire() {
+            tracing::warn!("Failed to expire old history entries: {}", e);
+        }
         Ok(history)
     }
 
-    /// Load history from file
-    fn load(&mut self) -> Result<(), String> {
-        if !self.file_path.exists() {
-            return Ok(());
+    /// Load history from file and merge the other machines' entries
+    fn load(&mut self) -> Result<()> {
+        if self.file_path.exists() {
+            let _lock = self.lock()?;
+            self.entries = read_entries(&self.file_path)?;
+            self.on_disk = self.entries.iter().map(|e| e.timestamp).collect();
         }
 
-        let file = File::open(&self.file_path)
-            .map_err(|e| format!("Failed to open history file: {}", e))?;
+        // An unreachable sync folder (e.g. a network drive) shouldn't stop the app
+        let merged = match self.sync_file.clone() {
+            Some(sync_file) => self.sync(&sync_file).unwrap_or_else(|e| {
+                tracing::warn!("History sync failed: {}", e);
+                false
+            }),
+            None => false,
+        };
 
-        let reader = BufReader::new(file);
-        self.entries.clear();
+        // Save merged entries, and entries archived because max_entries was
+        // lowered so they aren't archived again on the next load
+        if self.trim() || merged {
+            self.save()?;
+        }
+        self.reindex();
 
-        for line in reader.lines() {
-            let line = line.map_err(|e| format!("Failed to read line: {}", e))?;
-            if line.trim().is_empty() {
-                continue;
+        Ok(())
+    }
+
+    /// Rebuild the index after entries were inserted out of order
+    fn reindex(&mut self) {
+        self.index.clear();
+        for entry in &self.entries {
+            self.index.push(entry.indexed_text());
+        }
+    }
+
+    /// Merge the entries other machines wrote to the sync folder.
+    /// Returns whether any were added.
+    fn sync(&mut self, sync_file: &Path) -> Result<bool> {
+        let Some(dir) = sync_file.parent() else {
+            return Ok(false);
+        };
+        fs::create_dir_all(dir).map_err(Error::io("Failed to create sync folder"))?;
+
+        // First run on this machine: publish the history it already has
+        if !sync_file.exists() {
+            append_entries(sync_file, self.entries.iter().filter(|e| !e.secret))?;
+        }
+
+        // This machine's own file is skipped, so cleared entries stay cleared
+        let mut synced = Vec::new();
+        for path in history_files(dir)? {
+            if path != *sync_file {
+                synced.extend(read_entries(&path)?);
             }
+        }
+        Ok(self.merge(synced))
+    }
+
+    /// Add entries not already present (same timestamp and text).
+    /// Returns whether any were added.
+    ///
+    /// Entries too old to survive the next trim or expiry are left out, so
+    /// they don't end up in this machine's archive.
+    fn merge(&mut self, entries: Vec<HistoryEntry>) -> bool {
+        let cutoff = self.retention_cutoff();
+        let mut known: HashSet<(DateTime<Utc>, String)> = self
+            .entries
+            .iter()
+            .map(|e| (e.timestamp, e.text.clone()))
+            .collect();
+        let mut new: Vec<HistoryEntry> = entries
+            .into_iter()
+            .filter(|e| !e.text.trim().is_empty())
+            .filter(|e| cutoff.is_none_or(|cutoff| e.timestamp >= cutoff))
+            .filter(|e| known.insert((e.timestamp, e.text.clone())))
+            .collect();
 
-            match serde_json::from_str::<HistoryEntry>(&line) {
-                Ok(entry) => self.entries.push(entry),
-                Err(e) => eprintln!("Failed to parse history entry: {}", e),
+        let mut timestamps: Vec<DateTime<Utc>> = self
+            .entries
+            .iter()
+            .chain(&new)
+            .map(|e| e.timestamp)
+            .collect();
+        if timestamps.len() > self.max_entries {
+            timestamps.sort();
+            let oldest_kept = timestamps[timestamps.len() - self.max_entries];
+            new.retain(|e| e.timestamp >= oldest_kept);
+        }
+        if new.is_empty() {
+            return false;
+        }
+
+        self.entries.extend(new);
+        self.entries.sort_by_key(|e| e.timestamp);
+        true
+    }
+
+    /// Append entries to this machine's sync file, if syncing; secret entries
+    /// stay on this machine
+    fn publish(&self, entries: &[HistoryEntry]) {
+        if let Some(sync_file) = &self.sync_file {
+            if let Err(e) = append_entries(sync_file, entries.iter().filter(|e| !e.secret)) {
+                tracing::warn!("History sync failed: {}", e);
             }
         }
+    }
 
-        // Keep only the most recent entries
-        if self.entries.len() > self.max_entries {
-            self.entries.drain(0..self.entries.len() - self.max_entries);
+    /// Keep only the most recent `max_entries`, moving the rest to the archive.
+    /// Returns whether any entries were moved.
+    ///
+    /// If the archive can't be written the entries stay in the history.
+    fn trim(&mut self) -> bool {
+        if self.entries.len() <= self.max_entries {
+            return false;
         }
 
-        Ok(())
+        let excess = self.entries.len() - self.max_entries;
+        if let Err(e) = archive(&self.archive_dir(), &self.entries[..excess]) {
+            tracing::warn!("Keeping {} old history entries: {}", excess, e);
+            return false;
+        }
+        let archived = self.entries.drain(..excess);
+        match &mut self.archive {
+            Some(loaded) => loaded.push(archived),
+            None => drop(archived),
+        }
+        self.index.remove_front(excess);
+        true
     }
 
-    /// Save history to file
-    fn save(&self) -> Result<(), String> {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&self.file_path)
-            .map_err(|e| format!("Failed to open history file for writing: {}", e))?;
+    /// Entries written before this are past `retention_days`
+    fn retention_cutoff(&self) -> Option<DateTime<Utc>> {
+        (self.retention_days > 0).then(|| Utc::now() - Duration::days(self.retention_days.into()))
+    }
 
-        for entry in &self.entries {
-            let json = serde_json::to_string(entry)
-                .map_err(|e| format!("Failed to serialize entry: {}", e))?;
-            writeln!(file, "{}", json).map_err(|e| format!("Failed to write entry: {}", e))?;
+    /// Take entries past `retention_days` out of the history, by
+    /// `retention_action`. Returns the number taken out.
+    ///
+    /// Deleting also drops them from the archive, the trash and this machine's
+    /// sync file; other machines' sync files are theirs to expire.
+    pub fn expire(&mut self) -> Result<usize> {
+        let Some(cutoff) = self.retention_cutoff() else {
+            return Ok(0);
+        };
+        let expired = self.entries.partition_point(|e| e.timestamp < cutoff);
+
+        match self.retention_action {
+            RetentionAction::Archive => {
+                if expired == 0 {
+                    return Ok(0);
+                }
+                archive(&self.archive_dir(), &self.entries[..expired])?;
+                let archived = self.entries.drain(..expired);
+                match &mut self.archive {
+                    Some(loaded) => loaded.push(archived),
+                    None => drop(archived),
+                }
+            }
+            RetentionAction::Delete => {
+                let dir = self.archive_dir();
+                let mut files = if dir.exists() {
+                    history_files(&dir)?
+                } else {
+                    Vec::new()
+                };
+                files.extend(self.sync_file.clone().filter(|path| path.exists()));
+                for path in files {
+                    retain_entries(&path, |e| e.timestamp >= cutoff)?;
+                }
+                self.trash.remove_before(cutoff)?;
+                // Reloaded without the deleted entries on the next archive search
+                self.archive = None;
+                // Last, so a failure above leaves the entries and the index matching
+                self.entries.drain(..expired);
+            }
+        }
+
+        if expired > 0 {
+            self.index.remove_front(expired);
+            self.save()?;
+            tracing::info!("Expired {} history entries", expired);
         }
+        Ok(expired)
+    }
+
+    /// Save history to file, keeping what other instances changed in it
+    ///
+    /// Entries that appeared in the file since the last load or save are
+    /// added, and entries that disappeared from it (cleared, trimmed or
+    /// expired elsewhere) are dropped, and entries made secret elsewhere stay
+    /// secret, before the file is rewritten.
+    fn save(&mut self) -> Result<()> {
+        let _lock = self.lock()?;
+        if self.file_path.exists() {
+            let current = read_entries(&self.file_path)?;
+            let mut made_secret = false;
+            for entry in self.entries.iter_mut().filter(|e| !e.secret) {
+                if let Some(secret) = current
+                    .iter()
+                    .find(|e| e.secret && e.timestamp == entry.timestamp)
+                {
+                    *entry = secret.clone();
+                    made_secret = true;
+                }
+            }
+            if made_secret {
+                self.reindex();
+            }
+            let current_ids: HashSet<_> = current.iter().map(|e| e.timestamp).collect();
+            let on_disk = &self.on_disk;
+            let before = self.entries.len();
+            self.entries
+                .retain(|e| !on_disk.contains(&e.timestamp) || current_ids.contains(&e.timestamp));
+            let removed = before - self.entries.len();
 
+            let known: HashSet<_> = self.entries.iter().map(|e| e.timestamp).collect();
+            let added: Vec<HistoryEntry> = current
+                .into_iter()
+                .filter(|e| !on_disk.contains(&e.timestamp) && !known.contains(&e.timestamp))
+                .collect();
+            if removed > 0 || !added.is_empty() {
+                tracing::info!(
+                    "Merged changes from another instance: {} added, {} removed",
+                    added.len(),
+                    removed
+                );
+                self.entries.extend(added);
+                self.entries.sort_by_key(|e| e.timestamp);
+                self.trim();
+                self.reindex();
+            }
+        }
+
+        write_entries(&self.file_path, &self.entries)?;
+        self.on_disk = self.entries.iter().map(|e| e.timestamp).collect();
         Ok(())
     }
 
-    /// Add a new entry to history
-    pub fn add(&mut self, text: String) -> Result<(), String> {
+    /// Lock the history file against other instances until the returned
+    /// file is dropped
+    fn lock(&self) -> Result<File> {
+        let mut path = self.file_path.clone().into_os_string();
+        path.push(".lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(PathBuf::from(path))
+            .map_err(Error::io("Failed to open history lock file"))?;
+        file.lock()
+            .map_err(Error::io("Failed to lock history file"))?;
+        Ok(file)
+    }
+
+    /// Add a new entry to history, noting the window it went to
+    pub fn add(&mut self, text: String, target: &WindowInfo) -> Result<()> {
         if text.trim().is_empty() {
             return Ok(());
         }
 
-        let entry = HistoryEntry::new(text);
+        let entry = HistoryEntry::new(text, target);
+        self.publish(std::slice::from_ref(&entry));
+        self.index.push(&entry.text);
         self.entries.push(entry);
+        self.trim();
 
-        // Trim old entries if exceeding max
-        if self.entries.len() > self.max_entries {
-            self.entries.drain(0..self.entries.len() - self.max_entries);
+        self.save()
+    }
+
+    /// Add a new secret entry, encrypted and kept on this machine
+    pub fn add_secret(&mut self, text: String, target: &WindowInfo) -> Result<()> {
+        if text.trim().is_empty() {
+            return Ok(());
         }
 
+        let mut entry = HistoryEntry::new(crate::secrets::protect(&text)?, target);
+        entry.secret = true;
+        self.index.push(entry.indexed_text());
+        self.entries.push(entry);
+        self.trim();
+
         self.save()
     }
 
+    /// Encrypt the entry with timestamp `id` and take it out of this
+    /// machine's sync file. Returns false if it already was secret.
+    pub fn mark_secret(&mut self, id: DateTime<Utc>) -> Result<bool> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|e| e.timestamp == id)
+            .ok_or_else(|| Error::NotFound("History entry not found".to_string()))?;
+        if entry.secret {
+            return Ok(false);
+        }
+        entry.text = crate::secrets::protect(&entry.text)?;
+        entry.secret = true;
+        self.reindex();
+
+        if let Some(sync_file) = self.sync_file.clone().filter(|path| path.exists()) {
+            if let Err(e) = retain_entries(&sync_file, |e| e.timestamp != id) {
+                tracing::warn!("Failed to remove secret entry from sync folder: {}", e);
+            }
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Merge entries from another source, skipping ones already present.
+    /// Returns the number of entries added.
+    pub fn import(&mut self, entries: Vec<HistoryEntry>) -> Result<usize> {
+        let added = self.insert(entries)?;
+        self.publish(&added);
+        Ok(added.len())
+    }
+
+    /// Add entries not already present in timestamp order and save.
+    /// Returns the entries added.
+    fn insert(&mut self, entries: Vec<HistoryEntry>) -> Result<Vec<HistoryEntry>> {
+        let mut added = Vec::new();
+        for entry in entries {
+            if entry.text.trim().is_empty() {
+                continue;
+            }
+            let exists = self
+                .entries
+                .iter()
+                .any(|e| e.timestamp == entry.timestamp && e.text == entry.text);
+            if !exists {
+                added.push(entry.clone());
+                self.entries.push(entry);
+            }
+        }
+
+        self.entries.sort_by_key(|e| e.timestamp);
+        self.trim();
+        self.reindex();
+
+        self.save()?;
+        Ok(added)
+    }
+
     /// Get all entries (most recent first)
     pub fn entries(&self) -> Vec<HistoryEntry> {
         let mut entries = self.entries.clone();
@@ -122,17 +606,43 @@ impl History {
         entries
     }
 
+    /// Number of stored entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Path of the history file
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Directory of the monthly archive files
+    pub fn archive_dir(&self) -> PathBuf {
+        self.file_path.with_file_name(ARCHIVE_DIR)
+    }
+
+    /// Find an entry by its timestamp, which the UI uses as its id
+    pub fn find(&self, timestamp: DateTime<Utc>) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|e| e.timestamp == timestamp)
+    }
+
+    /// Get the most recent entry
+    pub fn latest(&self) -> Option<&HistoryEntry> {
+        self.entries.last()
+    }
+
     /// Search history entries by text
     pub fn search(&self, query: &str) -> Vec<HistoryEntry> {
         if query.trim().is_empty() {
             return self.entries();
         }
 
-        let query_lower = query.to_lowercase();
+        let first = self.index.first();
         let mut results: Vec<_> = self
-            .entries
-            .iter()
-            .filter(|e| e.text.to_lowercase().contains(&query_lower))
+            .index
+            .search(&query.to_lowercase())
+            .into_iter()
+            .filter_map(|id| self.entries.get((id - first) as usize))
             .cloned()
             .collect();
 
@@ -140,17 +650,250 @@ impl History {
         results
     }
 
-    /// Clear all history entries
-    pub fn clear(&mut self) -> Result<(), String> {
+    /// Search the archived entries (most recent first); an empty query finds nothing
+    ///
+    /// The first search reads the whole archive into memory.
+    pub fn search_archive(&mut self, query: &str) -> Result<Vec<HistoryEntry>> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let archive = match &mut self.archive {
+            Some(archive) => archive,
+            None => self.archive.insert(Archive::load(&self.archive_dir())?),
+        };
+        Ok(archive.search(&query.to_lowercase()))
+    }
+
+    /// Move all history entries to the trash (the archive is kept)
+    pub fn clear(&mut self) -> Result<()> {
+        self.trash.add(&self.entries)?;
         self.entries.clear();
+        self.reindex();
         self.save()
     }
 
+    /// Move entries matching `options` to the trash, after copying the
+    /// history file to `history.jsonl.bak`
+    ///
+    /// An entry matching several rules is counted under the first of age,
+    /// length and duplication. Of duplicates, the most recent is kept. The
+    /// archive is left alone.
+    pub fn compact(&mut self, options: &CompactOptions) -> Result<CompactReport> {
+        let mut backup = self.file_path.clone().into_os_string();
+        backup.push(".bak");
+        let backup = PathBuf::from(backup);
+        if self.file_path.exists() {
+            fs::copy(&self.file_path, &backup)
+                .map_err(Error::io("Failed to back up history file"))?;
+        }
+
+        let cutoff = options
+            .older_than_days
+            .map(|days| Utc::now() - Duration::days(days.into()));
+        let mut report = CompactReport {
+            backup,
+            ..Default::default()
+        };
+        let mut seen = HashSet::new();
+        // Newest first, so the latest of the duplicates is the one kept
+        let (mut kept, mut removed) = (Vec::with_capacity(self.entries.len()), Vec::new());
+        for entry in self.entries.iter().rev() {
+            if cutoff.is_some_and(|cutoff| entry.timestamp < cutoff) {
+                report.old += 1;
+            } else if options
+                .min_chars
+                .is_some_and(|min| entry.text.trim().chars().count() < min)
+            {
+                report.short += 1;
+            } else if options.duplicates && !seen.insert(&entry.text) {
+                report.duplicates += 1;
+            } else {
+                kept.push(entry.clone());
+                continue;
+            }
+            removed.push(entry.clone());
+        }
+
+        if report.removed() > 0 {
+            self.trash.add(&removed)?;
+            kept.reverse();
+            self.entries = kept;
+            self.reindex();
+            self.save()?;
+        }
+        Ok(report)
+    }
+
+    /// Entries in the trash, most recently deleted first
+    pub fn trash_entries(&self) -> Result<Vec<Trashed<HistoryEntry>>> {
+        let mut trashed = self.trash.entries()?;
+        trashed.reverse();
+        Ok(trashed)
+    }
+
+    /// Put the trashed entry with timestamp `id` back, or every trashed entry
+    /// without one. Returns the number restored.
+    pub fn restore_from_trash(&mut self, id: Option<DateTime<Utc>>) -> Result<usize> {
+        let entries: Vec<HistoryEntry> = self
+            .trash
+            .entries()?
+            .into_iter()
+            .map(|trashed| trashed.entry)
+            .filter(|entry| id.is_none_or(|id| entry.timestamp == id))
+            .collect();
+        // Already in this machine's sync file, so not published again
+        let restored = self.insert(entries)?.len();
+        self.trash.remove(id)?;
+        Ok(restored)
+    }
+
+    pub fn empty_trash(&self) -> Result<()> {
+        self.trash.empty()
+    }
+
     /// Get the default history file path
-    pub fn default_path() -> Result<PathBuf, String> {
-        let config_dir = directories::ProjectDirs::from("com", "prompt-line", "prompt-line-rs")
-            .ok_or_else(|| "Failed to get config directory".to_string())?;
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(crate::paths::data_dir()?.join("history.jsonl"))
+    }
+}
+
+/// Read a JSONL history file, skipping lines that don't parse
+fn read_entries(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let file = File::open(path).map_err(Error::io("Failed to open history file"))?;
+
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(Error::io("Failed to read history file"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str::<HistoryEntry>(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => tracing::warn!("Failed to parse history entry: {}", e),
+        }
+    }
+    Ok(entries)
+}
+
+/// Replace the contents of a JSONL history file
+fn write_entries(path: &Path, entries: &[HistoryEntry]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .map_err(Error::io("Failed to open history file for writing"))?;
+
+    for entry in entries {
+        let json = serde_json::to_string(entry)
+            .map_err(|e| Error::Config(format!("Failed to serialize entry: {}", e)))?;
+        writeln!(file, "{}", json).map_err(Error::io("Failed to write history entry"))?;
+    }
+
+    Ok(())
+}
+
+/// Keep only the entries of a JSONL history file matching `keep`, deleting
+/// the file if none are left
+fn retain_entries(path: &Path, keep: impl Fn(&HistoryEntry) -> bool) -> Result<()> {
+    let entries = read_entries(path)?;
+    let kept: Vec<HistoryEntry> = entries.iter().filter(|e| keep(e)).cloned().collect();
+    if kept.len() == entries.len() {
+        return Ok(());
+    }
+    if kept.is_empty() {
+        return fs::remove_file(path).map_err(Error::io("Failed to delete history file"));
+    }
+    write_entries(path, &kept)
+}
+
+/// Append entries to a JSONL history file in a single write
+fn append_entries<'a>(
+    path: &Path,
+    entries: impl IntoIterator<Item = &'a HistoryEntry>,
+) -> Result<()> {
+    let mut lines = String::new();
+    for entry in entries {
+        let json = serde_json::to_string(entry)
+            .map_err(|e| Error::Config(format!("Failed to serialize entry: {}", e)))?;
+        lines.push_str(&json);
+        lines.push('\n');
+    }
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(Error::io("Failed to append history entries"))
+}
+
+/// Append entries to the archive file of the (local) month they were written
+fn archive(dir: &Path, entries: &[HistoryEntry]) -> Result<()> {
+    fs::create_dir_all(dir).map_err(Error::io("Failed to create history archive"))?;
+
+    let mut by_month: BTreeMap<String, Vec<&HistoryEntry>> = BTreeMap::new();
+    for entry in entries {
+        let month = entry.timestamp.with_timezone(&Local).format("%Y-%m");
+        by_month
+            .entry(format!("history-{}.jsonl", month))
+            .or_default()
+            .push(entry);
+    }
+
+    for (name, entries) in by_month {
+        append_entries(&dir.join(name), entries)?;
+    }
+    Ok(())
+}
+
+/// `history-*.jsonl` files in a directory, sorted by name
+fn history_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(Error::io("Failed to read history folder"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("history-") && name.ends_with(".jsonl"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Name of this machine's sync file, made safe for file names
+fn machine_name() -> String {
+    let name = std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_default();
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "local".to_string()
+    } else {
+        name
+    }
+}
 
-        Ok(config_dir.data_dir().join("history.jsonl"))
+/// Find an archived entry by its timestamp; it is in the file of its month
+pub fn find_archived(dir: &Path, timestamp: DateTime<Utc>) -> Result<Option<HistoryEntry>> {
+    let month = timestamp.with_timezone(&Local).format("%Y-%m");
+    let path = dir.join(format!("history-{}.jsonl", month));
+    if !path.exists() {
+        return Ok(None);
     }
+    Ok(read_entries(&path)?
+        .into_iter()
+        .find(|e| e.timestamp == timestamp))
 }
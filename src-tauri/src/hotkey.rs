@@ -0,0 +1,637 @@
+//! Global hotkey registration
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+
+use crate::error::{Error, Result};
+use crate::lock::{MutexExt, RwLockExt};
+use crate::AppState;
+
+/// Launch shortcut in effect, reported to the UI via `effective-shortcut-changed`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EffectiveShortcut {
+    /// Registered shortcut, `None` if no shortcut could be registered
+    pub shortcut: Option<String>,
+    /// Shortcut from config
+    pub configured: String,
+    /// Whether a fallback was registered instead of the configured shortcut
+    pub is_fallback: bool,
+}
+
+/// Why global hotkeys are currently paused
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HotkeyPause {
+    /// Paused from the tray or `set_hotkeys_paused`
+    pub manual: bool,
+    /// Paused because a fullscreen app is in front
+    pub auto: bool,
+}
+
+impl HotkeyPause {
+    pub fn is_paused(&self) -> bool {
+        self.manual || self.auto
+    }
+}
+
+/// Action triggered by a global shortcut
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShortcutAction {
+    /// Show/hide the main window
+    Toggle,
+    /// Paste the most recent history entry into the focused app
+    PasteLast,
+    /// Paste the next item of the paste queue into the focused app
+    PasteNext,
+    /// Restore the clipboard from before the last paste (and undo it in the app)
+    UndoPaste,
+    /// Open the settings window
+    ShowSettings,
+    /// Flip the voice input toggle
+    ToggleVoice,
+    /// Record while held and transcribe locally with Whisper
+    PushToTalk,
+    /// Open the history picker to paste an entry into the focused app
+    ShowHistoryPicker,
+    /// Paste the named prompt template into the focused app
+    Snippet(String),
+}
+
+impl ShortcutAction {
+    /// Parse an action name as used in `[shortcuts.global]` and `tray_left_click`
+    pub fn parse(name: &str) -> Option<Self> {
+        if let Some((kind, template)) = name.split_once(':') {
+            let template = template.trim();
+            return (kind.trim().eq_ignore_ascii_case("snippet") && !template.is_empty())
+                .then(|| Self::Snippet(template.to_string()));
+        }
+        match name.trim().to_lowercase().as_str() {
+            "toggle" => Some(Self::Toggle),
+            "paste_last" => Some(Self::PasteLast),
+            "paste_next" => Some(Self::PasteNext),
+            "undo_paste" => Some(Self::UndoPaste),
+            "show_settings" | "settings" => Some(Self::ShowSettings),
+            "toggle_voice" => Some(Self::ToggleVoice),
+            "push_to_talk" => Some(Self::PushToTalk),
+            "show_history_picker" | "history_picker" => Some(Self::ShowHistoryPicker),
+            _ => None,
+        }
+    }
+}
+
+/// Shortcuts tried in order when the configured launch shortcut can't be registered
+const FALLBACK_SHORTCUTS: &[&str] = &[
+    "Ctrl+Shift+Space",
+    "Win+Shift+Space",
+    "Alt+Space",
+    "Ctrl+Alt+P",
+];
+
+/// Key names accepted in shortcut strings, in display form, with the Windows
+/// virtual-key code sent for them
+///
+/// The settings UI gets this list via `get_shortcut_keys`, so both sides agree on
+/// spelling. Matching is case-insensitive.
+const KEY_CODES: &[(&str, Code, u16)] = &[
+    ("A", Code::KeyA, 0x41),
+    ("B", Code::KeyB, 0x42),
+    ("C", Code::KeyC, 0x43),
+    ("D", Code::KeyD, 0x44),
+    ("E", Code::KeyE, 0x45),
+    ("F", Code::KeyF, 0x46),
+    ("G", Code::KeyG, 0x47),
+    ("H", Code::KeyH, 0x48),
+    ("I", Code::KeyI, 0x49),
+    ("J", Code::KeyJ, 0x4A),
+    ("K", Code::KeyK, 0x4B),
+    ("L", Code::KeyL, 0x4C),
+    ("M", Code::KeyM, 0x4D),
+    ("N", Code::KeyN, 0x4E),
+    ("O", Code::KeyO, 0x4F),
+    ("P", Code::KeyP, 0x50),
+    ("Q", Code::KeyQ, 0x51),
+    ("R", Code::KeyR, 0x52),
+    ("S", Code::KeyS, 0x53),
+    ("T", Code::KeyT, 0x54),
+    ("U", Code::KeyU, 0x55),
+    ("V", Code::KeyV, 0x56),
+    ("W", Code::KeyW, 0x57),
+    ("X", Code::KeyX, 0x58),
+    ("Y", Code::KeyY, 0x59),
+    ("Z", Code::KeyZ, 0x5A),
+    ("0", Code::Digit0, 0x30),
+    ("1", Code::Digit1, 0x31),
+    ("2", Code::Digit2, 0x32),
+    ("3", Code::Digit3, 0x33),
+    ("4", Code::Digit4, 0x34),
+    ("5", Code::Digit5, 0x35),
+    ("6", Code::Digit6, 0x36),
+    ("7", Code::Digit7, 0x37),
+    ("8", Code::Digit8, 0x38),
+    ("9", Code::Digit9, 0x39),
+    ("F1", Code::F1, 0x70),
+    ("F2", Code::F2, 0x71),
+    ("F3", Code::F3, 0x72),
+    ("F4", Code::F4, 0x73),
+    ("F5", Code::F5, 0x74),
+    ("F6", Code::F6, 0x75),
+    ("F7", Code::F7, 0x76),
+    ("F8", Code::F8, 0x77),
+    ("F9", Code::F9, 0x78),
+    ("F10", Code::F10, 0x79),
+    ("F11", Code::F11, 0x7A),
+    ("F12", Code::F12, 0x7B),
+    ("F13", Code::F13, 0x7C),
+    ("F14", Code::F14, 0x7D),
+    ("F15", Code::F15, 0x7E),
+    ("F16", Code::F16, 0x7F),
+    ("F17", Code::F17, 0x80),
+    ("F18", Code::F18, 0x81),
+    ("F19", Code::F19, 0x82),
+    ("F20", Code::F20, 0x83),
+    ("F21", Code::F21, 0x84),
+    ("F22", Code::F22, 0x85),
+    ("F23", Code::F23, 0x86),
+    ("F24", Code::F24, 0x87),
+    // Whitespace and editing
+    ("Space", Code::Space, 0x20),
+    ("Enter", Code::Enter, 0x0D),
+    ("Escape", Code::Escape, 0x1B),
+    ("Tab", Code::Tab, 0x09),
+    ("Backspace", Code::Backspace, 0x08),
+    ("Delete", Code::Delete, 0x2E),
+    ("Insert", Code::Insert, 0x2D),
+    // Navigation
+    ("Home", Code::Home, 0x24),
+    ("End", Code::End, 0x23),
+    ("PageUp", Code::PageUp, 0x21),
+    ("PageDown", Code::PageDown, 0x22),
+    ("Up", Code::ArrowUp, 0x26),
+    ("Down", Code::ArrowDown, 0x28),
+    ("Left", Code::ArrowLeft, 0x25),
+    ("Right", Code::ArrowRight, 0x27),
+    // Numpad
+    ("Num0", Code::Numpad0, 0x60),
+    ("Num1", Code::Numpad1, 0x61),
+    ("Num2", Code::Numpad2, 0x62),
+    ("Num3", Code::Numpad3, 0x63),
+    ("Num4", Code::Numpad4, 0x64),
+    ("Num5", Code::Numpad5, 0x65),
+    ("Num6", Code::Numpad6, 0x66),
+    ("Num7", Code::Numpad7, 0x67),
+    ("Num8", Code::Numpad8, 0x68),
+    ("Num9", Code::Numpad9, 0x69),
+    ("NumAdd", Code::NumpadAdd, 0x6B),
+    ("NumSubtract", Code::NumpadSubtract, 0x6D),
+    ("NumMultiply", Code::NumpadMultiply, 0x6A),
+    ("NumDivide", Code::NumpadDivide, 0x6F),
+    ("NumDecimal", Code::NumpadDecimal, 0x6E),
+    ("NumEnter", Code::NumpadEnter, 0x0D),
+    // Punctuation (US layout positions)
+    ("-", Code::Minus, 0xBD),
+    ("=", Code::Equal, 0xBB),
+    ("[", Code::BracketLeft, 0xDB),
+    ("]", Code::BracketRight, 0xDD),
+    ("\\", Code::Backslash, 0xDC),
+    (";", Code::Semicolon, 0xBA),
+    ("'", Code::Quote, 0xDE),
+    ("`", Code::Backquote, 0xC0),
+    (",", Code::Comma, 0xBC),
+    (".", Code::Period, 0xBE),
+    ("/", Code::Slash, 0xBF),
+    // Misc
+    ("PrintScreen", Code::PrintScreen, 0x2C),
+    ("Pause", Code::Pause, 0x13),
+    ("ScrollLock", Code::ScrollLock, 0x91),
+];
+
+/// Alternative spellings mapped to their `KEY_CODES` name
+const KEY_ALIASES: &[(&str, &str)] = &[
+    ("return", "Enter"),
+    ("esc", "Escape"),
+    ("del", "Delete"),
+    ("ins", "Insert"),
+    ("pgup", "PageUp"),
+    ("pgdn", "PageDown"),
+    ("arrowup", "Up"),
+    ("arrowdown", "Down"),
+    ("arrowleft", "Left"),
+    ("arrowright", "Right"),
+    ("minus", "-"),
+    ("equal", "="),
+    ("comma", ","),
+    ("period", "."),
+    ("slash", "/"),
+    ("backslash", "\\"),
+    ("semicolon", ";"),
+    ("quote", "'"),
+    ("backquote", "`"),
+    ("prtsc", "PrintScreen"),
+];
+
+/// Look up a key name (or alias) case-insensitively
+fn key_code(name: &str) -> Option<Code> {
+    let name = KEY_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map(|(_, canonical)| *canonical)
+        .unwrap_or(name);
+
+    // "numpad0".."numpad9" etc. are accepted as the long form of "Num0".."Num9"
+    let name = match name.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("numpad") => format!("Num{}", &name[6..]),
+        _ => name.to_string(),
+    };
+
+    KEY_CODES
+        .iter()
+        .find(|(key, _, _)| key.eq_ignore_ascii_case(&name))
+        .map(|(_, code, _)| *code)
+}
+
+/// Display name of a key code
+fn key_name(code: Code) -> Option<&'static str> {
+    KEY_CODES
+        .iter()
+        .find(|(_, c, _)| *c == code)
+        .map(|(name, _, _)| *name)
+}
+
+/// Windows virtual-key code that types a key code
+#[cfg(windows)]
+pub fn virtual_key(code: Code) -> Option<u16> {
+    KEY_CODES
+        .iter()
+        .find(|(_, c, _)| *c == code)
+        .map(|(_, _, vk)| *vk)
+}
+
+/// All key names accepted in shortcut strings
+pub fn key_names() -> Vec<&'static str> {
+    KEY_CODES.iter().map(|(name, _, _)| *name).collect()
+}
+
+/// Parse a shortcut string like "Ctrl+Shift+Space" into Modifiers and Code
+///
+/// Returns `None` for unknown key names or when zero or several keys are given.
+pub fn parse_shortcut(shortcut_str: &str) -> Option<(Option<Modifiers>, Code)> {
+    check_shortcut(shortcut_str).ok()
+}
+
+/// Parse a shortcut string, explaining what is wrong with invalid ones
+pub fn check_shortcut(shortcut_str: &str) -> Result<(Option<Modifiers>, Code)> {
+    let mut modifiers = Modifiers::empty();
+    let mut key_code_found = None;
+
+    for part in shortcut_str.split('+').map(|s| s.trim()) {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "control" => modifiers |= Modifiers::CONTROL,
+            "shift" => modifiers |= Modifiers::SHIFT,
+            "alt" => modifiers |= Modifiers::ALT,
+            "win" | "super" | "meta" | "cmd" | "command" => modifiers |= Modifiers::SUPER,
+            "" => {
+                return Err(Error::Shortcut(format!(
+                    "Empty key in shortcut '{}'",
+                    shortcut_str
+                )))
+            }
+            _ => {
+                if key_code_found.is_some() {
+                    return Err(Error::Shortcut(format!(
+                        "Shortcut '{}' has more than one key; use modifiers plus a single key",
+                        shortcut_str
+                    )));
+                }
+                let code = key_code(part)
+                    .ok_or_else(|| Error::Shortcut(format!("Unknown key '{}'", part)))?;
+                key_code_found = Some(code);
+            }
+        }
+    }
+
+    let code = key_code_found
+        .ok_or_else(|| Error::Shortcut(format!("Shortcut '{}' has no key", shortcut_str)))?;
+    let mods = if modifiers.is_empty() {
+        None
+    } else {
+        Some(modifiers)
+    };
+    Ok((mods, code))
+}
+
+/// Format modifiers and key in canonical display form ("Win+Ctrl+Alt+Shift+Key")
+pub fn format_shortcut(modifiers: Option<Modifiers>, code: Code) -> Option<String> {
+    let modifiers = modifiers.unwrap_or_else(Modifiers::empty);
+    let mut parts = Vec::new();
+    if modifiers.contains(Modifiers::SUPER) {
+        parts.push("Win");
+    }
+    if modifiers.contains(Modifiers::CONTROL) {
+        parts.push("Ctrl");
+    }
+    if modifiers.contains(Modifiers::ALT) {
+        parts.push("Alt");
+    }
+    if modifiers.contains(Modifiers::SHIFT) {
+        parts.push("Shift");
+    }
+    parts.push(key_name(code)?);
+    Some(parts.join("+"))
+}
+
+/// Normalize a shortcut string to its canonical display form
+pub fn normalize_shortcut(shortcut_str: &str) -> Result<String> {
+    let (modifiers, code) = check_shortcut(shortcut_str)?;
+    format_shortcut(modifiers, code).ok_or_else(|| invalid_shortcut(shortcut_str))
+}
+
+/// Check whether a shortcut can be registered right now
+///
+/// Shortcuts already registered by this app count as available. Others are
+/// registered and immediately unregistered to probe for conflicts.
+pub fn is_shortcut_available(app: &AppHandle, shortcut_str: &str) -> Result<bool> {
+    let (modifiers, code) = check_shortcut(shortcut_str)?;
+    let shortcut = Shortcut::new(modifiers, code);
+
+    let global_shortcut = app.global_shortcut();
+    if global_shortcut.is_registered(shortcut) {
+        return Ok(true);
+    }
+
+    match global_shortcut.register(shortcut) {
+        Ok(()) => {
+            let _ = global_shortcut.unregister(shortcut);
+            Ok(true)
+        }
+        Err(_) => Ok(false),
+    }
+}
+
+fn invalid_shortcut(shortcut_str: &str) -> Error {
+    Error::Shortcut(format!("Invalid shortcut: {}", shortcut_str))
+}
+
+/// Parse a shortcut string into a registrable `Shortcut`
+pub fn to_shortcut(shortcut_str: &str) -> Option<Shortcut> {
+    parse_shortcut(shortcut_str).map(|(modifiers, code)| Shortcut::new(modifiers, code))
+}
+
+/// Run the action bound to a global shortcut
+///
+/// Actions run on press, except push-to-talk and the launch toggle in
+/// hold-to-show mode, which also react to release.
+pub fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut, key_state: ShortcutState) {
+    let state = app.state::<AppState>();
+    let action = state.shortcut_actions.locked().get(shortcut).cloned();
+    let Some(action) = action else {
+        return;
+    };
+
+    let hold_to_show = state.config.read_locked().behavior.hold_to_show;
+    if hold_to_show && matches!(action, ShortcutAction::Toggle) {
+        crate::hold_launch(app, key_state == ShortcutState::Pressed);
+    } else if action == ShortcutAction::PushToTalk {
+        crate::speech::push_to_talk(app, key_state == ShortcutState::Pressed);
+    } else if key_state == ShortcutState::Pressed {
+        run_action(app, &action);
+    }
+}
+
+/// Run a shortcut action
+pub fn run_action(app: &AppHandle, action: &ShortcutAction) {
+    crate::crash::breadcrumb(&format!("{:?}", action));
+    match action {
+        ShortcutAction::Toggle => crate::toggle_window(app),
+        ShortcutAction::PasteLast => crate::paste_last_entry(app),
+        ShortcutAction::PasteNext => crate::paste_next_queued(app),
+        ShortcutAction::UndoPaste => crate::undo_last_paste_from_hotkey(app),
+        ShortcutAction::ShowSettings => crate::show_settings_window(app),
+        ShortcutAction::ToggleVoice => crate::toggle_voice(app),
+        ShortcutAction::PushToTalk => crate::speech::toggle(app),
+        ShortcutAction::ShowHistoryPicker => crate::show_history_picker(app),
+        ShortcutAction::Snippet(name) => crate::paste_snippet(app, name),
+    }
+}
+
+/// Register a global shortcut bound to an action
+///
+/// While hotkeys are paused the binding is only recorded and registered on resume.
+fn register_action(
+    app: &AppHandle,
+    shortcut_str: &str,
+    action: ShortcutAction,
+) -> Result<Shortcut> {
+    let shortcut = to_shortcut(shortcut_str).ok_or_else(|| invalid_shortcut(shortcut_str))?;
+
+    let paused = app.state::<AppState>().hotkey_pause.locked().is_paused();
+    if !paused {
+        app.global_shortcut().register(shortcut).map_err(|e| {
+            Error::Shortcut(format!(
+                "Failed to register shortcut '{}': {}",
+                shortcut_str, e
+            ))
+        })?;
+    }
+
+    app.state::<AppState>()
+        .shortcut_actions
+        .locked()
+        .insert(shortcut, action);
+    Ok(shortcut)
+}
+
+/// Unregister a global shortcut and drop its action
+fn unregister_action(app: &AppHandle, shortcut: Shortcut) {
+    let _ = app.global_shortcut().unregister(shortcut);
+    app.state::<AppState>()
+        .shortcut_actions
+        .locked()
+        .remove(&shortcut);
+}
+
+/// Register the additional `[shortcuts.global]` hotkeys, replacing previous ones
+///
+/// Returns an error listing the entries that could not be registered.
+pub fn register_global_shortcuts(app: &AppHandle, global: &BTreeMap<String, String>) -> Result<()> {
+    let state = app.state::<AppState>();
+    let launch_shortcut = *state.launch_shortcut.locked();
+
+    // Drop everything except the launch shortcut
+    let previous: Vec<Shortcut> = state
+        .shortcut_actions
+        .locked()
+        .keys()
+        .copied()
+        .filter(|s| Some(*s) != launch_shortcut)
+        .collect();
+    for shortcut in previous {
+        unregister_action(app, shortcut);
+    }
+
+    let mut errors = Vec::new();
+    for (shortcut_str, action_name) in global {
+        let Some(action) = ShortcutAction::parse(action_name) else {
+            errors.push(format!(
+                "Unknown action '{}' for {}",
+                action_name, shortcut_str
+            ));
+            continue;
+        };
+        if to_shortcut(shortcut_str).is_some_and(|shortcut| Some(shortcut) == launch_shortcut) {
+            errors.push(format!("{} is already the launch shortcut", shortcut_str));
+            continue;
+        }
+        match register_action(app, shortcut_str, action) {
+            Ok(_) => tracing::info!("Registered hotkey: {} ({})", shortcut_str, action_name),
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Shortcut(errors.join("; ")))
+    }
+}
+
+/// Register the launch shortcut, trying the fallbacks if it is unavailable
+///
+/// Returns the name of the shortcut that was registered.
+pub fn register_launch_with_fallback(app: &AppHandle, launch_shortcut: &str) -> Option<String> {
+    let registered = if register_launch_shortcut(app, launch_shortcut).is_ok() {
+        tracing::info!("Registered hotkey: {}", launch_shortcut);
+        Some(launch_shortcut.to_string())
+    } else {
+        let fallback = FALLBACK_SHORTCUTS
+            .iter()
+            .find(|name| register_launch_shortcut(app, name).is_ok());
+        match fallback {
+            Some(name) => crate::notify::info(
+                app,
+                &crate::i18n::tr(
+                    "notify.hotkey_fallback",
+                    &[("shortcut", launch_shortcut), ("fallback", name)],
+                ),
+            ),
+            None => crate::notify::error(app, &crate::i18n::t("notify.no_hotkey")),
+        }
+        fallback.map(|name| name.to_string())
+    };
+
+    set_effective_shortcut(app, registered.clone(), launch_shortcut);
+    registered
+}
+
+/// Record the launch shortcut in effect and tell the tray and UI
+fn set_effective_shortcut(app: &AppHandle, registered: Option<String>, configured: &str) {
+    let effective = EffectiveShortcut {
+        is_fallback: registered.as_deref().is_some_and(|s| s != configured),
+        shortcut: registered,
+        configured: configured.to_string(),
+    };
+
+    crate::tray::set_launch_shortcut(app, effective.shortcut.as_deref());
+    let _ = app.emit("effective-shortcut-changed", &effective);
+    *app.state::<AppState>().effective_shortcut.locked() = effective;
+}
+
+/// Register a launch shortcut and remember it in `AppState`
+fn register_launch_shortcut(app: &AppHandle, shortcut_str: &str) -> Result<()> {
+    let shortcut = register_action(app, shortcut_str, ShortcutAction::Toggle)?;
+    *app.state::<AppState>().launch_shortcut.locked() = Some(shortcut);
+    Ok(())
+}
+
+/// Replace the registered launch shortcut, restoring the old one on failure
+pub fn change_launch_shortcut(app: &AppHandle, shortcut_str: &str) -> Result<()> {
+    let shortcut = to_shortcut(shortcut_str).ok_or_else(|| invalid_shortcut(shortcut_str))?;
+
+    let state = app.state::<AppState>();
+    let old_shortcut = *state.launch_shortcut.locked();
+    if old_shortcut == Some(shortcut) {
+        return Ok(());
+    }
+
+    if let Some(old) = old_shortcut {
+        unregister_action(app, old);
+    }
+
+    if let Err(e) = register_action(app, shortcut_str, ShortcutAction::Toggle) {
+        if let Some(old) = old_shortcut {
+            let _ = app.global_shortcut().register(old);
+            state
+                .shortcut_actions
+                .locked()
+                .insert(old, ShortcutAction::Toggle);
+        }
+        return Err(e);
+    }
+
+    *state.launch_shortcut.locked() = Some(shortcut);
+    tracing::info!("Registered hotkey: {}", shortcut_str);
+    set_effective_shortcut(app, Some(shortcut_str.to_string()), shortcut_str);
+    Ok(())
+}
+
+/// Pause or resume all global hotkeys from the tray or settings
+pub fn set_paused(app: &AppHandle, paused: bool) {
+    update_pause(app, |pause| pause.manual = paused);
+}
+
+/// Pause or resume hotkeys because a fullscreen app appeared or went away
+fn set_auto_paused(app: &AppHandle, paused: bool) {
+    update_pause(app, |pause| pause.auto = paused);
+}
+
+/// Apply a pause change, (un)registering shortcuts when the effective state flips
+fn update_pause(app: &AppHandle, change: impl FnOnce(&mut HotkeyPause)) {
+    let state = app.state::<AppState>();
+    let (was_paused, is_paused) = {
+        let mut pause = state.hotkey_pause.locked();
+        let was_paused = pause.is_paused();
+        change(&mut pause);
+        (was_paused, pause.is_paused())
+    };
+    if was_paused == is_paused {
+        return;
+    }
+
+    crate::tray::set_tray_state(app, |tray| tray.hotkeys_paused = is_paused);
+
+    let global_shortcut = app.global_shortcut();
+    if is_paused {
+        let _ = global_shortcut.unregister_all();
+        tracing::info!("Global hotkeys paused");
+    } else {
+        let shortcuts: Vec<Shortcut> = state.shortcut_actions.locked().keys().copied().collect();
+        for shortcut in shortcuts {
+            if let Err(e) = global_shortcut.register(shortcut) {
+                tracing::warn!("Failed to re-register hotkey: {}", e);
+            }
+        }
+        tracing::info!("Global hotkeys resumed");
+    }
+
+    let _ = app.emit("hotkeys-paused-changed", is_paused);
+}
+
+/// Watch for fullscreen apps and pause hotkeys while one is in front
+pub fn spawn_fullscreen_watcher(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(2));
+
+        let state = app.state::<AppState>();
+        let enabled = state.config.read_locked().behavior.auto_pause_fullscreen;
+        let auto_paused = state.hotkey_pause.locked().auto;
+        // Off: only undo a pause from before it was turned off
+        if !enabled && !auto_paused {
+            continue;
+        }
+        let fullscreen = enabled && crate::foreground::is_fullscreen_app_active();
+        if fullscreen != auto_paused {
+            set_auto_paused(&app, fullscreen);
+        }
+    });
+}
@@ -0,0 +1,116 @@
+//! Translated strings for the tray menu, notifications and settings window
+//!
+//! Catalogs are flat JSON objects in `locales/<code>.json`, built into the
+//! binary. English has every key; other catalogs may leave some out, and
+//! those are shown in English. `{name}` in a string is replaced by the
+//! argument of that name. The locale comes from the top-level `locale`
+//! setting; empty follows the OS.
+
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+
+use crate::lock::RwLockExt;
+
+/// Locales with a catalog, English first
+pub const LOCALES: &[&str] = &["en", "ja"];
+
+/// Locale used by `t` and `tr`
+static CURRENT: RwLock<&str> = RwLock::new("en");
+
+/// Supported locale for a `locale` setting: its language if there is a
+/// catalog for it, otherwise English
+pub fn resolve(setting: &str) -> &'static str {
+    let wanted = match setting.trim() {
+        "" => system_locale(),
+        setting => setting.to_string(),
+    };
+    // "ja-JP", "ja_JP.UTF-8" -> "ja"
+    let language = wanted
+        .split(['-', '_', '.'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    LOCALES
+        .iter()
+        .copied()
+        .find(|locale| *locale == language)
+        .unwrap_or(LOCALES[0])
+}
+
+/// Use the locale of a `locale` setting for strings from now on
+pub fn set_locale(setting: &str) {
+    *CURRENT.write_locked() = resolve(setting);
+}
+
+pub fn current() -> &'static str {
+    *CURRENT.read_locked()
+}
+
+/// Every string of a locale, in English where it has no translation
+pub fn strings(locale: &str) -> BTreeMap<String, String> {
+    let mut strings = catalog(LOCALES[0]).clone();
+    strings.extend(
+        catalog(locale)
+            .iter()
+            .map(|(key, text)| (key.clone(), text.clone())),
+    );
+    strings
+}
+
+/// String `key` in the current locale
+pub fn t(key: &str) -> String {
+    tr(key, &[])
+}
+
+/// String `key` in the current locale with its `{name}` placeholders filled in
+pub fn tr(key: &str, args: &[(&str, &str)]) -> String {
+    let text = catalog(current())
+        .get(key)
+        .or_else(|| catalog(LOCALES[0]).get(key))
+        .map_or(key, String::as_str);
+    args.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+fn catalog(locale: &str) -> &'static BTreeMap<String, String> {
+    static CATALOGS: OnceLock<Vec<BTreeMap<String, String>>> = OnceLock::new();
+    let catalogs = CATALOGS.get_or_init(|| {
+        LOCALES
+            .iter()
+            .map(|locale| serde_json::from_str(source(locale)).expect("locale catalog is valid"))
+            .collect()
+    });
+    let index = LOCALES.iter().position(|l| *l == locale).unwrap_or(0);
+    &catalogs[index]
+}
+
+fn source(locale: &str) -> &'static str {
+    match locale {
+        "ja" => include_str!("../locales/ja.json"),
+        _ => include_str!("../locales/en.json"),
+    }
+}
+
+/// The user's locale name, e.g. "ja-JP"
+#[cfg(windows)]
+fn system_locale() -> String {
+    use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buffer = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(&mut buffer) };
+    // The length includes the terminating null
+    if len <= 1 {
+        return String::new();
+    }
+    String::from_utf16_lossy(&buffer[..len as usize - 1])
+}
+
+#[cfg(not(windows))]
+fn system_locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_default()
+}
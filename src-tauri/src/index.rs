@@ -0,0 +1,106 @@
+//! Search index for history
+//!
+//! Each entry's text is lowercased once, when it is added, and split into
+//! overlapping three-character windows. Text containing the query has every
+//! trigram of the query, so a search intersects a few posting lists instead
+//! of scanning every entry, then checks the candidates against the stored
+//! lowercase text. Trigrams are made of chars, so Japanese and other text
+//! without spaces is indexed like any other. Queries shorter than three
+//! characters can't use the posting lists and scan the lowercase texts.
+//!
+//! Entries get consecutive ids in the order they are pushed; the oldest can
+//! be dropped from the front.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// Ids of the entries containing each trigram, in increasing order
+    postings: HashMap<[char; 3], Vec<u32>>,
+    /// Lowercased text of each entry, starting at id `first`
+    texts: VecDeque<String>,
+    first: u32,
+}
+
+impl SearchIndex {
+    /// Index the next entry; returns its id
+    pub fn push(&mut self, text: &str) -> u32 {
+        let id = self.first + self.texts.len() as u32;
+        let lower = text.to_lowercase();
+        let mut seen = HashSet::new();
+        for trigram in trigrams(&lower) {
+            if seen.insert(trigram) {
+                self.postings.entry(trigram).or_default().push(id);
+            }
+        }
+        self.texts.push_back(lower);
+        id
+    }
+
+    /// Forget the `count` oldest entries
+    pub fn remove_front(&mut self, count: usize) {
+        let count = count.min(self.texts.len());
+        self.texts.drain(..count);
+        self.first += count as u32;
+        let first = self.first;
+        self.postings.retain(|_, ids| {
+            let stale = ids.partition_point(|&id| id < first);
+            ids.drain(..stale);
+            !ids.is_empty()
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.postings.clear();
+        self.texts.clear();
+        self.first = 0;
+    }
+
+    /// Id of the oldest entry still indexed
+    pub fn first(&self) -> u32 {
+        self.first
+    }
+
+    /// Ids of the entries containing `query` (lowercased), in increasing order
+    pub fn search(&self, query: &str) -> Vec<u32> {
+        let contains = |id: &u32| {
+            self.texts
+                .get((id - self.first) as usize)
+                .is_some_and(|text| text.contains(query))
+        };
+        match self.candidates(query) {
+            Some(ids) => ids.into_iter().filter(contains).collect(),
+            None => (self.first..self.first + self.texts.len() as u32)
+                .filter(contains)
+                .collect(),
+        }
+    }
+
+    /// Ids of the entries having every trigram of `query`; `None` if the
+    /// query is too short to narrow anything down
+    fn candidates(&self, query: &str) -> Option<Vec<u32>> {
+        let mut lists: Vec<&Vec<u32>> = Vec::new();
+        for trigram in trigrams(query).collect::<HashSet<_>>() {
+            match self.postings.get(&trigram) {
+                Some(ids) => lists.push(ids),
+                None => return Some(Vec::new()),
+            }
+        }
+        // Start from the rarest trigram so the intersection stays small
+        lists.sort_by_key(|ids| ids.len());
+        let (first, rest) = lists.split_first()?;
+        let mut ids = (*first).clone();
+        for list in rest {
+            ids.retain(|id| list.binary_search(id).is_ok());
+            if ids.is_empty() {
+                break;
+            }
+        }
+        Some(ids)
+    }
+}
+
+fn trigrams(text: &str) -> impl Iterator<Item = [char; 3]> {
+    let chars: Vec<char> = text.chars().collect();
+    (0..chars.len().saturating_sub(2)).map(move |i| [chars[i], chars[i + 1], chars[i + 2]])
+}
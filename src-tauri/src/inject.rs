@@ -0,0 +1,228 @@
+//! Pasting without synthetic keystrokes
+//!
+//! A simulated Ctrl+V is lost when the target app remaps its keys or a modal
+//! dialog swallows the keystroke. These paste methods talk to the focused
+//! control instead: `WmPaste` sends it `WM_PASTE` (it pastes the clipboard
+//! itself), `Uia` sets its value through UI Automation's ValuePattern.
+//!
+//! A keystroke or `WM_PASTE` that reaches an app while its IME is composing
+//! lands in the middle of the composition. Whether another process is
+//! composing can't be queried, so `ImeClosed` turns the target's IME off for
+//! the paste (which commits any composition) and back on afterwards.
+
+use crate::error::{Error, Result};
+
+/// How long a hung target may block `WM_PASTE`
+#[cfg(windows)]
+const SEND_TIMEOUT_MS: u32 = 1000;
+
+/// Send `WM_PASTE` to the control with keyboard focus in the foreground app
+#[cfg(windows)]
+pub fn send_wm_paste() -> Result<()> {
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SendMessageTimeoutW, SMTO_ABORTIFHUNG, WM_PASTE,
+    };
+
+    let target = focused_control()?;
+    let sent = unsafe {
+        SendMessageTimeoutW(
+            target,
+            WM_PASTE,
+            WPARAM(0),
+            LPARAM(0),
+            SMTO_ABORTIFHUNG,
+            SEND_TIMEOUT_MS,
+            None,
+        )
+    };
+    if sent.0 == 0 {
+        return Err(Error::Platform(
+            "The target app did not respond to WM_PASTE".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// `IMC_GETOPENSTATUS`, missing from the `windows` crate
+#[cfg(windows)]
+const IMC_GETOPENSTATUS: usize = 5;
+
+/// Time for the app to handle the paste before its IME is turned back on
+#[cfg(windows)]
+const IME_REOPEN_DELAY_MS: u64 = 100;
+
+/// The foreground app's IME, turned off while this is alive
+#[cfg(windows)]
+pub struct ImeClosed {
+    ime_window: windows::Win32::Foundation::HWND,
+}
+
+#[cfg(windows)]
+impl ImeClosed {
+    /// Turn off the IME of the focused control in the foreground app if it
+    /// is on; `None` if it was off or has no IME
+    pub fn for_foreground() -> Option<Self> {
+        use windows::Win32::UI::Input::Ime::{ImmGetDefaultIMEWnd, IMC_SETOPENSTATUS};
+
+        let focus = focused_control().ok()?;
+        let ime_window = unsafe { ImmGetDefaultIMEWnd(focus) };
+        if ime_window.0.is_null() {
+            return None;
+        }
+        if ime_control(ime_window, IMC_GETOPENSTATUS, 0)? == 0 {
+            return None;
+        }
+        ime_control(ime_window, IMC_SETOPENSTATUS as usize, 0)?;
+        tracing::debug!("Turned the target's IME off for the paste");
+        Some(Self { ime_window })
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ImeClosed {
+    fn drop(&mut self) {
+        use windows::Win32::UI::Input::Ime::IMC_SETOPENSTATUS;
+
+        // Simulated keys are queued; a sent message would overtake them
+        std::thread::sleep(std::time::Duration::from_millis(IME_REOPEN_DELAY_MS));
+        if ime_control(self.ime_window, IMC_SETOPENSTATUS as usize, 1).is_none() {
+            tracing::warn!("Failed to turn the target's IME back on");
+        }
+    }
+}
+
+/// Send `WM_IME_CONTROL` to an IME window; `None` if the app didn't respond
+#[cfg(windows)]
+fn ime_control(
+    ime_window: windows::Win32::Foundation::HWND,
+    command: usize,
+    value: isize,
+) -> Option<usize> {
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SendMessageTimeoutW, SMTO_ABORTIFHUNG, WM_IME_CONTROL,
+    };
+
+    let mut result = 0usize;
+    let sent = unsafe {
+        SendMessageTimeoutW(
+            ime_window,
+            WM_IME_CONTROL,
+            WPARAM(command),
+            LPARAM(value),
+            SMTO_ABORTIFHUNG,
+            SEND_TIMEOUT_MS,
+            Some(&mut result),
+        )
+    };
+    (sent.0 != 0).then_some(result)
+}
+
+/// Focused control of the foreground window's thread, or the window itself
+#[cfg(windows)]
+fn focused_control() -> Result<windows::Win32::Foundation::HWND> {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetGUIThreadInfo, GetWindowThreadProcessId, GUITHREADINFO,
+    };
+
+    unsafe {
+        let foreground = GetForegroundWindow();
+        if foreground.0.is_null() {
+            return Err(Error::Platform("No foreground window".to_string()));
+        }
+        let thread = GetWindowThreadProcessId(foreground, None);
+        let mut info = GUITHREADINFO {
+            cbSize: std::mem::size_of::<GUITHREADINFO>() as u32,
+            ..Default::default()
+        };
+        match GetGUIThreadInfo(thread, &mut info) {
+            Ok(()) if !info.hwndFocus.0.is_null() => Ok(info.hwndFocus),
+            _ => Ok(foreground),
+        }
+    }
+}
+
+/// Append text to the focused element's value through UI Automation
+///
+/// ValuePattern has no caret position, so the text always goes at the end of
+/// the field.
+#[cfg(windows)]
+pub fn insert_with_uia(text: &str) -> Result<()> {
+    use windows::core::BSTR;
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+        COINIT_MULTITHREADED,
+    };
+    use windows::Win32::UI::Accessibility::{
+        CUIAutomation, IUIAutomation, IUIAutomationValuePattern, UIA_ValuePatternId,
+    };
+
+    let platform = |context: &str| {
+        let context = context.to_string();
+        move |e: windows::core::Error| Error::Platform(format!("{}: {}", context, e))
+    };
+
+    let initialized = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }.is_ok();
+    let result = unsafe {
+        CoCreateInstance::<_, IUIAutomation>(&CUIAutomation, None, CLSCTX_INPROC_SERVER)
+            .map_err(platform("UI Automation unavailable"))
+            .and_then(|automation| {
+                automation
+                    .GetFocusedElement()
+                    .map_err(platform("No focused element"))
+            })
+            .and_then(|element| {
+                element
+                    .GetCurrentPatternAs::<IUIAutomationValuePattern>(UIA_ValuePatternId)
+                    .map_err(platform("The focused element has no editable value"))
+            })
+            .and_then(|pattern| {
+                if pattern
+                    .CurrentIsReadOnly()
+                    .map_err(platform("Failed to read the focused element"))?
+                    .as_bool()
+                {
+                    return Err(Error::Platform(
+                        "The focused element is read-only".to_string(),
+                    ));
+                }
+                let current = pattern
+                    .CurrentValue()
+                    .map_err(platform("Failed to read the focused element"))?;
+                let value = BSTR::from(format!("{}{}", current, text));
+                pattern
+                    .SetValue(&value)
+                    .map_err(platform("Failed to set the focused element's value"))
+            })
+    };
+    if initialized {
+        unsafe { CoUninitialize() };
+    }
+    result
+}
+
+/// No IME to turn off outside Windows
+#[cfg(not(windows))]
+pub struct ImeClosed;
+
+#[cfg(not(windows))]
+impl ImeClosed {
+    pub fn for_foreground() -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(not(windows))]
+pub fn send_wm_paste() -> Result<()> {
+    Err(Error::Platform(
+        "WM_PASTE is only supported on Windows".to_string(),
+    ))
+}
+
+#[cfg(not(windows))]
+pub fn insert_with_uia(_text: &str) -> Result<()> {
+    Err(Error::Platform(
+        "UI Automation is only supported on Windows".to_string(),
+    ))
+}
@@ -0,0 +1,231 @@
+//! Clipboard and keyboard access used by the paste path
+//!
+//! Commands go through the `ClipboardProvider` and `KeySimulator` held in
+//! `AppState` instead of calling `clipboard` directly, so the paste path can
+//! run without touching the OS: `--dry-run` swaps in `DryRun`, which only logs
+//! what it would do, and tests use `Mock`, which records it.
+
+use std::sync::Arc;
+
+use crate::config::{BehaviorConfig, PasteMethod};
+use crate::error::Result;
+use crate::{clipboard, inject};
+
+pub trait ClipboardProvider: Send + Sync {
+    /// Replace the clipboard contents with text
+    fn copy(&self, text: &str, behavior: &BehaviorConfig) -> Result<()>;
+
+    /// Current clipboard text; `None` if it is empty, holds no text or can't be read
+    fn read_text(&self) -> Option<String>;
+
+    /// Kind, size and start of the clipboard contents
+    fn preview(&self) -> Result<clipboard::ClipboardPreview>;
+
+    /// Check that the clipboard can be opened and read
+    fn check_access(&self) -> Result<()>;
+}
+
+pub trait KeySimulator: Send + Sync {
+    /// Press a shortcut such as "Ctrl+V" in the foreground app
+    fn send_shortcut(&self, shortcut: &str) -> Result<()>;
+
+    /// Paste into the foreground app; `text` is what was just copied, for
+    /// methods that insert it directly
+    ///
+    /// With `ime_safe`, the app's IME is turned off around the shortcut or
+    /// `WM_PASTE` so neither lands in an unfinished composition.
+    fn paste(&self, method: PasteMethod, shortcut: &str, text: &str, ime_safe: bool) -> Result<()> {
+        let _ime = (ime_safe && method != PasteMethod::Uia)
+            .then(inject::ImeClosed::for_foreground)
+            .flatten();
+        match method {
+            PasteMethod::Keys => self.send_shortcut(shortcut),
+            PasteMethod::WmPaste => inject::send_wm_paste(),
+            PasteMethod::Uia => inject::insert_with_uia(text),
+        }
+    }
+
+    /// Start voice input after `delay_ms`; returns without waiting
+    fn start_voice_input(&self, shortcut: &str, delay_ms: u32) -> Result<()>;
+
+    /// Stop voice input; returns once the keys are sent
+    fn stop_voice_input(&self, shortcut: &str) -> Result<()>;
+}
+
+/// The OS clipboard and simulated key presses
+pub struct System;
+
+impl ClipboardProvider for System {
+    fn copy(&self, text: &str, behavior: &BehaviorConfig) -> Result<()> {
+        clipboard::copy_to_clipboard(text, behavior)
+    }
+
+    fn read_text(&self) -> Option<String> {
+        clipboard::read_text()
+    }
+
+    fn preview(&self) -> Result<clipboard::ClipboardPreview> {
+        clipboard::preview()
+    }
+
+    fn check_access(&self) -> Result<()> {
+        clipboard::check_access()
+    }
+}
+
+impl KeySimulator for System {
+    fn send_shortcut(&self, shortcut: &str) -> Result<()> {
+        clipboard::simulate_paste(shortcut)
+    }
+
+    fn start_voice_input(&self, shortcut: &str, delay_ms: u32) -> Result<()> {
+        clipboard::trigger_voice_input(shortcut, delay_ms)
+    }
+
+    fn stop_voice_input(&self, shortcut: &str) -> Result<()> {
+        clipboard::stop_voice_input(shortcut)
+    }
+}
+
+/// Logs clipboard writes and key presses instead of performing them
+pub struct DryRun;
+
+impl ClipboardProvider for DryRun {
+    fn copy(&self, text: &str, _behavior: &BehaviorConfig) -> Result<()> {
+        tracing::info!("[dry run] Copy {} characters", text.chars().count());
+        Ok(())
+    }
+
+    fn read_text(&self) -> Option<String> {
+        None
+    }
+
+    fn preview(&self) -> Result<clipboard::ClipboardPreview> {
+        Ok(clipboard::ClipboardPreview::other())
+    }
+
+    fn check_access(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl KeySimulator for DryRun {
+    fn send_shortcut(&self, shortcut: &str) -> Result<()> {
+        tracing::info!("[dry run] Press {}", shortcut);
+        Ok(())
+    }
+
+    fn paste(&self, method: PasteMethod, shortcut: &str, text: &str, ime_safe: bool) -> Result<()> {
+        if ime_safe && method != PasteMethod::Uia {
+            tracing::info!("[dry run] Turn the target's IME off for the paste");
+        }
+        match method {
+            PasteMethod::Keys => self.send_shortcut(shortcut),
+            method => {
+                tracing::info!(
+                    "[dry run] Paste {} characters with {:?}",
+                    text.chars().count(),
+                    method
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn start_voice_input(&self, shortcut: &str, delay_ms: u32) -> Result<()> {
+        tracing::info!(
+            "[dry run] Start voice input with {} after {}ms",
+            shortcut,
+            delay_ms
+        );
+        Ok(())
+    }
+
+    fn stop_voice_input(&self, shortcut: &str) -> Result<()> {
+        tracing::info!("[dry run] Stop voice input with {}", shortcut);
+        Ok(())
+    }
+}
+
+/// Records clipboard writes and key presses for tests; the clipboard holds
+/// whatever was copied last
+#[cfg(test)]
+#[derive(Default)]
+pub struct Mock {
+    pub clipboard: std::sync::Mutex<Option<String>>,
+    /// Every text copied, in order
+    pub copied: std::sync::Mutex<Vec<String>>,
+    /// Shortcuts pressed, or the paste method for methods without one
+    pub pressed: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl ClipboardProvider for Mock {
+    fn copy(&self, text: &str, _behavior: &BehaviorConfig) -> Result<()> {
+        use crate::lock::MutexExt;
+
+        self.copied.locked().push(text.to_string());
+        *self.clipboard.locked() = Some(text.to_string());
+        Ok(())
+    }
+
+    fn read_text(&self) -> Option<String> {
+        use crate::lock::MutexExt;
+
+        self.clipboard.locked().clone()
+    }
+
+    fn preview(&self) -> Result<clipboard::ClipboardPreview> {
+        Ok(clipboard::ClipboardPreview::other())
+    }
+
+    fn check_access(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl KeySimulator for Mock {
+    fn send_shortcut(&self, shortcut: &str) -> Result<()> {
+        use crate::lock::MutexExt;
+
+        self.pressed.locked().push(shortcut.to_string());
+        Ok(())
+    }
+
+    fn paste(
+        &self,
+        method: PasteMethod,
+        shortcut: &str,
+        _text: &str,
+        _ime_safe: bool,
+    ) -> Result<()> {
+        use crate::lock::MutexExt;
+
+        match method {
+            PasteMethod::Keys => self.send_shortcut(shortcut),
+            method => {
+                self.pressed.locked().push(format!("{:?}", method));
+                Ok(())
+            }
+        }
+    }
+
+    fn start_voice_input(&self, shortcut: &str, _delay_ms: u32) -> Result<()> {
+        self.send_shortcut(shortcut)
+    }
+
+    fn stop_voice_input(&self, shortcut: &str) -> Result<()> {
+        self.send_shortcut(shortcut)
+    }
+}
+
+/// Implementations for this run: `DryRun` with `--dry-run`, otherwise `System`
+pub fn from_args() -> (Arc<dyn ClipboardProvider>, Arc<dyn KeySimulator>) {
+    if std::env::args().skip(1).any(|arg| arg == "--dry-run") {
+        tracing::info!("Dry run: clipboard writes and key presses are only logged");
+        (Arc::new(DryRun), Arc::new(DryRun))
+    } else {
+        (Arc::new(System), Arc::new(System))
+    }
+}
@@ -0,0 +1,170 @@
+//! Handing command-line requests to the instance already running
+//!
+//! `--show`, `--action <name>` (any `[shortcuts.global]` action, e.g.
+//! "paste_last") and `--paste-entry <id>` are carried out by the running
+//! instance, so jump list tasks and scripts don't start a second copy of the
+//! app. The running
+//! instance listens on a localhost port, written with a random token to
+//! `instance.port` in the data folder; a launch with one of the flags sends
+//! the request there and exits. With no instance running, the app starts as
+//! usual and carries out the request itself.
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::error::{Error, Result};
+
+const PORT_FILE: &str = "instance.port";
+/// A running instance answers at once; anything slower is a stale port file
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+/// Longest request line read; real ones are a token, a flag and an id or name
+const MAX_REQUEST_BYTES: u64 = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    /// Show the main window
+    Show,
+    /// Run a global hotkey action
+    Action(String),
+    /// Paste the history entry with this timestamp into the focused app
+    PasteEntry(String),
+}
+
+impl Request {
+    /// The request given on the command line, if any
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--show" => return Some(Self::Show),
+                "--action" => return iter.next().map(|name| Self::Action(name.clone())),
+                "--paste-entry" => return iter.next().map(|id| Self::PasteEntry(id.clone())),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Command-line arguments that send this request (for jump list items)
+    #[cfg(windows)]
+    pub fn args(&self) -> String {
+        match self {
+            Self::Show => "--show".to_string(),
+            Self::Action(name) => format!("--action {}", name),
+            Self::PasteEntry(id) => format!("--paste-entry {}", id),
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        if line == "show" {
+            return Some(Self::Show);
+        }
+        match line.split_once(' ')? {
+            ("action", name) => Some(Self::Action(name.to_string())),
+            ("paste-entry", id) => Some(Self::PasteEntry(id.to_string())),
+            _ => None,
+        }
+    }
+
+    fn line(&self) -> String {
+        match self {
+            Self::Show => "show".to_string(),
+            Self::Action(name) => format!("action {}", name),
+            Self::PasteEntry(id) => format!("paste-entry {}", id),
+        }
+    }
+}
+
+/// Send a request to the running instance; false if none took it
+pub fn forward(request: &Request) -> bool {
+    let Some((port, token)) = read_port_file() else {
+        return false;
+    };
+    allow_foreground();
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let sent = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).and_then(|mut stream| {
+        stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+        writeln!(stream, "{} {}", token, request.line())?;
+        // The instance answers "ok" once it has checked the token
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        Ok(reply.trim() == "ok")
+    });
+    sent.unwrap_or(false)
+}
+
+/// Take requests from later launches, carrying each out with `handle`
+pub fn listen(app: &AppHandle, handle: fn(&AppHandle, Request)) -> Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .map_err(Error::io("Failed to listen for other launches"))?;
+    let port = listener
+        .local_addr()
+        .map_err(Error::io("Failed to listen for other launches"))?
+        .port();
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    fs::write(port_file()?, format!("{} {}", port, token))
+        .map_err(Error::io("Failed to write instance port file"))?;
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // A connection that never finishes its line must not hold up later launches
+            if stream.set_read_timeout(Some(CONNECT_TIMEOUT)).is_err() {
+                continue;
+            }
+            let mut line = String::new();
+            let mut reader = BufReader::new(&stream).take(MAX_REQUEST_BYTES);
+            if reader.read_line(&mut line).is_err() {
+                continue;
+            }
+            let request = line
+                .trim()
+                .split_once(' ')
+                .filter(|(sent, _)| *sent == token)
+                .and_then(|(_, request)| Request::parse(request));
+            let Some(request) = request else {
+                tracing::warn!("Ignored a malformed request from another launch");
+                continue;
+            };
+            let _ = (&stream).write_all(b"ok\n");
+            tracing::info!("Request from another launch: {:?}", request);
+            handle(&app, request);
+        }
+    });
+    Ok(())
+}
+
+/// Let the running instance take the foreground, which a launch from the jump
+/// list may do but a background process may not
+#[cfg(windows)]
+fn allow_foreground() {
+    use windows::Win32::UI::WindowsAndMessaging::{AllowSetForegroundWindow, ASFW_ANY};
+
+    unsafe {
+        let _ = AllowSetForegroundWindow(ASFW_ANY);
+    }
+}
+
+#[cfg(not(windows))]
+fn allow_foreground() {}
+
+fn port_file() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join(PORT_FILE))
+}
+
+fn read_port_file() -> Option<(u16, String)> {
+    let content = fs::read_to_string(port_file().ok()?).ok()?;
+    let (port, token) = content.trim().split_once(' ')?;
+    Some((port.parse().ok()?, token.to_string()))
+}
@@ -0,0 +1,180 @@
+//! Taskbar jump list (Windows)
+//!
+//! Right-clicking the taskbar button, pinned or not, offers "New Prompt" and
+//! "Paste Last Entry", plus the most recent history entries if
+//! `behavior.jump_list_entries` asks for them (none by default: Explorer keeps
+//! the list on disk, outside the app). Each item starts the app with
+//! `--action` or `--paste-entry`, which `instance` hands to the running
+//! instance. The list is rebuilt whenever the history changes, but not while
+//! history isn't saved (incognito, screen sharing). Secret entries are left out.
+
+use tauri::AppHandle;
+
+#[cfg(windows)]
+use crate::instance::Request;
+
+/// Characters of an entry shown as its jump list title
+#[cfg(windows)]
+const TITLE_CHARS: usize = 60;
+
+/// Rebuild the jump list from the current history, in the background
+///
+/// Callers may still hold the history lock, so it is read on the new thread.
+#[cfg(windows)]
+pub fn update(app: &AppHandle) {
+    use tauri::Manager;
+
+    use crate::lock::{MutexExt, RwLockExt};
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        // One rebuild at a time; a later one has the newer history anyway
+        static BUILDING: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        let _building = BUILDING.locked();
+
+        let state = app.state::<crate::AppState>();
+        if !crate::saves_history(&state) {
+            return;
+        }
+        let count = state.config.read_locked().behavior.jump_list_entries;
+        let recent: Vec<(String, Request)> = state
+            .history
+            .locked()
+            .entries()
+            .into_iter()
+            .filter(|entry| !entry.secret)
+            .take(count)
+            .map(|entry| {
+                (
+                    title(&entry.text),
+                    Request::PasteEntry(entry.timestamp.to_rfc3339()),
+                )
+            })
+            .collect();
+        if let Err(e) = build(&recent) {
+            tracing::warn!("{}", e);
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn update(_app: &AppHandle) {}
+
+/// First line of an entry, shortened
+#[cfg(windows)]
+fn title(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or_default();
+    if line.chars().count() > TITLE_CHARS {
+        let cut: String = line.chars().take(TITLE_CHARS).collect();
+        format!("{}...", cut.trim_end())
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(windows)]
+fn build(recent: &[(String, Request)]) -> crate::error::Result<()> {
+    use windows::core::{Interface, HSTRING};
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+        COINIT_APARTMENTTHREADED,
+    };
+    use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+    use windows::Win32::UI::Shell::{
+        DestinationList, EnumerableObjectCollection, ICustomDestinationList,
+    };
+
+    use crate::error::Error;
+    use crate::i18n::t;
+
+    let exe = std::env::current_exe().map_err(Error::io("Failed to locate the executable"))?;
+    let tasks = [
+        (t("jumplist.new_prompt"), Request::Show),
+        (
+            t("jumplist.paste_last"),
+            Request::Action("paste_last".to_string()),
+        ),
+    ];
+
+    let initialized = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) }.is_ok();
+    let result = unsafe {
+        (|| -> windows::core::Result<()> {
+            let list: ICustomDestinationList =
+                CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+            let mut slots = 0u32;
+            // Items the user removed from the list; entries are never offered again
+            // under the same id, so there is nothing to leave out
+            let _removed: IObjectArray = list.BeginList(&mut slots)?;
+
+            let collection = || -> windows::core::Result<IObjectCollection> {
+                CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)
+            };
+            let task_items = collection()?;
+            for (title, request) in &tasks {
+                task_items.AddObject(&link(&exe, request, title)?)?;
+            }
+            list.AddUserTasks(&task_items.cast::<IObjectArray>()?)?;
+
+            if !recent.is_empty() {
+                let recent_items = collection()?;
+                for (title, request) in recent.iter().take(slots as usize) {
+                    recent_items.AddObject(&link(&exe, request, title)?)?;
+                }
+                list.AppendCategory(
+                    &HSTRING::from(t("jumplist.recent")),
+                    &recent_items.cast::<IObjectArray>()?,
+                )?;
+            }
+            list.CommitList()
+        })()
+    };
+    if initialized {
+        unsafe { CoUninitialize() };
+    }
+    result.map_err(|e| Error::Platform(format!("Failed to update the jump list: {}", e)))
+}
+
+/// Shortcut starting this executable with the request (and the folder flags
+/// of this launch, so it reaches this instance)
+#[cfg(windows)]
+unsafe fn link(
+    exe: &std::path::Path,
+    request: &Request,
+    title: &str,
+) -> windows::core::Result<windows::Win32::UI::Shell::IShellLinkW> {
+    use windows::core::{Interface, HSTRING, PROPVARIANT};
+    use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+    use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+    use windows::Win32::UI::Shell::{IShellLinkW, ShellLink};
+
+    let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+    link.SetPath(&HSTRING::from(exe.as_os_str()))?;
+    link.SetArguments(&HSTRING::from(format!(
+        "{}{}",
+        folder_args(),
+        request.args()
+    )))?;
+    link.SetIconLocation(&HSTRING::from(exe.as_os_str()), 0)?;
+    let store: IPropertyStore = link.cast()?;
+    store.SetValue(&PKEY_Title, &PROPVARIANT::from(title))?;
+    store.Commit()?;
+    Ok(link)
+}
+
+/// `--config`, `--data-dir` and `--portable` as given to this launch
+#[cfg(windows)]
+fn folder_args() -> String {
+    let mut args = String::new();
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--portable" || arg.starts_with("--config=") || arg.starts_with("--data-dir=") {
+            args.push_str(&format!("\"{}\" ", arg));
+        } else if arg == "--config" || arg == "--data-dir" {
+            if let Some(value) = iter.next() {
+                args.push_str(&format!("{} \"{}\" ", arg, value));
+            }
+        }
+    }
+    args
+}
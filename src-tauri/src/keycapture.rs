@@ -0,0 +1,201 @@
+//! Recording the next key combination for the settings UI
+//!
+//! `capture_next` installs a low-level keyboard hook until one combination is
+//! pressed, so shortcut fields can be set by pressing the keys instead of
+//! typing their names. The combination is swallowed: pressing a hotkey that is
+//! already registered (by this app or another) records it rather than running
+//! it. Modifiers alone don't count; capture waits for the key pressed with
+//! them. Escape on its own cancels.
+
+use crate::error::{Error, Result};
+
+/// Give up when nothing is pressed for this long
+#[cfg(windows)]
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[cfg(windows)]
+thread_local! {
+    /// Modifier names and virtual-key code of the combination the hook saw
+    static CAPTURED: std::cell::RefCell<Option<(Vec<&'static str>, u16)>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Wait for the next key combination and return it in canonical form
+/// ("Ctrl+Shift+K"); `None` if cancelled with Escape or timed out
+#[cfg(windows)]
+pub fn capture_next() -> Result<Option<String>> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Input::KeyboardAndMouse::VK_ESCAPE;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetMessageW, KillTimer, SetTimer, SetWindowsHookExW, UnhookWindowsHookEx, MSG,
+        WH_KEYBOARD_LL, WM_TIMER,
+    };
+
+    CAPTURED.with_borrow_mut(|captured| *captured = None);
+    let hook = unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook_proc), None, 0) }
+        .map_err(|e| Error::Platform(format!("Failed to install keyboard hook: {}", e)))?;
+    let timer = unsafe { SetTimer(HWND::default(), 0, TIMEOUT.as_millis() as u32, None) };
+
+    // Low-level hooks are called from this thread's message loop; the hook
+    // posts WM_QUIT once it has a combination
+    let mut msg = MSG::default();
+    while unsafe { GetMessageW(&mut msg, HWND::default(), 0, 0) }.as_bool() {
+        if msg.message == WM_TIMER {
+            tracing::debug!("Shortcut capture timed out");
+            break;
+        }
+    }
+    unsafe {
+        let _ = KillTimer(HWND::default(), timer);
+        let _ = UnhookWindowsHookEx(hook);
+    }
+
+    let Some((modifiers, vk)) = CAPTURED.with_borrow_mut(Option::take) else {
+        return Ok(None);
+    };
+    if modifiers.is_empty() && vk == VK_ESCAPE.0 {
+        return Ok(None);
+    }
+    let key = key_name(vk).ok_or_else(|| {
+        Error::Shortcut(format!(
+            "That key (virtual-key code {:#04x}) can't be used in shortcuts",
+            vk
+        ))
+    })?;
+    let mut parts = modifiers;
+    parts.push(&key);
+    crate::hotkey::normalize_shortcut(&parts.join("+")).map(Some)
+}
+
+#[cfg(not(windows))]
+pub fn capture_next() -> Result<Option<String>> {
+    Err(Error::Platform(
+        "Shortcut capture is only supported on Windows".to_string(),
+    ))
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn hook_proc(
+    code: i32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::Foundation::LRESULT;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, PostQuitMessage, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT, WM_KEYDOWN,
+        WM_SYSKEYDOWN,
+    };
+
+    if code == HC_ACTION as i32 && matches!(wparam.0 as u32, WM_KEYDOWN | WM_SYSKEYDOWN) {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        let vk = info.vkCode as u16;
+        if !is_modifier(vk) {
+            let modifiers = held_modifiers();
+            CAPTURED.with_borrow_mut(|captured| *captured = Some((modifiers, vk)));
+            PostQuitMessage(0);
+            // Swallow the key so the combination doesn't act anywhere
+            return LRESULT(1);
+        }
+    }
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+#[cfg(windows)]
+fn is_modifier(vk: u16) -> bool {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    [
+        VK_SHIFT,
+        VK_LSHIFT,
+        VK_RSHIFT,
+        VK_CONTROL,
+        VK_LCONTROL,
+        VK_RCONTROL,
+        VK_MENU,
+        VK_LMENU,
+        VK_RMENU,
+        VK_LWIN,
+        VK_RWIN,
+    ]
+    .iter()
+    .any(|key| key.0 == vk)
+}
+
+/// Modifiers down right now, in the order shortcuts are written
+#[cfg(windows)]
+fn held_modifiers() -> Vec<&'static str> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        GetAsyncKeyState, VIRTUAL_KEY, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT,
+    };
+
+    // High bit set: the key is down right now
+    let down = |keys: &[VIRTUAL_KEY]| {
+        keys.iter()
+            .any(|key| unsafe { GetAsyncKeyState(key.0 as i32) } < 0)
+    };
+    [
+        ("Win", down(&[VK_LWIN, VK_RWIN])),
+        ("Ctrl", down(&[VK_CONTROL])),
+        ("Alt", down(&[VK_MENU])),
+        ("Shift", down(&[VK_SHIFT])),
+    ]
+    .into_iter()
+    .filter(|(_, held)| *held)
+    .map(|(name, _)| name)
+    .collect()
+}
+
+/// Name `hotkey::check_shortcut` accepts for a virtual-key code
+#[cfg(windows)]
+fn key_name(vk: u16) -> Option<String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    let name = match VIRTUAL_KEY(vk) {
+        // Letters and digits have their ASCII code
+        VIRTUAL_KEY(0x30..=0x39) | VIRTUAL_KEY(0x41..=0x5A) => {
+            return Some(char::from(vk as u8).to_string())
+        }
+        key if (VK_F1.0..=VK_F24.0).contains(&key.0) => {
+            return Some(format!("F{}", key.0 - VK_F1.0 + 1))
+        }
+        key if (VK_NUMPAD0.0..=VK_NUMPAD9.0).contains(&key.0) => {
+            return Some(format!("Num{}", key.0 - VK_NUMPAD0.0))
+        }
+        VK_SPACE => "Space",
+        VK_RETURN => "Enter",
+        VK_ESCAPE => "Escape",
+        VK_TAB => "Tab",
+        VK_BACK => "Backspace",
+        VK_DELETE => "Delete",
+        VK_INSERT => "Insert",
+        VK_HOME => "Home",
+        VK_END => "End",
+        VK_PRIOR => "PageUp",
+        VK_NEXT => "PageDown",
+        VK_UP => "Up",
+        VK_DOWN => "Down",
+        VK_LEFT => "Left",
+        VK_RIGHT => "Right",
+        VK_ADD => "NumAdd",
+        VK_SUBTRACT => "NumSubtract",
+        VK_MULTIPLY => "NumMultiply",
+        VK_DIVIDE => "NumDivide",
+        VK_DECIMAL => "NumDecimal",
+        VK_OEM_MINUS => "-",
+        VK_OEM_PLUS => "=",
+        VK_OEM_4 => "[",
+        VK_OEM_6 => "]",
+        VK_OEM_5 => "\\",
+        VK_OEM_1 => ";",
+        VK_OEM_7 => "'",
+        VK_OEM_3 => "`",
+        VK_OEM_COMMA => ",",
+        VK_OEM_PERIOD => ".",
+        VK_OEM_2 => "/",
+        VK_SNAPSHOT => "PrintScreen",
+        VK_PAUSE => "Pause",
+        VK_SCROLL => "ScrollLock",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
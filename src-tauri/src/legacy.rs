@@ -0,0 +1,162 @@
+//! Import data from the original Electron prompt-line
+//!
+//! prompt-line keeps its data in `~/.prompt-line/`: `history.jsonl` (one
+//! `{"text", "timestamp", "id"}` object per line, timestamps in milliseconds)
+//! and its settings as JSON. Both are converted into this app's formats.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::history::{History, HistoryEntry};
+
+/// History file names used by prompt-line versions
+const HISTORY_FILES: [&str; 2] = ["history.jsonl", "history.json"];
+/// Settings file names used by prompt-line versions
+const SETTINGS_FILES: [&str; 2] = ["settings.json", "config.json"];
+
+/// What was imported
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    pub source: String,
+    pub history_found: usize,
+    pub history_imported: usize,
+    pub settings_imported: bool,
+}
+
+/// Default prompt-line data directory (`~/.prompt-line`)
+pub fn default_dir() -> Result<PathBuf> {
+    directories::BaseDirs::new()
+        .map(|dirs| dirs.home_dir().join(".prompt-line"))
+        .ok_or_else(|| Error::Platform("Failed to determine home directory".to_string()))
+}
+
+/// Import prompt-line's history and settings from `dir` into `history` and `config`
+pub fn import(dir: &Path, history: &mut History, config: &mut Config) -> Result<ImportSummary> {
+    if !dir.is_dir() {
+        return Err(Error::Config(format!(
+            "prompt-line data not found in {}",
+            dir.display()
+        )));
+    }
+
+    let mut summary = ImportSummary {
+        source: dir.display().to_string(),
+        ..Default::default()
+    };
+
+    if let Some(path) = first_existing(dir, &HISTORY_FILES) {
+        let entries = read_history(&path)?;
+        summary.history_found = entries.len();
+        summary.history_imported = history.import(entries)?;
+    }
+
+    if let Some(path) = first_existing(dir, &SETTINGS_FILES) {
+        let contents =
+            fs::read_to_string(&path).map_err(Error::io("Failed to read prompt-line settings"))?;
+        let settings: Value = serde_json::from_str(&contents)
+            .map_err(|e| Error::Config(format!("Failed to parse prompt-line settings: {}", e)))?;
+        apply_settings(&settings, config);
+        summary.settings_imported = true;
+    }
+
+    Ok(summary)
+}
+
+fn first_existing(dir: &Path, names: &[&str]) -> Option<PathBuf> {
+    names.iter().map(|name| dir.join(name)).find(|p| p.exists())
+}
+
+/// Read history as JSON lines or a JSON array, skipping entries that don't parse
+fn read_history(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let contents =
+        fs::read_to_string(path).map_err(Error::io("Failed to read prompt-line history"))?;
+
+    let values: Vec<Value> = match serde_json::from_str::<Vec<Value>>(&contents) {
+        Ok(values) => values,
+        Err(_) => contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    tracing::warn!("Skipping prompt-line history line: {}", e);
+                    None
+                }
+            })
+            .collect(),
+    };
+
+    Ok(values.iter().filter_map(history_entry).collect())
+}
+
+fn history_entry(value: &Value) -> Option<HistoryEntry> {
+    let text = value.get("text")?.as_str()?.to_string();
+    let timestamp = match value.get("timestamp") {
+        Some(Value::Number(ms)) => DateTime::from_timestamp_millis(ms.as_i64()?)?,
+        Some(Value::String(s)) => DateTime::parse_from_rfc3339(s).ok()?.with_timezone(&Utc),
+        _ => return None,
+    };
+    Some(HistoryEntry {
+        text,
+        timestamp,
+        app: None,
+        title: None,
+        secret: false,
+    })
+}
+
+/// Copy the settings that have an equivalent here; everything else keeps its current value
+fn apply_settings(settings: &Value, config: &mut Config) {
+    let shortcut = |key: &str| {
+        settings
+            .pointer(&format!("/shortcuts/{}", key))
+            .and_then(Value::as_str)
+            .map(convert_shortcut)
+    };
+    let shortcuts = &mut config.shortcuts;
+    let mapping: [(&str, &mut String); 6] = [
+        ("main", &mut shortcuts.launch),
+        ("paste", &mut shortcuts.paste),
+        ("close", &mut shortcuts.close),
+        ("historyNext", &mut shortcuts.history_next),
+        ("historyPrev", &mut shortcuts.history_prev),
+        ("search", &mut shortcuts.search),
+    ];
+    for (key, target) in mapping {
+        if let Some(value) = shortcut(key) {
+            *target = value;
+        }
+    }
+
+    if let Some(max) = settings
+        .pointer("/history/maxItems")
+        .and_then(Value::as_u64)
+    {
+        config.history.max_entries = max as usize;
+    }
+}
+
+/// Electron accelerators use Cmd/Command on macOS; this app uses Ctrl
+fn convert_shortcut(shortcut: &str) -> String {
+    shortcut
+        .split('+')
+        .map(|part| match part.to_lowercase().as_str() {
+            "cmd" | "command" | "commandorcontrol" | "cmdorctrl" | "ctrl" | "control" => {
+                "Ctrl".to_string()
+            }
+            "option" | "alt" => "Alt".to_string(),
+            "shift" => "Shift".to_string(),
+            "escape" | "esc" => "Escape".to_string(),
+            "enter" | "return" => "Enter".to_string(),
+            "space" => "Space".to_string(),
+            key if key.chars().count() == 1 => key.to_uppercase(),
+            _ => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
@@ -1,225 +1,1494 @@
 //! Tauri application library
 
+mod ai;
+mod autostart;
+mod calc;
+mod capture;
 mod clipboard;
 mod config;
+mod counters;
+mod crash;
+mod dates;
+mod diagnostics;
+mod dictation;
+mod drafts;
+mod editor;
+mod error;
+mod find;
+mod foreground;
+mod generate;
+mod highlight;
 mod history;
+mod hotkey;
+mod i18n;
+mod index;
+mod inject;
+mod input;
+mod instance;
+mod jumplist;
+mod keycapture;
+mod legacy;
+mod links;
+mod llama;
+mod lock;
+mod logging;
+mod markdown;
+mod notify;
+mod paths;
+mod prompts;
+mod queue;
+mod screenshare;
+mod secrets;
+mod session;
+mod speech;
+mod spell;
+mod symbols;
+mod sync;
+mod theme;
+mod tokens;
+mod translate;
+mod trash;
+mod tray;
+mod window;
 
-use std::sync::Mutex;
-use tauri::{
-    menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Manager, WebviewUrl, WebviewWindowBuilder,
-};
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::Shortcut;
+
+use error::{Error, Result};
+use i18n::{t, tr};
+use lock::{MutexExt, RwLockExt};
 
 /// Application state shared across commands
+///
+/// Commands are `async` and run on the async runtime rather than the main
+/// thread. The locks here are only held for short in-memory work and never
+/// across an `.await`; the config is read far more often than written, so it
+/// sits behind an `RwLock`. Take them with `lock::MutexExt`/`lock::RwLockExt`
+/// so a panic elsewhere cannot poison them for the rest of the session.
 pub struct AppState {
     pub history: Mutex<history::History>,
-    pub config: Mutex<config::Config>,
-    /// Process name of the window that was active before showing prompt-line
-    pub previous_process: Mutex<Option<String>>,
-    /// Voice input toggle state (controlled by main window toggle)
+    pub config: RwLock<config::Config>,
+    /// Window that was active before showing prompt-line
+    pub previous_window: Mutex<foreground::WindowInfo>,
+    /// Voice input toggle state (controlled by main window toggle, kept in `session.json`)
     pub voice_toggle_on: Mutex<bool>,
+    /// Voice input was triggered and has not been stopped yet
+    pub dictation_active: Mutex<bool>,
+    /// Push-to-talk recording for local transcription
+    pub speech: speech::Recorder,
+    /// llama.cpp server for `[ai] provider = "local"`
+    pub llama: llama::LocalServer,
+    /// Hunspell dictionaries for `[spellcheck]`
+    pub spell: spell::SpellChecker,
+    /// Clipboard used for pasting (`input::DryRun` with `--dry-run`)
+    pub clipboard: Arc<dyn input::ClipboardProvider>,
+    /// Key presses for pasting and voice input
+    pub keys: Arc<dyn input::KeySimulator>,
+    /// Currently registered launch shortcut
+    pub launch_shortcut: Mutex<Option<Shortcut>>,
+    /// Registered global shortcuts and the action each one triggers
+    pub shortcut_actions: Mutex<HashMap<Shortcut, hotkey::ShortcutAction>>,
+    /// Launch shortcut actually in effect (may be a fallback)
+    pub effective_shortcut: Mutex<hotkey::EffectiveShortcut>,
+    /// Global hotkey pause state (gaming mode)
+    pub hotkey_pause: Mutex<hotkey::HotkeyPause>,
+    /// Incognito mode: pasted text is not saved to history
+    pub incognito: Mutex<bool>,
+    /// Screen sharing detected for `behavior.screen_share_privacy`
+    pub screen_share: Mutex<screenshare::ScreenShare>,
+    /// Debounced draft writer
+    pub drafts: drafts::DraftManager,
+    /// Main window geometry persisted between runs
+    pub window_state: Mutex<window::WindowState>,
+    /// Pinned: the main window is not hidden on focus loss
+    pub pinned: Mutex<bool>,
+    /// Items waiting for the "paste_next" hotkey
+    pub paste_queue: Mutex<queue::PasteQueue>,
+    /// What the last paste replaced, for `undo_last_paste`
+    pub last_paste: Mutex<Option<LastPaste>>,
+}
+
+/// Clipboard text replaced by a paste and the app it went to
+pub struct LastPaste {
+    /// `None` if the clipboard was empty or held something other than text
+    previous: Option<String>,
+    /// Window the text was pasted into
+    target: foreground::WindowInfo,
+}
+
+/// Run blocking work (sleeps, input simulation, child processes) on the
+/// blocking thread pool so it holds up neither the webview IPC nor async commands
+async fn blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tauri::async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|e| Error::Platform(format!("Background task failed: {}", e)))?
+}
+
+/// Get history entries, optionally filtered by query
+///
+/// Texts are cut to `history.preview_chars`; `get_history_entry_text` has the full one.
+#[tauri::command]
+async fn get_history(
+    query: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<history::EntryPreview>> {
+    let max_chars = state.config.read_locked().history.preview_chars;
+    let entries = state.history.locked().search(&query);
+    Ok(entries.iter().map(|e| e.preview(max_chars)).collect())
+}
+
+/// Search entries moved out of the history by `max_entries`
+#[tauri::command]
+async fn search_archive(
+    query: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<history::EntryPreview>> {
+    let max_chars = state.config.read_locked().history.preview_chars;
+    let entries = blocking(move || {
+        let state = app.state::<AppState>();
+        let mut history = state.history.locked();
+        history.search_archive(&query)
+    })
+    .await?;
+    Ok(entries.iter().map(|e| e.preview(max_chars)).collect())
+}
+
+/// Full text of a history or archive entry, identified by its timestamp
+#[tauri::command]
+async fn get_history_entry_text(
+    id: chrono::DateTime<chrono::Utc>,
+    state: tauri::State<'_, AppState>,
+) -> Result<String> {
+    let entry = find_entry(&state, id).await?;
+    if entry.secret {
+        return Err(Error::Secret(
+            "This entry is secret; reveal it to read it".to_string(),
+        ));
+    }
+    Ok(entry.text)
+}
+
+/// Decrypted text of a secret entry (also the text of any other entry)
+#[tauri::command]
+async fn reveal_entry(
+    id: chrono::DateTime<chrono::Utc>,
+    state: tauri::State<'_, AppState>,
+) -> Result<String> {
+    let entry = find_entry(&state, id).await?;
+    if entry.secret {
+        tracing::info!("Revealed a secret history entry");
+    }
+    entry.plain_text()
+}
+
+/// Encrypt a history entry and keep it masked, out of searches, the jump
+/// list, the sync folder and remote backups
+#[tauri::command]
+async fn mark_entry_secret(
+    id: chrono::DateTime<chrono::Utc>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<()> {
+    if state.history.locked().mark_secret(id)? {
+        emit_history_changed(&app);
+    }
+    Ok(())
+}
+
+async fn find_entry(
+    state: &AppState,
+    id: chrono::DateTime<chrono::Utc>,
+) -> Result<history::HistoryEntry> {
+    let (found, dir) = {
+        let history = state.history.locked();
+        (history.find(id).cloned(), history.archive_dir())
+    };
+    if let Some(entry) = found {
+        return Ok(entry);
+    }
+    blocking(move || history::find_archived(&dir, id))
+        .await?
+        .ok_or_else(|| Error::NotFound("History entry not found".to_string()))
+}
+
+/// Move all history entries to the trash
+#[tauri::command]
+async fn clear_history(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<()> {
+    state.history.locked().clear()?;
+    emit_history_changed(&app);
+    Ok(())
+}
+
+/// Remove duplicate, short or old entries, backing up the history file first
+#[tauri::command]
+async fn compact_history(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    options: history::CompactOptions,
+) -> Result<history::CompactReport> {
+    let report = state.history.locked().compact(&options)?;
+    tracing::info!(
+        "Compacted history: {} duplicates, {} short, {} old",
+        report.duplicates,
+        report.short,
+        report.old
+    );
+    if report.removed() > 0 {
+        emit_history_changed(&app);
+    }
+    Ok(report)
+}
+
+/// Entries in the trash, most recently deleted first
+#[tauri::command]
+async fn get_trash(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<trash::Trashed<history::EntryPreview>>> {
+    let max_chars = state.config.read_locked().history.preview_chars;
+    let trashed = state.history.locked().trash_entries()?;
+    Ok(trashed
+        .into_iter()
+        .map(|t| trash::Trashed {
+            entry: t.entry.preview(max_chars),
+            deleted_at: t.deleted_at,
+        })
+        .collect())
+}
+
+/// Put a trashed entry back into the history, or all of them without an id
+#[tauri::command]
+async fn restore_from_trash(
+    id: Option<chrono::DateTime<chrono::Utc>>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize> {
+    let restored = state.history.locked().restore_from_trash(id)?;
+    emit_history_changed(&app);
+    Ok(restored)
+}
+
+/// Delete the trashed entries for good
+#[tauri::command]
+async fn empty_trash(state: tauri::State<'_, AppState>) -> Result<()> {
+    state.history.locked().empty_trash()
+}
+
+/// Tell the windows the history changed so they reload it
+fn emit_history_changed(app: &tauri::AppHandle) {
+    let _ = app.emit("history-changed", ());
+    jumplist::update(app);
+}
+
+/// Expire history entries past `history.retention_days` every hour; the
+/// startup sweep is done when the history is loaded
+fn spawn_retention_sweeper(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(60 * 60));
+
+        match app.state::<AppState>().history.locked().expire() {
+            Ok(0) => {}
+            Ok(_) => emit_history_changed(&app),
+            Err(e) => tracing::warn!("Failed to expire old history entries: {}", e),
+        }
+    });
+}
+
+/// Tell the windows a draft was deleted or replaced (not sent for autosaves)
+fn emit_draft_changed(app: &tauri::AppHandle, name: &str) {
+    let _ = app.emit("draft-changed", name);
+}
+
+/// Save text to history and copy to clipboard
+///
+/// With `append`, the text is added to the end of the clipboard text
+/// (separated by `behavior.append_separator`) instead of replacing it. With
+/// `secret` (text revealed from a secret entry), it is saved as a secret entry.
+#[tauri::command]
+async fn paste_and_save(
+    text: String,
+    append: Option<bool>,
+    secret: Option<bool>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<()> {
+    crash::breadcrumb("paste_and_save");
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+
+    // Save to history unless in incognito mode or sharing the screen
+    if saves_history(&state) {
+        let target = state.previous_window.locked().clone();
+        if secret == Some(true) {
+            state.history.locked().add_secret(text.clone(), &target)?;
+        } else {
+            state.history.locked().add(text.clone(), &target)?;
+        }
+        emit_history_changed(&app);
+    }
+
+    // Stop dictation before focus returns to the target app
+    let handle = app.clone();
+    if let Err(e) = blocking(move || stop_dictation(&handle)).await {
+        tracing::warn!("Failed to stop voice input: {}", e);
+    }
+
+    // Copy to clipboard
+    let behavior = state.config.read_locked().behavior.clone();
+    let target = state.previous_window.locked().clone();
+    blocking(move || {
+        let state = app.state::<AppState>();
+        let text = match state.clipboard.read_text() {
+            Some(existing) if append == Some(true) && !existing.is_empty() => {
+                format!("{}{}{}", existing, behavior.append_separator.as_str(), text)
+            }
+            _ => text,
+        };
+        copy_for_paste(&state, &text, &behavior, target)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Copy text that is about to be pasted, remembering what it replaces
+fn copy_for_paste(
+    state: &AppState,
+    text: &str,
+    behavior: &config::BehaviorConfig,
+    target: foreground::WindowInfo,
+) -> Result<()> {
+    let previous = state.clipboard.read_text();
+    state.clipboard.copy(text, behavior)?;
+    *state.last_paste.locked() = Some(LastPaste { previous, target });
+    Ok(())
+}
+
+/// Restore the clipboard from before the last paste, and press the target
+/// app's `undo_shortcut` if it has one and is still in front
+#[tauri::command]
+async fn undo_last_paste(app: tauri::AppHandle) -> Result<()> {
+    crash::breadcrumb("undo_last_paste");
+    blocking(move || undo_paste(&app)).await
+}
+
+fn undo_paste(app: &tauri::AppHandle) -> Result<()> {
+    let state = app.state::<AppState>();
+    let last = state
+        .last_paste
+        .locked()
+        .take()
+        .ok_or_else(|| Error::Config("Nothing to undo".to_string()))?;
+    let (behavior, undo_shortcut) = {
+        let config = state.config.read_locked();
+        (
+            config.behavior.clone(),
+            config.undo_shortcut_for(&last.target),
+        )
+    };
+
+    if let Some(shortcut) = undo_shortcut {
+        let target_in_front = last
+            .target
+            .process_name
+            .as_deref()
+            .is_some_and(|target| foreground::foreground_window().is_process(target));
+        if target_in_front {
+            if foreground::is_foreground_input_blocked() {
+                return Err(Error::InputBlocked);
+            }
+            state.keys.send_shortcut(&shortcut)?;
+        } else {
+            tracing::info!("Paste target is not in front; only restoring the clipboard");
+        }
+    }
+
+    match last.previous {
+        Some(previous) => state.clipboard.copy(&previous, &behavior)?,
+        None => tracing::info!("The clipboard held no text before the paste; leaving it as is"),
+    }
+    Ok(())
+}
+
+/// Append text to a capture target (the first one if none is named) and save it to history
+#[tauri::command]
+async fn capture_to_file(
+    text: String,
+    target: Option<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<String> {
+    crash::breadcrumb("capture_to_file");
+    if text.trim().is_empty() {
+        return Err(Error::Config("Nothing to capture".to_string()));
+    }
+
+    let capture_target = {
+        let config = state.config.read_locked();
+        let targets = &config.capture.targets;
+        match &target {
+            Some(name) => targets.iter().find(|t| &t.name == name).cloned(),
+            None => targets.first().cloned(),
+        }
+    };
+    let capture_target = capture_target.ok_or_else(|| match &target {
+        Some(name) => Error::Config(format!("Unknown capture target: {}", name)),
+        None => Error::Config("No capture targets configured ([[capture.targets]])".to_string()),
+    })?;
+
+    let path = capture::append(&capture_target, &text)?;
+    if saves_history(&state) {
+        let source = state.previous_window.locked().clone();
+        state.history.locked().add(text, &source)?;
+        emit_history_changed(&app);
+    }
+    Ok(path.display().to_string())
+}
+
+/// Paste into the previous app with the configured method (default: press Ctrl+V)
+/// Uses app-specific override if the previous window matches a configured process
+#[tauri::command]
+async fn simulate_paste(
+    text: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<()> {
+    crash::breadcrumb("simulate_paste");
+    // Use the matching app override, if any
+    let previous = state.previous_window.locked().clone();
+    let (method, shortcut, ime_safe) = {
+        let config = state.config.read_locked();
+        (
+            config.paste_method_for(&previous),
+            config.paste_shortcut_for(&previous),
+            config.behavior.ime_safe_paste,
+        )
+    };
+
+    let keys = state.keys.clone();
+    let pasted_text = text.clone();
+    let result = blocking(move || {
+        // Wait for window to hide and focus to return to previous app, unless
+        // it never left (`behavior.no_activate`)
+        let in_front =
+            previous.process_name.is_some() && foreground::foreground_window() == previous;
+        if !in_front {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        if foreground::is_foreground_input_blocked() {
+            return Err(Error::InputBlocked);
+        }
+        keys.paste(method, &shortcut, &pasted_text, ime_safe)
+    })
+    .await;
+
+    match &result {
+        Ok(()) => notify::pasted(&app),
+        Err(e) => notify::paste_failed(
+            &app,
+            &tr("notify.paste_failed", &[("error", &e.to_string())]),
+            text,
+        ),
+    }
+    result
+}
+
+/// Join several history entries (identified by their timestamps, in the
+/// order given) and paste the result into the previous app
+///
+/// Without a separator, `history.join_separator` is used. The joined text is
+/// saved to history like any other paste, as a secret entry if any of the
+/// entries is secret.
+#[tauri::command]
+async fn paste_entries(
+    ids: Vec<chrono::DateTime<chrono::Utc>>,
+    separator: Option<config::JoinSeparator>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<()> {
+    crash::breadcrumb("paste_entries");
+    let separator = separator.unwrap_or_else(|| state.config.read_locked().history.join_separator);
+    let (text, secret) = {
+        let history = state.history.locked();
+        let entries = ids
+            .iter()
+            .map(|id| {
+                history.find(*id).ok_or_else(|| {
+                    Error::NotFound("A selected entry is no longer in the history".to_string())
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let texts = entries
+            .iter()
+            .map(|e| e.plain_text())
+            .collect::<Result<Vec<_>>>()?;
+        (
+            texts.join(separator.as_str()),
+            entries.iter().any(|e| e.secret),
+        )
+    };
+    if text.trim().is_empty() {
+        return Err(Error::Config("Select the entries to paste".to_string()));
+    }
+
+    paste_and_save(text.clone(), None, Some(secret), app.clone(), state.clone()).await?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+    }
+    simulate_paste(text, app, state).await
+}
+
+/// Paste a history or archive entry chosen in the history picker into the
+/// app it was summoned over; the text is saved to history like any other paste
+#[tauri::command]
+async fn paste_history_entry(
+    id: chrono::DateTime<chrono::Utc>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<()> {
+    crash::breadcrumb("paste_history_entry");
+    let entry = find_entry(&state, id).await?;
+    let text = entry.plain_text()?;
+
+    paste_and_save(
+        text.clone(),
+        None,
+        Some(entry.secret),
+        app.clone(),
+        state.clone(),
+    )
+    .await?;
+    if let Some(window) = app.get_webview_window("picker") {
+        let _ = window.hide();
+    }
+    simulate_paste(text, app, state).await
+}
+
+/// Add texts (e.g. selected history entries) to the back of the paste queue
+///
+/// Returns the number of items now queued.
+#[tauri::command]
+async fn queue_paste_items(
+    items: Vec<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize> {
+    crash::breadcrumb("queue_paste_items");
+    let added = state.paste_queue.locked().extend(items);
+    if added == 0 {
+        return Err(Error::Config("Nothing to queue".to_string()));
+    }
+    Ok(paste_queue_changed(&app))
+}
+
+/// Split the buffer into paragraphs (or lines) and add them to the paste queue
+///
+/// Returns the number of items now queued.
+#[tauri::command]
+async fn queue_paste_text(
+    text: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize> {
+    crash::breadcrumb("queue_paste_text");
+    let added = state.paste_queue.locked().extend(queue::split(&text));
+    if added == 0 {
+        return Err(Error::Config("Nothing to queue".to_string()));
+    }
+    Ok(paste_queue_changed(&app))
+}
+
+/// Items waiting in the paste queue, next one first
+#[tauri::command]
+async fn get_paste_queue(state: tauri::State<'_, AppState>) -> Result<Vec<String>> {
+    Ok(state.paste_queue.locked().items())
+}
+
+/// Drop everything in the paste queue
+#[tauri::command]
+async fn clear_paste_queue(app: tauri::AppHandle) -> Result<()> {
+    crash::breadcrumb("clear_paste_queue");
+    clear_queued(&app);
+    Ok(())
+}
+
+/// Get current configuration
+#[tauri::command]
+async fn get_config(state: tauri::State<'_, AppState>) -> Result<config::Config> {
+    Ok(state.config.read_locked().clone())
+}
+
+/// Get the theme with `system` mode resolved and the custom stylesheet loaded
+#[tauri::command]
+async fn get_theme(
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<theme::Theme> {
+    let config = state.config.read_locked().theme.clone();
+    Ok(theme::resolve(&app, &config))
+}
+
+/// Window layout and template for the app the window was summoned over
+#[derive(Debug, Clone, serde::Serialize)]
+struct AppProfile {
+    process_name: Option<String>,
+    exe_path: Option<String>,
+    title: Option<String>,
+    window: config::WindowConfig,
+    template: Option<String>,
+}
+
+/// Get the app profile for the previously focused window; without an app
+/// template, the prompt template associated with its title is used
+#[tauri::command]
+async fn get_app_profile(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<AppProfile> {
+    let previous = state.previous_window.locked().clone();
+    let (window, template) = {
+        let config = state.config.read_locked();
+        let template = config
+            .app_override(&previous)
+            .and_then(|o| o.template.clone())
+            .filter(|t| !t.is_empty());
+        (config.window_for(&previous), template)
+    };
+
+    let template = match template {
+        Some(template) => Some(prompts::fill_window_variables(&template, &previous)),
+        None => {
+            let target = previous.clone();
+            blocking(move || Ok(default_prompt(&app, &target))).await?
+        }
+    };
+
+    Ok(AppProfile {
+        window,
+        process_name: previous.process_name,
+        exe_path: previous.exe_path,
+        title: previous.title,
+        template,
+    })
+}
+
+/// The default prompt template for the previous window's title, rendered
+/// (reads prompts.toml and the clipboard, so run it off the async runtime)
+fn default_prompt(app: &tauri::AppHandle, previous: &foreground::WindowInfo) -> Option<String> {
+    let library = prompts::load().map_err(|e| tracing::warn!("{}", e)).ok()?;
+    let name = library.default_for(previous.title.as_deref())?;
+    let clipboard = app.state::<AppState>().clipboard.read_text();
+    // Counters are left for the user to fill: the window may close without a paste
+    library
+        .render_keeping_counters(name, previous, clipboard.as_deref())
+        .ok()
+        .filter(|t| !t.is_empty())
+}
+
+/// List prompt templates matching `query`, defaults for the previous window first
+#[tauri::command]
+async fn list_prompts(
+    query: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<prompts::PromptSummary>> {
+    let title = state.previous_window.locked().title.clone();
+    Ok(prompts::load()?.list(title.as_deref(), &query))
+}
+
+/// Render a prompt template for the previous window; `values` fill its
+/// variables, unfilled ones without a default stay as `{{name}}`, and so
+/// does `{{clipboard}}` when the clipboard holds no text
+#[tauri::command]
+async fn insert_prompt(
+    name: String,
+    values: Option<HashMap<String, String>>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<String> {
+    crash::breadcrumb("insert_prompt");
+    let previous = state.previous_window.locked().clone();
+    blocking(move || {
+        let clipboard = app.state::<AppState>().clipboard.read_text();
+        prompts::load()?.render(
+            &name,
+            &previous,
+            &values.unwrap_or_default(),
+            clipboard.as_deref(),
+        )
+    })
+    .await
+}
+
+/// Kind (text, image, files), size and start of the clipboard contents
+#[tauri::command]
+async fn get_clipboard_preview(app: tauri::AppHandle) -> Result<clipboard::ClipboardPreview> {
+    blocking(move || app.state::<AppState>().clipboard.preview()).await
+}
+
+/// Advance a named counter and return its new value
+#[tauri::command]
+async fn next_counter(name: String) -> Result<u64> {
+    counters::next(&name)
+}
+
+/// Start a named counter over, so its next value is 1
+#[tauri::command]
+async fn reset_counter(name: String) -> Result<()> {
+    counters::reset(&name)
+}
+
+/// Save a named draft (empty text deletes it)
+#[tauri::command]
+async fn save_draft(name: String, text: String, state: tauri::State<'_, AppState>) -> Result<()> {
+    crash::set_draft(&name, &text);
+    let max_versions = state.config.read_locked().history.draft_versions;
+    state.drafts.save(&name, &text, max_versions)
+}
+
+/// List the names of saved drafts, default first
+#[tauri::command]
+async fn list_drafts(state: tauri::State<'_, AppState>) -> Result<Vec<String>> {
+    state.drafts.flush();
+    drafts::list()
+}
+
+/// Load a named draft
+#[tauri::command]
+async fn load_draft(name: String, state: tauri::State<'_, AppState>) -> Result<String> {
+    state.drafts.flush();
+    drafts::load(&name)
+}
+
+/// Delete a named draft
+#[tauri::command]
+async fn delete_draft(
+    name: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<()> {
+    crash::set_draft(&name, "");
+    state.drafts.flush();
+    let max_versions = state.config.read_locked().history.draft_versions;
+    drafts::snapshot(&name, max_versions, true)?;
+    drafts::delete(&name)?;
+    emit_draft_changed(&app, &name);
+    Ok(())
+}
+
+/// Open the buffer in the external editor and return the edited text
+/// (runs off the main thread until the editor exits)
+#[tauri::command]
+async fn edit_in_external_editor(
+    text: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<String> {
+    crash::breadcrumb("edit_in_external_editor");
+    let editor = state.config.read_locked().behavior.external_editor.clone();
+    blocking(move || editor::edit(&text, &editor)).await
+}
+
+/// List saved earlier versions of all drafts, newest first
+#[tauri::command]
+async fn list_draft_versions(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<drafts::DraftVersion>> {
+    state.drafts.flush();
+    drafts::list_versions()
+}
+
+/// Put an earlier version back into its draft (the current text is kept as a version)
+#[tauri::command]
+async fn restore_draft_version(
+    id: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<drafts::DraftVersion> {
+    state.drafts.flush();
+    let max_versions = state.config.read_locked().history.draft_versions;
+    let version = drafts::restore_version(&id, max_versions)?;
+    crash::set_draft(&version.name, &version.text);
+    emit_draft_changed(&app, &version.name);
+    Ok(version)
+}
+
+/// Trigger Windows voice input with the configured shortcut
+#[tauri::command]
+async fn trigger_voice_input(
+    delay_ms: u32,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<()> {
+    let shortcut = state
+        .config
+        .read_locked()
+        .voice
+        .trigger_shortcut()
+        .to_string();
+    let keys = state.keys.clone();
+    blocking(move || keys.start_voice_input(&shortcut, delay_ms)).await?;
+    dictation::started(&app);
+    Ok(())
+}
+
+/// Stop voice input if it was triggered, before the window is hidden
+#[tauri::command]
+async fn stop_voice_input(app: tauri::AppHandle) -> Result<()> {
+    blocking(move || stop_dictation(&app)).await
+}
+
+/// Get voice toggle state
+#[tauri::command]
+async fn get_voice_toggle(state: tauri::State<'_, AppState>) -> Result<bool> {
+    Ok(*state.voice_toggle_on.locked())
+}
+
+/// Set voice toggle state
+#[tauri::command]
+async fn set_voice_toggle(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    enabled: bool,
+) -> Result<()> {
+    *state.voice_toggle_on.locked() = enabled;
+    save_voice_toggle(enabled);
+    update_voice_tray_state(&app);
+    Ok(())
+}
+
+/// Get the last lines of the log file for the diagnostics pane
+#[tauri::command]
+async fn get_recent_logs(lines: Option<usize>) -> Result<Vec<String>> {
+    logging::recent_logs(lines.unwrap_or(200))
+}
+
+/// Collect a health-check report for support requests
+#[tauri::command]
+async fn run_diagnostics(app: tauri::AppHandle) -> diagnostics::DiagnosticsReport {
+    diagnostics::run(&app)
+}
+
+/// Enable or disable starting the app at login
+#[tauri::command]
+async fn set_autostart(enabled: bool, app: tauri::AppHandle) -> Result<()> {
+    autostart::set(&app, enabled)
+}
+
+/// Get whether incognito mode is on
+#[tauri::command]
+async fn get_incognito(app: tauri::AppHandle) -> bool {
+    is_incognito(&app)
+}
+
+/// Turn incognito mode on or off
+#[tauri::command]
+async fn set_incognito_mode(app: tauri::AppHandle, enabled: bool) {
+    set_incognito(&app, enabled);
+}
+
+/// Get whether history is hidden because the screen is being shared
+#[tauri::command]
+async fn get_screen_share_private(state: tauri::State<'_, AppState>) -> Result<bool> {
+    Ok(state.screen_share.locked().is_private())
+}
+
+/// Get whether the main window is pinned
+#[tauri::command]
+async fn get_pinned(state: tauri::State<'_, AppState>) -> Result<bool> {
+    Ok(*state.pinned.locked())
+}
+
+/// Pin the main window so it is not hidden when it loses focus
+#[tauri::command]
+async fn set_pinned(
+    pinned: bool,
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<()> {
+    *state.pinned.locked() = pinned;
+    let _ = app.emit("pinned-changed", pinned);
+    Ok(())
+}
+
+/// Get whether the main window stays above other windows
+#[tauri::command]
+async fn get_always_on_top(app: tauri::AppHandle) -> bool {
+    window::is_always_on_top(&app)
+}
+
+/// Keep the main window above other windows, or let them cover it
+#[tauri::command]
+async fn set_always_on_top(on: bool, app: tauri::AppHandle) -> Result<()> {
+    window::set_always_on_top(&app, on)
+}
+
+/// Check and save configuration, re-register the launch shortcut and apply window size
+#[tauri::command]
+async fn save_config(
+    new_config: config::Config,
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<()> {
+    crash::breadcrumb("save_config");
+    new_config.validate_all()?;
+    apply_config(&app, &state, new_config, true)
+}
+
+/// Get the translated UI strings of a locale ("en", "ja"), or of the
+/// configured one; untranslated strings are in English
+#[tauri::command]
+async fn get_strings(locale: Option<String>) -> Result<std::collections::BTreeMap<String, String>> {
+    let locale = match locale {
+        Some(locale) => i18n::resolve(&locale),
+        None => i18n::current(),
+    };
+    Ok(i18n::strings(locale))
+}
+
+/// Get one top-level table of the settings (e.g. "window", "shortcuts")
+#[tauri::command]
+async fn get_config_section(
+    section: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<serde_json::Value> {
+    state.config.read_locked().section(&section)
+}
+
+/// Replace one top-level table of the settings, leaving the others as they are
+#[tauri::command]
+async fn set_config_section(
+    section: String,
+    value: serde_json::Value,
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<()> {
+    crash::breadcrumb("set_config_section");
+    let new_config = state.config.read_locked().with_section(&section, value)?;
+    apply_config(&app, &state, new_config, true)
+}
+
+/// Apply a new config to the running app, optionally writing it to the active profile.
+/// An unusable launch shortcut rejects the config; failed global shortcuts are reported
+/// after everything else has been applied.
+fn apply_config(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    new_config: config::Config,
+    save: bool,
+) -> Result<()> {
+    // Swap the global shortcut first so an unusable one rejects the config
+    let (launch_changed, autostart_changed, on_top_changed, locale_changed) = {
+        let config = state.config.read_locked();
+        (
+            config.shortcuts.launch != new_config.shortcuts.launch,
+            config.behavior.autostart != new_config.behavior.autostart,
+            config.window.always_on_top != new_config.window.always_on_top,
+            config.locale != new_config.locale,
+        )
+    };
+    if launch_changed {
+        hotkey::change_launch_shortcut(app, &new_config.shortcuts.launch)?;
+    }
+    // Saving the same launch shortcut retries it if startup had to register a
+    // fallback (or nothing) instead; failing again keeps what is registered
+    let launch_result = if !launch_changed
+        && hotkey::to_shortcut(&new_config.shortcuts.launch)
+            .is_some_and(|shortcut| Some(shortcut) != *state.launch_shortcut.locked())
+    {
+        hotkey::change_launch_shortcut(app, &new_config.shortcuts.launch)
+    } else {
+        Ok(())
+    };
+    if autostart_changed {
+        autostart::apply(app, new_config.behavior.autostart)?;
+    }
+    let global_result = hotkey::register_global_shortcuts(app, &new_config.shortcuts.global);
+    if on_top_changed {
+        if let Err(e) = window::set_always_on_top(app, new_config.window.always_on_top) {
+            tracing::warn!("{}", e);
+        }
+    }
+
+    // Resize main window if it exists (a size set by hand is kept)
+    if let Some(window) = app.get_webview_window("main") {
+        let previous = state.previous_window.locked().clone();
+        let window_config = new_config.window_for(&previous);
+        window::apply_size(app, &window, &window_config);
+        window::apply_appearance(&window, &window_config);
+        let _ = window.emit("window-changed", &window_config);
+    }
+    if locale_changed {
+        i18n::set_locale(&new_config.locale);
+        tray::set_labels(app);
+    }
+    tray::set_window_presets(app, &new_config.window);
+
+    if save {
+        new_config.save()?;
+    }
+    *state.config.write_locked() = new_config;
+    update_voice_tray_state(app);
+    jumplist::update(app);
+    theme::emit(app);
+    let config = state.config.read_locked().clone();
+    let _ = app.emit("config-changed", &config);
+
+    launch_result.map_err(|e| {
+        Error::Shortcut(format!(
+            "Settings applied, but the launch shortcut is still unavailable: {}",
+            e
+        ))
+    })?;
+    global_result.map_err(|e| {
+        Error::Shortcut(format!(
+            "Settings applied, but some global shortcuts failed: {}",
+            e
+        ))
+    })
+}
+
+/// Restore default settings, keeping the old file as `config.toml.bak`
+#[tauri::command]
+async fn reset_config(
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<config::Config> {
+    crash::breadcrumb("reset_config");
+    config::Config::backup()?;
+    apply_config(&app, &state, config::Config::default(), true)?;
+    Ok(state.config.read_locked().clone())
+}
+
+/// Write the current settings to a file of the user's choosing
+#[tauri::command]
+async fn export_config(path: String, state: tauri::State<'_, AppState>) -> Result<()> {
+    let config = state.config.read_locked().clone();
+    config.save_to(std::path::Path::new(&path))?;
+    tracing::info!("Exported config to {}", path);
+    Ok(())
+}
+
+/// Replace the current settings with an exported file, keeping the old file as `config.toml.bak`
+#[tauri::command]
+async fn import_config(
+    path: String,
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<config::Config> {
+    crash::breadcrumb("import_config");
+    let new_config = config::Config::read(std::path::Path::new(&path))?;
+    new_config.validate_all()?;
+    config::Config::backup()?;
+    apply_config(&app, &state, new_config, true)?;
+    tracing::info!("Imported config from {}", path);
+    Ok(state.config.read_locked().clone())
+}
+
+/// Import history and settings from the original Electron prompt-line
+/// (`~/.prompt-line` unless another directory is given)
+#[tauri::command]
+async fn import_legacy_data(
+    dir: Option<String>,
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<legacy::ImportSummary> {
+    crash::breadcrumb("import_legacy_data");
+    let dir = match dir {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => legacy::default_dir()?,
+    };
+    let mut new_config = state.config.read_locked().clone();
+    let summary = legacy::import(&dir, &mut state.history.locked(), &mut new_config)?;
+    tracing::info!("Imported prompt-line data: {:?}", summary);
+    if summary.history_imported > 0 {
+        emit_history_changed(&app);
+    }
+
+    if summary.settings_imported {
+        config::Config::backup()?;
+        apply_config(&app, &state, new_config, true)?;
+    }
+    Ok(summary)
+}
+
+/// Upload a remote backup now; returns false if nothing changed since the last one
+#[tauri::command]
+async fn backup_now(app: tauri::AppHandle) -> Result<bool> {
+    crash::breadcrumb("backup_now");
+    blocking(move || sync::backup(&app)).await
+}
+
+/// Merge the remote backup's history and replace the settings with its config,
+/// keeping the old file as `config.toml.bak`
+#[tauri::command]
+async fn restore_backup(
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<sync::RestoreSummary> {
+    crash::breadcrumb("restore_backup");
+    let sync_config = state.config.read_locked().sync.clone();
+    let (created, new_config, entries) = blocking(move || sync::download(&sync_config)).await?;
+
+    let history_imported = state.history.locked().import(entries)?;
+    if history_imported > 0 {
+        emit_history_changed(&app);
+    }
+    config::Config::backup()?;
+    apply_config(&app, &state, new_config, true)?;
+    tracing::info!("Restored backup from {}", created);
+
+    Ok(sync::RestoreSummary {
+        created,
+        history_imported,
+        config: state.config.read_locked().clone(),
+    })
 }
 
-/// Get the process name of the foreground window
-#[cfg(windows)]
-fn get_foreground_process_name() -> Option<String> {
-    use windows::Win32::Foundation::{CloseHandle, MAX_PATH};
-    use windows::Win32::System::ProcessStatus::K32GetModuleBaseNameW;
-    use windows::Win32::System::Threading::{
-        OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
-    };
-    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+/// Rewrite text with the configured LLM, streaming the reply as `rewrite-chunk`
+/// events; without an `instruction`, `action` picks one (default: rewrite)
+#[tauri::command]
+async fn rewrite_text(
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+    text: String,
+    instruction: Option<String>,
+    action: Option<ai::TextAction>,
+) -> Result<String> {
+    crash::breadcrumb("rewrite_text");
+    let config = state.config.read_locked().ai.clone();
+    let instruction = instruction
+        .filter(|i| !i.trim().is_empty())
+        .unwrap_or_else(|| action.unwrap_or_default().instruction(&config));
+    blocking(move || ai::rewrite(&app, &config, &text, &instruction)).await
+}
 
-    unsafe {
-        let hwnd = GetForegroundWindow();
-        if hwnd.0.is_null() {
-            return None;
-        }
+/// Translate text with the `[translate]` backend into `target_lang`
+/// (`[translate] target_lang` if not given)
+#[tauri::command]
+async fn translate_text(
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+    text: String,
+    target_lang: Option<String>,
+) -> Result<String> {
+    crash::breadcrumb("translate_text");
+    let config = state.config.read_locked().clone();
+    let target_lang = target_lang.unwrap_or_default();
+    blocking(move || translate::translate(&app, &config, &text, &target_lang)).await
+}
 
-        let mut process_id: u32 = 0;
-        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
-        if process_id == 0 {
-            return None;
-        }
+/// Misspelled words of text with suggestions; dictionaries load on the first call
+#[tauri::command]
+async fn spellcheck(
+    text: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<spell::Misspelling>> {
+    let config = state.config.read_locked().spellcheck.clone();
+    blocking(move || app.state::<AppState>().spell.check(&config, &text)).await
+}
 
-        let handle = OpenProcess(
-            PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
-            false,
-            process_id,
-        )
-        .ok()?;
-        if handle.is_invalid() {
-            return None;
-        }
+/// Emojis and symbols matching a query such as "shrug" or ":arrow"
+#[tauri::command]
+async fn search_symbols(
+    query: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<symbols::Symbol>> {
+    let skin_tone = state.config.read_locked().symbols.skin_tone;
+    blocking(move || Ok(symbols::search(&query, skin_tone))).await
+}
 
-        let mut buffer = [0u16; MAX_PATH as usize];
-        let len = K32GetModuleBaseNameW(handle, None, &mut buffer);
-        let _ = CloseHandle(handle);
+/// Remember an inserted emoji or symbol for the picker
+#[tauri::command]
+async fn record_symbol(symbol: String) -> Result<()> {
+    blocking(move || symbols::record(&symbol)).await
+}
 
-        if len == 0 {
-            return None;
-        }
+/// Compute an expression such as "2^10", "sqrt(2) * 3" or "5 km in mi"
+#[tauri::command]
+async fn evaluate_expression(expr: String) -> Result<String> {
+    blocking(move || calc::evaluate(&expr)).await
+}
 
-        Some(String::from_utf16_lossy(&buffer[..len as usize]))
-    }
+/// A UUID, timestamp, password or lorem ipsum text for the command palette
+#[tauri::command]
+async fn generate_text(
+    kind: generate::GeneratorKind,
+    options: Option<generate::GenerateOptions>,
+) -> Result<String> {
+    let options = options.unwrap_or_default();
+    blocking(move || generate::generate(kind, &options)).await
 }
 
-#[cfg(not(windows))]
-fn get_foreground_process_name() -> Option<String> {
-    None
+/// Matches of a regex in text (ripgrep syntax; flags such as "i", "w", "F")
+#[tauri::command]
+async fn regex_find(text: String, pattern: String, flags: String) -> Result<Vec<find::Match>> {
+    blocking(move || find::find(&text, &pattern, &flags)).await
 }
 
-/// Get history entries, optionally filtered by query
+/// Replace every match of a regex in text; `$1` / `${name}` insert groups
 #[tauri::command]
-fn get_history(query: String, state: tauri::State<'_, AppState>) -> Vec<history::HistoryEntry> {
-    state.history.lock().unwrap().search(&query)
+async fn regex_replace(
+    text: String,
+    pattern: String,
+    replacement: String,
+    flags: String,
+) -> Result<find::Replaced> {
+    crash::breadcrumb("regex_replace");
+    blocking(move || find::replace(&text, &pattern, &replacement, &flags)).await
 }
 
-/// Clear all history entries
+/// Markdown as sanitized HTML for the preview pane
 #[tauri::command]
-fn clear_history(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    state.history.lock().unwrap().clear()
+async fn render_markdown(text: String) -> Result<String> {
+    blocking(move || Ok(markdown::to_html(&text))).await
 }
 
-/// Save text to history and copy to clipboard
+/// Code as highlighted HTML spans; the language is detected without a hint
 #[tauri::command]
-fn paste_and_save(text: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    if text.trim().is_empty() {
-        return Ok(());
-    }
+async fn highlight_code(text: String, lang_hint: Option<String>) -> Result<highlight::Highlighted> {
+    blocking(move || Ok(highlight::highlight(&text, lang_hint.as_deref()))).await
+}
 
-    // Save to history
-    state.history.lock().unwrap().add(text.clone())?;
+/// URLs, emails, existing file paths and hex colors in text
+#[tauri::command]
+async fn analyze_text(text: String) -> Result<Vec<links::Detected>> {
+    blocking(move || Ok(links::analyze(&text))).await
+}
 
-    // Copy to clipboard
-    clipboard::copy_to_clipboard(&text)?;
+/// Open an http(s) or mailto link with the default app
+#[tauri::command]
+async fn open_url(url: String) -> Result<()> {
+    crash::breadcrumb("open_url");
+    blocking(move || links::open_url(&url)).await
+}
 
-    Ok(())
+/// Show a file or folder in Explorer
+#[tauri::command]
+async fn reveal_in_explorer(path: String) -> Result<()> {
+    crash::breadcrumb("reveal_in_explorer");
+    blocking(move || links::reveal(&path)).await
 }
 
-/// Simulate paste shortcut (configurable, default: Ctrl+V)
-/// Uses app-specific override if the previous window matches a configured process
+/// Count the tokens of text for an LLM model (`[ai] model` if not given)
 #[tauri::command]
-fn simulate_paste(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    // Wait for window to hide and focus to return to previous app
-    std::thread::sleep(std::time::Duration::from_millis(100));
+async fn count_tokens(
+    text: String,
+    model: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<tokens::TokenCount> {
+    let model = model.unwrap_or_else(|| state.config.read_locked().ai.model.clone());
+    blocking(move || Ok(tokens::count(&text, &model))).await
+}
 
-    let config = state.config.lock().unwrap();
-    let previous_process = state.previous_process.lock().unwrap();
+/// Store a secret in the OS credential store; an empty value removes it
+#[tauri::command]
+async fn set_secret(name: secrets::Secret, value: String) -> Result<()> {
+    crash::breadcrumb("set_secret");
+    blocking(move || secrets::set(name, &value)).await
+}
 
-    // Find matching app override
-    let shortcut = if let Some(ref process_name) = *previous_process {
-        let process_lower = process_name.to_lowercase();
-        config
-            .behavior
-            .app_overrides
-            .iter()
-            .find(|o| !o.process_name.is_empty() && o.process_name.to_lowercase() == process_lower)
-            .map(|o| o.shortcut.clone())
-            .unwrap_or_else(|| config.behavior.simulate_paste_shortcut.clone())
-    } else {
-        config.behavior.simulate_paste_shortcut.clone()
-    };
+/// Whether a secret is stored; its value never leaves the backend
+#[tauri::command]
+async fn get_secret(name: secrets::Secret) -> Result<bool> {
+    blocking(move || Ok(secrets::get(name)?.is_some())).await
+}
 
-    drop(config);
-    drop(previous_process);
+/// Forget the main window's manual size and position and return to the configured layout
+#[tauri::command]
+async fn reset_window_geometry(
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<()> {
+    window::reset(&app);
+    if let Some(main) = app.get_webview_window("main") {
+        let previous = state.previous_window.locked().clone();
+        let config = state.config.read_locked().window_for(&previous);
+        window::place(&app, &main, &config);
+    }
+    Ok(())
+}
 
-    clipboard::simulate_paste(&shortcut)
+/// Get the current layout mode (compact or expanded)
+#[tauri::command]
+async fn get_window_mode(app: tauri::AppHandle) -> config::WindowMode {
+    window::mode(&app)
 }
 
-/// Get current configuration
+/// Switch between the compact bar and the expanded editor, resizing the window
 #[tauri::command]
-fn get_config(state: tauri::State<'_, AppState>) -> config::Config {
-    state.config.lock().unwrap().clone()
+async fn set_window_mode(
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+    mode: config::WindowMode,
+) -> Result<()> {
+    if let Some(main) = app.get_webview_window("main") {
+        let previous = state.previous_window.locked().clone();
+        let config = state.config.read_locked().window_for(&previous);
+        window::set_mode(&app, &main, &config, mode);
+    }
+    let _ = app.emit("mode-changed", mode);
+    Ok(())
 }
 
-/// Get draft file path
-fn draft_path() -> Result<std::path::PathBuf, String> {
-    let config_dir = directories::ProjectDirs::from("com", "prompt-line", "prompt-line-rs")
-        .ok_or_else(|| "Failed to get config directory".to_string())?;
-    Ok(config_dir.data_dir().join("draft.txt"))
+/// List the available config profiles, default first
+#[tauri::command]
+async fn list_profiles() -> Result<Vec<String>> {
+    config::list_profiles()
+}
+
+/// Get the name of the active config profile
+#[tauri::command]
+async fn get_active_profile() -> String {
+    config::active_profile()
 }
 
-/// Save draft text
+/// Switch to another config profile and remember it for the next launch
 #[tauri::command]
-fn save_draft(text: String) -> Result<(), String> {
-    let path = draft_path()?;
+async fn switch_profile(
+    name: String,
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<()> {
+    change_profile(&app, &state, &name)
+}
 
-    // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
+fn change_profile(app: &tauri::AppHandle, state: &AppState, name: &str) -> Result<()> {
+    crash::breadcrumb("switch_profile");
+    if !config::list_profiles()?
+        .iter()
+        .any(|profile| profile == name)
+    {
+        return Err(Error::Config(format!("Unknown profile: {}", name)));
     }
+    let new_config = config::Config::load_from(&config::profile_path(name)?)?;
+    config::set_active_profile(name)?;
+
+    let result = apply_config(app, state, new_config, false);
+    tracing::info!("Switched to profile {}", name);
+    tray::set_active_profile(app, name);
+    let _ = app.emit("profile-changed", name);
+    result
+}
 
-    std::fs::write(&path, &text).map_err(|e| format!("Failed to save draft: {}", e))?;
+/// Switch profile from the tray, reporting failures as a notification
+pub(crate) fn switch_profile_from_tray(app: &tauri::AppHandle, name: &str) {
+    let state = app.state::<AppState>();
+    if let Err(e) = change_profile(app, &state, name) {
+        tracing::error!("{}", e);
+        notify::error(app, &e.to_string());
+    }
+    // Re-sync the checks even on failure (check items toggle themselves)
+    tray::set_active_profile(app, &config::active_profile());
+}
 
-    Ok(())
+/// List the window preset names from `[window.presets]`
+#[tauri::command]
+async fn list_window_presets(state: tauri::State<'_, AppState>) -> Result<Vec<String>> {
+    let config = state.config.read_locked();
+    Ok(config.window.presets.keys().cloned().collect())
 }
 
-/// Load draft text
+/// Activate a window preset (empty for none), applying its size and font right away
 #[tauri::command]
-fn load_draft() -> Result<String, String> {
-    let path = draft_path()?;
+async fn set_window_preset(
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+    name: String,
+) -> Result<()> {
+    change_window_preset(&app, &state, &name)
+}
+
+fn change_window_preset(app: &tauri::AppHandle, state: &AppState, name: &str) -> Result<()> {
+    let mut new_config = state.config.read_locked().clone();
+    if !name.is_empty() && !new_config.window.presets.contains_key(name) {
+        return Err(Error::Config(format!("Unknown window preset: {}", name)));
+    }
+    new_config.window.preset = name.to_string();
+    apply_config(app, state, new_config, true)?;
+    tracing::info!("Switched to window preset {:?}", name);
+    Ok(())
+}
 
-    if !path.exists() {
-        return Ok(String::new());
+/// Switch window preset from the tray, reporting failures as a notification
+pub(crate) fn switch_window_preset_from_tray(app: &tauri::AppHandle, name: &str) {
+    let state = app.state::<AppState>();
+    if let Err(e) = change_window_preset(app, &state, name) {
+        tracing::error!("{}", e);
+        notify::error(app, &e.to_string());
     }
+    // Re-sync the checks even on failure (check items toggle themselves)
+    let window = state.config.read_locked().window.clone();
+    tray::set_window_presets(app, &window);
+}
 
-    std::fs::read_to_string(&path).map_err(|e| format!("Failed to load draft: {}", e))
+/// Get the key names accepted in global shortcut strings
+#[tauri::command]
+async fn get_shortcut_keys() -> Vec<&'static str> {
+    hotkey::key_names()
 }
 
-/// Clear draft
+/// Normalize a global shortcut string for display (e.g. "ctrl+shift+f5" -> "Ctrl+Shift+F5")
 #[tauri::command]
-fn clear_draft() -> Result<(), String> {
-    let path = draft_path()?;
+async fn normalize_shortcut(shortcut: String) -> Result<String> {
+    hotkey::normalize_shortcut(&shortcut)
+}
 
-    if path.exists() {
-        std::fs::remove_file(&path).map_err(|e| format!("Failed to clear draft: {}", e))?;
-    }
+/// Get the launch shortcut actually registered (may differ from config if a fallback was used)
+#[tauri::command]
+async fn get_effective_shortcut(
+    state: tauri::State<'_, AppState>,
+) -> Result<hotkey::EffectiveShortcut> {
+    Ok(state.effective_shortcut.locked().clone())
+}
 
-    Ok(())
+/// Pause or resume all global hotkeys (gaming mode)
+#[tauri::command]
+async fn set_hotkeys_paused(paused: bool, app: tauri::AppHandle) {
+    hotkey::set_paused(&app, paused);
 }
 
-/// Trigger Windows voice input (Win+H)
+/// Get whether global hotkeys are paused
 #[tauri::command]
-fn trigger_voice_input(delay_ms: u32) -> Result<(), String> {
-    clipboard::trigger_voice_input(delay_ms)
+async fn get_hotkeys_paused(state: tauri::State<'_, AppState>) -> Result<bool> {
+    Ok(state.hotkey_pause.locked().is_paused())
 }
 
+/// Validate global shortcut syntax without registering it
+#[tauri::command]
+async fn parse_shortcut_check(shortcut: String) -> Result<()> {
+    hotkey::check_shortcut(&shortcut).map(|_| ())
+}
 
-/// Get voice toggle state
+/// App windows on screen with their process names and icons, for adding app overrides
 #[tauri::command]
-fn get_voice_toggle(state: tauri::State<'_, AppState>) -> bool {
-    *state.voice_toggle_on.lock().unwrap()
+async fn list_running_apps() -> Result<Vec<foreground::RunningApp>> {
+    blocking(|| Ok(foreground::running_apps())).await
 }
 
-/// Set voice toggle state
+/// Wait for the user to click (or switch to) another app's window and return
+/// its process name and title; `None` if nothing was picked in time
 #[tauri::command]
-fn set_voice_toggle(state: tauri::State<'_, AppState>, enabled: bool) {
-    *state.voice_toggle_on.lock().unwrap() = enabled;
+async fn pick_window() -> Result<Option<foreground::WindowInfo>> {
+    blocking(foreground::pick_window).await
 }
 
-/// Save configuration and apply window size
+/// Wait for the user to press a key combination and return it normalized
+/// ("Ctrl+Shift+K"); `None` if cancelled with Escape or nothing was pressed
 #[tauri::command]
-fn save_config(
-    new_config: config::Config,
-    state: tauri::State<'_, AppState>,
-    app: tauri::AppHandle,
-) -> Result<(), String> {
-    // Resize main window if it exists
-    if let Some(window) = app.get_webview_window("main") {
-        let width = new_config.window.width_pixels();
-        let height = new_config.window.height_pixels();
-        let size = tauri::LogicalSize::new(width, height);
-        let _ = window.set_size(size);
-    }
+async fn capture_next_shortcut() -> Result<Option<String>> {
+    blocking(keycapture::capture_next).await
+}
 
-    new_config.save()?;
-    let mut config = state.config.lock().unwrap();
-    *config = new_config;
-    Ok(())
+/// Check whether a global shortcut is free to register (not taken by another app)
+#[tauri::command]
+async fn test_shortcut(shortcut: String, app: tauri::AppHandle) -> Result<bool> {
+    hotkey::is_shortcut_available(&app, &shortcut)
 }
 
 /// Show settings window
@@ -231,75 +1500,345 @@ fn show_settings_window(app: &tauri::AppHandle) {
         return;
     }
 
-    // Create new settings window (always_on_top so it appears above main window)
+    // Create new settings window, on top if the main window is so it appears above it
     let _window =
         WebviewWindowBuilder::new(app, "settings", WebviewUrl::App("settings.html".into()))
             .title("Settings - prompt-line-rs")
             .inner_size(500.0, 450.0)
             .resizable(true)
             .center()
-            .always_on_top(true)
+            .always_on_top(window::is_always_on_top(app))
             .build();
 }
 
-/// Parse a shortcut string like "Ctrl+Shift+Space" into Modifiers and Code
-fn parse_shortcut(shortcut_str: &str) -> Option<(Option<Modifiers>, Code)> {
-    let parts: Vec<&str> = shortcut_str.split('+').map(|s| s.trim()).collect();
-    if parts.is_empty() {
-        return None;
-    }
-
-    let mut modifiers = Modifiers::empty();
-    let mut key_code = None;
-
-    for part in &parts {
-        match part.to_lowercase().as_str() {
-            "ctrl" | "control" => modifiers |= Modifiers::CONTROL,
-            "shift" => modifiers |= Modifiers::SHIFT,
-            "alt" => modifiers |= Modifiers::ALT,
-            "win" | "super" | "cmd" | "command" => modifiers |= Modifiers::SUPER,
-            "space" => key_code = Some(Code::Space),
-            "enter" | "return" => key_code = Some(Code::Enter),
-            "escape" | "esc" => key_code = Some(Code::Escape),
-            "tab" => key_code = Some(Code::Tab),
-            "a" => key_code = Some(Code::KeyA),
-            "b" => key_code = Some(Code::KeyB),
-            "c" => key_code = Some(Code::KeyC),
-            "d" => key_code = Some(Code::KeyD),
-            "e" => key_code = Some(Code::KeyE),
-            "f" => key_code = Some(Code::KeyF),
-            "g" => key_code = Some(Code::KeyG),
-            "h" => key_code = Some(Code::KeyH),
-            "i" => key_code = Some(Code::KeyI),
-            "j" => key_code = Some(Code::KeyJ),
-            "k" => key_code = Some(Code::KeyK),
-            "l" => key_code = Some(Code::KeyL),
-            "m" => key_code = Some(Code::KeyM),
-            "n" => key_code = Some(Code::KeyN),
-            "o" => key_code = Some(Code::KeyO),
-            "p" => key_code = Some(Code::KeyP),
-            "q" => key_code = Some(Code::KeyQ),
-            "r" => key_code = Some(Code::KeyR),
-            "s" => key_code = Some(Code::KeyS),
-            "t" => key_code = Some(Code::KeyT),
-            "u" => key_code = Some(Code::KeyU),
-            "v" => key_code = Some(Code::KeyV),
-            "w" => key_code = Some(Code::KeyW),
-            "x" => key_code = Some(Code::KeyX),
-            "y" => key_code = Some(Code::KeyY),
-            "z" => key_code = Some(Code::KeyZ),
-            _ => {}
+/// Show the history picker over the foreground app (global shortcut action)
+///
+/// The picker is a small window with only the searchable history list; the
+/// chosen entry is pasted into the app it was summoned over. It is created on
+/// first use and hides when it loses focus.
+fn show_history_picker(app: &tauri::AppHandle) {
+    // Over the main window, the entry goes where the main window's text would
+    let over_main = app
+        .get_webview_window("main")
+        .is_some_and(|window| window.is_focused().unwrap_or(false));
+    if !over_main {
+        *app.state::<AppState>().previous_window.locked() = foreground::foreground_window();
+    }
+
+    if let Some(window) = app.get_webview_window("picker") {
+        let _ = window.center();
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = app.emit_to("picker", "picker-shown", ());
+        return;
+    }
+
+    let built = WebviewWindowBuilder::new(app, "picker", WebviewUrl::App("picker.html".into()))
+        .title("History - prompt-line-rs")
+        .inner_size(480.0, 360.0)
+        .decorations(false)
+        .skip_taskbar(true)
+        .always_on_top(true)
+        .center()
+        .build();
+    if let Err(e) = built {
+        notify::error(
+            app,
+            &tr("notify.picker_failed", &[("error", &e.to_string())]),
+        );
+    }
+}
+
+/// Paste the most recent history entry into the focused app (global shortcut action)
+fn paste_last_entry(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        let Some(entry) = state.history.locked().latest().cloned() else {
+            return;
+        };
+        let text = match entry.plain_text() {
+            Ok(text) => text,
+            Err(e) => {
+                notify::error(
+                    &app,
+                    &tr("notify.paste_last_failed", &[("error", &e.to_string())]),
+                );
+                return;
+            }
+        };
+
+        match paste_into_foreground(&app, &text) {
+            Ok(()) => notify::pasted(&app),
+            Err(e) => notify::paste_failed(
+                &app,
+                &tr("notify.paste_last_failed", &[("error", &e.to_string())]),
+                text,
+            ),
+        }
+    });
+}
+
+/// Paste text into the focused app again ("Retry" on a failed paste toast)
+#[cfg(windows)]
+fn retry_paste(app: &tauri::AppHandle, text: String) {
+    let app = app.clone();
+    std::thread::spawn(move || match paste_into_foreground(&app, &text) {
+        Ok(()) => notify::pasted(&app),
+        Err(e) => notify::paste_failed(
+            &app,
+            &tr("notify.paste_failed", &[("error", &e.to_string())]),
+            text,
+        ),
+    });
+}
+
+/// Put text on the clipboard without pasting it ("Copy Only" on a failed paste toast)
+#[cfg(windows)]
+fn copy_only(app: &tauri::AppHandle, text: &str) {
+    let state = app.state::<AppState>();
+    let behavior = state.config.read_locked().behavior.clone();
+    match state.clipboard.copy(text, &behavior) {
+        Ok(()) => notify::info(app, &t("notify.copied")),
+        Err(e) => notify::error(app, &tr("notify.copy_failed", &[("error", &e.to_string())])),
+    }
+}
+
+/// Paste a history entry into the focused app (jump list item)
+fn paste_entry(app: &tauri::AppHandle, id: &str) {
+    let app = app.clone();
+    let id = id.to_string();
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        let text = chrono::DateTime::parse_from_rfc3339(&id)
+            .ok()
+            .and_then(|id| state.history.locked().find(id.to_utc()).cloned())
+            .ok_or_else(|| Error::NotFound("History entry not found".to_string()))
+            .and_then(|entry| entry.plain_text());
+        let text = match text {
+            Ok(text) => text,
+            Err(e) => {
+                notify::error(
+                    &app,
+                    &tr("notify.paste_entry_failed", &[("error", &e.to_string())]),
+                );
+                return;
+            }
+        };
+        match paste_into_foreground(&app, &text) {
+            Ok(()) => notify::pasted(&app),
+            Err(e) => notify::paste_failed(
+                &app,
+                &tr("notify.paste_entry_failed", &[("error", &e.to_string())]),
+                text,
+            ),
         }
+    });
+}
+
+/// Carry out a request given on the command line, to this launch or a later one
+fn run_request(app: &tauri::AppHandle, request: instance::Request) {
+    // A jump list item leaves the taskbar focused; pastes go to the app below it
+    foreground::leave_taskbar();
+    match request {
+        instance::Request::Show => show_main_window(app),
+        instance::Request::Action(name) => match hotkey::ShortcutAction::parse(&name) {
+            Some(action) => hotkey::run_action(app, &action),
+            None => notify::error(app, &tr("notify.unknown_action", &[("name", &name)])),
+        },
+        instance::Request::PasteEntry(id) => paste_entry(app, &id),
     }
+}
 
-    key_code.map(|code| {
-        let mods = if modifiers.is_empty() {
-            None
-        } else {
-            Some(modifiers)
+/// Paste a prompt template, filled for the focused window, into it (global shortcut action)
+///
+/// Variables without a default stay as `{{name}}` to type over.
+fn paste_snippet(app: &tauri::AppHandle, name: &str) {
+    let app = app.clone();
+    let name = name.to_string();
+    std::thread::spawn(move || {
+        let target = foreground::foreground_window();
+        let clipboard = app.state::<AppState>().clipboard.read_text();
+        let result = prompts::load()
+            .and_then(|library| {
+                library.render(&name, &target, &HashMap::new(), clipboard.as_deref())
+            })
+            .and_then(|text| paste_into_foreground(&app, &text));
+        match result {
+            Ok(()) => notify::pasted(&app),
+            Err(e) => notify::error(
+                &app,
+                &tr(
+                    "notify.snippet_failed",
+                    &[("name", &name), ("error", &e.to_string())],
+                ),
+            ),
+        }
+    });
+}
+
+/// Paste the next item of the paste queue into the focused app (global shortcut action)
+///
+/// An item that fails to paste stays at the front of the queue.
+fn paste_next_queued(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        let Some(text) = state.paste_queue.locked().pop() else {
+            notify::info(&app, &t("notify.queue_empty"));
+            return;
         };
-        (mods, code)
-    })
+
+        let result = paste_into_foreground(&app, &text);
+        if result.is_err() {
+            state.paste_queue.locked().push_front(text);
+        }
+        let remaining = paste_queue_changed(&app);
+        match result {
+            Ok(()) if remaining == 0 => notify::info(&app, &t("notify.queue_done")),
+            Ok(()) => notify::pasted(&app),
+            Err(e) => notify::error(
+                &app,
+                &tr("notify.queue_failed", &[("error", &e.to_string())]),
+            ),
+        }
+    });
+}
+
+/// Copy text and paste it into the foreground app with its configured method
+fn paste_into_foreground(app: &tauri::AppHandle, text: &str) -> Result<()> {
+    paste_into(
+        &app.state::<AppState>(),
+        foreground::foreground_window(),
+        text,
+    )
+}
+
+/// Copy text and paste it into `target` with its paste method and shortcut
+fn paste_into(state: &AppState, target: foreground::WindowInfo, text: &str) -> Result<()> {
+    let (method, shortcut, behavior) = {
+        let config = state.config.read_locked();
+        (
+            config.paste_method_for(&target),
+            config.paste_shortcut_for(&target),
+            config.behavior.clone(),
+        )
+    };
+
+    copy_for_paste(state, text, &behavior, target)?;
+    if foreground::is_foreground_input_blocked() {
+        return Err(Error::InputBlocked);
+    }
+    state
+        .keys
+        .paste(method, &shortcut, text, behavior.ime_safe_paste)
+}
+
+/// Undo the last paste (global shortcut action)
+fn undo_last_paste_from_hotkey(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || match undo_paste(&app) {
+        Ok(()) => notify::info(&app, &t("notify.undone")),
+        Err(e) => notify::error(
+            &app,
+            &tr("notify.undo_failed", &[("error", &e.to_string())]),
+        ),
+    });
+}
+
+/// Drop everything in the paste queue (also from the tray)
+fn clear_queued(app: &tauri::AppHandle) {
+    app.state::<AppState>().paste_queue.locked().clear();
+    paste_queue_changed(app);
+}
+
+/// Show the number of queued items in the tray and UI; returns it
+fn paste_queue_changed(app: &tauri::AppHandle) -> usize {
+    let queued = app.state::<AppState>().paste_queue.locked().len();
+    tray::set_tray_state(app, |tray| tray.queued = queued);
+    let _ = app.emit("paste-queue-changed", queued);
+    queued
+}
+
+/// Flip the voice input toggle and notify the main window
+fn toggle_voice(app: &tauri::AppHandle) {
+    if let Some(state) = app.try_state::<AppState>() {
+        let mut toggle_on = state.voice_toggle_on.locked();
+        *toggle_on = !*toggle_on;
+        save_voice_toggle(*toggle_on);
+        let _ = app.emit("voice-toggle-changed", *toggle_on);
+    }
+    update_voice_tray_state(app);
+}
+
+/// Remember the voice toggle for the next run
+fn save_voice_toggle(enabled: bool) {
+    let mut session = session::Session::load();
+    session.voice_toggle_on = enabled;
+    if let Err(e) = session.save() {
+        tracing::warn!("{}", e);
+    }
+}
+
+/// Show the voice badge when voice input is enabled and toggled on
+fn update_voice_tray_state(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let voice_on = state.config.read_locked().voice.enabled && *state.voice_toggle_on.locked();
+    tray::set_tray_state(app, |tray| tray.voice_on = voice_on);
+}
+
+fn is_incognito(app: &tauri::AppHandle) -> bool {
+    *app.state::<AppState>().incognito.locked()
+}
+
+/// Turn incognito mode on or off and reflect it in the tray and UI
+fn set_incognito(app: &tauri::AppHandle, enabled: bool) {
+    *app.state::<AppState>().incognito.locked() = enabled;
+    tray::set_tray_state(app, |tray| tray.incognito = enabled);
+    update_clipboard_privacy(app);
+    let _ = app.emit("incognito-changed", enabled);
+}
+
+/// Whether pasted text goes to history: not in incognito mode or while the
+/// screen is shared (`behavior.screen_share_privacy`)
+fn saves_history(state: &AppState) -> bool {
+    !*state.incognito.locked() && !state.screen_share.locked().is_private()
+}
+
+/// Keep copies out of clipboard history whenever history isn't saved either
+fn update_clipboard_privacy(app: &tauri::AppHandle) {
+    clipboard::set_private(!saves_history(&app.state::<AppState>()));
+}
+
+/// Hide the main window after it lost focus, if enabled and not pinned
+fn hide_on_blur(window: &tauri::Window) {
+    let state = window.state::<AppState>();
+    let enabled = state.config.read_locked().behavior.hide_on_blur;
+    if enabled && !*state.pinned.locked() {
+        let _ = window.hide();
+        stop_dictation_in_background(window.app_handle());
+    }
+}
+
+/// Send the stop shortcut if voice input was triggered and not stopped yet,
+/// so the microphone doesn't keep listening after the window is gone
+fn stop_dictation(app: &tauri::AppHandle) -> Result<()> {
+    let state = app.state::<AppState>();
+    if !std::mem::take(&mut *state.dictation_active.locked()) {
+        return Ok(());
+    }
+    let shortcut = state.config.read_locked().voice.stop_shortcut().to_string();
+    state.keys.stop_voice_input(&shortcut)
+}
+
+/// `stop_dictation` for hides done by the backend, off the event loop
+fn stop_dictation_in_background(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = stop_dictation(&app) {
+            tracing::warn!("Failed to stop voice input: {}", e);
+        }
+    });
 }
 
 /// Toggle window visibility
@@ -307,166 +1846,425 @@ fn toggle_window(app: &tauri::AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
         if window.is_visible().unwrap_or(false) {
             let _ = window.hide();
+            stop_dictation_in_background(app);
         } else {
-            // Record the process name of the foreground window before showing
-            if let Some(state) = app.try_state::<AppState>() {
-                let process_name = get_foreground_process_name();
-                *state.previous_process.lock().unwrap() = process_name;
-            }
-            let _ = window.show();
-            let _ = window.set_focus();
-
-            // Trigger voice input if enabled in config AND toggle is on
-            if let Some(state) = app.try_state::<AppState>() {
-                let config = state.config.lock().unwrap();
-                let voice_enabled = config.voice.enabled;
-                let delay_ms = config.voice.delay_ms;
-                drop(config); // Release lock
-
-                if voice_enabled {
-                    let toggle_on = *state.voice_toggle_on.lock().unwrap();
-                    if toggle_on {
-                        let _ = clipboard::trigger_voice_input(delay_ms);
-                    }
-                }
+            show_main_window(app);
+        }
+    }
+}
+
+/// Show the main window over the current foreground app
+fn show_main_window(app: &tauri::AppHandle) {
+    if !reveal_main_window(app) {
+        return;
+    }
+
+    // Trigger voice input if enabled in config AND toggle (or the app's default) is on
+    if let Some(state) = app.try_state::<AppState>() {
+        let toggle_on = *state.voice_toggle_on.locked();
+        let previous = state.previous_window.locked().clone();
+        let config = state.config.read_locked();
+        let voice_on = config.voice_for(&previous, toggle_on);
+        let delay_ms = config.voice.delay_ms;
+        let shortcut = config.voice.trigger_shortcut().to_string();
+        drop(config); // Release lock
+
+        if voice_on {
+            match state.keys.start_voice_input(&shortcut, delay_ms) {
+                Ok(()) => dictation::started(app),
+                Err(e) => tracing::warn!("Failed to trigger voice input: {}", e),
             }
         }
     }
 }
 
+/// Place and show the main window without triggering voice input
+///
+/// Returns `false` if there is no main window.
+fn reveal_main_window(app: &tauri::AppHandle) -> bool {
+    if let Some(window) = app.get_webview_window("main") {
+        // Record the foreground window before showing
+        let mut no_activate = false;
+        if let Some(state) = app.try_state::<AppState>() {
+            let previous = foreground::foreground_window();
+
+            // Apply per-app window size
+            let (window_config, inactive) = {
+                let config = state.config.read_locked();
+                (config.window_for(&previous), config.behavior.no_activate)
+            };
+            window::place(app, &window, &window_config);
+            no_activate = inactive;
+
+            *state.previous_window.locked() = previous;
+        }
+        if no_activate {
+            window::show_inactive(&window);
+            // No focus event tells the frontend it was shown
+            let _ = app.emit("shown-inactive", ());
+        } else {
+            window::show_active(&window);
+        }
+        true
+    } else {
+        false
+    }
+}
+
+/// Handle the launch shortcut in hold-to-show mode
+///
+/// Pressing shows the window; releasing asks the frontend to paste or hide.
+fn hold_launch(app: &tauri::AppHandle, pressed: bool) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    if pressed {
+        // Key repeat sends Pressed again while held
+        if !window.is_visible().unwrap_or(false) {
+            show_main_window(app);
+        }
+    } else if window.is_visible().unwrap_or(false) {
+        let paste = app
+            .state::<AppState>()
+            .config
+            .read_locked()
+            .behavior
+            .paste_on_release;
+        let _ = window.emit("launch-released", paste);
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Load configuration
+    crash::install();
     let config = config::Config::load().expect("Failed to load config");
+    let _log_guard = logging::init(&config.logging.level);
+    i18n::set_locale(&config.locale);
+
+    // Hand `--show`/`--action`/`--paste-entry` to the running instance if there is one
+    let request = instance::Request::from_args();
+    if let Some(request) = &request {
+        if instance::forward(request) {
+            tracing::info!("Handed {:?} to the running instance", request);
+            return;
+        }
+    }
+    tracing::info!("Starting prompt-line-rs {}", env!("CARGO_PKG_VERSION"));
+    drafts::migrate_legacy_draft();
+    let (clipboard, keys) = input::from_args();
     let launch_shortcut = config.shortcuts.launch.clone();
+    let global_shortcuts = config.shortcuts.global.clone();
+    let autostart = config.behavior.autostart;
+    let start_hidden = config.behavior.start_hidden;
+    let window_config = config.window.clone();
 
     // Initialize history
     let history_path = history::History::default_path().expect("Failed to get history path");
-    let history = history::History::new(history_path, config.history.max_entries)
-        .expect("Failed to initialize history");
+    let history =
+        history::History::new(history_path, &config.history).expect("Failed to initialize history");
+    let retention_days = config.history.retention_days;
 
     tauri::Builder::default()
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
-                .with_handler(|app, _shortcut, event| {
-                    if event.state() == ShortcutState::Pressed {
-                        toggle_window(app);
-                    }
+                .with_handler(|app, shortcut, event| {
+                    hotkey::handle_shortcut(app, shortcut, event.state());
                 })
                 .build(),
         )
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_autostart::init(
+            tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+            None,
+        ))
         .setup(move |app| {
-            let launch_shortcut = launch_shortcut.clone();
-
             // Setup system tray
-            let show_label = format!("Show ({})", &launch_shortcut);
-            let show_item = MenuItem::with_id(app, "show", &show_label, true, None::<&str>)?;
-            let settings_item =
-                MenuItem::with_id(app, "settings", "Settings...", true, None::<&str>)?;
-            let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_item, &settings_item, &quit_item])?;
-
-            let tooltip = format!("prompt-line-rs ({})", &launch_shortcut);
-            let _tray = TrayIconBuilder::new()
-                .icon(
-                    tauri::image::Image::from_bytes(include_bytes!("../icons/32x32.png"))
-                        .expect("Failed to load icon"),
-                )
-                .menu(&menu)
-                .tooltip(&tooltip)
-                .on_menu_event(|app, event| match event.id.as_ref() {
-                    "show" => {
-                        toggle_window(app);
-                    }
-                    "settings" => {
-                        show_settings_window(app);
-                    }
-                    "quit" => {
-                        app.exit(0);
-                    }
-                    _ => {}
-                })
-                .on_tray_icon_event(|tray, event| {
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } = event
-                    {
-                        let app = tray.app_handle();
-                        toggle_window(app);
-                    }
-                })
-                .build(app)?;
+            tray::create(app.handle(), &launch_shortcut, autostart)?;
+            update_voice_tray_state(app.handle());
 
-            // Try to register the configured shortcut first
-            let mut registered = false;
+            crash::report_previous_crash(app.handle());
 
-            if let Some((modifiers, code)) = parse_shortcut(&launch_shortcut) {
-                let shortcut = Shortcut::new(modifiers, code);
-                if app.global_shortcut().register(shortcut).is_ok() {
-                    println!("Registered hotkey: {}", launch_shortcut);
-                    registered = true;
-                }
+            // Keep the login item in line with the config
+            if let Err(e) = autostart::apply(app.handle(), autostart) {
+                tracing::warn!("{}", e);
+            }
+
+            // Register the configured shortcut, falling back to alternatives
+            hotkey::register_launch_with_fallback(app.handle(), &launch_shortcut);
+            if let Err(e) = hotkey::register_global_shortcuts(app.handle(), &global_shortcuts) {
+                notify::error(app.handle(), &e.to_string());
+            }
+            hotkey::spawn_fullscreen_watcher(app.handle());
+            screenshare::spawn_watcher(app.handle());
+            sync::start(app.handle());
+            if retention_days > 0 {
+                spawn_retention_sweeper(app.handle());
             }
 
-            // Fallback shortcuts if configured one fails
-            if !registered {
-                let fallback_shortcuts = [
-                    (
-                        Some(Modifiers::CONTROL | Modifiers::SHIFT),
-                        Code::Space,
-                        "Ctrl+Shift+Space",
-                    ),
-                    (
-                        Some(Modifiers::SUPER | Modifiers::SHIFT),
-                        Code::Space,
-                        "Win+Shift+Space",
-                    ),
-                    (Some(Modifiers::ALT), Code::Space, "Alt+Space"),
-                    (
-                        Some(Modifiers::CONTROL | Modifiers::ALT),
-                        Code::KeyP,
-                        "Ctrl+Alt+P",
-                    ),
-                ];
-
-                for (modifiers, code, name) in fallback_shortcuts {
-                    let shortcut = Shortcut::new(modifiers, code);
-                    if app.global_shortcut().register(shortcut).is_ok() {
-                        println!("Registered fallback hotkey: {}", name);
-                        registered = true;
-                        break;
-                    }
+            if let Some(window) = app.get_webview_window("main") {
+                window::apply_appearance(&window, &window_config);
+                let _ = window.set_always_on_top(window_config.always_on_top);
+            }
+            if start_hidden {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.hide();
                 }
+            } else {
+                show_main_window(app.handle());
             }
 
-            if !registered {
-                eprintln!("Warning: Failed to register any hotkey");
+            if let Err(e) = instance::listen(app.handle(), run_request) {
+                tracing::warn!("{}", e);
+            }
+            jumplist::update(app.handle());
+            if let Some(request) = request {
+                run_request(app.handle(), request);
             }
 
             Ok(())
         })
         .manage(AppState {
             history: Mutex::new(history),
-            config: Mutex::new(config),
-            previous_process: Mutex::new(None),
-            voice_toggle_on: Mutex::new(false),
+            config: RwLock::new(config),
+            previous_window: Mutex::new(foreground::WindowInfo::default()),
+            voice_toggle_on: Mutex::new(session::Session::load().voice_toggle_on),
+            launch_shortcut: Mutex::new(None),
+            shortcut_actions: Mutex::new(HashMap::new()),
+            effective_shortcut: Mutex::new(hotkey::EffectiveShortcut::default()),
+            hotkey_pause: Mutex::new(hotkey::HotkeyPause::default()),
+            incognito: Mutex::new(false),
+            screen_share: Mutex::new(screenshare::ScreenShare::default()),
+            drafts: drafts::DraftManager::spawn(),
+            window_state: Mutex::new(window::WindowState::load()),
+            pinned: Mutex::new(false),
+            paste_queue: Mutex::new(queue::PasteQueue::default()),
+            last_paste: Mutex::new(None),
+            dictation_active: Mutex::new(false),
+            speech: speech::Recorder::default(),
+            llama: llama::LocalServer::default(),
+            spell: spell::SpellChecker::default(),
+            clipboard,
+            keys,
+        })
+        .on_window_event(|window, event| {
+            if window.label() == "picker" {
+                if let tauri::WindowEvent::Focused(false) = event {
+                    let _ = window.hide();
+                }
+                return;
+            }
+            if window.label() != "main" {
+                return;
+            }
+            match event {
+                tauri::WindowEvent::Moved(position) => window::on_moved(window, *position),
+                tauri::WindowEvent::Resized(size) => window::on_resized(window, *size),
+                tauri::WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                    window::on_scale_changed(window, *new_inner_size)
+                }
+                tauri::WindowEvent::Focused(false) => {
+                    window::persist(window.app_handle());
+                    hide_on_blur(window);
+                }
+                tauri::WindowEvent::ThemeChanged(_) => theme::emit(window.app_handle()),
+                _ => {}
+            }
         })
         .invoke_handler(tauri::generate_handler![
             get_history,
+            search_archive,
+            get_history_entry_text,
+            reveal_entry,
+            mark_entry_secret,
             clear_history,
+            compact_history,
+            get_trash,
+            restore_from_trash,
+            empty_trash,
             paste_and_save,
             simulate_paste,
+            paste_entries,
+            paste_history_entry,
+            undo_last_paste,
+            capture_to_file,
             get_config,
+            get_theme,
             save_config,
+            get_config_section,
+            get_strings,
+            set_config_section,
+            reset_config,
+            export_config,
+            import_config,
+            import_legacy_data,
+            backup_now,
+            restore_backup,
+            rewrite_text,
+            translate_text,
+            spellcheck,
+            search_symbols,
+            record_symbol,
+            evaluate_expression,
+            generate_text,
+            regex_find,
+            regex_replace,
+            render_markdown,
+            highlight_code,
+            analyze_text,
+            open_url,
+            reveal_in_explorer,
+            count_tokens,
+            queue_paste_items,
+            queue_paste_text,
+            get_paste_queue,
+            clear_paste_queue,
+            set_secret,
+            get_secret,
+            reset_window_geometry,
+            get_window_mode,
+            set_window_mode,
+            list_profiles,
+            get_active_profile,
+            switch_profile,
+            list_window_presets,
+            set_window_preset,
+            get_app_profile,
+            get_shortcut_keys,
+            normalize_shortcut,
+            parse_shortcut_check,
+            get_effective_shortcut,
+            set_hotkeys_paused,
+            get_hotkeys_paused,
+            get_incognito,
+            set_incognito_mode,
+            get_screen_share_private,
+            get_pinned,
+            set_pinned,
+            get_always_on_top,
+            set_always_on_top,
+            set_autostart,
+            get_recent_logs,
+            run_diagnostics,
+            test_shortcut,
+            capture_next_shortcut,
+            list_running_apps,
+            pick_window,
+            list_prompts,
+            insert_prompt,
+            next_counter,
+            reset_counter,
+            get_clipboard_preview,
             save_draft,
+            list_drafts,
             load_draft,
-            clear_draft,
+            delete_draft,
+            list_draft_versions,
+            edit_in_external_editor,
+            restore_draft_version,
             trigger_voice_input,
+            stop_voice_input,
             get_voice_toggle,
             set_voice_toggle,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Write any draft still waiting for its debounce
+                app.state::<AppState>().drafts.flush();
+                app.state::<AppState>().llama.stop();
+                window::persist(app);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// App state with an empty history in a temporary folder, pasting through `mock`
+    fn test_state(name: &str, mock: Arc<input::Mock>) -> AppState {
+        let config = config::Config::default();
+        let dir = std::env::temp_dir().join(format!(
+            "prompt-line-rs-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let history = history::History::new(dir.join("history.jsonl"), &config.history)
+            .expect("history in a temporary folder");
+        AppState {
+            history: Mutex::new(history),
+            config: RwLock::new(config),
+            previous_window: Mutex::new(foreground::WindowInfo::default()),
+            voice_toggle_on: Mutex::new(false),
+            launch_shortcut: Mutex::new(None),
+            shortcut_actions: Mutex::new(HashMap::new()),
+            effective_shortcut: Mutex::new(hotkey::EffectiveShortcut::default()),
+            hotkey_pause: Mutex::new(hotkey::HotkeyPause::default()),
+            incognito: Mutex::new(false),
+            screen_share: Mutex::new(screenshare::ScreenShare::default()),
+            drafts: drafts::DraftManager::spawn(),
+            window_state: Mutex::new(window::WindowState::default()),
+            pinned: Mutex::new(false),
+            paste_queue: Mutex::new(queue::PasteQueue::default()),
+            last_paste: Mutex::new(None),
+            dictation_active: Mutex::new(false),
+            speech: speech::Recorder::default(),
+            llama: llama::LocalServer::default(),
+            spell: spell::SpellChecker::default(),
+            clipboard: mock.clone(),
+            keys: mock,
+        }
+    }
+
+    fn window(process_name: &str) -> foreground::WindowInfo {
+        foreground::WindowInfo {
+            process_name: Some(process_name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn default_config_passes_validation() {
+        config::Config::default()
+            .validate_all()
+            .expect("default config is valid");
+    }
+
+    #[test]
+    fn paste_copies_and_presses_the_app_shortcut() {
+        let mock = Arc::new(input::Mock::default());
+        *mock.clipboard.locked() = Some("before".to_string());
+        let state = test_state("app-shortcut", mock.clone());
+
+        paste_into(&state, window("alacritty.exe"), "hello").expect("paste");
+
+        assert_eq!(*mock.copied.locked(), ["hello"]);
+        assert_eq!(*mock.pressed.locked(), ["Ctrl+Shift+V"]);
+        let last = state.last_paste.locked().take().expect("last paste");
+        assert_eq!(last.previous.as_deref(), Some("before"));
+        assert!(last.target.is_process("alacritty.exe"));
+    }
+
+    #[test]
+    fn paste_uses_the_default_shortcut_elsewhere() {
+        let mock = Arc::new(input::Mock::default());
+        let state = test_state("default-shortcut", mock.clone());
+        let shortcut = state
+            .config
+            .read_locked()
+            .behavior
+            .simulate_paste_shortcut
+            .clone();
+
+        paste_into(&state, window("notepad.exe"), "hello").expect("paste");
+
+        assert_eq!(*mock.pressed.locked(), [shortcut]);
+        assert_eq!(mock.clipboard.locked().as_deref(), Some("hello"));
+        let last = state.last_paste.locked().take().expect("last paste");
+        assert_eq!(last.previous, None);
+    }
 }
@@ -0,0 +1,251 @@
+//! URLs, emails, file paths and colors in text, and opening them
+//!
+//! `analyze` finds what a history entry or the buffer points at, so the UI can
+//! offer "open" and "reveal" buttons next to it. Paths count only when they
+//! exist on this machine, so prose such as "and/or" never shows up as one.
+
+use regex::Regex;
+use serde::Serialize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::error::{Error, Result};
+
+/// Schemes `open_url` hands to the browser or mail client
+const OPENABLE_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Url,
+    Email,
+    Path,
+    Color,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Detected {
+    pub kind: Kind,
+    /// As written in the text
+    pub text: String,
+    /// UTF-16 offset of the first character
+    pub start: usize,
+    /// UTF-16 offset after the last character
+    pub end: usize,
+    /// What the action uses: the URL, a "mailto:" address, the full path or
+    /// the color in lowercase
+    pub target: String,
+}
+
+/// Everything found in `text`, in order of appearance
+pub fn analyze(text: &str) -> Vec<Detected> {
+    let mut found: Vec<(Range<usize>, Kind, String)> = Vec::new();
+
+    let mut finder = linkify::LinkFinder::new();
+    finder.kinds(&[linkify::LinkKind::Url, linkify::LinkKind::Email]);
+    for link in finder.links(text) {
+        let target = link.as_str().to_string();
+        match link.kind() {
+            linkify::LinkKind::Email => found.push((
+                link.start()..link.end(),
+                Kind::Email,
+                format!("mailto:{}", target),
+            )),
+            _ if is_openable(&target) => found.push((link.start()..link.end(), Kind::Url, target)),
+            _ => {}
+        }
+    }
+    for captures in path_pattern().captures_iter(text) {
+        let Some(path) = captures.get(1).or_else(|| captures.get(2)) else {
+            continue;
+        };
+        // Sentence punctuation after a path
+        let written = path
+            .as_str()
+            .trim_end_matches(['.', ',', ';', ':', ')', ']', '}']);
+        let range = path.start()..path.start() + written.len();
+        if overlaps(&found, &range) {
+            continue;
+        }
+        if let Some(full) = existing_path(written) {
+            found.push((range, Kind::Path, full.display().to_string()));
+        }
+    }
+
+    for captures in color_pattern().captures_iter(text) {
+        let color = captures.get(1).expect("group 1 always takes part");
+        let digits = &color.as_str()[1..];
+        // "#123" is more likely an issue number than a color
+        let short = digits.len() <= 4;
+        // A color inside a link or path is part of its name
+        if (short && digits.chars().all(|c| c.is_ascii_digit())) || overlaps(&found, &color.range())
+        {
+            continue;
+        }
+        found.push((color.range(), Kind::Color, color.as_str().to_lowercase()));
+    }
+
+    found.sort_by_key(|(range, _, _)| range.start);
+    let mut detected = Vec::with_capacity(found.len());
+    let (mut byte, mut utf16) = (0, 0);
+    for (range, kind, target) in found {
+        // Ranges don't overlap, so offsets only move forward
+        utf16 += text[byte..range.start].encode_utf16().count();
+        let start = utf16;
+        utf16 += text[range.clone()].encode_utf16().count();
+        byte = range.end;
+        detected.push(Detected {
+            kind,
+            text: text[range].to_string(),
+            start,
+            end: utf16,
+            target,
+        });
+    }
+    detected
+}
+
+/// Whether `range` shares any byte with something already found
+fn overlaps(found: &[(Range<usize>, Kind, String)], range: &Range<usize>) -> bool {
+    found
+        .iter()
+        .any(|(other, _, _)| range.start < other.end && other.start < range.end)
+}
+
+/// Open a web or mail link with the default browser or mail client
+pub fn open_url(url: &str) -> Result<()> {
+    let url = url.trim();
+    if !is_openable(url) {
+        return Err(Error::Config(format!(
+            "Only web and mail links can be opened: {}",
+            url
+        )));
+    }
+    tracing::info!("Opening {}", url);
+    shell_open(url)
+}
+
+/// Show a file or folder selected in Explorer (the file manager elsewhere)
+pub fn reveal(path: &str) -> Result<()> {
+    let path = existing_path(path.trim())
+        .ok_or_else(|| Error::NotFound(format!("Not found: {}", path)))?;
+    reveal_path(&path)
+}
+
+fn is_openable(url: &str) -> bool {
+    url.split_once(':').is_some_and(|(scheme, _)| {
+        OPENABLE_SCHEMES
+            .iter()
+            .any(|openable| scheme.eq_ignore_ascii_case(openable))
+    })
+}
+
+/// Absolute form of `path` (with `~` expanded) if it exists
+fn existing_path(path: &str) -> Option<PathBuf> {
+    let path = crate::paths::expand_home(path).ok()?;
+    (path.is_absolute() && path.exists()).then_some(path)
+}
+
+/// Drive (`C:\`), UNC (`\\server\share`), home (`~/`) and Unix absolute
+/// paths, either unquoted (up to whitespace) or in double quotes
+fn path_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        let start = r#"(?:[A-Za-z]:[\\/]|\\\\[^\\/\s"<>|?*]+[\\/]|~[\\/]|/[^/\s"<>|?*])"#;
+        Regex::new(&format!(
+            r#""({start}[^"\r\n<>|?*]*)"|(?:^|[\s(\[{{'`=])({start}[^\s"'<>|?*]*)"#
+        ))
+        .expect("path pattern is valid")
+    })
+}
+
+/// `#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa`
+fn color_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?:^|[^\w&#])(#(?:[0-9A-Fa-f]{8}|[0-9A-Fa-f]{6}|[0-9A-Fa-f]{3,4}))\b")
+            .expect("color pattern is valid")
+    })
+}
+
+#[cfg(windows)]
+fn shell_open(target: &str) -> Result<()> {
+    use windows::core::{w, HSTRING, PCWSTR};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    let result = unsafe {
+        ShellExecuteW(
+            HWND::default(),
+            w!("open"),
+            &HSTRING::from(target),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // Values up to 32 are error codes
+    if result.0 as usize <= 32 {
+        return Err(Error::Platform(format!(
+            "Failed to open {} (error {})",
+            target, result.0 as usize
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn shell_open(target: &str) -> Result<()> {
+    std::process::Command::new("xdg-open")
+        .arg(target)
+        .spawn()
+        .map(|_| ())
+        .map_err(Error::io("Failed to start xdg-open"))
+}
+
+#[cfg(windows)]
+fn reveal_path(path: &Path) -> Result<()> {
+    use std::os::windows::process::CommandExt;
+
+    // Explorer wants the quotes around the path only, so the argument is passed raw
+    std::process::Command::new("explorer.exe")
+        .raw_arg(format!("/select,\"{}\"", path.display()))
+        .spawn()
+        .map(|_| ())
+        .map_err(Error::io("Failed to start Explorer"))
+}
+
+#[cfg(not(windows))]
+fn reveal_path(path: &Path) -> Result<()> {
+    // No portable way to select a file; open the folder containing it
+    let folder = if path.is_dir() {
+        path
+    } else {
+        path.parent().unwrap_or(path)
+    };
+    shell_open(&folder.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_in_an_existing_path_is_part_of_the_path() {
+        let dir = std::env::temp_dir().join(format!("prompt-line-rs-links-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temporary folder");
+        let file = dir.join("#ff0000.png");
+        std::fs::write(&file, b"").expect("file with a color in its name");
+        let path = file.display().to_string();
+
+        let detected = analyze(&format!("see \"{}\" and #00ff00", path));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let kinds: Vec<Kind> = detected.iter().map(|found| found.kind).collect();
+        assert_eq!(kinds, [Kind::Path, Kind::Color]);
+        assert_eq!(detected[0].text, path);
+        assert_eq!(detected[1].target, "#00ff00");
+    }
+}
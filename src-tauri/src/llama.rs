@@ -0,0 +1,182 @@
+//! Local GGUF models through llama.cpp
+//!
+//! For text that must not leave the machine, `[ai] provider = "local"` sends
+//! the text actions to llama.cpp's `llama-server` running `ai.local.model`
+//! instead of a cloud API. The server is started on first use on a free
+//! loopback port, kept running while the model and context size stay the
+//! same, and stopped when the app exits. It speaks the same OpenAI-compatible
+//! API, so replies stream through `ai` unchanged.
+//!
+//! Running llama.cpp as a child process keeps its native build (and GPU
+//! backends) out of this crate: any llama.cpp release works.
+
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::LocalModelConfig;
+use crate::error::{Error, Result};
+use crate::lock::MutexExt;
+
+/// Large models can take a while to load from disk
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The running `llama-server`, if any
+#[derive(Default)]
+pub struct LocalServer {
+    running: Mutex<Option<Running>>,
+}
+
+struct Running {
+    child: Child,
+    port: u16,
+    model: PathBuf,
+    context_size: u32,
+}
+
+impl LocalServer {
+    /// Base URL of a server with the configured model, starting (or
+    /// restarting) it if needed. Blocks while the model loads.
+    pub fn endpoint(&self, config: &LocalModelConfig) -> Result<String> {
+        let model = model_path(config)?;
+        let mut running = self.running.locked();
+        if let Some(server) = running.as_mut() {
+            let alive = matches!(server.child.try_wait(), Ok(None));
+            if alive && server.model == model && server.context_size == config.context_size {
+                return Ok(base_url(server.port));
+            }
+        }
+        if let Some(server) = running.take() {
+            server.stop();
+        }
+
+        let server = start(config, model)?;
+        let url = base_url(server.port);
+        *running = Some(server);
+        Ok(url)
+    }
+
+    /// Stop the server; the next request starts it again
+    pub fn stop(&self) {
+        if let Some(server) = self.running.locked().take() {
+            server.stop();
+        }
+    }
+}
+
+impl Running {
+    fn stop(mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        tracing::info!("Stopped llama-server");
+    }
+}
+
+fn base_url(port: u16) -> String {
+    format!("http://127.0.0.1:{}/v1", port)
+}
+
+fn model_path(config: &LocalModelConfig) -> Result<PathBuf> {
+    let model = config.model.trim();
+    if model.is_empty() {
+        return Err(Error::Config(
+            "No local model configured (ai.local.model)".to_string(),
+        ));
+    }
+    let mut path = crate::paths::expand_home(model)?;
+    if path.is_relative() {
+        path = crate::paths::config_dir()?.join(path);
+    }
+    if !path.is_file() {
+        return Err(Error::Config(format!(
+            "Local model not found: {}",
+            path.display()
+        )));
+    }
+    Ok(path)
+}
+
+fn start(config: &LocalModelConfig, model: PathBuf) -> Result<Running> {
+    let port = free_port()?;
+    let program = config.server.trim();
+    let mut command = Command::new(program);
+    command
+        .arg("--model")
+        .arg(&model)
+        .args(["--ctx-size", &config.context_size.to_string()])
+        .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let child = command.spawn().map_err(|e| {
+        Error::Config(format!(
+            "Failed to start {}: {} (install llama.cpp or set ai.local.server)",
+            program, e
+        ))
+    })?;
+    tracing::info!(
+        "Starting llama-server with {} on port {}",
+        model.display(),
+        port
+    );
+
+    let mut server = Running {
+        child,
+        port,
+        model,
+        context_size: config.context_size,
+    };
+    match wait_ready(&mut server) {
+        Ok(()) => Ok(server),
+        Err(e) => {
+            server.stop();
+            Err(e)
+        }
+    }
+}
+
+/// Poll `/health` until the model is loaded
+fn wait_ready(server: &mut Running) -> Result<()> {
+    let url = format!("http://127.0.0.1:{}/health", server.port);
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(2))
+        .build();
+    let started = Instant::now();
+    loop {
+        if let Ok(Some(status)) = server.child.try_wait() {
+            return Err(Error::Platform(format!(
+                "llama-server exited while loading the model ({})",
+                status
+            )));
+        }
+        // 503 while loading, 200 once ready
+        if agent.get(&url).call().is_ok() {
+            tracing::info!("llama-server ready after {:?}", started.elapsed());
+            return Ok(());
+        }
+        if started.elapsed() > STARTUP_TIMEOUT {
+            return Err(Error::Platform(
+                "llama-server did not finish loading the model in time".to_string(),
+            ));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// A loopback port nothing listens on right now
+fn free_port() -> Result<u16> {
+    TcpListener::bind(("127.0.0.1", 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(Error::io("Failed to find a free port"))
+}
@@ -0,0 +1,56 @@
+//! Poison-tolerant access to the shared state locks
+//!
+//! A panic while a lock is held poisons it, and `.lock().unwrap()` would then
+//! panic in every later command until the app is restarted. These helpers log
+//! the poisoning, clear it and carry on with the data as the panicking thread
+//! left it; every value behind these locks stays usable after a partial update.
+
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+pub trait MutexExt<T> {
+    /// Lock, recovering from poisoning instead of panicking
+    fn locked(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> MutexExt<T> for Mutex<T> {
+    fn locked(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(|poisoned| {
+            recovered::<T>();
+            self.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+}
+
+pub trait RwLockExt<T> {
+    /// Lock for reading, recovering from poisoning instead of panicking
+    fn read_locked(&self) -> RwLockReadGuard<'_, T>;
+
+    /// Lock for writing, recovering from poisoning instead of panicking
+    fn write_locked(&self) -> RwLockWriteGuard<'_, T>;
+}
+
+impl<T> RwLockExt<T> for RwLock<T> {
+    fn read_locked(&self) -> RwLockReadGuard<'_, T> {
+        self.read().unwrap_or_else(|poisoned| {
+            recovered::<T>();
+            self.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+
+    fn write_locked(&self) -> RwLockWriteGuard<'_, T> {
+        self.write().unwrap_or_else(|poisoned| {
+            recovered::<T>();
+            self.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+}
+
+fn recovered<T>() {
+    tracing::error!(
+        "Recovered {} lock poisoned by an earlier panic",
+        std::any::type_name::<T>()
+    );
+}
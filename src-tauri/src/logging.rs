@@ -0,0 +1,87 @@
+//! Logging to stdout and rotated log files in the data directory
+
+use std::path::PathBuf;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+
+use crate::error::{Error, Result};
+
+const LOG_FILE_PREFIX: &str = "prompt-line-rs";
+const LOG_FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+
+/// Start logging at the given level ("error", "warn", "info", "debug", "trace")
+///
+/// Keep the returned guard alive for the lifetime of the app so buffered
+/// lines are flushed to the log file.
+pub fn init(level: &str) -> Option<WorkerGuard> {
+    let level = level.parse::<LevelFilter>().unwrap_or(LevelFilter::INFO);
+    let stdout_layer = tracing_subscriber::fmt::layer().with_target(false);
+
+    let file = log_dir().and_then(|dir| {
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix(LOG_FILE_SUFFIX)
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)
+            .map_err(|e| Error::Platform(format!("Failed to open log file: {}", e)))
+    });
+
+    match file {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let file_layer = tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_target(false)
+                .with_writer(writer);
+            tracing_subscriber::registry()
+                .with(level)
+                .with(stdout_layer)
+                .with(file_layer)
+                .init();
+            Some(guard)
+        }
+        Err(e) => {
+            tracing_subscriber::registry()
+                .with(level)
+                .with(stdout_layer)
+                .init();
+            tracing::warn!("{}", e);
+            None
+        }
+    }
+}
+
+/// Get the log directory
+pub fn log_dir() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join("logs"))
+}
+
+/// Read the last `max_lines` lines of the newest log file
+pub fn recent_logs(max_lines: usize) -> Result<Vec<String>> {
+    let dir = log_dir()?;
+    let entries = std::fs::read_dir(&dir).map_err(Error::io("Failed to read log directory"))?;
+
+    let newest = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(LOG_FILE_PREFIX) && name.ends_with(LOG_FILE_SUFFIX)
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path);
+
+    let Some(path) = newest else {
+        return Ok(Vec::new());
+    };
+
+    let content = std::fs::read_to_string(&path).map_err(Error::io("Failed to read log file"))?;
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    Ok(lines[start..].iter().map(|line| line.to_string()).collect())
+}
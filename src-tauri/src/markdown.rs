@@ -0,0 +1,99 @@
+//! Markdown to HTML for the preview pane
+//!
+//! CommonMark with tables, strikethrough, task lists and footnotes. The output
+//! is safe to put into the page as is: raw HTML in the text is escaped and
+//! shown literally, and links or images with a script-capable scheme
+//! (`javascript:`, `vbscript:`, `data:`) lose their target. Code blocks are
+//! colored by `highlight`, using the fence's language if it names one.
+
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+
+use crate::highlight;
+
+/// Schemes that can run code when a link is followed
+const UNSAFE_SCHEMES: &[&str] = &["javascript:", "vbscript:", "data:"];
+
+pub fn to_html(text: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    // Code block contents are collected here and emitted highlighted at its end
+    let mut code: Option<(Option<String>, String)> = None;
+    let events = Parser::new_ext(text, options).filter_map(|event| match event {
+        Event::Start(Tag::CodeBlock(kind)) => {
+            let lang = match kind {
+                CodeBlockKind::Fenced(info) => info.split_whitespace().next().map(str::to_string),
+                CodeBlockKind::Indented => None,
+            };
+            code = Some((lang, String::new()));
+            None
+        }
+        Event::Text(text) if code.is_some() => {
+            if let Some((_, content)) = code.as_mut() {
+                content.push_str(&text);
+            }
+            None
+        }
+        Event::End(TagEnd::CodeBlock) => {
+            let (lang, content) = code.take()?;
+            let highlighted = highlight::highlight(&content, lang.as_deref());
+            Some(Event::Html(CowStr::from(format!(
+                "<pre><code>{}</code></pre>\n",
+                highlighted.html
+            ))))
+        }
+        event => Some(sanitize(event)),
+    });
+
+    let mut output = String::with_capacity(text.len() * 3 / 2);
+    html::push_html(&mut output, events);
+    output
+}
+
+fn sanitize(event: Event<'_>) -> Event<'_> {
+    match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        other => other,
+    }
+}
+
+fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+    // Browsers ignore whitespace and control characters inside the scheme
+    let scheme: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .take(16)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if UNSAFE_SCHEMES
+        .iter()
+        .any(|unsafe_scheme| scheme.starts_with(unsafe_scheme))
+    {
+        CowStr::Borrowed("")
+    } else {
+        url
+    }
+}
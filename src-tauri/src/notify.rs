@@ -0,0 +1,93 @@
+//! Desktop notifications for errors and confirmations
+//!
+//! A failed paste gets "Retry" and "Copy Only" buttons on Windows; the toast
+//! is shown with `tauri-winrt-notification` directly because the notification
+//! plugin has no actions on desktop.
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::lock::RwLockExt;
+use crate::AppState;
+
+const TITLE: &str = "prompt-line-rs";
+
+/// Log an error and show it as a toast
+pub fn error(app: &AppHandle, message: &str) {
+    tracing::error!("{}", message);
+    show(app, message);
+}
+
+/// Show an informational toast
+pub fn info(app: &AppHandle, message: &str) {
+    tracing::info!("{}", message);
+    show(app, message);
+}
+
+/// Confirm a successful paste if `behavior.notify_on_paste` is on
+pub fn pasted(app: &AppHandle) {
+    let enabled = app
+        .state::<AppState>()
+        .config
+        .read_locked()
+        .behavior
+        .notify_on_paste;
+    if enabled {
+        show(app, &crate::i18n::t("notify.pasted"));
+    }
+}
+
+/// Report a failed paste of `text`, offering to retry it or just copy it
+#[cfg(windows)]
+pub fn paste_failed(app: &AppHandle, message: &str, text: String) {
+    use crate::i18n::t;
+    use tauri_winrt_notification::Toast;
+
+    tracing::error!("{}", message);
+    let handler_app = app.clone();
+    let result = Toast::new(&app_id(app))
+        .title(TITLE)
+        .text1(message)
+        .add_button(&t("notify.retry"), "retry")
+        .add_button(&t("notify.copy_only"), "copy")
+        .on_activated(move |action| {
+            // Clicking the toast body sends no action
+            match action.as_deref() {
+                Some("retry") => crate::retry_paste(&handler_app, text.clone()),
+                Some("copy") => crate::copy_only(&handler_app, &text),
+                _ => {}
+            }
+            Ok(())
+        })
+        .show();
+    if let Err(e) = result {
+        tracing::warn!("Failed to show notification with actions: {}", e);
+        show(app, message);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn paste_failed(app: &AppHandle, message: &str, _text: String) {
+    error(app, message);
+}
+
+/// AppUserModelID toasts are shown under: the installer registers the bundle
+/// identifier; a development build borrows PowerShell's, as the plugin does
+#[cfg(windows)]
+fn app_id(app: &AppHandle) -> String {
+    let dev_build = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+        .is_some_and(|dir| dir.ends_with("target/debug") || dir.ends_with("target/release"));
+    if dev_build {
+        tauri_winrt_notification::Toast::POWERSHELL_APP_ID.to_string()
+    } else {
+        app.config().identifier.clone()
+    }
+}
+
+fn show(app: &AppHandle, body: &str) {
+    if let Err(e) = app.notification().builder().title(TITLE).body(body).show() {
+        tracing::warn!("Failed to show notification: {}", e);
+    }
+}
@@ -0,0 +1,106 @@
+//! Config and data directory resolution
+//!
+//! Precedence: `--config <dir>` / `--data-dir <dir>` flags, then the
+//! `PROMPT_LINE_CONFIG_DIR` / `PROMPT_LINE_DATA_DIR` environment variables,
+//! then portable mode (`data/` next to the exe), then the platform directories.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::error::{Error, Result};
+
+const CONFIG_DIR_ENV: &str = "PROMPT_LINE_CONFIG_DIR";
+const DATA_DIR_ENV: &str = "PROMPT_LINE_DATA_DIR";
+/// File next to the executable that turns on portable mode
+const PORTABLE_FLAG_FILE: &str = "portable.flag";
+
+struct Paths {
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+}
+
+static PATHS: OnceLock<Option<Paths>> = OnceLock::new();
+
+/// Directory holding `config.toml`
+pub fn config_dir() -> Result<PathBuf> {
+    paths().map(|p| p.config_dir.clone())
+}
+
+/// Directory holding history, drafts, logs and crash reports
+pub fn data_dir() -> Result<PathBuf> {
+    paths().map(|p| p.data_dir.clone())
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &str) -> Result<PathBuf> {
+    match path.strip_prefix('~') {
+        Some(rest) => {
+            let home = directories::BaseDirs::new()
+                .map(|dirs| dirs.home_dir().to_path_buf())
+                .ok_or_else(|| Error::Platform("Failed to determine home directory".to_string()))?;
+            Ok(home.join(rest.trim_start_matches(['/', '\\'])))
+        }
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+fn paths() -> Result<&'static Paths> {
+    PATHS
+        .get_or_init(resolve)
+        .as_ref()
+        .ok_or_else(|| Error::Platform("Failed to get config directory".to_string()))
+}
+
+fn resolve() -> Option<Paths> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let project_dirs = || directories::ProjectDirs::from("com", "prompt-line", "prompt-line-rs");
+    let portable_dir = portable_dir(&args);
+
+    let config_dir = flag_value(&args, "--config")
+        .or_else(|| env_dir(CONFIG_DIR_ENV))
+        .or_else(|| portable_dir.clone())
+        .or_else(|| project_dirs().map(|d| d.config_dir().to_path_buf()))?;
+    let data_dir = flag_value(&args, "--data-dir")
+        .or_else(|| env_dir(DATA_DIR_ENV))
+        .or_else(|| portable_dir.clone())
+        .or_else(|| project_dirs().map(|d| d.data_dir().to_path_buf()))?;
+
+    Some(Paths {
+        config_dir,
+        data_dir,
+    })
+}
+
+/// Whether config and data live in `data/` next to the executable
+pub fn is_portable() -> bool {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    portable_dir(&args).is_some()
+}
+
+/// `data/` next to the exe when `--portable` is given or `portable.flag` exists
+fn portable_dir(args: &[String]) -> Option<PathBuf> {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let portable =
+        args.iter().any(|arg| arg == "--portable") || exe_dir.join(PORTABLE_FLAG_FILE).exists();
+    portable.then(|| exe_dir.join("data"))
+}
+
+/// Value of `--name <value>` or `--name=<value>`
+fn flag_value(args: &[String], name: &str) -> Option<PathBuf> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == name {
+            return iter.next().map(PathBuf::from);
+        }
+        if let Some(value) = arg.strip_prefix(name).and_then(|v| v.strip_prefix('=')) {
+            return Some(PathBuf::from(value));
+        }
+    }
+    None
+}
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
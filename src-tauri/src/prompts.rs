@@ -0,0 +1,352 @@
+//! Prompt template library
+//!
+//! Reusable prompts live in `prompts.toml` next to config.toml, apart from
+//! history. A template has an optional system prompt and role ahead of its
+//! body, and `{{name}}` variables filled from the caller or the template's
+//! defaults (unfilled ones are left in place to type over). `{{app_name}}`,
+//! `{{app_path}}` and `{{app_title}}` are always filled with the process name,
+//! executable path and title of the window the app was summoned over,
+//! `{{date...}}` with a date (see `dates`), `{{counter:name}}` with the
+//! next value of a `counters` counter, and `{{clipboard}}` with the clipboard
+//! text (left in place when the clipboard holds no text). Dates and counters
+//! are filled first, so variable values, window details and clipboard text
+//! are pasted as they are even when they look like placeholders.
+//!
+//! Templates are matched against the title of the window the app was summoned
+//! over: `titles` makes a template the default there (e.g. "ChatGPT" or
+//! "Claude" in a browser tab), and `variants` swap in model-specific wording.
+//! The file is read on every use, so edits apply without a restart.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+use crate::foreground::WindowInfo;
+
+const PROMPTS_FILE: &str = "prompts.toml";
+
+/// Variables filled from the target window rather than asked for
+const WINDOW_VARIABLES: &[&str] = &["app_name", "app_path", "app_title"];
+
+/// Filled with the clipboard text when `render` gets it
+const CLIPBOARD_VARIABLE: &str = "clipboard";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptLibrary {
+    #[serde(default)]
+    pub prompts: Vec<PromptTemplate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Instructions put first, for chats without a separate system prompt field
+    #[serde(default)]
+    pub system: String,
+    /// Persona line between the system prompt and the body
+    /// (e.g. "You are a senior Rust reviewer.")
+    #[serde(default)]
+    pub role: String,
+    #[serde(default)]
+    pub body: String,
+    /// Default values of `{{name}}` variables
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    /// Window titles (case-insensitive substrings) this template is the default for
+    #[serde(default)]
+    pub titles: Vec<String>,
+    /// Wording used instead when the window title matches
+    #[serde(default)]
+    pub variants: Vec<PromptVariant>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptVariant {
+    /// Window title substring, e.g. "Claude"
+    pub title: String,
+    #[serde(default)]
+    pub system: Option<String>,
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+/// A template as listed in the picker
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptSummary {
+    pub name: String,
+    pub description: String,
+    /// Variables in the order they appear
+    pub variables: Vec<String>,
+    /// Title of the variant in effect for the current window, if any
+    pub variant: Option<String>,
+    /// Default template for the current window
+    pub default: bool,
+}
+
+/// Read `prompts.toml`; a missing file is an empty library
+pub fn load() -> Result<PromptLibrary> {
+    let path = prompts_path()?;
+    if !path.exists() {
+        return Ok(PromptLibrary::default());
+    }
+    let contents = fs::read_to_string(&path).map_err(Error::io("Failed to read prompts"))?;
+    toml::from_str(&contents)
+        .map_err(|e| Error::Config(format!("Failed to parse {}: {}", PROMPTS_FILE, e)))
+}
+
+fn prompts_path() -> Result<PathBuf> {
+    Ok(crate::paths::config_dir()?.join(PROMPTS_FILE))
+}
+
+impl PromptLibrary {
+    /// Templates whose name or description contains `query`, defaults for the
+    /// window `title` first
+    pub fn list(&self, title: Option<&str>, query: &str) -> Vec<PromptSummary> {
+        let query = query.trim().to_lowercase();
+        let mut summaries: Vec<PromptSummary> = self
+            .prompts
+            .iter()
+            .filter(|p| {
+                query.is_empty()
+                    || p.name.to_lowercase().contains(&query)
+                    || p.description.to_lowercase().contains(&query)
+            })
+            .map(|p| PromptSummary {
+                name: p.name.clone(),
+                description: p.description.clone(),
+                variables: p.resolve(title).variables(),
+                variant: p.variant(title).map(|v| v.title.clone()),
+                default: p.is_default_for(title),
+            })
+            .collect();
+        summaries.sort_by_key(|s| !s.default);
+        summaries
+    }
+
+    /// The template's text for the `target` window, with variables filled and
+    /// its counters advanced
+    pub fn render(
+        &self,
+        name: &str,
+        target: &WindowInfo,
+        values: &HashMap<String, String>,
+        clipboard: Option<&str>,
+    ) -> Result<String> {
+        self.fill(name, target, values, clipboard, true)
+    }
+
+    /// Like `render`, but with `{{counter:name}}` left as it is, for text put
+    /// in the text box that may be dismissed without pasting
+    pub fn render_keeping_counters(
+        &self,
+        name: &str,
+        target: &WindowInfo,
+        clipboard: Option<&str>,
+    ) -> Result<String> {
+        self.fill(name, target, &HashMap::new(), clipboard, false)
+    }
+
+    fn fill(
+        &self,
+        name: &str,
+        target: &WindowInfo,
+        values: &HashMap<String, String>,
+        clipboard: Option<&str>,
+        advance_counters: bool,
+    ) -> Result<String> {
+        let template = self
+            .prompts
+            .iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| Error::Config(format!("Unknown prompt template: {}", name)))?;
+        let resolved = template.resolve(target.title.as_deref());
+        let variables = resolved.variables();
+        let mut text = crate::dates::fill(&resolved.text())?;
+        if advance_counters {
+            text = crate::counters::fill(&text)?;
+        }
+        Ok(fill_placeholders(&text, |name| {
+            if name == CLIPBOARD_VARIABLE {
+                return clipboard;
+            }
+            if let Some(value) = window_variable(target, name) {
+                return Some(value);
+            }
+            if !variables.iter().any(|variable| variable == name) {
+                return None;
+            }
+            values
+                .get(name)
+                .or_else(|| template.variables.get(name))
+                .map(String::as_str)
+        }))
+    }
+
+    /// Name of the first template that is the default for the window `title`
+    pub fn default_for(&self, title: Option<&str>) -> Option<&str> {
+        self.prompts
+            .iter()
+            .find(|p| p.is_default_for(title))
+            .map(|p| p.name.as_str())
+    }
+}
+
+/// System prompt, role and body after applying a variant
+struct Resolved<'a> {
+    parts: [&'a str; 3],
+}
+
+impl PromptTemplate {
+    fn is_default_for(&self, title: Option<&str>) -> bool {
+        self.titles.iter().any(|t| title_matches(title, t))
+    }
+
+    fn variant(&self, title: Option<&str>) -> Option<&PromptVariant> {
+        self.variants
+            .iter()
+            .find(|v| title_matches(title, &v.title))
+    }
+
+    fn resolve(&self, title: Option<&str>) -> Resolved<'_> {
+        let variant = self.variant(title);
+        Resolved {
+            parts: [
+                variant
+                    .and_then(|v| v.system.as_deref())
+                    .unwrap_or(&self.system),
+                variant
+                    .and_then(|v| v.role.as_deref())
+                    .unwrap_or(&self.role),
+                variant
+                    .and_then(|v| v.body.as_deref())
+                    .unwrap_or(&self.body),
+            ],
+        }
+    }
+}
+
+impl Resolved<'_> {
+    fn variables(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for part in self.parts {
+            for name in placeholders(part) {
+                // Filled by `render` rather than asked for
+                let builtin = WINDOW_VARIABLES.contains(&name)
+                    || name == "date"
+                    || name == CLIPBOARD_VARIABLE;
+                if !builtin && !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names
+    }
+
+    /// The non-empty parts, a blank line apart
+    fn text(&self) -> String {
+        self.parts
+            .iter()
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Replace `{{app_name}}`, `{{app_path}}` and `{{app_title}}` with what is
+/// known about `target`; unknown parts become empty
+pub fn fill_window_variables(text: &str, target: &WindowInfo) -> String {
+    fill_placeholders(text, |name| window_variable(target, name))
+}
+
+/// Value of a window variable for `target`, `None` if `name` isn't one
+fn window_variable<'a>(target: &'a WindowInfo, name: &str) -> Option<&'a str> {
+    let value = match name {
+        "app_name" => &target.process_name,
+        "app_path" => &target.exe_path,
+        "app_title" => &target.title,
+        _ => return None,
+    };
+    Some(value.as_deref().unwrap_or(""))
+}
+
+/// Replace each `{{name}}` in `text` that `value` knows in a single pass, so
+/// text put in is never searched for placeholders itself
+fn fill_placeholders<'a>(text: &str, value: impl Fn(&str) -> Option<&'a str>) -> String {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let found = after
+            .find("}}")
+            .and_then(|end| Some((end, value(&after[..end])?)));
+        let Some((end, value)) = found else {
+            filled.push_str(&rest[..start + 2]);
+            rest = after;
+            continue;
+        };
+        filled.push_str(&rest[..start]);
+        filled.push_str(value);
+        rest = &after[end + 2..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// Case-insensitive substring match of a window title; empty patterns never match
+fn title_matches(title: Option<&str>, pattern: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    match title {
+        Some(title) if !pattern.is_empty() => title.to_lowercase().contains(&pattern),
+        _ => false,
+    }
+}
+
+/// Names of the `{{name}}` placeholders in `text`
+fn placeholders(text: &str) -> impl Iterator<Item = &str> {
+    text.split("{{").skip(1).filter_map(|rest| {
+        let name = rest.split_once("}}")?.0;
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+        valid.then_some(name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserted_text_is_not_filled_again() {
+        let library: PromptLibrary = toml::from_str(
+            r#"
+            [[prompts]]
+            name = "ask"
+            body = "{{topic}} in {{app_name}}: {{clipboard}}"
+            "#,
+        )
+        .expect("valid library");
+        let target = WindowInfo {
+            process_name: Some("{{topic}}".to_string()),
+            ..Default::default()
+        };
+        let values = HashMap::from([("topic".to_string(), "{{app_name}}".to_string())]);
+
+        let text = library
+            .render("ask", &target, &values, Some("{{counter:ticket}} {{date}}"))
+            .expect("render");
+
+        assert_eq!(
+            text,
+            "{{app_name}} in {{topic}}: {{counter:ticket}} {{date}}"
+        );
+    }
+}
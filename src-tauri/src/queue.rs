@@ -0,0 +1,72 @@
+//! Paste queue for the "paste_next" global hotkey
+//!
+//! Items are queued from history entries or from the buffer split into
+//! pieces; each press of the hotkey pastes the front item into the focused app
+//! and drops it. The queue lives in memory only and is gone after a restart.
+
+use std::collections::VecDeque;
+
+/// More than this is almost certainly a mistake (a whole file split by lines)
+const MAX_ITEMS: usize = 500;
+
+#[derive(Debug, Default)]
+pub struct PasteQueue {
+    items: VecDeque<String>,
+}
+
+impl PasteQueue {
+    /// Append items to the back, skipping blank ones and any beyond `MAX_ITEMS`
+    ///
+    /// Returns the number added.
+    pub fn extend(&mut self, items: impl IntoIterator<Item = String>) -> usize {
+        let before = self.items.len();
+        let room = MAX_ITEMS.saturating_sub(before);
+        self.items.extend(
+            items
+                .into_iter()
+                .filter(|item| !item.trim().is_empty())
+                .take(room),
+        );
+        self.items.len() - before
+    }
+
+    /// Take the next item to paste
+    pub fn pop(&mut self) -> Option<String> {
+        self.items.pop_front()
+    }
+
+    /// Put an item back at the front after a failed paste
+    pub fn push_front(&mut self, item: String) {
+        self.items.push_front(item);
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn items(&self) -> Vec<String> {
+        self.items.iter().cloned().collect()
+    }
+}
+
+/// Split the buffer into queue items: paragraphs if it has blank lines,
+/// otherwise one item per line
+pub fn split(text: &str) -> Vec<String> {
+    let lines: Vec<&str> = text
+        .trim_matches(['\r', '\n'])
+        .lines()
+        .map(str::trim_end)
+        .collect();
+    if !lines.iter().any(|line| line.trim().is_empty()) {
+        return lines.into_iter().map(str::to_string).collect();
+    }
+    lines
+        .split(|line| line.trim().is_empty())
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| paragraph.join("\n"))
+        .collect()
+}
@@ -0,0 +1,202 @@
+//! Privacy while the screen is shared or recorded
+//!
+//! With `behavior.screen_share_privacy` on, a watcher checks every few seconds
+//! whether the screen is being captured. While it is, incognito applies
+//! (nothing is saved to history, and copies are kept out of clipboard history
+//! and managers) and the windows blur history previews. "Show History While
+//! Sharing" in the tray lifts this until sharing stops.
+//!
+//! Capture is detected from the privacy consent store, where Windows records
+//! which apps use Windows.Graphics.Capture right now (browsers sharing a
+//! screen or tab, Teams, OBS, the Snipping Tool recorder), and from shown
+//! windows of `behavior.screen_share_processes` for apps capturing some other
+//! way.
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::lock::{MutexExt, RwLockExt};
+use crate::AppState;
+
+/// How often the watcher looks for screen capture
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScreenShare {
+    /// The screen is being captured (and `screen_share_privacy` is on)
+    pub active: bool,
+    /// Privacy lifted from the tray until sharing stops
+    pub overridden: bool,
+}
+
+impl ScreenShare {
+    /// Whether history is hidden and not saved
+    pub fn is_private(&self) -> bool {
+        self.active && !self.overridden
+    }
+}
+
+/// Watch for screen capture while `behavior.screen_share_privacy` is on
+pub fn spawn_watcher(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        let state = app.state::<AppState>();
+        let (enabled, processes) = {
+            let config = state.config.read_locked();
+            (
+                config.behavior.screen_share_privacy,
+                config.behavior.screen_share_processes.clone(),
+            )
+        };
+        let active = enabled && is_screen_captured(&processes);
+        update(&app, |share| {
+            if share.active != active {
+                share.active = active;
+                // The next share starts private again
+                share.overridden = false;
+            }
+        });
+    });
+}
+
+/// Lift or restore privacy for the current share (tray item)
+pub fn set_overridden(app: &AppHandle, overridden: bool) {
+    update(app, |share| share.overridden = share.active && overridden);
+}
+
+fn update(app: &AppHandle, change: impl FnOnce(&mut ScreenShare)) {
+    let state = app.state::<AppState>();
+    let (was, share) = {
+        let mut share = state.screen_share.locked();
+        let was = *share;
+        change(&mut share);
+        (was, *share)
+    };
+    if was == share {
+        return;
+    }
+
+    crate::tray::set_tray_state(app, |tray| {
+        tray.screen_shared = share.active;
+        tray.share_overridden = share.overridden;
+    });
+    if was.is_private() != share.is_private() {
+        tracing::info!(
+            "Screen sharing privacy {}",
+            if share.is_private() { "on" } else { "off" }
+        );
+        crate::update_clipboard_privacy(app);
+        let _ = app.emit("screen-share-changed", share.is_private());
+    }
+}
+
+/// Whether an app is capturing the screen, or a window of `processes` is shown
+fn is_screen_captured(processes: &[String]) -> bool {
+    let names: Vec<&str> = processes.iter().map(String::as_str).collect();
+    is_graphics_capture_in_use()
+        || (!names.is_empty() && crate::foreground::is_process_window_shown(&names))
+}
+
+/// Consent store capabilities of Windows.Graphics.Capture, with and without
+/// the yellow capture border
+#[cfg(windows)]
+const CAPTURE_CAPABILITIES: &[&str] = &[
+    "graphicsCaptureProgrammatic",
+    "graphicsCaptureWithoutBorder",
+];
+
+#[cfg(windows)]
+const CONSENT_STORE: &str =
+    r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore";
+
+#[cfg(windows)]
+fn is_graphics_capture_in_use() -> bool {
+    CAPTURE_CAPABILITIES
+        .iter()
+        .any(|capability| is_capability_in_use(&format!(r"{}\{}", CONSENT_STORE, capability)))
+}
+
+#[cfg(not(windows))]
+fn is_graphics_capture_in_use() -> bool {
+    false
+}
+
+/// Whether an app listed under a consent store key uses the capability now:
+/// it has a start time and no stop time. Packaged apps have a key each;
+/// desktop apps are under `NonPackaged`.
+#[cfg(windows)]
+fn is_capability_in_use(path: &str) -> bool {
+    use windows::core::{HSTRING, PWSTR};
+    use windows::Win32::System::Registry::{
+        RegCloseKey, RegEnumKeyExW, RegGetValueW, RegOpenKeyExW, HKEY, HKEY_CURRENT_USER, KEY_READ,
+        RRF_RT_REG_QWORD,
+    };
+
+    let mut key = HKEY::default();
+    if unsafe {
+        RegOpenKeyExW(
+            HKEY_CURRENT_USER,
+            &HSTRING::from(path),
+            0,
+            KEY_READ,
+            &mut key,
+        )
+    }
+    .is_err()
+    {
+        return false;
+    }
+
+    let mut apps = Vec::new();
+    let mut name = [0u16; 512];
+    for index in 0.. {
+        let mut len = name.len() as u32;
+        let result = unsafe {
+            RegEnumKeyExW(
+                key,
+                index,
+                PWSTR(name.as_mut_ptr()),
+                &mut len,
+                None,
+                PWSTR::null(),
+                None,
+                None,
+            )
+        };
+        if result.is_err() {
+            break;
+        }
+        apps.push(String::from_utf16_lossy(&name[..len as usize]));
+    }
+
+    let time = |app: &str, value: &str| -> Option<u64> {
+        let mut data = 0u64;
+        let mut size = std::mem::size_of::<u64>() as u32;
+        unsafe {
+            RegGetValueW(
+                key,
+                &HSTRING::from(app),
+                &HSTRING::from(value),
+                RRF_RT_REG_QWORD,
+                None,
+                Some(&mut data as *mut u64 as *mut core::ffi::c_void),
+                Some(&mut size),
+            )
+        }
+        .ok()
+        .ok()?;
+        Some(data)
+    };
+    let in_use = apps.iter().any(|app| {
+        if app == "NonPackaged" {
+            return is_capability_in_use(&format!(r"{}\{}", path, app));
+        }
+        time(app, "LastUsedTimeStart").is_some_and(|start| start != 0)
+            && time(app, "LastUsedTimeStop") == Some(0)
+    });
+    unsafe {
+        let _ = RegCloseKey(key);
+    }
+    in_use
+}
@@ -0,0 +1,183 @@
+//! Passwords and API keys kept out of config.toml
+//!
+//! Each `Secret` is an entry of the `prompt-line-rs` service in the OS
+//! credential store (Windows Credential Manager, macOS Keychain, Secret
+//! Service on Linux). Only the backend reads the values; the frontend can
+//! store one and ask whether it is set, never read it back.
+//!
+//! `protect` and `unprotect` encrypt text for the current Windows user with
+//! DPAPI, for secret history entries.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+const SERVICE: &str = "prompt-line-rs";
+
+/// Credential store entries, named in kebab-case to the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Secret {
+    /// WebDAV password or S3 secret key of the backup server
+    SyncServer,
+    /// Passphrase the remote backup is encrypted with
+    SyncPassphrase,
+    /// API key of the `[ai]` endpoint
+    AiApiKey,
+    /// DeepL or Google Cloud Translation API key
+    TranslateApiKey,
+}
+
+impl Secret {
+    fn name(self) -> &'static str {
+        match self {
+            Secret::SyncServer => "sync-server",
+            Secret::SyncPassphrase => "sync-passphrase",
+            Secret::AiApiKey => "ai-api-key",
+            Secret::TranslateApiKey => "translate-api-key",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Secret::SyncServer => "backup server password",
+            Secret::SyncPassphrase => "backup encryption passphrase",
+            Secret::AiApiKey => "AI API key",
+            Secret::TranslateApiKey => "translation API key",
+        }
+    }
+}
+
+/// The stored value, `None` if there is none
+pub fn get(secret: Secret) -> Result<Option<String>> {
+    match entry(secret)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(Error::Platform(format!(
+            "Failed to read the {}: {}",
+            secret.description(),
+            e
+        ))),
+    }
+}
+
+/// The stored value, or an error telling the user to set it in Settings
+pub fn require(secret: Secret) -> Result<String> {
+    get(secret)?.ok_or_else(|| {
+        Error::Config(format!(
+            "No {} stored; set it in Settings",
+            secret.description()
+        ))
+    })
+}
+
+/// Store a value; an empty one removes the entry
+pub fn set(secret: Secret, value: &str) -> Result<()> {
+    let entry = entry(secret)?;
+    let result = if value.is_empty() {
+        match entry.delete_credential() {
+            Err(keyring::Error::NoEntry) => Ok(()),
+            result => result,
+        }
+    } else {
+        entry.set_password(value)
+    };
+    result.map_err(|e| {
+        Error::Platform(format!(
+            "Failed to save the {}: {}",
+            secret.description(),
+            e
+        ))
+    })
+}
+
+fn entry(secret: Secret) -> Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, secret.name())
+        .map_err(|e| Error::Platform(format!("Credential store unavailable: {}", e)))
+}
+
+/// Encrypt text so only the current Windows user can read it (base64)
+#[cfg(windows)]
+pub fn protect(text: &str) -> Result<String> {
+    use base64::Engine;
+    use windows::Win32::Security::Cryptography::{
+        CryptProtectData, CRYPTPROTECT_UI_FORBIDDEN, CRYPT_INTEGER_BLOB,
+    };
+
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: text.len() as u32,
+        pbData: text.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB::default();
+    unsafe {
+        CryptProtectData(
+            &input,
+            windows::core::w!("prompt-line-rs history entry"),
+            None,
+            None,
+            None,
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+    }
+    .map_err(|e| Error::Platform(format!("Failed to encrypt the entry: {}", e)))?;
+    let encrypted = unsafe { take_blob(output) };
+    Ok(base64::engine::general_purpose::STANDARD.encode(encrypted))
+}
+
+/// Decrypt text from `protect`
+#[cfg(windows)]
+pub fn unprotect(data: &str) -> Result<String> {
+    use base64::Engine;
+    use windows::Win32::Security::Cryptography::{
+        CryptUnprotectData, CRYPTPROTECT_UI_FORBIDDEN, CRYPT_INTEGER_BLOB,
+    };
+
+    let mut encrypted = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| Error::Secret(format!("Damaged secret entry: {}", e)))?;
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: encrypted.len() as u32,
+        pbData: encrypted.as_mut_ptr(),
+    };
+    let mut output = CRYPT_INTEGER_BLOB::default();
+    // Fails for entries encrypted by another user or on another machine
+    unsafe {
+        CryptUnprotectData(
+            &input,
+            None,
+            None,
+            None,
+            None,
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+    }
+    .map_err(|e| Error::Platform(format!("Failed to decrypt the entry: {}", e)))?;
+    let text = unsafe { take_blob(output) };
+    String::from_utf8(text).map_err(|e| Error::Secret(format!("Damaged secret entry: {}", e)))
+}
+
+/// Copy out and free a blob DPAPI allocated
+#[cfg(windows)]
+unsafe fn take_blob(blob: windows::Win32::Security::Cryptography::CRYPT_INTEGER_BLOB) -> Vec<u8> {
+    use windows::Win32::Foundation::{LocalFree, HLOCAL};
+
+    let data = std::slice::from_raw_parts(blob.pbData, blob.cbData as usize).to_vec();
+    let _ = LocalFree(HLOCAL(blob.pbData as *mut core::ffi::c_void));
+    data
+}
+
+#[cfg(not(windows))]
+pub fn protect(_text: &str) -> Result<String> {
+    Err(Error::Platform(
+        "Secret entries are only supported on Windows".to_string(),
+    ))
+}
+
+#[cfg(not(windows))]
+pub fn unprotect(_data: &str) -> Result<String> {
+    Err(Error::Platform(
+        "Secret entries are only supported on Windows".to_string(),
+    ))
+}
@@ -0,0 +1,44 @@
+//! Toggles from the main window and tray that survive a restart
+//!
+//! Kept in `session.json` in the data directory rather than in config.toml,
+//! so flipping a toggle never rewrites the user's config.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+
+const SESSION_FILE: &str = "session.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Session {
+    /// Voice input toggle in the main window
+    #[serde(default)]
+    pub voice_toggle_on: bool,
+}
+
+impl Session {
+    /// Load the saved session, or start with everything off
+    pub fn load() -> Self {
+        session_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = session_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(Error::io("Failed to create directory"))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Config(format!("Failed to serialize session: {}", e)))?;
+        fs::write(path, json).map_err(Error::io("Failed to save session"))
+    }
+}
+
+fn session_path() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join(SESSION_FILE))
+}
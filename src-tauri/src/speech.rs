@@ -0,0 +1,334 @@
+//! Local speech-to-text with Whisper
+//!
+//! Holding the `push_to_talk` hotkey records the default microphone. On
+//! release the audio is transcribed by whisper.cpp on the recording thread and
+//! each decoded segment is sent to the main window as a `speech-text` event;
+//! `speech-state` reports `recording`, `transcribing` and `idle`.
+//!
+//! whisper.cpp needs CMake and a C++ toolchain, so this is only built with the
+//! `whisper` cargo feature. Without it the hotkey reports that the build has
+//! no local transcription.
+
+use tauri::AppHandle;
+
+#[cfg(feature = "whisper")]
+use std::path::PathBuf;
+#[cfg(feature = "whisper")]
+use std::sync::{mpsc, Arc, Mutex};
+#[cfg(feature = "whisper")]
+use tauri::{Emitter, Manager};
+
+#[cfg(feature = "whisper")]
+use crate::config::WhisperConfig;
+#[cfg(feature = "whisper")]
+use crate::error::{Error, Result};
+#[cfg(feature = "whisper")]
+use crate::lock::{MutexExt, RwLockExt};
+#[cfg(feature = "whisper")]
+use crate::AppState;
+
+/// Sample rate whisper.cpp expects
+#[cfg(feature = "whisper")]
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// Recordings shorter than this are treated as an accidental tap
+#[cfg(feature = "whisper")]
+const MIN_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize / 4;
+
+/// Push-to-talk recording and the loaded model
+#[derive(Default)]
+pub struct Recorder {
+    /// Ends the recording; `Some` while the hotkey is held
+    #[cfg(feature = "whisper")]
+    stop: Mutex<Option<mpsc::Sender<()>>>,
+    /// Model kept loaded between recordings, with the file it came from
+    #[cfg(feature = "whisper")]
+    model: Mutex<Option<(PathBuf, Arc<whisper_rs::WhisperContext>)>>,
+}
+
+/// Start recording when the hotkey is pressed, transcribe when it is released
+#[cfg(feature = "whisper")]
+pub fn push_to_talk(app: &AppHandle, pressed: bool) {
+    let state = app.state::<AppState>();
+    if !pressed {
+        if let Some(stop) = state.speech.stop.locked().take() {
+            let _ = stop.send(());
+        }
+        return;
+    }
+
+    let mut stop = state.speech.stop.locked();
+    // Key repeat sends Pressed again while held
+    if stop.is_some() {
+        return;
+    }
+
+    let config = state.config.read_locked().voice.whisper.clone();
+    let (sender, receiver) = mpsc::channel();
+    let handle = app.clone();
+    let spawned = std::thread::Builder::new()
+        .name("speech".to_string())
+        .spawn(move || {
+            if let Err(e) = record_and_transcribe(&handle, receiver, &config) {
+                crate::notify::error(
+                    &handle,
+                    &crate::i18n::tr("notify.speech_failed", &[("error", &e.to_string())]),
+                );
+            }
+            emit_state(&handle, "idle");
+        });
+    match spawned {
+        Ok(_) => *stop = Some(sender),
+        Err(e) => {
+            crate::notify::error(
+                app,
+                &crate::i18n::tr("notify.speech_start_failed", &[("error", &e.to_string())]),
+            );
+            return;
+        }
+    }
+    drop(stop);
+
+    // The transcription goes into the main window's buffer
+    let visible = app
+        .get_webview_window("main")
+        .is_some_and(|window| window.is_visible().unwrap_or(false));
+    if !visible {
+        crate::reveal_main_window(app);
+    }
+}
+
+/// Local transcription is not part of this build - stub
+#[cfg(not(feature = "whisper"))]
+pub fn push_to_talk(app: &AppHandle, pressed: bool) {
+    if pressed {
+        crate::notify::error(app, &crate::i18n::t("notify.no_whisper"));
+    }
+}
+
+/// Start or stop recording, for callers without a key release (the tray icon)
+pub fn toggle(app: &AppHandle) {
+    push_to_talk(app, !is_recording(app));
+}
+
+#[cfg(feature = "whisper")]
+fn is_recording(app: &AppHandle) -> bool {
+    app.state::<AppState>().speech.stop.locked().is_some()
+}
+
+#[cfg(not(feature = "whisper"))]
+fn is_recording(_app: &AppHandle) -> bool {
+    false
+}
+
+#[cfg(feature = "whisper")]
+fn emit_state(app: &AppHandle, state: &str) {
+    let _ = app.emit("speech-state", state);
+}
+
+#[cfg(feature = "whisper")]
+fn record_and_transcribe(
+    app: &AppHandle,
+    stop: mpsc::Receiver<()>,
+    config: &WhisperConfig,
+) -> Result<()> {
+    let recording = Recording::start()?;
+    emit_state(app, "recording");
+
+    // Load while recording: nothing said is lost to a slow first load, and a
+    // bad model path is reported before the user has finished speaking
+    let model = load_model(app, config)?;
+
+    // Released (or the sender was dropped)
+    let _ = stop.recv();
+    let mut audio = recording.finish();
+    if audio.len() < MIN_SAMPLES {
+        return Ok(());
+    }
+    // whisper.cpp rejects input shorter than a second
+    if audio.len() < WHISPER_SAMPLE_RATE as usize {
+        audio.resize(WHISPER_SAMPLE_RATE as usize + 1, 0.0);
+    }
+
+    emit_state(app, "transcribing");
+    transcribe(app, &model, &audio, config)
+}
+
+/// Model from `voice.whisper.model`, loaded once and reused until the path changes
+#[cfg(feature = "whisper")]
+fn load_model(app: &AppHandle, config: &WhisperConfig) -> Result<Arc<whisper_rs::WhisperContext>> {
+    let model = config.model.trim();
+    if model.is_empty() {
+        return Err(Error::Config(
+            "No Whisper model configured (voice.whisper.model)".to_string(),
+        ));
+    }
+    let mut path = crate::paths::expand_home(model)?;
+    if path.is_relative() {
+        path = crate::paths::config_dir()?.join(path);
+    }
+
+    let state = app.state::<AppState>();
+    let mut loaded = state.speech.model.locked();
+    if let Some((loaded_path, context)) = loaded.as_ref() {
+        if *loaded_path == path {
+            return Ok(context.clone());
+        }
+    }
+
+    // Route whisper.cpp's logging through tracing instead of stderr
+    whisper_rs::install_logging_hooks();
+
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| Error::Config(format!("Invalid model path: {}", path.display())))?;
+    let context = whisper_rs::WhisperContext::new_with_params(
+        path_str,
+        whisper_rs::WhisperContextParameters::default(),
+    )
+    .map_err(|e| Error::Config(format!("Failed to load {}: {}", path.display(), e)))?;
+    tracing::info!("Loaded Whisper model {}", path.display());
+
+    let context = Arc::new(context);
+    *loaded = Some((path, context.clone()));
+    Ok(context)
+}
+
+/// Mono audio being recorded from the default microphone
+#[cfg(feature = "whisper")]
+struct Recording {
+    stream: cpal::Stream,
+    samples: Arc<Mutex<Vec<f32>>>,
+    sample_rate: u32,
+}
+
+#[cfg(feature = "whisper")]
+impl Recording {
+    fn start() -> Result<Self> {
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+        use cpal::SampleFormat;
+
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or_else(|| Error::Platform("No microphone found".to_string()))?;
+        let supported = device
+            .default_input_config()
+            .map_err(|e| Error::Platform(format!("Microphone unavailable: {}", e)))?;
+        let config = supported.config();
+
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, samples.clone()),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, samples.clone()),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, samples.clone()),
+            format => Err(Error::Platform(format!(
+                "Unsupported microphone sample format: {}",
+                format
+            ))),
+        }?;
+        stream
+            .play()
+            .map_err(|e| Error::Platform(format!("Failed to start recording: {}", e)))?;
+
+        Ok(Self {
+            stream,
+            samples,
+            sample_rate: config.sample_rate.0,
+        })
+    }
+
+    /// Stop recording and return the audio resampled to 16 kHz
+    fn finish(self) -> Vec<f32> {
+        drop(self.stream);
+        let samples = std::mem::take(&mut *self.samples.locked());
+        resample(&samples, self.sample_rate)
+    }
+}
+
+#[cfg(feature = "whisper")]
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    samples: Arc<Mutex<Vec<f32>>>,
+) -> Result<cpal::Stream>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    use cpal::traits::DeviceTrait;
+
+    let channels = config.channels.max(1) as usize;
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                // Downmix to mono
+                let mono = data.chunks(channels).map(|frame| {
+                    frame.iter().map(|s| s.to_sample::<f32>()).sum::<f32>() / frame.len() as f32
+                });
+                samples.locked().extend(mono);
+            },
+            |e| tracing::warn!("Microphone stream error: {}", e),
+            None,
+        )
+        .map_err(|e| Error::Platform(format!("Failed to open microphone: {}", e)))
+}
+
+/// Linear resampling to 16 kHz; plenty for speech recognition
+#[cfg(feature = "whisper")]
+fn resample(samples: &[f32], from_rate: u32) -> Vec<f32> {
+    if from_rate == WHISPER_SAMPLE_RATE || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let step = from_rate as f64 / WHISPER_SAMPLE_RATE as f64;
+    let len = (samples.len() as f64 / step) as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * step;
+            let index = pos as usize;
+            let frac = (pos - index as f64) as f32;
+            let a = samples[index];
+            let b = samples.get(index + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Run whisper.cpp over the audio, emitting each segment as soon as it is decoded
+#[cfg(feature = "whisper")]
+fn transcribe(
+    app: &AppHandle,
+    model: &whisper_rs::WhisperContext,
+    audio: &[f32],
+    config: &WhisperConfig,
+) -> Result<()> {
+    use whisper_rs::{FullParams, SamplingStrategy, SegmentCallbackData};
+
+    let mut state = model
+        .create_state()
+        .map_err(|e| Error::Platform(format!("Failed to start Whisper: {}", e)))?;
+
+    let language = match config.language.trim() {
+        "" => "auto",
+        language => language,
+    };
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some(language));
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_special(false);
+    params.set_print_timestamps(false);
+
+    let handle = app.clone();
+    params.set_segment_callback_safe(move |segment: SegmentCallbackData| {
+        let text = segment.text.trim();
+        if !text.is_empty() {
+            let _ = handle.emit("speech-text", text);
+        }
+    });
+
+    state
+        .full(params, audio)
+        .map_err(|e| Error::Platform(format!("Transcription failed: {}", e)))?;
+    Ok(())
+}
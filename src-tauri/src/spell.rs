@@ -0,0 +1,596 @@
+//! Spell checking with Hunspell dictionaries
+//!
+//! Reads the `.aff`/`.dic` pairs used by LibreOffice, Firefox and hunspell
+//! itself, and expands every stem with its prefix and suffix rules once, when
+//! the dictionaries are first needed. Compounding, twofold affixes and
+//! morphology are not supported; ordinary word lists are checked the same
+//! way hunspell checks them.
+//!
+//! Only words that look like prose are checked: tokens with digits, code or
+//! paths, all-caps acronyms, camelCase identifiers and words in scripts the
+//! dictionaries don't cover (e.g. Japanese) are skipped. Ranges are UTF-16
+//! offsets so the frontend can use them on the textarea directly.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::config::SpellcheckConfig;
+use crate::error::{Error, Result};
+use crate::lock::MutexExt;
+
+const DICTIONARIES_DIR: &str = "dictionaries";
+const MAX_SUGGESTIONS: usize = 5;
+/// Two-edit suggestions grow with the square of the length; skip them for long words
+const MAX_TWO_EDIT_LENGTH: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Misspelling {
+    /// UTF-16 offset of the first character
+    pub start: usize,
+    /// UTF-16 offset after the last character
+    pub end: usize,
+    pub word: String,
+    /// Closest dictionary words, best first
+    pub suggestions: Vec<String>,
+}
+
+/// Dictionaries of the current `[spellcheck]` config, loaded on first use
+#[derive(Default)]
+pub struct SpellChecker {
+    loaded: Mutex<Option<(SpellcheckConfig, Arc<Dictionary>)>>,
+}
+
+impl SpellChecker {
+    /// Misspelled words in `text` with suggestions
+    pub fn check(&self, config: &SpellcheckConfig, text: &str) -> Result<Vec<Misspelling>> {
+        if !config.enabled {
+            return Err(Error::Config(
+                "Spell checking is disabled; enable it in Settings ([spellcheck])".to_string(),
+            ));
+        }
+        let dictionary = self.dictionary(config)?;
+        Ok(words(text)
+            .into_iter()
+            .filter(|word| dictionary.should_check(word.text) && !dictionary.contains(word.text))
+            .map(|word| Misspelling {
+                start: word.start,
+                end: word.end,
+                word: word.text.to_string(),
+                suggestions: dictionary.suggest(word.text),
+            })
+            .collect())
+    }
+
+    fn dictionary(&self, config: &SpellcheckConfig) -> Result<Arc<Dictionary>> {
+        let mut loaded = self.loaded.locked();
+        if let Some((loaded_config, dictionary)) = loaded.as_ref() {
+            if loaded_config == config {
+                return Ok(dictionary.clone());
+            }
+        }
+        let dictionary = Arc::new(Dictionary::load(config)?);
+        *loaded = Some((config.clone(), dictionary.clone()));
+        Ok(dictionary)
+    }
+}
+
+/// Every accepted word form of the configured dictionaries
+pub struct Dictionary {
+    words: HashSet<String>,
+    /// Lowercase letters the dictionaries use, for skipping other scripts
+    alphabet: HashSet<char>,
+    /// Letters tried when generating suggestions (`TRY`), most frequent first
+    try_chars: Vec<char>,
+}
+
+impl Dictionary {
+    fn load(config: &SpellcheckConfig) -> Result<Self> {
+        if config.dictionaries.is_empty() {
+            return Err(Error::Config(
+                "No dictionaries configured (spellcheck.dictionaries)".to_string(),
+            ));
+        }
+        let mut dictionary = Dictionary {
+            words: HashSet::new(),
+            alphabet: HashSet::new(),
+            try_chars: Vec::new(),
+        };
+        for name in &config.dictionaries {
+            let base = find_dictionary(name)?;
+            let affixes = Affixes::parse(&read_text(&base.with_extension("aff"), None)?);
+            let dic = read_text(&base.with_extension("dic"), affixes.encoding.as_deref())?;
+            let before = dictionary.words.len();
+            affixes.expand(&dic, &mut dictionary.words);
+            for c in affixes.try_chars {
+                if !dictionary.try_chars.contains(&c) {
+                    dictionary.try_chars.push(c);
+                }
+            }
+            tracing::info!(
+                "Loaded dictionary {} ({} word forms)",
+                base.display(),
+                dictionary.words.len() - before
+            );
+        }
+        dictionary
+            .words
+            .extend(config.words.iter().map(|w| w.trim().to_string()));
+
+        dictionary.alphabet = dictionary
+            .words
+            .iter()
+            .flat_map(|word| word.chars())
+            .flat_map(char::to_lowercase)
+            .filter(|c| c.is_alphabetic())
+            .collect();
+        let mut letters: Vec<char> = dictionary.alphabet.iter().copied().collect();
+        letters.sort_unstable();
+        for c in letters {
+            if !dictionary.try_chars.contains(&c) {
+                dictionary.try_chars.push(c);
+            }
+        }
+        Ok(dictionary)
+    }
+
+    /// Whether `word` looks like prose in a language of the dictionaries
+    fn should_check(&self, word: &str) -> bool {
+        let mut chars = word.chars();
+        let rest_has_upper = chars.next().is_some() && chars.any(char::is_uppercase);
+        word.chars().count() > 1
+            && !rest_has_upper
+            && word
+                .chars()
+                .flat_map(char::to_lowercase)
+                .all(|c| !c.is_alphabetic() || self.alphabet.contains(&c))
+    }
+
+    /// The word as written, or lowercased at the start of a sentence
+    fn contains(&self, word: &str) -> bool {
+        let word = word.replace('’', "'");
+        self.words.contains(&word) || self.words.contains(&word.to_lowercase())
+    }
+
+    fn suggest(&self, word: &str) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        let mut suggestions: Vec<String> = Vec::new();
+        let add = |suggestions: &mut Vec<String>, candidate: &str| {
+            if let Some(form) = self.dictionary_form(candidate, capitalized) {
+                if form != word && !suggestions.contains(&form) {
+                    suggestions.push(form);
+                }
+            }
+        };
+
+        let first = self.edits(&lower);
+        for candidate in &first {
+            add(&mut suggestions, candidate);
+        }
+        // Missing space: "helloworld" -> "hello world"
+        let chars: Vec<char> = lower.chars().collect();
+        for i in 2..chars.len().saturating_sub(1) {
+            let left: String = chars[..i].iter().collect();
+            let right: String = chars[i..].iter().collect();
+            if self.words.contains(&left) && self.words.contains(&right) {
+                let split = format!("{} {}", left, right);
+                let split = if capitalized {
+                    capitalize(&split)
+                } else {
+                    split
+                };
+                if !suggestions.contains(&split) {
+                    suggestions.push(split);
+                }
+            }
+        }
+        if suggestions.is_empty() && chars.len() <= MAX_TWO_EDIT_LENGTH {
+            for candidate in &first {
+                for second in self.edits(candidate) {
+                    add(&mut suggestions, &second);
+                }
+                if suggestions.len() >= MAX_SUGGESTIONS {
+                    break;
+                }
+            }
+        }
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+
+    /// `candidate` as the dictionary has it, capitalized like the misspelling
+    fn dictionary_form(&self, candidate: &str, capitalized: bool) -> Option<String> {
+        let form = if self.words.contains(candidate) {
+            candidate.to_string()
+        } else if self.words.contains(&capitalize(candidate)) {
+            capitalize(candidate)
+        } else {
+            return None;
+        };
+        Some(if capitalized { capitalize(&form) } else { form })
+    }
+
+    /// Words one edit away, likeliest kinds of typo first
+    fn edits(&self, word: &str) -> Vec<String> {
+        let chars: Vec<char> = word.chars().collect();
+        let collect = |chars: &[char]| chars.iter().collect::<String>();
+        let mut edits = Vec::new();
+        for i in 0..chars.len().saturating_sub(1) {
+            let mut swapped = chars.clone();
+            swapped.swap(i, i + 1);
+            edits.push(collect(&swapped));
+        }
+        for i in 0..chars.len() {
+            for &c in &self.try_chars {
+                if c != chars[i] {
+                    let mut replaced = chars.clone();
+                    replaced[i] = c;
+                    edits.push(collect(&replaced));
+                }
+            }
+        }
+        for i in 0..chars.len() {
+            let mut deleted = chars.clone();
+            deleted.remove(i);
+            edits.push(collect(&deleted));
+        }
+        for i in 0..=chars.len() {
+            for &c in &self.try_chars {
+                let mut inserted = chars.clone();
+                inserted.insert(i, c);
+                edits.push(collect(&inserted));
+            }
+        }
+        edits
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// `<name>.aff`/`.dic` in the `dictionaries` folder next to config.toml, then
+/// in the system's hunspell folders; a name with a path is used as is
+fn find_dictionary(name: &str) -> Result<PathBuf> {
+    let name = name.trim();
+    if name.contains(['/', '\\']) {
+        let mut path = crate::paths::expand_home(name)?;
+        if path.is_relative() {
+            path = crate::paths::config_dir()?.join(path);
+        }
+        return has_dictionary(&path)
+            .then_some(path.clone())
+            .ok_or_else(|| missing_dictionary(&path.display().to_string()));
+    }
+
+    let mut dirs = vec![crate::paths::config_dir()?.join(DICTIONARIES_DIR)];
+    if cfg!(not(windows)) {
+        dirs.push(PathBuf::from("/usr/share/hunspell"));
+        dirs.push(PathBuf::from("/usr/share/myspell"));
+    }
+    dirs.into_iter()
+        .map(|dir| dir.join(name))
+        .find(|path| has_dictionary(path))
+        .ok_or_else(|| missing_dictionary(name))
+}
+
+fn has_dictionary(base: &Path) -> bool {
+    base.with_extension("aff").is_file() && base.with_extension("dic").is_file()
+}
+
+fn missing_dictionary(name: &str) -> Error {
+    Error::Config(format!(
+        "Dictionary not found: {} (put {}.aff and .dic in the {} folder next to config.toml)",
+        name, name, DICTIONARIES_DIR
+    ))
+}
+
+/// Read a dictionary file in `encoding` (`SET`); without one, the `.aff`
+/// file's own `SET` line is honored
+fn read_text(path: &Path, encoding: Option<&str>) -> Result<String> {
+    let bytes = fs::read(path).map_err(Error::io("Failed to read dictionary"))?;
+    let encoding = encoding.map(str::to_string).or_else(|| {
+        bytes
+            .split(|&b| b == b'\n')
+            .find_map(|line| line.strip_prefix(b"SET "))
+            .map(|set| String::from_utf8_lossy(set).trim().to_string())
+    });
+    Ok(match encoding.as_deref() {
+        // Each byte is the code point of the same value
+        Some("ISO8859-1") => bytes.iter().map(|&b| b as char).collect(),
+        _ => String::from_utf8_lossy(&bytes).into_owned(),
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FlagType {
+    /// One character per flag (the default)
+    Char,
+    /// Two characters per flag
+    Long,
+    /// Comma-separated numbers
+    Num,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AffixKind {
+    Prefix,
+    Suffix,
+}
+
+struct AffixRule {
+    strip: String,
+    add: String,
+    condition: Vec<CharClass>,
+}
+
+enum CharClass {
+    Any,
+    OneOf(Vec<char>),
+    NoneOf(Vec<char>),
+}
+
+struct AffixGroup {
+    kind: AffixKind,
+    /// Can combine with an affix of the other kind
+    cross_product: bool,
+    rules: Vec<AffixRule>,
+}
+
+/// The parts of an `.aff` file that word forms depend on
+struct Affixes {
+    encoding: Option<String>,
+    flag_type: FlagType,
+    try_chars: Vec<char>,
+    groups: HashMap<String, AffixGroup>,
+    /// Stems that are never words on their own
+    need_affix: Option<String>,
+    only_in_compound: Option<String>,
+    forbidden: Option<String>,
+}
+
+impl Affixes {
+    fn parse(text: &str) -> Self {
+        let mut affixes = Affixes {
+            encoding: None,
+            flag_type: FlagType::Char,
+            try_chars: Vec::new(),
+            groups: HashMap::new(),
+            need_affix: None,
+            only_in_compound: None,
+            forbidden: None,
+        };
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["SET", encoding, ..] => affixes.encoding = Some(encoding.to_string()),
+                ["FLAG", "long", ..] => affixes.flag_type = FlagType::Long,
+                ["FLAG", "num", ..] => affixes.flag_type = FlagType::Num,
+                ["TRY", chars, ..] => affixes.try_chars = chars.chars().collect(),
+                ["NEEDAFFIX", flag, ..] => affixes.need_affix = Some(flag.to_string()),
+                ["ONLYINCOMPOUND", flag, ..] => affixes.only_in_compound = Some(flag.to_string()),
+                ["FORBIDDENWORD", flag, ..] => affixes.forbidden = Some(flag.to_string()),
+                [kind @ ("PFX" | "SFX"), flag, cross, count] if count.parse::<usize>().is_ok() => {
+                    affixes.groups.insert(
+                        flag.to_string(),
+                        AffixGroup {
+                            kind: if *kind == "PFX" {
+                                AffixKind::Prefix
+                            } else {
+                                AffixKind::Suffix
+                            },
+                            cross_product: *cross == "Y",
+                            rules: Vec::new(),
+                        },
+                    );
+                }
+                ["PFX" | "SFX", flag, strip, add, rest @ ..] => {
+                    if let Some(group) = affixes.groups.get_mut(*flag) {
+                        let zero = |s: &str| {
+                            if s == "0" {
+                                String::new()
+                            } else {
+                                s.to_string()
+                            }
+                        };
+                        // Continuation flags after "/" are not supported
+                        let add = add.split_once('/').map_or(*add, |(add, _)| add);
+                        group.rules.push(AffixRule {
+                            strip: zero(strip),
+                            add: zero(add),
+                            condition: parse_condition(rest.first().copied().unwrap_or(".")),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        affixes
+    }
+
+    fn flags(&self, text: &str) -> Vec<String> {
+        match self.flag_type {
+            FlagType::Char => text.chars().map(String::from).collect(),
+            FlagType::Long => text
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|pair| pair.iter().collect())
+                .collect(),
+            FlagType::Num => text.split(',').map(|n| n.trim().to_string()).collect(),
+        }
+    }
+
+    /// Add every form of every `.dic` entry to `words`
+    fn expand(&self, dic: &str, words: &mut HashSet<String>) {
+        // The first line is the entry count
+        for line in dic.lines().skip(1) {
+            let Some(entry) = line.split_whitespace().next() else {
+                continue;
+            };
+            let (stem, flags) = match entry.split_once('/') {
+                Some((stem, flags)) => (stem, self.flags(flags)),
+                None => (entry, Vec::new()),
+            };
+            let has = |flag: &Option<String>| flag.as_ref().is_some_and(|f| flags.contains(f));
+            if stem.is_empty() || has(&self.forbidden) || has(&self.only_in_compound) {
+                continue;
+            }
+            if !has(&self.need_affix) {
+                words.insert(stem.to_string());
+            }
+
+            let groups: Vec<&AffixGroup> =
+                flags.iter().filter_map(|f| self.groups.get(f)).collect();
+            let mut prefixed = Vec::new();
+            for group in groups.iter().filter(|g| g.kind == AffixKind::Prefix) {
+                for form in group.apply(stem) {
+                    if group.cross_product {
+                        prefixed.push(form.clone());
+                    }
+                    words.insert(form);
+                }
+            }
+            for group in groups.iter().filter(|g| g.kind == AffixKind::Suffix) {
+                for form in group.apply(stem) {
+                    words.insert(form);
+                }
+                if group.cross_product {
+                    for prefixed in &prefixed {
+                        words.extend(group.apply(prefixed));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl AffixGroup {
+    fn apply(&self, word: &str) -> Vec<String> {
+        let chars: Vec<char> = word.chars().collect();
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let len = rule.condition.len();
+                if len > chars.len() {
+                    return None;
+                }
+                let (tested, strip_ok) = match self.kind {
+                    AffixKind::Prefix => (&chars[..len], word.starts_with(&rule.strip)),
+                    AffixKind::Suffix => (&chars[chars.len() - len..], word.ends_with(&rule.strip)),
+                };
+                let matches = tested
+                    .iter()
+                    .zip(&rule.condition)
+                    .all(|(c, class)| class.matches(*c));
+                if !matches || !strip_ok || rule.strip.len() >= word.len() {
+                    return None;
+                }
+                Some(match self.kind {
+                    AffixKind::Prefix => format!("{}{}", rule.add, &word[rule.strip.len()..]),
+                    AffixKind::Suffix => {
+                        format!("{}{}", &word[..word.len() - rule.strip.len()], rule.add)
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Any => true,
+            CharClass::OneOf(chars) => chars.contains(&c),
+            CharClass::NoneOf(chars) => !chars.contains(&c),
+        }
+    }
+}
+
+/// An affix condition such as `[^aeiou]y`, one class per character
+fn parse_condition(condition: &str) -> Vec<CharClass> {
+    if condition == "." {
+        return Vec::new();
+    }
+    let mut classes = Vec::new();
+    let mut chars = condition.chars();
+    while let Some(c) = chars.next() {
+        classes.push(match c {
+            '.' => CharClass::Any,
+            '[' => {
+                let set: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                match set.strip_prefix('^') {
+                    Some(set) => CharClass::NoneOf(set.chars().collect()),
+                    None => CharClass::OneOf(set.chars().collect()),
+                }
+            }
+            c => CharClass::OneOf(vec![c]),
+        });
+    }
+    classes
+}
+
+struct Word<'a> {
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+/// Runs of letters (with inner apostrophes) outside of code-like tokens
+fn words(text: &str) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut utf16 = 0;
+    for token in text.split_inclusive(char::is_whitespace) {
+        let token_start = utf16;
+        utf16 += token.encode_utf16().count();
+        if looks_like_code(token.trim_end()) {
+            continue;
+        }
+
+        let mut offset = token_start;
+        let mut current: Option<(usize, usize)> = None; // (byte, utf16) start
+        let chars: Vec<(usize, char)> = token.char_indices().collect();
+        for (i, &(byte, c)) in chars.iter().enumerate() {
+            let inner_apostrophe = matches!(c, '\'' | '’')
+                && current.is_some()
+                && chars
+                    .get(i + 1)
+                    .is_some_and(|(_, next)| next.is_alphabetic());
+            if c.is_alphabetic() || inner_apostrophe {
+                current.get_or_insert((byte, offset));
+            } else if let Some((start_byte, start)) = current.take() {
+                words.push(Word {
+                    text: &token[start_byte..byte],
+                    start,
+                    end: offset,
+                });
+            }
+            offset += c.len_utf16();
+        }
+        if let Some((start_byte, start)) = current {
+            words.push(Word {
+                text: &token[start_byte..],
+                start,
+                end: offset,
+            });
+        }
+    }
+    words
+}
+
+/// URLs, paths, identifiers and numbers mixed with letters
+fn looks_like_code(token: &str) -> bool {
+    let has_letter = token.chars().any(char::is_alphabetic);
+    let inner = token.trim_matches(|c: char| !c.is_alphanumeric());
+    has_letter
+        && (inner.contains(['/', '\\', '_', '@', '`', '=', '<', '>', '{', '}', '#', '$'])
+            || inner.chars().any(|c| c.is_ascii_digit())
+            || inner.split('.').filter(|part| !part.is_empty()).count() > 1)
+}
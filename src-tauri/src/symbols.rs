@@ -0,0 +1,247 @@
+//! Emoji and symbol search
+//!
+//! Searches the CLDR emoji list (names and GitHub-style shortcodes such as
+//! `shrug`) and the Unicode names of common symbol blocks: arrows, math,
+//! currency, Greek letters, CJK punctuation, ... Emojis with skin tones come
+//! in `[symbols] skin_tone` and list their other tones as variants.
+//!
+//! Inserted symbols are remembered in `recent_symbols.json` in the data
+//! directory; they are listed for an empty query and ranked first otherwise.
+
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::config::SkinTone;
+use crate::error::{Error, Result};
+
+const RECENT_FILE: &str = "recent_symbols.json";
+const MAX_RECENT: usize = 24;
+const MAX_RESULTS: usize = 60;
+
+/// Unicode blocks searched by character name, besides the emojis
+const SYMBOL_RANGES: &[(u32, u32)] = &[
+    (0x00A1, 0x00BF), // Latin-1 punctuation and symbols
+    (0x00D7, 0x00D7), // ×
+    (0x00F7, 0x00F7), // ÷
+    (0x0391, 0x03C9), // Greek letters
+    (0x2010, 0x205E), // General punctuation
+    (0x2070, 0x209C), // Superscripts and subscripts
+    (0x20A0, 0x20C0), // Currency
+    (0x2100, 0x218B), // Letterlike symbols and number forms
+    (0x2190, 0x23FF), // Arrows, math operators, technical
+    (0x2460, 0x24FF), // Enclosed alphanumerics
+    (0x2500, 0x27BF), // Box drawing, shapes, misc symbols, dingbats
+    (0x27F0, 0x297F), // Supplemental arrows
+    (0x3000, 0x303F), // CJK symbols and punctuation
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Symbol {
+    /// What gets inserted
+    pub symbol: String,
+    pub name: String,
+    /// Emoji shortcode without colons, e.g. "shrug"
+    pub shortcode: Option<String>,
+    /// Other skin tones of the emoji
+    pub variants: Vec<String>,
+    /// Inserted recently
+    pub recent: bool,
+}
+
+/// Emojis and symbols matching `query` (a leading ':' is ignored); recently
+/// used ones for an empty query
+pub fn search(query: &str, skin_tone: SkinTone) -> Vec<Symbol> {
+    let recent = load_recent();
+    let query = query.trim().trim_matches(':').to_lowercase();
+    if query.is_empty() {
+        return recent.iter().filter_map(|symbol| lookup(symbol)).collect();
+    }
+
+    let words = query.replace('_', " ");
+    let mut matches: Vec<(u8, bool, Symbol)> = Vec::new();
+    for emoji in emojis::iter() {
+        let shortcodes: Vec<&str> = emoji.shortcodes().collect();
+        let score = if shortcodes.contains(&query.as_str()) {
+            Some(0)
+        } else if shortcodes.iter().any(|s| s.starts_with(&query)) {
+            Some(1)
+        } else {
+            name_score(emoji.name(), &words)
+                .or_else(|| shortcodes.iter().any(|s| s.contains(&query)).then_some(3))
+        };
+        if let Some(score) = score {
+            let mut symbol = emoji_symbol(emoji, skin_tone);
+            symbol.recent = is_recent(&recent, emoji);
+            matches.push((score, symbol.recent, symbol));
+        }
+    }
+    for (c, name) in symbol_names() {
+        if let Some(score) = name_score(name, &words) {
+            let symbol = c.to_string();
+            let is_recent = recent.contains(&symbol);
+            matches.push((
+                score,
+                is_recent,
+                Symbol {
+                    symbol,
+                    name: name.clone(),
+                    shortcode: None,
+                    variants: Vec::new(),
+                    recent: is_recent,
+                },
+            ));
+        }
+    }
+
+    // Stable: CLDR order within the same rank
+    matches.sort_by_key(|(score, recent, _)| (*score, !*recent));
+    matches
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, _, symbol)| symbol)
+        .collect()
+}
+
+/// Remember an inserted symbol, most recent first
+pub fn record(symbol: &str) -> Result<()> {
+    if symbol.is_empty() {
+        return Ok(());
+    }
+    let mut recent = load_recent();
+    recent.retain(|s| s != symbol);
+    recent.insert(0, symbol.to_string());
+    recent.truncate(MAX_RECENT);
+
+    let path = recent_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(Error::io("Failed to create directory"))?;
+    }
+    let json = serde_json::to_string_pretty(&recent)
+        .map_err(|e| Error::Config(format!("Failed to serialize recent symbols: {}", e)))?;
+    fs::write(path, json).map_err(Error::io("Failed to save recent symbols"))
+}
+
+fn load_recent() -> Vec<String> {
+    recent_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn recent_path() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join(RECENT_FILE))
+}
+
+/// 2 when a word of `name` starts with `query`, 3 when it only contains it
+fn name_score(name: &str, query: &str) -> Option<u8> {
+    let name = name.to_lowercase();
+    if name
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(query))
+        || name.starts_with(query)
+    {
+        Some(2)
+    } else if name.contains(query) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// A recently inserted symbol as listed for an empty query
+fn lookup(symbol: &str) -> Option<Symbol> {
+    if let Some(emoji) = emojis::get(symbol) {
+        return Some(Symbol {
+            symbol: symbol.to_string(),
+            name: emoji.name().to_string(),
+            shortcode: emoji.shortcode().map(str::to_string),
+            variants: Vec::new(),
+            recent: true,
+        });
+    }
+    let mut chars = symbol.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    let name = unicode_names2::name(c)?;
+    Some(Symbol {
+        symbol: symbol.to_string(),
+        name: name.to_string().to_lowercase(),
+        shortcode: None,
+        variants: Vec::new(),
+        recent: true,
+    })
+}
+
+fn emoji_symbol(emoji: &'static emojis::Emoji, skin_tone: SkinTone) -> Symbol {
+    let toned = emoji
+        .with_skin_tone(emoji_skin_tone(skin_tone))
+        .unwrap_or(emoji);
+    // Only the one-person tones; couples have 26 combinations
+    let variants = emoji
+        .skin_tones()
+        .map(|tones| {
+            tones
+                .filter(|tone| tone.as_str() != toned.as_str())
+                .filter(|tone| {
+                    matches!(
+                        tone.skin_tone(),
+                        Some(
+                            emojis::SkinTone::Default
+                                | emojis::SkinTone::Light
+                                | emojis::SkinTone::MediumLight
+                                | emojis::SkinTone::Medium
+                                | emojis::SkinTone::MediumDark
+                                | emojis::SkinTone::Dark
+                        )
+                    )
+                })
+                .map(|tone| tone.as_str().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    Symbol {
+        symbol: toned.as_str().to_string(),
+        name: emoji.name().to_string(),
+        shortcode: emoji.shortcode().map(str::to_string),
+        variants,
+        recent: false,
+    }
+}
+
+/// Whether any tone of `emoji` was inserted recently
+fn is_recent(recent: &[String], emoji: &'static emojis::Emoji) -> bool {
+    match emoji.skin_tones() {
+        Some(mut tones) => tones.any(|tone| recent.iter().any(|s| s == tone.as_str())),
+        None => recent.iter().any(|s| s == emoji.as_str()),
+    }
+}
+
+fn emoji_skin_tone(skin_tone: SkinTone) -> emojis::SkinTone {
+    match skin_tone {
+        SkinTone::Default => emojis::SkinTone::Default,
+        SkinTone::Light => emojis::SkinTone::Light,
+        SkinTone::MediumLight => emojis::SkinTone::MediumLight,
+        SkinTone::Medium => emojis::SkinTone::Medium,
+        SkinTone::MediumDark => emojis::SkinTone::MediumDark,
+        SkinTone::Dark => emojis::SkinTone::Dark,
+    }
+}
+
+/// Lowercase names of the characters in `SYMBOL_RANGES` that aren't emojis,
+/// built on first use
+fn symbol_names() -> &'static [(char, String)] {
+    static NAMES: OnceLock<Vec<(char, String)>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        SYMBOL_RANGES
+            .iter()
+            .flat_map(|&(first, last)| first..=last)
+            .filter_map(char::from_u32)
+            .filter(|c| emojis::get(&c.to_string()).is_none())
+            .filter_map(|c| Some((c, unicode_names2::name(c)?.to_string().to_lowercase())))
+            .collect()
+    })
+}
@@ -0,0 +1,386 @@
+//! Encrypted remote backup of history and config
+//!
+//! With `[sync] enabled`, a background thread uploads a snapshot of the
+//! history and the active config every `interval_minutes` to a WebDAV folder
+//! or an S3 bucket, skipping uploads when nothing changed. `restore` downloads
+//! it on another machine, merges the history and replaces the config.
+//!
+//! Snapshots are encrypted with AES-256-GCM under a key derived from the
+//! user's passphrase with Argon2id, so the server only ever sees ciphertext.
+//! The passphrase and the server password are kept in `secrets`.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::config::{Config, SyncConfig, SyncProvider};
+use crate::error::{Error, Result};
+use crate::history::HistoryEntry;
+use crate::lock::{MutexExt, RwLockExt};
+use crate::secrets::{self, Secret};
+use crate::AppState;
+
+/// Start of every backup file, followed by salt, nonce and ciphertext
+const MAGIC: &[u8] = b"PLRSBAK1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Digest of the last uploaded snapshot's contents
+static LAST_UPLOAD: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+/// Contents of a backup before encryption
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    created: DateTime<Utc>,
+    /// The active profile's config.toml
+    config: String,
+    history: Vec<HistoryEntry>,
+}
+
+/// What `restore` brought back
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreSummary {
+    /// When the backup was made
+    pub created: DateTime<Utc>,
+    /// History entries not already present
+    pub history_imported: usize,
+    /// The restored config, already applied
+    pub config: Config,
+}
+
+/// Upload backups periodically while `[sync] enabled` is set
+pub fn start(app: &AppHandle) {
+    let handle = app.clone();
+    let spawned = std::thread::Builder::new()
+        .name("sync".to_string())
+        .spawn(move || {
+            let mut minutes = 0;
+            loop {
+                std::thread::sleep(Duration::from_secs(60));
+                let config = handle.state::<AppState>().config.read_locked().sync.clone();
+                if !config.enabled {
+                    minutes = 0;
+                    continue;
+                }
+                minutes += 1;
+                if minutes < config.interval_minutes.max(1) {
+                    continue;
+                }
+                minutes = 0;
+                if let Err(e) = backup(&handle) {
+                    tracing::warn!("Backup failed: {}", e);
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::warn!("Failed to start backup thread: {}", e);
+    }
+}
+
+/// Upload a backup now, unless it would be the same as the last one.
+/// Returns whether anything was uploaded.
+pub fn backup(app: &AppHandle) -> Result<bool> {
+    let state = app.state::<AppState>();
+    let (sync, config) = {
+        let config = state.config.read_locked();
+        (config.sync.clone(), config.clone())
+    };
+    let snapshot = Snapshot {
+        created: Utc::now(),
+        config: toml::to_string_pretty(&config)
+            .map_err(|e| Error::Config(format!("Failed to serialize config: {}", e)))?,
+        history: state
+            .history
+            .locked()
+            .entries()
+            .into_iter()
+            .filter(|entry| sync.include_secret_entries || !entry.secret)
+            .collect(),
+    };
+
+    let passphrase = secrets::require(Secret::SyncPassphrase)?;
+    // The passphrase is part of the digest so changing it forces a new upload
+    let digest = {
+        let mut hasher = Sha256::new();
+        hasher.update(passphrase.as_bytes());
+        hasher.update(snapshot.config.as_bytes());
+        for entry in &snapshot.history {
+            hasher.update(entry.timestamp.to_rfc3339().as_bytes());
+            hasher.update(entry.text.as_bytes());
+        }
+        hasher.finalize().to_vec()
+    };
+    if LAST_UPLOAD.locked().as_ref() == Some(&digest) {
+        return Ok(false);
+    }
+
+    let json = serde_json::to_vec(&snapshot)
+        .map_err(|e| Error::Config(format!("Failed to serialize backup: {}", e)))?;
+    let encrypted = encrypt(&json, &passphrase)?;
+
+    Remote::new(&sync)?.put(&sync.file_name, &encrypted)?;
+    tracing::info!(
+        "Uploaded backup ({} history entries, {} bytes)",
+        snapshot.history.len(),
+        encrypted.len()
+    );
+    *LAST_UPLOAD.locked() = Some(digest);
+    Ok(true)
+}
+
+/// Download the backup and decrypt it; the caller merges and applies it
+pub fn download(sync: &SyncConfig) -> Result<(DateTime<Utc>, Config, Vec<HistoryEntry>)> {
+    let passphrase = secrets::require(Secret::SyncPassphrase)?;
+    let encrypted = Remote::new(sync)?.get(&sync.file_name)?;
+    let json = decrypt(&encrypted, &passphrase)?;
+    let snapshot: Snapshot = serde_json::from_slice(&json)
+        .map_err(|e| Error::Config(format!("Failed to parse backup: {}", e)))?;
+    let config = toml::from_str(&snapshot.config)
+        .map_err(|e| Error::Config(format!("Failed to parse backed up config: {}", e)))?;
+    Ok((snapshot.created, config, snapshot.history))
+}
+
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| Error::Config("Failed to encrypt backup".to_string()))?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let rest = data
+        .strip_prefix(MAGIC)
+        .filter(|rest| rest.len() > SALT_LEN + NONCE_LEN)
+        .ok_or_else(|| Error::Config("Not a prompt-line-rs backup".to_string()))?;
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| Error::Config("Wrong passphrase or damaged backup".to_string()))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key<Aes256Gcm>> {
+    let mut key = Key::<Aes256Gcm>::default();
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| Error::Config(format!("Failed to derive key: {}", e)))?;
+    Ok(key)
+}
+
+/// Server the backup is stored on
+enum Remote {
+    WebDav {
+        /// Folder URL without a trailing slash
+        url: String,
+        username: String,
+        password: Option<String>,
+    },
+    S3 {
+        /// Scheme and host, e.g. "https://s3.eu-west-1.amazonaws.com"
+        origin: String,
+        host: String,
+        /// Path of the bucket on the endpoint, e.g. "/backups"
+        bucket_path: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+    },
+}
+
+impl Remote {
+    fn new(config: &SyncConfig) -> Result<Self> {
+        let url = config.url.trim().trim_end_matches('/');
+        if url.is_empty() {
+            return Err(Error::Config(
+                "No backup server configured (sync.url)".to_string(),
+            ));
+        }
+
+        match config.provider {
+            SyncProvider::WebDav => Ok(Self::WebDav {
+                url: url.to_string(),
+                username: config.username.clone(),
+                password: secrets::get(Secret::SyncServer)?,
+            }),
+            SyncProvider::S3 => {
+                let bucket = config.bucket.trim();
+                if bucket.is_empty() {
+                    return Err(Error::Config(
+                        "No S3 bucket configured (sync.bucket)".to_string(),
+                    ));
+                }
+                let (scheme, rest) = url
+                    .split_once("://")
+                    .ok_or_else(|| Error::Config(format!("Invalid S3 endpoint: {}", url)))?;
+                let (host, base_path) = rest.split_once('/').unwrap_or((rest, ""));
+                let base_path = match base_path {
+                    "" => String::new(),
+                    path => format!("/{}", path),
+                };
+                Ok(Self::S3 {
+                    origin: format!("{}://{}", scheme, host),
+                    host: host.to_string(),
+                    bucket_path: format!("{}/{}", base_path, uri_encode(bucket)),
+                    region: config.region.trim().to_string(),
+                    access_key: config.username.trim().to_string(),
+                    secret_key: secrets::require(Secret::SyncServer)?,
+                })
+            }
+        }
+    }
+
+    fn put(&self, name: &str, body: &[u8]) -> Result<()> {
+        self.request("PUT", name, body)?
+            .send_bytes(body)
+            .map(drop)
+            .map_err(|e| network_error("upload", e))
+    }
+
+    fn get(&self, name: &str) -> Result<Vec<u8>> {
+        let response = self
+            .request("GET", name, &[])?
+            .call()
+            .map_err(|e| network_error("download", e))?;
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut body)
+            .map_err(Error::io("Failed to download backup"))?;
+        Ok(body)
+    }
+
+    /// Authenticated request for a file in the backup folder or bucket
+    fn request(&self, method: &str, name: &str, body: &[u8]) -> Result<ureq::Request> {
+        let agent = agent()?;
+        match self {
+            Self::WebDav {
+                url,
+                username,
+                password,
+            } => {
+                let request = agent.request(method, &format!("{}/{}", url, uri_encode(name)));
+                Ok(match password {
+                    Some(password) => {
+                        use base64::Engine;
+                        let credentials = base64::engine::general_purpose::STANDARD
+                            .encode(format!("{}:{}", username, password));
+                        request.set("Authorization", &format!("Basic {}", credentials))
+                    }
+                    None => request,
+                })
+            }
+            Self::S3 {
+                origin,
+                host,
+                bucket_path,
+                region,
+                access_key,
+                secret_key,
+            } => {
+                let path = format!("{}/{}", bucket_path, uri_encode(name));
+                let now = Utc::now();
+                let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+                let date = now.format("%Y%m%d").to_string();
+                let payload_hash = hex::encode(Sha256::digest(body));
+
+                // AWS Signature Version 4
+                let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+                let canonical_request = format!(
+                    "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+                    method, path, host, payload_hash, amz_date, signed_headers, payload_hash
+                );
+                let scope = format!("{}/{}/s3/aws4_request", date, region);
+                let string_to_sign = format!(
+                    "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+                    amz_date,
+                    scope,
+                    hex::encode(Sha256::digest(canonical_request.as_bytes()))
+                );
+                let key = [region.as_str(), "s3", "aws4_request"].iter().fold(
+                    hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes()),
+                    |key, part| hmac_sha256(&key, part.as_bytes()),
+                );
+                let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+                let authorization = format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    access_key, scope, signed_headers, signature
+                );
+
+                Ok(agent
+                    .request(method, &format!("{}{}", origin, path))
+                    .set("x-amz-content-sha256", &payload_hash)
+                    .set("x-amz-date", &amz_date)
+                    .set("Authorization", &authorization))
+            }
+        }
+    }
+}
+
+fn agent() -> Result<ureq::Agent> {
+    let tls = native_tls::TlsConnector::new()
+        .map_err(|e| Error::Network(format!("TLS unavailable: {}", e)))?;
+    Ok(ureq::AgentBuilder::new()
+        .tls_connector(Arc::new(tls))
+        .timeout(REQUEST_TIMEOUT)
+        .build())
+}
+
+fn network_error(action: &str, error: ureq::Error) -> Error {
+    match error {
+        ureq::Error::Status(404, _) if action == "download" => {
+            Error::Network("No backup found on the server".to_string())
+        }
+        ureq::Error::Status(401 | 403, _) => Error::Network(format!(
+            "Backup {} refused: check the user name and password",
+            action
+        )),
+        ureq::Error::Status(code, response) => Error::Network(format!(
+            "Backup {} failed: HTTP {} {}",
+            action,
+            code,
+            response.status_text()
+        )),
+        ureq::Error::Transport(e) => Error::Network(format!("Backup {} failed: {}", action, e)),
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encode a path segment (RFC 3986 unreserved characters are kept)
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
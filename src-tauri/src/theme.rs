@@ -0,0 +1,77 @@
+//! Theme resolution for the webviews
+//!
+//! `system` mode follows the OS theme reported by the main window. Both
+//! windows are sent a `theme-changed` event whenever the result may differ,
+//! so they restyle without a reload.
+
+use serde::Serialize;
+use std::fs;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{ThemeConfig, ThemeMode};
+use crate::lock::RwLockExt;
+
+/// Theme as applied by the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct Theme {
+    /// Configured mode
+    pub mode: ThemeMode,
+    /// Whether the dark palette is in effect once `system` is resolved
+    pub dark: bool,
+    /// Accent color override
+    pub accent: Option<String>,
+    /// Contents of the custom stylesheet
+    pub custom_css: Option<String>,
+}
+
+/// Resolve the configured theme against the current OS theme
+pub fn resolve(app: &AppHandle, config: &ThemeConfig) -> Theme {
+    let dark = match config.mode {
+        ThemeMode::Dark => true,
+        ThemeMode::Light => false,
+        ThemeMode::System => os_prefers_dark(app),
+    };
+    let accent = Some(config.accent.trim())
+        .filter(|accent| !accent.is_empty())
+        .map(str::to_string);
+
+    Theme {
+        mode: config.mode,
+        dark,
+        accent,
+        custom_css: read_custom_css(&config.custom_css),
+    }
+}
+
+/// Send the current theme to every window
+pub fn emit(app: &AppHandle) {
+    let Some(state) = app.try_state::<crate::AppState>() else {
+        return;
+    };
+    let config = state.config.read_locked().theme.clone();
+    let _ = app.emit("theme-changed", resolve(app, &config));
+}
+
+fn os_prefers_dark(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|window| window.theme().ok())
+        .is_none_or(|theme| theme != tauri::Theme::Light)
+}
+
+fn read_custom_css(path: &str) -> Option<String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return None;
+    }
+    let mut path = crate::paths::expand_home(path).ok()?;
+    if path.is_relative() {
+        path = crate::paths::config_dir().ok()?.join(path);
+    }
+    match fs::read_to_string(&path) {
+        Ok(css) => Some(css),
+        Err(e) => {
+            tracing::warn!("Failed to read custom CSS {}: {}", path.display(), e);
+            None
+        }
+    }
+}
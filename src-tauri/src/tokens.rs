@@ -0,0 +1,41 @@
+//! Token counts for LLM prompts
+//!
+//! Counts with the OpenAI encodings bundled in tiktoken-rs, so no network or
+//! model files are needed. Models tiktoken doesn't know (Claude, Gemini,
+//! local models) are counted with `o200k_base` and marked as estimates; their
+//! own tokenizers differ by a few percent, which is fine for a budget warning.
+
+use serde::Serialize;
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::CoreBPE;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenCount {
+    pub tokens: usize,
+    /// Encoding used, e.g. "o200k_base"
+    pub encoding: &'static str,
+    /// False when the model is unknown and the count is an estimate
+    pub exact: bool,
+}
+
+/// Count the tokens `text` takes for `model`
+pub fn count(text: &str, model: &str) -> TokenCount {
+    let tokenizer = get_tokenizer(model.trim());
+    let (bpe, encoding) = encoding(tokenizer.unwrap_or(Tokenizer::O200kBase));
+    TokenCount {
+        tokens: bpe.encode_ordinary(text).len(),
+        encoding,
+        exact: tokenizer.is_some(),
+    }
+}
+
+/// Shared instance of the encoding; each is built on first use
+fn encoding(tokenizer: Tokenizer) -> (&'static CoreBPE, &'static str) {
+    match tokenizer {
+        Tokenizer::O200kBase => (tiktoken_rs::o200k_base_singleton(), "o200k_base"),
+        Tokenizer::Cl100kBase => (tiktoken_rs::cl100k_base_singleton(), "cl100k_base"),
+        Tokenizer::P50kBase => (tiktoken_rs::p50k_base_singleton(), "p50k_base"),
+        Tokenizer::P50kEdit => (tiktoken_rs::p50k_edit_singleton(), "p50k_edit"),
+        Tokenizer::R50kBase | Tokenizer::Gpt2 => (tiktoken_rs::r50k_base_singleton(), "r50k_base"),
+    }
+}
@@ -0,0 +1,121 @@
+//! Translate the buffer
+//!
+//! `translate` sends the text to the `[translate]` backend: the `[ai]` model
+//! (API or local, streaming `rewrite-chunk` events like a rewrite), DeepL or
+//! Google Cloud Translation. The DeepL and Google API key is kept in
+//! `secrets`. With `on_paste`, the frontend translates every paste first, so
+//! drafts can be written in one language and sent in another.
+
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::config::{Config, TranslateBackend};
+use crate::error::{Error, Result};
+use crate::secrets::{self, Secret};
+
+const DEEPL_URL: &str = "https://api.deepl.com/v2/translate";
+/// Keys of DeepL's free plan end in ":fx" and only work here
+const DEEPL_FREE_URL: &str = "https://api-free.deepl.com/v2/translate";
+const GOOGLE_URL: &str = "https://translation.googleapis.com/language/translate/v2";
+
+/// Translate `text` into `target_lang` (`[translate] target_lang` if empty)
+pub fn translate(
+    app: &AppHandle,
+    config: &Config,
+    text: &str,
+    target_lang: &str,
+) -> Result<String> {
+    if text.trim().is_empty() {
+        return Err(Error::Config("Nothing to translate".to_string()));
+    }
+    let target = match target_lang.trim() {
+        "" => config.translate.target_lang.trim(),
+        target => target,
+    };
+    if target.is_empty() {
+        return Err(Error::Config(
+            "No target language configured (translate.target_lang)".to_string(),
+        ));
+    }
+
+    tracing::info!(
+        "Translating {} characters into {} with {:?}",
+        text.chars().count(),
+        target,
+        config.translate.backend
+    );
+    match config.translate.backend {
+        TranslateBackend::Ai => {
+            let instruction = format!(
+                "Translate this text into the language \"{}\", keeping its meaning, tone and \
+                 formatting.",
+                target
+            );
+            crate::ai::rewrite(app, &config.ai, text, &instruction)
+        }
+        TranslateBackend::Deepl => deepl(text, target),
+        TranslateBackend::Google => google(text, target),
+    }
+}
+
+fn deepl(text: &str, target: &str) -> Result<String> {
+    let key = secrets::require(Secret::TranslateApiKey)?;
+    let url = if key.ends_with(":fx") {
+        DEEPL_FREE_URL
+    } else {
+        DEEPL_URL
+    };
+    let reply: Value = crate::ai::agent()?
+        .post(url)
+        .set("Authorization", &format!("DeepL-Auth-Key {}", key))
+        .send_json(json!({
+            "text": [text],
+            "target_lang": target.to_uppercase(),
+        }))
+        .map_err(network_error)?
+        .into_json()
+        .map_err(|e| Error::Network(format!("Invalid DeepL response: {}", e)))?;
+    translation(&reply["translations"][0]["text"])
+}
+
+fn google(text: &str, target: &str) -> Result<String> {
+    let key = secrets::require(Secret::TranslateApiKey)?;
+    let reply: Value = crate::ai::agent()?
+        .post(GOOGLE_URL)
+        .query("key", &key)
+        .send_json(json!({
+            "q": text,
+            "target": target,
+            "format": "text",
+        }))
+        .map_err(network_error)?
+        .into_json()
+        .map_err(|e| Error::Network(format!("Invalid Google response: {}", e)))?;
+    translation(&reply["data"]["translations"][0]["translatedText"])
+}
+
+fn translation(value: &Value) -> Result<String> {
+    value
+        .as_str()
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+        .ok_or_else(|| Error::Network("The translation response has no text".to_string()))
+}
+
+fn network_error(error: ureq::Error) -> Error {
+    match error {
+        ureq::Error::Status(401 | 403, _) => {
+            Error::Network("Translation refused: check the API key".to_string())
+        }
+        // DeepL's quota
+        ureq::Error::Status(456, _) => {
+            Error::Network("Translation quota exceeded for this billing period".to_string())
+        }
+        ureq::Error::Status(code, response) => Error::Network(format!(
+            "Translation failed: HTTP {} {}",
+            code,
+            response.status_text()
+        )),
+        ureq::Error::Transport(e) => Error::Network(format!("Translation failed: {}", e)),
+    }
+}
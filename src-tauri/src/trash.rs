@@ -0,0 +1,129 @@
+//! Trash for deleted history entries
+//!
+//! Clearing or cleaning up the history moves the entries to `trash.jsonl`
+//! next to the history file, each marked with when it was deleted, so a
+//! misclick can be undone. Entries are dropped for good once they have been
+//! in the trash for `history.trash_days`. The trash is rewritten whole on
+//! every change; a trashed entry's timestamp is its id, as in the history.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+use crate::history::HistoryEntry;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trashed<T> {
+    #[serde(flatten)]
+    pub entry: T,
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct Trash {
+    path: PathBuf,
+    keep_days: u32,
+}
+
+impl Trash {
+    pub fn new(path: PathBuf, keep_days: u32) -> Self {
+        Self { path, keep_days }
+    }
+
+    /// Entries not yet expired, in the order they were deleted
+    pub fn entries(&self) -> Result<Vec<Trashed<HistoryEntry>>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content =
+            fs::read_to_string(&self.path).map_err(Error::io("Failed to read trash file"))?;
+        let cutoff = self.cutoff();
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(
+                |line| match serde_json::from_str::<Trashed<HistoryEntry>>(line) {
+                    Ok(trashed) => Some(trashed),
+                    Err(e) => {
+                        tracing::warn!("Failed to parse trash entry: {}", e);
+                        None
+                    }
+                },
+            )
+            .filter(|trashed| trashed.deleted_at > cutoff)
+            .collect())
+    }
+
+    /// Move entries into the trash, replacing earlier copies of them
+    pub fn add(&self, entries: &[HistoryEntry]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let ids: HashSet<DateTime<Utc>> = entries.iter().map(|e| e.timestamp).collect();
+        let mut trashed = self.entries()?;
+        trashed.retain(|t| !ids.contains(&t.entry.timestamp));
+        let deleted_at = Utc::now();
+        trashed.extend(
+            entries
+                .iter()
+                .cloned()
+                .map(|entry| Trashed { entry, deleted_at }),
+        );
+        self.write(&trashed)
+    }
+
+    /// Drop the entry with timestamp `id`, or every entry without one
+    pub fn remove(&self, id: Option<DateTime<Utc>>) -> Result<()> {
+        let Some(id) = id else {
+            return self.empty();
+        };
+        let mut trashed = self.entries()?;
+        trashed.retain(|t| t.entry.timestamp != id);
+        self.write(&trashed)
+    }
+
+    /// Drop the entries written (not deleted) before `cutoff`
+    pub fn remove_before(&self, cutoff: DateTime<Utc>) -> Result<()> {
+        let mut trashed = self.entries()?;
+        let before = trashed.len();
+        trashed.retain(|t| t.entry.timestamp >= cutoff);
+        if trashed.len() < before {
+            self.write(&trashed)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite the trash without expired entries
+    pub fn purge(&self) -> Result<()> {
+        if self.path.exists() {
+            self.write(&self.entries()?)?;
+        }
+        Ok(())
+    }
+
+    pub fn empty(&self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path).map_err(Error::io("Failed to empty trash"))?;
+        }
+        Ok(())
+    }
+
+    /// Entries deleted before this have expired
+    fn cutoff(&self) -> DateTime<Utc> {
+        Utc::now() - Duration::days(self.keep_days.into())
+    }
+
+    fn write(&self, trashed: &[Trashed<HistoryEntry>]) -> Result<()> {
+        let mut lines = String::new();
+        for entry in trashed {
+            let json = serde_json::to_string(entry)
+                .map_err(|e| Error::Config(format!("Failed to serialize entry: {}", e)))?;
+            lines.push_str(&json);
+            lines.push('\n');
+        }
+        fs::write(&self.path, lines).map_err(Error::io("Failed to write trash file"))
+    }
+}
@@ -0,0 +1,497 @@
+//! System tray icon and menu
+
+use std::sync::Mutex;
+use tauri::{
+    image::Image,
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Manager, Wry,
+};
+
+use crate::config::WindowConfig;
+use crate::hotkey::ShortcutAction;
+use crate::i18n::{t, tr};
+use crate::lock::{MutexExt, RwLockExt};
+
+const TRAY_ID: &str = "main";
+/// Menu id prefix of the profile items ("profile:<name>")
+const PROFILE_PREFIX: &str = "profile:";
+/// Menu id prefix of the window preset items ("preset:<name>", empty for none)
+const PRESET_PREFIX: &str = "preset:";
+
+/// Badge colors drawn over the tray icon (RGBA)
+const PAUSED_BADGE: [u8; 4] = [0xf5, 0x9e, 0x0b, 0xff];
+const INCOGNITO_BADGE: [u8; 4] = [0x8b, 0x5c, 0xf6, 0xff];
+const VOICE_BADGE: [u8; 4] = [0xef, 0x44, 0x44, 0xff];
+const QUEUE_BADGE: [u8; 4] = [0x22, 0xc5, 0x5e, 0xff];
+
+/// Modes shown on the tray icon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrayState {
+    pub hotkeys_paused: bool,
+    pub incognito: bool,
+    pub voice_on: bool,
+    /// Items left in the paste queue
+    pub queued: usize,
+    /// The screen is being shared (`behavior.screen_share_privacy`)
+    pub screen_shared: bool,
+    /// History shown anyway while sharing
+    pub share_overridden: bool,
+}
+
+impl TrayState {
+    /// History is hidden because the screen is shared
+    fn share_private(&self) -> bool {
+        self.screen_shared && !self.share_overridden
+    }
+}
+
+/// Tray menu items and state that change after creation
+pub struct TrayMenu {
+    show_item: MenuItem<Wry>,
+    pause_item: CheckMenuItem<Wry>,
+    incognito_item: CheckMenuItem<Wry>,
+    share_override_item: CheckMenuItem<Wry>,
+    autostart_item: CheckMenuItem<Wry>,
+    clear_queue_item: MenuItem<Wry>,
+    profile_menu: Submenu<Wry>,
+    profile_items: Vec<(String, CheckMenuItem<Wry>)>,
+    preset_menu: Submenu<Wry>,
+    /// Rebuilt when `[window.presets]` changes
+    preset_items: Mutex<Vec<(String, CheckMenuItem<Wry>)>>,
+    settings_item: MenuItem<Wry>,
+    quit_item: MenuItem<Wry>,
+    launch_shortcut: Mutex<Option<String>>,
+    state: Mutex<TrayState>,
+}
+
+/// Create the tray icon with its context menu
+pub fn create(app: &AppHandle, launch_shortcut: &str, autostart: bool) -> tauri::Result<()> {
+    let show_item = MenuItem::with_id(
+        app,
+        "show",
+        show_label(Some(launch_shortcut)),
+        true,
+        None::<&str>,
+    )?;
+    let pause_item = CheckMenuItem::with_id(
+        app,
+        "pause_hotkeys",
+        t("tray.pause_hotkeys"),
+        true,
+        false,
+        None::<&str>,
+    )?;
+    let incognito_item = CheckMenuItem::with_id(
+        app,
+        "incognito",
+        t("tray.incognito"),
+        true,
+        false,
+        None::<&str>,
+    )?;
+    let share_override_item = CheckMenuItem::with_id(
+        app,
+        "share_override",
+        t("tray.share_override"),
+        false,
+        false,
+        None::<&str>,
+    )?;
+    let autostart_item = CheckMenuItem::with_id(
+        app,
+        "autostart",
+        t("tray.autostart"),
+        true,
+        autostart,
+        None::<&str>,
+    )?;
+    let clear_queue_item =
+        MenuItem::with_id(app, "clear_queue", queue_label(0), false, None::<&str>)?;
+    let active = crate::config::active_profile();
+    let profiles = crate::config::list_profiles().unwrap_or_else(|e| {
+        tracing::warn!("{}", e);
+        vec![active.clone()]
+    });
+    let profile_items = profiles
+        .into_iter()
+        .map(|name| {
+            let item = CheckMenuItem::with_id(
+                app,
+                format!("{}{}", PROFILE_PREFIX, name),
+                &name,
+                true,
+                name == active,
+                None::<&str>,
+            )?;
+            Ok((name, item))
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let profile_menu = Submenu::with_id(app, "profiles", t("tray.profile"), true)?;
+    for (_, item) in &profile_items {
+        profile_menu.append(item)?;
+    }
+    let preset_menu = Submenu::with_id(app, "presets", t("tray.window_preset"), true)?;
+    let settings_item = MenuItem::with_id(app, "settings", t("tray.settings"), true, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", t("tray.quit"), true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &show_item,
+            &pause_item,
+            &incognito_item,
+            &share_override_item,
+            &autostart_item,
+            &clear_queue_item,
+            &profile_menu,
+            &preset_menu,
+            &settings_item,
+            &separator,
+            &quit_item,
+        ],
+    )?;
+
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
+        .icon(base_icon())
+        .menu(&menu)
+        .tooltip(tooltip(Some(launch_shortcut), TrayState::default()))
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            "show" => {
+                crate::toggle_window(app);
+            }
+            "pause_hotkeys" => {
+                let paused = app.state::<crate::AppState>().hotkey_pause.locked().manual;
+                crate::hotkey::set_paused(app, !paused);
+                sync_checks(app);
+            }
+            "incognito" => {
+                crate::set_incognito(app, !crate::is_incognito(app));
+                sync_checks(app);
+            }
+            "share_override" => {
+                let overridden = app
+                    .state::<crate::AppState>()
+                    .screen_share
+                    .locked()
+                    .overridden;
+                crate::screenshare::set_overridden(app, !overridden);
+                sync_checks(app);
+            }
+            "autostart" => {
+                let enabled = app
+                    .state::<crate::AppState>()
+                    .config
+                    .read_locked()
+                    .behavior
+                    .autostart;
+                if let Err(e) = crate::autostart::set(app, !enabled) {
+                    tracing::error!("{}", e);
+                    set_autostart_checked(app, enabled);
+                }
+            }
+            "clear_queue" => {
+                crate::clear_queued(app);
+            }
+            "settings" => {
+                crate::show_settings_window(app);
+            }
+            "quit" => {
+                app.exit(0);
+            }
+            id => {
+                if let Some(name) = id.strip_prefix(PROFILE_PREFIX) {
+                    crate::switch_profile_from_tray(app, name);
+                } else if let Some(name) = id.strip_prefix(PRESET_PREFIX) {
+                    crate::switch_window_preset_from_tray(app, name);
+                }
+            }
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                let app = tray.app_handle();
+                let click_action = app
+                    .state::<crate::AppState>()
+                    .config
+                    .read_locked()
+                    .behavior
+                    .tray_left_click
+                    .clone();
+                if click_action != "none" {
+                    let action = ShortcutAction::parse(&click_action).unwrap_or_else(|| {
+                        tracing::warn!("Unknown tray_left_click action: {}", click_action);
+                        ShortcutAction::Toggle
+                    });
+                    crate::hotkey::run_action(app, &action);
+                }
+            }
+        })
+        .build(app)?;
+
+    app.manage(TrayMenu {
+        show_item,
+        pause_item,
+        incognito_item,
+        share_override_item,
+        autostart_item,
+        clear_queue_item,
+        profile_menu,
+        profile_items,
+        preset_menu,
+        preset_items: Mutex::new(Vec::new()),
+        settings_item,
+        quit_item,
+        launch_shortcut: Mutex::new(Some(launch_shortcut.to_string())),
+        state: Mutex::new(TrayState::default()),
+    });
+    if let Some(state) = app.try_state::<crate::AppState>() {
+        let window = state.config.read_locked().window.clone();
+        set_window_presets(app, &window);
+    }
+    Ok(())
+}
+
+/// Show the registered launch shortcut in the tray menu and tooltip
+pub fn set_launch_shortcut(app: &AppHandle, shortcut: Option<&str>) {
+    let Some(menu) = app.try_state::<TrayMenu>() else {
+        return;
+    };
+    let _ = menu.show_item.set_text(show_label(shortcut));
+    *menu.launch_shortcut.locked() = shortcut.map(str::to_string);
+
+    let state = *menu.state.locked();
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip(shortcut, state)));
+    }
+}
+
+/// Relabel the menu and tooltip after the locale changed
+pub fn set_labels(app: &AppHandle) {
+    let Some(menu) = app.try_state::<TrayMenu>() else {
+        return;
+    };
+    let shortcut = menu.launch_shortcut.locked().clone();
+    let state = *menu.state.locked();
+    let _ = menu.show_item.set_text(show_label(shortcut.as_deref()));
+    let _ = menu.pause_item.set_text(t("tray.pause_hotkeys"));
+    let _ = menu.incognito_item.set_text(t("tray.incognito"));
+    let _ = menu.share_override_item.set_text(t("tray.share_override"));
+    let _ = menu.autostart_item.set_text(t("tray.autostart"));
+    let _ = menu.clear_queue_item.set_text(queue_label(state.queued));
+    let _ = menu.profile_menu.set_text(t("tray.profile"));
+    let _ = menu.preset_menu.set_text(t("tray.window_preset"));
+    let _ = menu.settings_item.set_text(t("tray.settings"));
+    let _ = menu.quit_item.set_text(t("tray.quit"));
+    for (name, item) in menu.preset_items.locked().iter() {
+        if name.is_empty() {
+            let _ = item.set_text(t("tray.default_preset"));
+        }
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip(shortcut.as_deref(), state)));
+    }
+}
+
+/// Update the modes shown on the tray icon, menu checks and tooltip; nothing
+/// is redrawn unless a mode changed
+pub fn set_tray_state(app: &AppHandle, update: impl FnOnce(&mut TrayState)) {
+    let Some(menu) = app.try_state::<TrayMenu>() else {
+        return;
+    };
+    let (was, state) = {
+        let mut state = menu.state.locked();
+        let was = *state;
+        update(&mut state);
+        (was, *state)
+    };
+    if was == state {
+        return;
+    }
+
+    set_checks(&menu, state);
+    let _ = menu.share_override_item.set_enabled(state.screen_shared);
+    let _ = menu.clear_queue_item.set_text(queue_label(state.queued));
+    let _ = menu.clear_queue_item.set_enabled(state.queued > 0);
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let shortcut = menu.launch_shortcut.locked().clone();
+        let _ = tray.set_icon(Some(state_icon(state)));
+        let _ = tray.set_tooltip(Some(tooltip(shortcut.as_deref(), state)));
+    }
+}
+
+/// Put the check items back in line with the modes; they toggle themselves
+/// when clicked, even if the click didn't change the mode (e.g. unpausing
+/// hotkeys while a fullscreen app keeps them paused)
+fn sync_checks(app: &AppHandle) {
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        let state = *menu.state.locked();
+        set_checks(&menu, state);
+    }
+}
+
+fn set_checks(menu: &TrayMenu, state: TrayState) {
+    let _ = menu.pause_item.set_checked(state.hotkeys_paused);
+    let _ = menu.incognito_item.set_checked(state.incognito);
+    let _ = menu.share_override_item.set_checked(state.share_overridden);
+}
+
+/// Keep the "Start at Login" check in sync with the autostart setting
+pub fn set_autostart_checked(app: &AppHandle, enabled: bool) {
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        let _ = menu.autostart_item.set_checked(enabled);
+    }
+}
+
+/// Check the active profile in the "Profile" submenu
+pub fn set_active_profile(app: &AppHandle, active: &str) {
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        for (name, item) in &menu.profile_items {
+            let _ = item.set_checked(name == active);
+        }
+    }
+}
+
+/// List the window presets in the "Window Preset" submenu and check the active one
+pub fn set_window_presets(app: &AppHandle, window: &WindowConfig) {
+    let Some(menu) = app.try_state::<TrayMenu>() else {
+        return;
+    };
+    let names: Vec<String> = std::iter::once(String::new())
+        .chain(window.presets.keys().cloned())
+        .collect();
+    let mut items = menu.preset_items.locked();
+
+    if !items.iter().map(|(name, _)| name).eq(names.iter()) {
+        for (_, item) in items.drain(..) {
+            let _ = menu.preset_menu.remove(&item);
+        }
+        for name in names {
+            let label = if name.is_empty() {
+                t("tray.default_preset")
+            } else {
+                name.clone()
+            };
+            let item = CheckMenuItem::with_id(
+                app,
+                format!("{}{}", PRESET_PREFIX, name),
+                label,
+                true,
+                false,
+                None::<&str>,
+            );
+            match item.and_then(|item| menu.preset_menu.append(&item).map(|_| item)) {
+                Ok(item) => items.push((name, item)),
+                Err(e) => tracing::warn!("Failed to add window preset menu item: {}", e),
+            }
+        }
+    }
+    // An unknown active preset is ignored, like in `WindowConfig::with_active_preset`
+    let active = if window.presets.contains_key(&window.preset) {
+        window.preset.as_str()
+    } else {
+        ""
+    };
+    for (name, item) in items.iter() {
+        let _ = item.set_checked(name == active);
+    }
+}
+
+fn base_icon() -> Image<'static> {
+    Image::from_bytes(include_bytes!("../icons/32x32.png")).expect("Failed to load icon")
+}
+
+/// Draw a badge in a corner of the icon for each active mode
+fn state_icon(state: TrayState) -> Image<'static> {
+    let icon = base_icon();
+    if state == TrayState::default() {
+        return icon;
+    }
+
+    let (width, height) = (icon.width(), icon.height());
+    let mut rgba = icon.rgba().to_vec();
+    let radius = width / 5;
+
+    let badges = [
+        (state.hotkeys_paused, radius, radius, PAUSED_BADGE),
+        (
+            state.incognito || state.share_private(),
+            radius,
+            height - radius - 1,
+            INCOGNITO_BADGE,
+        ),
+        (
+            state.voice_on,
+            width - radius - 1,
+            height - radius - 1,
+            VOICE_BADGE,
+        ),
+        (state.queued > 0, width - radius - 1, radius, QUEUE_BADGE),
+    ];
+    for (active, cx, cy, color) in badges {
+        if active {
+            draw_dot(&mut rgba, width, cx, cy, radius, color);
+        }
+    }
+
+    Image::new_owned(rgba, width, height)
+}
+
+fn draw_dot(rgba: &mut [u8], width: u32, cx: u32, cy: u32, radius: u32, color: [u8; 4]) {
+    let r2 = (radius * radius) as i64;
+    for y in cy.saturating_sub(radius)..=cy + radius {
+        for x in cx.saturating_sub(radius)..=cx + radius {
+            let (dx, dy) = (x as i64 - cx as i64, y as i64 - cy as i64);
+            let offset = ((y * width + x) * 4) as usize;
+            if dx * dx + dy * dy <= r2 && offset + 4 <= rgba.len() {
+                rgba[offset..offset + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+fn show_label(shortcut: Option<&str>) -> String {
+    match shortcut {
+        Some(shortcut) => tr("tray.show_shortcut", &[("shortcut", shortcut)]),
+        None => t("tray.show"),
+    }
+}
+
+fn tooltip(shortcut: Option<&str>, state: TrayState) -> String {
+    let mut text = match shortcut {
+        Some(shortcut) => format!("prompt-line-rs ({})", shortcut),
+        None => format!("prompt-line-rs ({})", t("tray.tooltip_no_hotkey")),
+    };
+    let modes = [
+        (state.hotkeys_paused, "tray.tooltip_paused"),
+        (state.incognito, "tray.tooltip_incognito"),
+        (state.share_private(), "tray.tooltip_screen_shared"),
+        (state.voice_on, "tray.tooltip_voice"),
+    ];
+    for (active, key) in modes {
+        if active {
+            text.push_str(" - ");
+            text.push_str(&t(key));
+        }
+    }
+    if state.queued > 0 {
+        text.push_str(" - ");
+        text.push_str(&tr(
+            "tray.tooltip_queued",
+            &[("count", &state.queued.to_string())],
+        ));
+    }
+    text
+}
+
+fn queue_label(queued: usize) -> String {
+    match queued {
+        0 => t("tray.clear_queue"),
+        queued => tr("tray.clear_queue_count", &[("count", &queued.to_string())]),
+    }
+}
@@ -0,0 +1,473 @@
+//! Main window placement and remembered geometry
+//!
+//! When the user moves or resizes the main window, its position and size are
+//! remembered per monitor and reused on the next show instead of the size
+//! computed from `[window]`. Manual sizes are kept separately for the compact
+//! and expanded layouts, and the last layout is restored on the next run.
+//!
+//! Sizes are worked out in logical pixels and converted with the scale factor
+//! of the monitor the window is shown on (or `window.scale`), so the window
+//! fits its content at 125%/150% scaling and across mixed-DPI monitors.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{
+    AppHandle, Emitter, LogicalSize, Manager, Monitor, PhysicalPosition, PhysicalSize,
+    WebviewWindow,
+};
+
+use crate::config::{PositionMode, WindowConfig, WindowMode, WindowPosition};
+use crate::error::{Error, Result};
+use crate::lock::{MutexExt, RwLockExt};
+
+const STATE_FILE: &str = "window-state.json";
+/// Size difference (logical pixels) still treated as the size we set ourselves
+const SIZE_TOLERANCE: f64 = 2.0;
+/// Lowest opacity accepted from the config, so the window never disappears
+pub const MIN_OPACITY: f64 = 0.2;
+
+/// Geometry the user left the window with on one monitor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonitorGeometry {
+    /// Top-left corner (physical pixels)
+    #[serde(default)]
+    pub position: Option<(i32, i32)>,
+    /// Manually resized size in expanded mode (logical pixels)
+    #[serde(default)]
+    pub size: Option<(f64, f64)>,
+    /// Manually resized size in compact mode (logical pixels)
+    #[serde(default)]
+    pub compact_size: Option<(f64, f64)>,
+}
+
+impl MonitorGeometry {
+    fn manual_size(&mut self, mode: WindowMode) -> &mut Option<(f64, f64)> {
+        match mode {
+            WindowMode::Expanded => &mut self.size,
+            WindowMode::Compact => &mut self.compact_size,
+        }
+    }
+}
+
+/// Window geometry kept between runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowState {
+    /// Monitor the window was last on
+    #[serde(default)]
+    pub last_monitor: Option<String>,
+    /// Geometry per monitor name
+    #[serde(default)]
+    pub monitors: BTreeMap<String, MonitorGeometry>,
+    /// Layout chosen with the mode toggle
+    #[serde(default)]
+    pub mode: WindowMode,
+    /// Size last applied by the app or by a DPI change (physical pixels), to
+    /// tell our resizes from the user's
+    #[serde(skip)]
+    applied_size: Option<(u32, u32)>,
+}
+
+/// Equal when the saved fields are; `applied_size` only matters while running
+impl PartialEq for WindowState {
+    fn eq(&self, other: &Self) -> bool {
+        self.last_monitor == other.last_monitor
+            && self.monitors == other.monitors
+            && self.mode == other.mode
+    }
+}
+
+impl WindowState {
+    /// Load the saved state, or start empty
+    pub fn load() -> Self {
+        state_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = state_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(Error::io("Failed to create directory"))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Config(format!("Failed to serialize window state: {}", e)))?;
+        fs::write(path, json).map_err(Error::io("Failed to save window state"))
+    }
+}
+
+fn state_path() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join(STATE_FILE))
+}
+
+fn monitor_key(monitor: &Monitor) -> String {
+    monitor
+        .name()
+        .cloned()
+        .unwrap_or_else(|| "default".to_string())
+}
+
+/// Current layout mode
+pub fn mode(app: &AppHandle) -> WindowMode {
+    app.try_state::<crate::AppState>()
+        .map(|state| state.window_state.locked().mode)
+        .unwrap_or_default()
+}
+
+/// Size computed from `config` for the current layout mode
+fn computed_size(app: &AppHandle, config: &WindowConfig) -> LogicalSize<f64> {
+    let (width, height) = config.size_pixels(mode(app));
+    LogicalSize::new(width, height)
+}
+
+/// Size and move the window for its configured position before it is shown.
+/// A size the user set by hand on the target monitor wins over the computed one.
+pub fn place(app: &AppHandle, window: &WebviewWindow, config: &WindowConfig) {
+    let computed = computed_size(app, config);
+    let position = config.position;
+    let Some(state) = app.try_state::<crate::AppState>() else {
+        let _ = window.set_size(computed);
+        return;
+    };
+
+    let monitor = match position {
+        WindowPosition::Fixed { x, y } => app.monitor_from_point(x as f64, y as f64).ok().flatten(),
+        WindowPosition::Mode(PositionMode::Center) => app.primary_monitor().ok().flatten(),
+        WindowPosition::Mode(PositionMode::Cursor) => cursor_monitor(app),
+        WindowPosition::Mode(PositionMode::Focused) => {
+            crate::foreground::foreground_monitor_center()
+                .and_then(|(x, y)| app.monitor_from_point(x, y).ok().flatten())
+                .or_else(|| cursor_monitor(app))
+        }
+        WindowPosition::Mode(PositionMode::Remember) => {
+            let last = state.window_state.locked().last_monitor.clone();
+            app.available_monitors()
+                .ok()
+                .and_then(|monitors| monitors.into_iter().find(|m| Some(monitor_key(m)) == last))
+                .or_else(|| app.primary_monitor().ok().flatten())
+        }
+    };
+
+    let mode = state.window_state.locked().mode;
+    let mut geometry = monitor
+        .as_ref()
+        .and_then(|m| {
+            let state = state.window_state.locked();
+            state.monitors.get(&monitor_key(m)).cloned()
+        })
+        .unwrap_or_default();
+    let size = geometry
+        .manual_size(mode)
+        .map(|(width, height)| LogicalSize::new(width, height))
+        .unwrap_or(computed)
+        .to_physical::<u32>(scale_factor(
+            config.scale,
+            monitor.as_ref(),
+            window.scale_factor().unwrap_or(1.0),
+        ));
+
+    let Some(monitor) = monitor else {
+        set_size(&state, window, size);
+        return;
+    };
+    let target = match position {
+        WindowPosition::Fixed { x, y } => PhysicalPosition::new(x, y),
+        WindowPosition::Mode(PositionMode::Remember) => geometry
+            .position
+            .filter(|&(x, y)| {
+                app.monitor_from_point(x as f64, y as f64)
+                    .ok()
+                    .flatten()
+                    .is_some()
+            })
+            .map(|(x, y)| PhysicalPosition::new(x, y))
+            .unwrap_or_else(|| center_on(&monitor, size)),
+        WindowPosition::Mode(_) => center_on(&monitor, size),
+    };
+    // Move first: crossing to a monitor with another scale factor makes the
+    // OS rescale the window, which would undo a size set beforehand
+    let _ = window.set_position(target);
+    set_size(&state, window, size);
+    let _ = window.set_position(target);
+}
+
+/// Resize to the size computed from `config` unless the user resized the
+/// window on its current monitor
+pub fn apply_size(app: &AppHandle, window: &WebviewWindow, config: &WindowConfig) {
+    let computed = computed_size(app, config);
+    let Some(state) = app.try_state::<crate::AppState>() else {
+        let _ = window.set_size(computed);
+        return;
+    };
+    let monitor = window.current_monitor().ok().flatten();
+    let manual = monitor.as_ref().and_then(|m| {
+        let mut state = state.window_state.locked();
+        let mode = state.mode;
+        *state.monitors.get_mut(&monitor_key(m))?.manual_size(mode)
+    });
+    let size = manual
+        .map(|(width, height)| LogicalSize::new(width, height))
+        .unwrap_or(computed)
+        .to_physical::<u32>(scale_factor(
+            config.scale,
+            monitor.as_ref(),
+            window.scale_factor().unwrap_or(1.0),
+        ));
+    set_size(&state, window, size);
+}
+
+fn set_size(state: &crate::AppState, window: &WebviewWindow, size: PhysicalSize<u32>) {
+    state.window_state.locked().applied_size = Some((size.width, size.height));
+    let _ = window.set_size(size);
+}
+
+/// Scale factor for converting logical sizes on `monitor`; a positive
+/// `window.scale` overrides the one the OS reports
+fn scale_factor(configured: Option<f64>, monitor: Option<&Monitor>, fallback: f64) -> f64 {
+    configured
+        .filter(|scale| *scale > 0.0)
+        .or_else(|| monitor.map(Monitor::scale_factor))
+        .unwrap_or(fallback)
+}
+
+fn cursor_monitor(app: &AppHandle) -> Option<Monitor> {
+    let cursor = app.cursor_position().ok()?;
+    app.monitor_from_point(cursor.x, cursor.y).ok().flatten()
+}
+
+/// Top-left corner that centers a window of `size` in the monitor's work area
+fn center_on(monitor: &Monitor, size: PhysicalSize<u32>) -> PhysicalPosition<i32> {
+    let area = monitor.work_area();
+    PhysicalPosition::new(
+        area.position.x + (area.size.width as i32 - size.width as i32) / 2,
+        area.position.y + (area.size.height as i32 - size.height as i32) / 2,
+    )
+}
+
+/// Apply `window.opacity` and `window.effect` to the main window
+#[cfg(windows)]
+pub fn apply_appearance(window: &WebviewWindow, config: &WindowConfig) {
+    use crate::config::WindowEffect;
+    use windows::Win32::Foundation::{COLORREF, HWND};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA,
+        WS_EX_LAYERED,
+    };
+
+    let Ok(handle) = window.hwnd() else {
+        return;
+    };
+    let hwnd = HWND(handle.0);
+    let opacity = config.opacity.clamp(MIN_OPACITY, 1.0);
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        if opacity < 1.0 {
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED.0 as isize);
+            let alpha = (opacity * 255.0).round() as u8;
+            if let Err(e) = SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA) {
+                tracing::warn!("Failed to set window opacity: {}", e);
+            }
+        } else if style & WS_EX_LAYERED.0 as isize != 0 {
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style & !(WS_EX_LAYERED.0 as isize));
+        }
+    }
+
+    // Clearing an effect that is not applied fails harmlessly
+    let _ = window_vibrancy::clear_acrylic(window);
+    let _ = window_vibrancy::clear_mica(window);
+    let result = match config.effect {
+        WindowEffect::None => Ok(()),
+        WindowEffect::Acrylic => window_vibrancy::apply_acrylic(window, None),
+        WindowEffect::Mica => window_vibrancy::apply_mica(window, None),
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to apply {:?} window effect: {}", config.effect, e);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn apply_appearance(_window: &WebviewWindow, _config: &WindowConfig) {}
+
+/// Keep the main window above other windows or not
+///
+/// The settings window follows along: left on top over a main window that
+/// isn't, it would cover it for good.
+pub fn set_always_on_top(app: &AppHandle, on: bool) -> Result<()> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| Error::Platform("No main window".to_string()))?;
+    window
+        .set_always_on_top(on)
+        .map_err(|e| Error::Platform(format!("Failed to change always-on-top: {}", e)))?;
+    if let Some(settings) = app.get_webview_window("settings") {
+        let _ = settings.set_always_on_top(on);
+    }
+    let _ = app.emit("always-on-top-changed", on);
+    Ok(())
+}
+
+/// Whether the main window is kept above other windows
+pub fn is_always_on_top(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|window| window.is_always_on_top().ok())
+        .unwrap_or(false)
+}
+
+/// Show the main window without activating it (`behavior.no_activate`)
+///
+/// `WS_EX_NOACTIVATE` also keeps clicks from activating it; the frontend
+/// takes focus itself when clicked.
+#[cfg(windows)]
+pub fn show_inactive(window: &WebviewWindow) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_SHOWNOACTIVATE};
+
+    set_no_activate(window, true);
+    let Ok(handle) = window.hwnd() else {
+        let _ = window.show();
+        return;
+    };
+    unsafe {
+        let _ = ShowWindow(HWND(handle.0), SW_SHOWNOACTIVATE);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn show_inactive(window: &WebviewWindow) {
+    let _ = window.show();
+}
+
+/// Show the main window and give it focus
+pub fn show_active(window: &WebviewWindow) {
+    set_no_activate(window, false);
+    let _ = window.show();
+    let _ = window.set_focus();
+}
+
+#[cfg(windows)]
+fn set_no_activate(window: &WebviewWindow, on: bool) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetWindowLongPtrW, GWL_EXSTYLE, WS_EX_NOACTIVATE,
+    };
+
+    let Ok(handle) = window.hwnd() else {
+        return;
+    };
+    let hwnd = HWND(handle.0);
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        let flag = WS_EX_NOACTIVATE.0 as isize;
+        let wanted = if on { style | flag } else { style & !flag };
+        if wanted != style {
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, wanted);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn set_no_activate(_window: &WebviewWindow, _on: bool) {}
+
+/// Track the main window's position as it moves
+pub fn on_moved(window: &tauri::Window, position: PhysicalPosition<i32>) {
+    let Some(monitor) = window.current_monitor().ok().flatten() else {
+        return;
+    };
+    let state = window.state::<crate::AppState>();
+    let mut state = state.window_state.locked();
+    let key = monitor_key(&monitor);
+    state.monitors.entry(key.clone()).or_default().position = Some((position.x, position.y));
+    state.last_monitor = Some(key);
+}
+
+/// Remember a size the user set by dragging the window border
+pub fn on_resized(window: &tauri::Window, size: PhysicalSize<u32>) {
+    if size.width == 0 || size.height == 0 || !window.is_visible().unwrap_or(false) {
+        return;
+    }
+    let Some(monitor) = window.current_monitor().ok().flatten() else {
+        return;
+    };
+    let app_state = window.state::<crate::AppState>();
+    let configured = app_state.config.read_locked().window.scale;
+    let scale = scale_factor(
+        configured,
+        Some(&monitor),
+        window.scale_factor().unwrap_or(1.0),
+    );
+    let mut state = app_state.window_state.locked();
+
+    let tolerance = SIZE_TOLERANCE * scale;
+    let ours = state.applied_size.is_some_and(|(width, height)| {
+        (size.width as f64 - width as f64).abs() <= tolerance
+            && (size.height as f64 - height as f64).abs() <= tolerance
+    });
+    if !ours {
+        let logical = size.to_logical::<f64>(scale);
+        let mode = state.mode;
+        *state
+            .monitors
+            .entry(monitor_key(&monitor))
+            .or_default()
+            .manual_size(mode) = Some((logical.width, logical.height));
+        state.applied_size = Some((size.width, size.height));
+    }
+}
+
+/// Treat the resize Windows does when the window crosses to a monitor with a
+/// different scale factor as ours, not as a manual resize
+pub fn on_scale_changed(window: &tauri::Window, new_inner_size: PhysicalSize<u32>) {
+    let state = window.state::<crate::AppState>();
+    state.window_state.locked().applied_size = Some((new_inner_size.width, new_inner_size.height));
+}
+
+/// Switch the layout mode, resize the window for it and remember the choice
+pub fn set_mode(app: &AppHandle, window: &WebviewWindow, config: &WindowConfig, mode: WindowMode) {
+    if let Some(state) = app.try_state::<crate::AppState>() {
+        state.window_state.locked().mode = mode;
+    }
+    apply_size(app, window, config);
+    persist(app);
+}
+
+/// Forget manual sizes and positions
+pub fn reset(app: &AppHandle) {
+    if let Some(state) = app.try_state::<crate::AppState>() {
+        let mut state = state.window_state.locked();
+        state.monitors.clear();
+        state.last_monitor = None;
+    }
+    persist(app);
+}
+
+/// Write the tracked geometry to disk if it changed since the last save
+pub fn persist(app: &AppHandle) {
+    let Some(state) = app.try_state::<crate::AppState>() else {
+        return;
+    };
+    let current = state.window_state.locked().clone();
+    if current == WindowState::load() {
+        return;
+    }
+    if let Err(e) = current.save() {
+        tracing::warn!("{}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applied_size_is_not_compared() {
+        let saved = WindowState::default();
+        let shown = WindowState {
+            applied_size: Some((800, 200)),
+            ..WindowState::default()
+        };
+        assert_eq!(shown, saved);
+    }
+}
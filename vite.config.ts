@@ -28,6 +28,7 @@ export default defineConfig({
       input: {
         main: resolve(__dirname, "index.html"),
         settings: resolve(__dirname, "settings.html"),
+        picker: resolve(__dirname, "picker.html"),
       },
     },
   },